@@ -11,7 +11,9 @@ pub(crate) fn build_builder(
     api_name: Ident,
     fields_init: TokenStream,
 ) -> (Ident, TokenStream) {
-    let Metadata { base_url, default } = metadata;
+    let Metadata {
+        base_url, default, ..
+    } = metadata;
     let name = Ident::new(format!("{}Builder", api_name).as_str(), Span::call_site());
 
     let mut builder = quote! {
@@ -41,6 +43,13 @@ pub(crate) fn build_builder(
                 }
             }
 
+            /// Share the Reqwest Client with other apis
+            pub fn with_shared_client(self, client: std::sync::Arc<apisdk::ReqwestClient>) -> Self {
+                Self {
+                    inner: self.inner.with_shared_client(client)
+                }
+            }
+
             /// Set UrlRewriter
             pub fn with_rewriter<T>(self, rewriter: T) -> Self where T: apisdk::UrlRewriter {
                 Self {
@@ -69,6 +78,27 @@ pub(crate) fn build_builder(
                 }
             }
 
+            /// Set initialiser with priority
+            pub fn with_initialiser_priority<T>(self, initialiser: T, priority: i32) -> Self where T: apisdk::Initialiser {
+                Self {
+                    inner: self.inner.with_initialiser_priority(initialiser, priority)
+                }
+            }
+
+            /// Set many initialisers
+            pub fn with_initialisers<I>(self, initialisers: I) -> Self where I: IntoIterator<Item = std::sync::Arc<dyn apisdk::Initialiser>> {
+                Self {
+                    inner: self.inner.with_initialisers(initialisers)
+                }
+            }
+
+            /// Add async initialiser
+            pub fn with_async_initialiser<T>(self, initialiser: T) -> Self where T: apisdk::AsyncInitialiser {
+                Self {
+                    inner: self.inner.with_async_initialiser(initialiser)
+                }
+            }
+
             /// Add middleware
             pub fn with_middleware<T>(self, middleware: T) -> Self where T: apisdk::Middleware {
                 Self {
@@ -76,6 +106,174 @@ pub(crate) fn build_builder(
                 }
             }
 
+            /// Set the preference of IP version
+            pub fn with_ip_version_preference(self, ip_version: apisdk::IpVersion) -> Self {
+                Self {
+                    inner: self.inner.with_ip_version_preference(ip_version)
+                }
+            }
+
+            /// Resolve the host to a specific address
+            pub fn with_host_override(self, host: impl ToString, addr: impl Into<std::net::SocketAddr>) -> Self {
+                Self {
+                    inner: self.inner.with_host_override(host, addr)
+                }
+            }
+
+            /// Trust the root certificates in a PEM bundle
+            pub fn with_ca_bundle_path(self, path: impl AsRef<std::path::Path>) -> apisdk::ApiResult<Self> {
+                Ok(Self {
+                    inner: self.inner.with_ca_bundle_path(path)?
+                })
+            }
+
+            /// Set the default headers of all requests from environment variables
+            pub fn with_base_headers_from_env(self, mappings: &[(&str, &str)], missing: apisdk::MissingEnvPolicy) -> apisdk::ApiResult<Self> {
+                Ok(Self {
+                    inner: self.inner.with_base_headers_from_env(mappings, missing)?
+                })
+            }
+
+            /// Limit the connections of each host
+            pub fn with_max_connections_per_host(self, max: usize) -> Self {
+                Self {
+                    inner: self.inner.with_max_connections_per_host(max)
+                }
+            }
+
+            /// Limit the concurrent in-flight requests of the api
+            pub fn with_max_concurrency(self, max: usize) -> Self {
+                Self {
+                    inner: self.inner.with_max_concurrency(max)
+                }
+            }
+
+            /// Enable TCP keep-alive
+            pub fn with_tcp_keepalive(self, interval: std::time::Duration) -> Self {
+                Self {
+                    inner: self.inner.with_tcp_keepalive(interval)
+                }
+            }
+
+            /// Set the timeout of idle connections in pool
+            pub fn with_pool_idle_timeout(self, timeout: std::time::Duration) -> Self {
+                Self {
+                    inner: self.inner.with_pool_idle_timeout(timeout)
+                }
+            }
+
+            /// Set the local address to bind
+            pub fn with_local_address(self, addr: std::net::IpAddr) -> Self {
+                Self {
+                    inner: self.inner.with_local_address(addr)
+                }
+            }
+
+            /// Set whether to decode compressed response automatically
+            pub fn with_auto_decompress(self, auto_decompress: bool) -> Self {
+                Self {
+                    inner: self.inner.with_auto_decompress(auto_decompress)
+                }
+            }
+
+            /// Set the encodings advertised in `Accept-Encoding`
+            pub fn with_accept_encoding(self, encodings: &[&str]) -> Self {
+                Self {
+                    inner: self.inner.with_accept_encoding(encodings)
+                }
+            }
+
+            /// Set the max length of response decoded by apisdk
+            pub fn with_max_decoded_len(self, len: usize) -> Self {
+                Self {
+                    inner: self.inner.with_max_decoded_len(len)
+                }
+            }
+
+            /// Set the default `Accept-Language` of all requests
+            pub fn with_accept_language(self, language: impl ToString) -> Self {
+                Self {
+                    inner: self.inner.with_accept_language(language)
+                }
+            }
+
+            /// Set the default headers of requests with the HTTP method
+            pub fn with_method_defaults(self, method: apisdk::Method, headers: apisdk::header::HeaderMap) -> Self {
+                Self {
+                    inner: self.inner.with_method_defaults(method, headers)
+                }
+            }
+
+            /// Set the policy of absolute URL in request path
+            pub fn with_absolute_url(self, policy: apisdk::AbsoluteUrlPolicy) -> Self {
+                Self {
+                    inner: self.inner.with_absolute_url(policy)
+                }
+            }
+
+            /// Set the prefix of relative paths, such as `/v2`
+            pub fn with_path_prefix(self, prefix: impl AsRef<str>) -> Self {
+                Self {
+                    inner: self.inner.with_path_prefix(prefix)
+                }
+            }
+
+            /// Set whether to join the base url and path verbatim
+            pub fn with_strict_path_join(self, strict: bool) -> Self {
+                Self {
+                    inner: self.inner.with_strict_path_join(strict)
+                }
+            }
+
+            /// Set the default timeout of all requests
+            pub fn with_default_timeout(self, timeout: std::time::Duration) -> Self {
+                Self {
+                    inner: self.inner.with_default_timeout(timeout)
+                }
+            }
+
+            /// Set the value of `User-Agent`
+            pub fn with_user_agent(self, user_agent: impl ToString) -> Self {
+                Self {
+                    inner: self.inner.with_user_agent(user_agent)
+                }
+            }
+
+            /// Set JsonPolicy
+            pub fn with_json_policy(self, policy: apisdk::JsonPolicy) -> Self {
+                Self {
+                    inner: self.inner.with_json_policy(policy)
+                }
+            }
+
+            /// Set whether to unwrap the json response which is encoded as json string
+            pub fn with_double_json_decode(self, double_json_decode: bool) -> Self {
+                Self {
+                    inner: self.inner.with_double_json_decode(double_json_decode)
+                }
+            }
+
+            /// Set whether to coalesce the identical in-flight GET requests
+            pub fn with_single_flight(self, single_flight: bool) -> Self {
+                Self {
+                    inner: self.inner.with_single_flight(single_flight)
+                }
+            }
+
+            /// Set the Clock
+            pub fn with_clock<T>(self, clock: T) -> Self where T: apisdk::Clock {
+                Self {
+                    inner: self.inner.with_clock(clock)
+                }
+            }
+
+            /// Set Date header automatically
+            pub fn with_auto_date(self, enabled: bool) -> Self {
+                Self {
+                    inner: self.inner.with_auto_date(enabled)
+                }
+            }
+
             /// Set log filter
             pub fn with_log<L>(self, level: L) -> Self where L: apisdk::IntoFilter {
                 Self {
@@ -83,6 +281,13 @@ pub(crate) fn build_builder(
                 }
             }
 
+            /// Set LogConfig, such as log filter and max body length
+            pub fn with_logger(self, config: apisdk::LogConfig) -> Self {
+                Self {
+                    inner: self.inner.with_logger(config)
+                }
+            }
+
             /// Disable log
             pub fn disable_log(self) -> Self {
                 Self {
@@ -107,6 +312,14 @@ pub(crate) fn build_builder(
                         #fields_init
                     }
                 }
+
+                /// Build the api instance, and load the async initialisers eagerly
+                pub async fn build_async(self) -> apisdk::ApiResult<#api_name> {
+                    Ok(#api_name {
+                        core: std::sync::Arc::new(self.inner.build_async().await?),
+                        #fields_init
+                    })
+                }
             }
         });
     }
@@ -124,7 +337,17 @@ pub(crate) fn build_api_impl(
     _fields_clone: TokenStream,
     builder_name: Ident,
 ) -> TokenStream {
-    let Metadata { default, .. } = metadata;
+    let Metadata {
+        default,
+        health,
+        follow_link,
+        sign_url,
+        reset,
+        pool_stats,
+        poll_changes,
+        tus_upload,
+        ..
+    } = metadata;
 
     let mut api = quote! {
         #(#api_attrs)*
@@ -166,6 +389,129 @@ pub(crate) fn build_api_impl(
         }
     };
 
+    if *follow_link {
+        api.extend(quote! {
+            impl #api_name {
+                /// Build a GET request to follow the link in json response
+                /// - body: the json response
+                /// - pointer: JSON Pointer to the link, such as `/_links/next/href`
+                pub async fn follow_link(
+                    &self,
+                    body: &apisdk::serde_json::Value,
+                    pointer: &str,
+                ) -> apisdk::ApiResult<apisdk::RequestBuilder> {
+                    self.core.follow_link(body, pointer).await
+                }
+            }
+        });
+    }
+
+    if *sign_url {
+        api.extend(quote! {
+            impl #api_name {
+                /// Build the final url of request without sending it, such as a pre-signed URL
+                /// - method: HTTP method
+                /// - path: relative path
+                pub async fn sign_url(
+                    &self,
+                    method: apisdk::Method,
+                    path: impl AsRef<str>,
+                ) -> apisdk::ApiResult<apisdk::Url> {
+                    self.core.sign_url(method, path).await
+                }
+            }
+        });
+    }
+
+    if *reset {
+        api.extend(quote! {
+            impl #api_name {
+                /// Rebuild the api core with the builder, and replace the config without recreating the api
+                /// - builder: the builder with new config
+                ///
+                /// The in-flight requests finish on the old config, and the new ones use the new config.
+                pub fn reset(&self, builder: #builder_name) {
+                    self.core.reset(builder.inner.build());
+                }
+            }
+        });
+    }
+
+    if *pool_stats {
+        api.extend(quote! {
+            impl #api_name {
+                /// Get the statistics of connection pool, such as the number of reused connections
+                pub fn pool_stats(&self) -> apisdk::PoolStats {
+                    self.core.pool_stats()
+                }
+            }
+        });
+    }
+
+    if *poll_changes {
+        api.extend(quote! {
+            impl #api_name {
+                /// Poll the path by GET, and emit the json response only when it changes
+                /// - path: relative path
+                /// - interval: the interval between polls
+                pub fn poll_changes(
+                    &self,
+                    path: impl ToString,
+                    interval: std::time::Duration,
+                ) -> apisdk::ChangeStream {
+                    apisdk::__internal::poll_changes(self.core.clone(), path.to_string(), interval)
+                }
+            }
+        });
+    }
+
+    if *tus_upload {
+        api.extend(quote! {
+            impl #api_name {
+                /// Upload the file by Tus protocol, and resume from the offset of server on failure
+                /// - path: relative path to create the upload
+                /// - file: the source to upload, such as `tokio::fs::File`
+                /// - chunk_size: the max size of each `PATCH` request
+                ///
+                /// Return the URL of the upload
+                pub async fn tus_upload<R>(
+                    &self,
+                    path: impl ToString,
+                    file: R,
+                    chunk_size: usize,
+                ) -> apisdk::ApiResult<apisdk::Url>
+                where
+                    R: apisdk::TusSource,
+                {
+                    apisdk::__internal::tus_upload(self.core.clone(), path.to_string(), file, chunk_size).await
+                }
+            }
+        });
+    }
+
+    if *health {
+        api.extend(quote! {
+            impl #api_name {
+                /// Check the health of service, by sending GET request without parsing response body
+                /// - path: relative path, such as `/health`
+                ///
+                /// Return whether the status of response is success (2xx)
+                pub async fn health(&self, path: impl AsRef<str>) -> apisdk::ApiResult<bool> {
+                    let req = self.core.build_request(apisdk::Method::GET, path).await?;
+                    apisdk::__internal::probe(
+                        req,
+                        apisdk::__internal::RequestConfigurator::new(
+                            module_path!(),
+                            None::<bool>,
+                            false,
+                        ),
+                    )
+                    .await
+                }
+            }
+        });
+    }
+
     if *default {
         api.extend(quote! {
             impl Default for #api_name {
@@ -234,9 +580,125 @@ pub(crate) fn build_macro_overrides(_fn_name: Ident) -> Vec<TokenStream> {
                         apisdk::#macro_with_name!($req, $arg1, $arg2, Self::__REQ_CONFIG.take()).await
                     }
                 };
+                ($req:expr, |$($closure:tt)+) => {
+                    async {
+                        apisdk::#macro_with_name!($req, (|$($closure)+), Self::__REQ_CONFIG.take()).await
+                    }
+                };
+                ($req:expr, move |$($closure:tt)+) => {
+                    async {
+                        apisdk::#macro_with_name!($req, (move |$($closure)+), Self::__REQ_CONFIG.take()).await
+                    }
+                };
+                ($req:expr, $arg1:expr, |$($closure:tt)+) => {
+                    async {
+                        apisdk::#macro_with_name!($req, $arg1, (|$($closure)+), Self::__REQ_CONFIG.take()).await
+                    }
+                };
+                ($req:expr, $arg1:expr, move |$($closure:tt)+) => {
+                    async {
+                        apisdk::#macro_with_name!($req, $arg1, (move |$($closure)+), Self::__REQ_CONFIG.take()).await
+                    }
+                };
             }
         }
     })
+    .chain([quote! {
+        #[allow(unused)]
+        macro_rules! send_json_to {
+            ($req:expr, $json:expr) => {
+                async {
+                    apisdk::_send_json_to_with!($req, $json, _, Self::__REQ_CONFIG.take()).await
+                }
+            };
+            ($req:expr, $json:expr, $t:ty) => {
+                async {
+                    apisdk::_send_json_to_with!($req, $json, $t, Self::__REQ_CONFIG.take()).await
+                }
+            };
+        }
+
+        #[allow(unused)]
+        macro_rules! send_raw {
+            ($req:expr) => {
+                async {
+                    apisdk::_send_raw_with!($req, Self::__REQ_CONFIG.take()).await
+                }
+            };
+        }
+
+        #[allow(unused)]
+        macro_rules! send_ndjson {
+            ($req:expr) => {
+                async {
+                    apisdk::_send_ndjson_with!($req, Self::__REQ_CONFIG.take()).await
+                }
+            };
+            ($req:expr, $items:expr $(, $($extractor:tt)+)?) => {
+                async {
+                    let req = apisdk::__internal::ndjson_body($req, $items)?;
+                    send!(req $(, $($extractor)+)?).await
+                }
+            };
+        }
+
+        #[allow(unused)]
+        macro_rules! send_lines {
+            ($req:expr) => {
+                async {
+                    apisdk::_send_lines_with!($req, Self::__REQ_CONFIG.take()).await
+                }
+            };
+        }
+
+        #[allow(unused)]
+        macro_rules! send_bytes {
+            ($req:expr, $bytes:expr, $content_type:expr $(, $($extractor:tt)+)?) => {
+                async {
+                    let req = apisdk::__internal::bytes_body($req, $bytes, $content_type);
+                    send!(req $(, $($extractor)+)?).await
+                }
+            };
+        }
+
+        #[allow(unused)]
+        macro_rules! send_channel_body {
+            ($req:expr, $receiver:expr $(, $($extractor:tt)+)?) => {
+                async {
+                    let req = apisdk::__internal::channel_body($req, $receiver);
+                    send!(req $(, $($extractor)+)?).await
+                }
+            };
+        }
+
+        #[allow(unused)]
+        macro_rules! send_json_array {
+            ($req:expr) => {
+                async {
+                    apisdk::_send_json_array_with!($req, apisdk::serde_json::Value, Self::__REQ_CONFIG.take()).await
+                }
+            };
+            ($req:expr, $t:ty) => {
+                async {
+                    apisdk::_send_json_array_with!($req, $t, Self::__REQ_CONFIG.take()).await
+                }
+            };
+        }
+
+        #[allow(unused)]
+        macro_rules! send_with_schema_validation {
+            ($req:expr, $schema:expr) => {
+                async {
+                    apisdk::_send_with_schema_validation_with!($req, $schema, Self::__REQ_CONFIG.take()).await
+                }
+            };
+            ($req:expr, $schema:expr, $ve:ty) => {
+                async {
+                    apisdk::_send_with_schema_validation_with!($req, $schema, Json<$ve>, Self::__REQ_CONFIG.take()).await
+                }
+            };
+        }
+    }])
     .collect()
 }
 
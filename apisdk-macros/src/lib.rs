@@ -27,6 +27,35 @@ use crate::parse::parse_fields;
 /// }
 /// ```
 ///
+/// ### Options
+///
+/// - `no_default`: do not implement `Default` for the api
+/// - `health`: generate `health(path)` method, which checks whether the service is healthy
+///
+/// ```
+/// #[http_api("https://host.of.service/base/path", health)]
+/// pub struct MyApi;
+///
+/// let healthy: bool = MyApi::default().health("/health").await?;
+/// ```
+///
+/// The following options generate the method of the same name, which delegates to `ApiCore`.
+/// They are opt-in, so they don't conflict with the methods defined by the api.
+///
+/// - `follow_link`: generate `follow_link(body, pointer)` method, which builds a GET request from the link in json response
+/// - `sign_url`: generate `sign_url(method, path)` method, which builds the final url without sending it
+/// - `reset`: generate `reset(builder)` method, which replaces the config of api at runtime
+/// - `pool_stats`: generate `pool_stats()` method, which returns the statistics of connection pool
+/// - `poll_changes`: generate `poll_changes(path, interval)` method, which emits the json response when it changes
+/// - `tus_upload`: generate `tus_upload(path, file, chunk_size)` method, which uploads the file by Tus protocol
+///
+/// ```
+/// #[http_api("https://host.of.service/base/path", sign_url, pool_stats)]
+/// pub struct MyApi;
+///
+/// let url = MyApi::default().sign_url(Method::GET, "/files/1").await?;
+/// ```
+///
 /// ### Define APIs
 ///
 /// ```
@@ -13,16 +13,32 @@ use syn::{
 pub(crate) struct Metadata {
     pub base_url: Literal,
     pub default: bool,
+    pub health: bool,
+    pub follow_link: bool,
+    pub sign_url: bool,
+    pub reset: bool,
+    pub pool_stats: bool,
+    pub poll_changes: bool,
+    pub tus_upload: bool,
 }
 
 impl From<proc_macro::TokenStream> for Metadata {
     fn from(value: proc_macro::TokenStream) -> Self {
         let mut iter = value.into_iter();
         let base_url = iter.next().unwrap().to_string();
-        let default = iter.all(|i| i.to_string() != "no_default");
+        let options: Vec<String> = iter.map(|i| i.to_string()).collect();
+        let default = !options.iter().any(|o| o == "no_default");
+        let has = |name: &str| options.iter().any(|o| o == name);
         Self {
             base_url: Literal::from_str(base_url.as_str()).unwrap(),
             default,
+            health: has("health"),
+            follow_link: has("follow_link"),
+            sign_url: has("sign_url"),
+            reset: has("reset"),
+            pool_stats: has("pool_stats"),
+            poll_changes: has("poll_changes"),
+            tus_upload: has("tus_upload"),
         }
     }
 }
@@ -1,18 +1,98 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    path::Path,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT_ENCODING, ACCEPT_LANGUAGE, HOST};
+use serde_json::Value;
 
 use crate::{
-    ApiAuthenticator, ApiError, ApiResult, AuthenticateMiddleware, Client, ClientBuilder,
-    DnsResolver, Initialiser, IntoUrl, LogConfig, LogMiddleware, Method, Middleware,
-    RequestBuilder, RequestTraceIdMiddleware, ReqwestDnsResolver, ReqwestUrlRewriter, Url, UrlOps,
-    UrlRewriter,
+    ApiAuthenticator, ApiError, ApiResult, AsyncInitialiser, AsyncInitialisers,
+    AuthenticateMiddleware, AutoDateMiddleware, Certificate, Client, ClientBuilder, Clock,
+    ConcurrencyLimit, DnsResolver, DoubleJsonDecode, HostLimitMiddleware, Initialiser, IntoUrl,
+    IpVersion, JsonPolicy, LogConfig, LogMiddleware, MaxDecodedLen, Method, Middleware,
+    MockMiddleware, NoDecompress, PoolCounter, PoolStats, PoolStatsMiddleware, RequestBuilder,
+    RequestTraceIdMiddleware, ReqwestDnsResolver, ReqwestUrlRewriter, RespondAsyncMiddleware,
+    SharedClock, SingleFlight, Url, UrlOps, UrlRewriter,
 };
+#[cfg(feature = "stats")]
+use crate::{RequestStats, RequestStatsMiddleware, StatsCounter};
+
+/// The default value of `User-Agent`, such as `apisdk-rs/0.0.11`
+pub const DEFAULT_USER_AGENT: &str = concat!("apisdk-rs/", env!("CARGO_PKG_VERSION"));
+
+/// This enum controls whether an absolute URL could be used as request path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AbsoluteUrlPolicy {
+    /// Use any absolute URL verbatim
+    #[default]
+    Allow,
+    /// Only use the absolute URL with the same host and port as base_url
+    SameHost,
+    /// Reject all absolute URLs
+    Deny,
+}
+
+/// This enum controls how to handle the missing environment variables of `with_base_headers_from_env`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissingEnvPolicy {
+    /// Skip the header
+    #[default]
+    Skip,
+    /// Fail with `ApiError::EnvHeader`
+    Error,
+}
 
 /// This struct is used to build an instance of ApiCore
 pub struct ApiBuilder {
     /// Reqwest ClientBuilder
     client: ClientBuilder,
+    /// The Reqwest Client shared with other apis
+    shared_client: Option<Arc<reqwest::Client>>,
     /// Base url for target api
     base_url: Url,
+    /// The value of `User-Agent`
+    user_agent: String,
+    /// The policy of absolute URL in request path
+    absolute_url: AbsoluteUrlPolicy,
+    /// The prefix of relative paths, such as `/v2`
+    path_prefix: Option<String>,
+    /// Whether to join the base url and path verbatim
+    strict_path_join: bool,
+    /// The default timeout of requests
+    default_timeout: Option<Duration>,
+    /// The max concurrent requests of each host
+    max_connections_per_host: Option<usize>,
+    /// The max concurrent requests of the api
+    max_concurrency: Option<usize>,
+    /// The interval of TCP keep-alive probes
+    tcp_keepalive: Option<Duration>,
+    /// The timeout of idle connections in pool
+    pool_idle_timeout: Option<Duration>,
+    /// The local address to bind
+    local_address: Option<IpAddr>,
+    /// The preference of IP version
+    ip_version: IpVersion,
+    /// The resolved addresses of specific hosts
+    host_overrides: Vec<(String, SocketAddr)>,
+    /// The additional root certificates
+    root_certificates: Vec<Certificate>,
+    /// Decode compressed response automatically
+    #[cfg_attr(not(any(feature = "gzip", feature = "deflate")), allow(dead_code))]
+    auto_decompress: bool,
+    /// The advertised encodings in `Accept-Encoding`
+    accept_encoding: Option<Vec<String>>,
+    /// The max length of response decoded by apisdk
+    max_decoded_len: Option<usize>,
+    /// The default value of `Accept-Language`
+    accept_language: Option<String>,
+    /// The default headers of each HTTP method
+    method_defaults: HashMap<Method, HeaderMap>,
+    /// The default headers of all requests
+    base_headers: HeaderMap,
     /// The holder of UrlRewriter
     rewriter: Option<ReqwestUrlRewriter>,
     /// The holder of DnsResolver
@@ -21,8 +101,20 @@ pub struct ApiBuilder {
     authenticator: Option<Arc<dyn ApiAuthenticator>>,
     /// The holder of LogConfig
     logger: Option<Arc<LogConfig>>,
-    /// The initialisers for Reqwest
-    initialisers: Vec<Arc<dyn Initialiser>>,
+    /// The holder of JsonPolicy
+    json_policy: Option<Arc<JsonPolicy>>,
+    /// Unwrap the json response which is encoded as json string
+    double_json_decode: bool,
+    /// Coalesce the identical in-flight GET requests
+    single_flight: bool,
+    /// The Clock to read time and wait
+    clock: Option<Arc<dyn Clock>>,
+    /// Whether to set `Date` header automatically
+    auto_date: bool,
+    /// The initialisers for Reqwest, with priority
+    initialisers: Vec<(i32, Arc<dyn Initialiser>)>,
+    /// The async initialisers, in declared order
+    async_initialisers: Vec<Arc<dyn AsyncInitialiser>>,
     /// The middlewares for Reqwest
     middlewares: Vec<Arc<dyn Middleware>>,
 }
@@ -33,12 +125,38 @@ impl ApiBuilder {
     pub fn new(base_url: impl IntoUrl + std::fmt::Debug) -> ApiResult<Self> {
         Ok(Self {
             client: ClientBuilder::default(),
+            shared_client: None,
             base_url: base_url.into_url().map_err(ApiError::InvalidUrl)?,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            absolute_url: AbsoluteUrlPolicy::default(),
+            path_prefix: None,
+            strict_path_join: false,
+            default_timeout: None,
+            auto_decompress: true,
+            accept_encoding: None,
+            max_decoded_len: None,
+            max_connections_per_host: None,
+            max_concurrency: None,
+            tcp_keepalive: None,
+            pool_idle_timeout: None,
+            local_address: None,
+            ip_version: IpVersion::default(),
+            host_overrides: vec![],
+            root_certificates: vec![],
+            accept_language: None,
+            method_defaults: HashMap::new(),
+            base_headers: HeaderMap::new(),
             rewriter: None,
             resolver: None,
             authenticator: None,
             logger: None,
+            json_policy: None,
+            double_json_decode: false,
+            single_flight: false,
+            clock: None,
+            auto_date: false,
             initialisers: vec![],
+            async_initialisers: vec![],
             middlewares: vec![],
         })
     }
@@ -49,6 +167,284 @@ impl ApiBuilder {
         Self { client, ..self }
     }
 
+    /// Share the Reqwest Client (and its connection pool) with other apis
+    /// - client: the shared Reqwest Client
+    ///
+    /// The options of ClientBuilder are ignored, since the Client has been built:
+    /// `with_client`, `with_user_agent`, `with_auto_decompress` (for gzip/deflate decoded by reqwest),
+    /// `with_accept_encoding`, `with_accept_language`, `with_base_headers_from_env`, `with_ca_bundle_path`, `with_tcp_keepalive`,
+    /// `with_pool_idle_timeout`, `with_local_address`, `with_ip_version_preference`, `with_host_override`,
+    /// the idle pool limit of `with_max_connections_per_host`, and the DNS resolution of `with_resolver`.
+    /// A warning is logged for each of them which is set, except `with_client`.
+    /// The middlewares, initialisers, UrlRewriter, and the in-flight limit of `with_max_connections_per_host`
+    /// are kept for each api.
+    pub fn with_shared_client(self, client: Arc<reqwest::Client>) -> Self {
+        Self {
+            shared_client: Some(client),
+            ..self
+        }
+    }
+
+    /// Set the value of `User-Agent`, which is `apisdk-rs/<version>` by default
+    /// - user_agent: the value of `User-Agent`
+    ///
+    /// It's applied as a default header, so the `User-Agent` set on a single request will win.
+    pub fn with_user_agent(self, user_agent: impl ToString) -> Self {
+        Self {
+            user_agent: user_agent.to_string(),
+            ..self
+        }
+    }
+
+    /// Set the policy of absolute URL in request path, which is `AbsoluteUrlPolicy::Allow` by default
+    /// - policy: AbsoluteUrlPolicy
+    pub fn with_absolute_url(self, policy: AbsoluteUrlPolicy) -> Self {
+        Self {
+            absolute_url: policy,
+            ..self
+        }
+    }
+
+    /// Set the prefix of relative paths, such as `/v2` for API versioning
+    /// - prefix: the path prefix, the leading and trailing `/` are optional
+    ///
+    /// It's inserted between the base url (after `UrlRewriter` and `DnsResolver`) and the relative path,
+    /// so `get("/users")` with base url `http://host/api` is resolved to `http://host/api/v2/users`.
+    /// The duplicated `/` is avoided, and the absolute URL in request path is not affected.
+    pub fn with_path_prefix(self, prefix: impl AsRef<str>) -> Self {
+        let prefix = prefix.as_ref().trim().trim_matches('/');
+        Self {
+            path_prefix: (!prefix.is_empty()).then(|| format!("/{}", prefix)),
+            ..self
+        }
+    }
+
+    /// Set whether to join the base url and path verbatim, which is `false` by default
+    /// - strict: join verbatim or not
+    ///
+    /// When enabled, `build_url` collapses the `/` between base url (and path prefix) and path into exactly one,
+    /// keeps the trailing `/` of path, and leaves the base url as is for the empty path,
+    /// so `/users` and `/users/` are resolved to different URLs as written.
+    pub fn with_strict_path_join(self, strict: bool) -> Self {
+        Self {
+            strict_path_join: strict,
+            ..self
+        }
+    }
+
+    /// Set the default timeout of all requests
+    /// - timeout: the duration from sending request until the response body is received
+    ///
+    /// It's applied in `build_request`, so `req.timeout()` could override it for a single request.
+    /// The timed-out request fails with `ApiError::Timeout`.
+    pub fn with_default_timeout(self, timeout: Duration) -> Self {
+        Self {
+            default_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Set whether to decode compressed response automatically, which is `true` by default
+    /// - auto_decompress: decode or not
+    ///
+    /// With `gzip` and/or `deflate` feature, reqwest decodes the advertised encodings while streaming,
+    /// and the others (e.g. `br` or `zstd`) are decoded after the response is read.
+    /// When disabled, the compressed response will be kept as `ResponseBody::Bytes`,
+    /// and `Content-Encoding` will be preserved (see `NoDecompress`).
+    pub fn with_auto_decompress(self, auto_decompress: bool) -> Self {
+        Self {
+            auto_decompress,
+            ..self
+        }
+    }
+
+    /// Set the encodings advertised in `Accept-Encoding` for all requests
+    /// - encodings: the content codings, such as `["gzip", "br"]`
+    ///
+    /// The response is decoded by its actual `Content-Encoding`, even if it's not advertised (see `with_auto_decompress`).
+    /// An empty list advertises `identity`.
+    pub fn with_accept_encoding(self, encodings: &[&str]) -> Self {
+        Self {
+            accept_encoding: Some(encodings.iter().map(|e| e.trim().to_lowercase()).collect()),
+            ..self
+        }
+    }
+
+    /// Set the max length of response decoded by apisdk, which is `MaxDecodedLen::DEFAULT` (64 MiB) by default
+    /// - len: the max length of decoded payload
+    ///
+    /// The response exceeding it fails with `ApiError::DecodeResponse`, which prevents decompression bombs.
+    /// The codings are decoded only if their features (`gzip`, `deflate`, `brotli` and `zstd`) are enabled.
+    pub fn with_max_decoded_len(self, len: usize) -> Self {
+        Self {
+            max_decoded_len: Some(len),
+            ..self
+        }
+    }
+
+    /// Check whether the encoding should be decoded automatically
+    /// - encoding: the content coding
+    #[cfg_attr(not(any(feature = "gzip", feature = "deflate")), allow(dead_code))]
+    fn should_decompress(&self, encoding: &str) -> bool {
+        if !self.auto_decompress {
+            return false;
+        }
+        match self.accept_encoding.as_ref() {
+            Some(encodings) => encodings
+                .iter()
+                .any(|e| e.split(';').next().map(str::trim) == Some(encoding)),
+            None => true,
+        }
+    }
+
+    /// Set the default `Accept-Language` of all requests
+    /// - language: the language ranges, such as `fr-FR` or `fr-FR, fr;q=0.9`
+    ///
+    /// It's a default header of Reqwest Client, so the value set by `req.header()`, `req.headers()`
+    /// or `with_method_defaults` takes precedence.
+    pub fn with_accept_language(self, language: impl ToString) -> Self {
+        Self {
+            accept_language: Some(language.to_string()),
+            ..self
+        }
+    }
+
+    /// Set the default headers of requests with the HTTP method
+    /// - method: HTTP method
+    /// - headers: the default headers
+    ///
+    /// It's applied in `build_request`, and merged if called more than once with the same method.
+    /// Use `req.headers()` to replace the default values, since `req.header()` will append.
+    pub fn with_method_defaults(self, method: Method, headers: HeaderMap) -> Self {
+        let mut s = self;
+        s.method_defaults.entry(method).or_default().extend(headers);
+        s
+    }
+
+    /// Set the default headers of all requests from environment variables
+    /// - mappings: the pairs of environment variable and header name, such as `("API_TOKEN", "X-Api-Token")`
+    /// - missing: how to handle the missing environment variables
+    ///
+    /// The environment variables are read immediately, and the headers set on a single request will win.
+    /// `ApiError::EnvHeader` will be returned, if the header name or value is invalid,
+    /// or the variable is missing with `MissingEnvPolicy::Error`.
+    pub fn with_base_headers_from_env(
+        self,
+        mappings: &[(&str, &str)],
+        missing: MissingEnvPolicy,
+    ) -> ApiResult<Self> {
+        let mut s = self;
+        for (var, name) in mappings {
+            let value = match std::env::var(var) {
+                Ok(value) => value,
+                Err(_) if missing == MissingEnvPolicy::Skip => continue,
+                Err(e) => return Err(ApiError::EnvHeader(format!("{} => {}", var, e))),
+            };
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| ApiError::EnvHeader(format!("{} => {}", name, e)))?;
+            let value = HeaderValue::from_str(&value)
+                .map_err(|e| ApiError::EnvHeader(format!("{} => {}", var, e)))?;
+            s.base_headers.insert(name, value);
+        }
+        Ok(s)
+    }
+
+    /// Limit the connections of each host
+    /// - max: the max connections of each host
+    ///
+    /// It limits both the idle connections in pool, and the concurrent in-flight requests.
+    /// The host is resolved by UrlRewriter / DnsResolver, so the limit is applied to each endpoint.
+    pub fn with_max_connections_per_host(self, max: usize) -> Self {
+        Self {
+            max_connections_per_host: Some(max),
+            ..self
+        }
+    }
+
+    /// Limit the concurrent in-flight requests of the api
+    /// - max: the max concurrent requests
+    ///
+    /// The permit is acquired before the request is sent, and released once the response has been decoded,
+    /// or the request fails or is cancelled. The raw and streaming responses release it when the headers arrive.
+    pub fn with_max_concurrency(self, max: usize) -> Self {
+        Self {
+            max_concurrency: Some(max),
+            ..self
+        }
+    }
+
+    /// Enable TCP keep-alive on the connections
+    /// - interval: the interval of keep-alive probes
+    ///
+    /// It detects the dead connections behind NAT or firewalls, which drop the idle connections silently.
+    pub fn with_tcp_keepalive(self, interval: Duration) -> Self {
+        Self {
+            tcp_keepalive: Some(interval),
+            ..self
+        }
+    }
+
+    /// Set the timeout of idle connections in pool, which is 90 seconds by default
+    /// - timeout: how long an idle connection is kept alive
+    ///
+    /// The idle connections are evicted after the timeout, so it should be shorter than the one of peer.
+    pub fn with_pool_idle_timeout(self, timeout: Duration) -> Self {
+        Self {
+            pool_idle_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Set the local address to bind, such as the source IP on a multi-homed host
+    /// - addr: the local IP address
+    ///
+    /// If the address could not be bound, the request fails on connecting with `ApiError::Reqwest`.
+    pub fn with_local_address(self, addr: IpAddr) -> Self {
+        Self {
+            local_address: Some(addr),
+            ..self
+        }
+    }
+
+    /// Set the preference of IP version, which is `IpVersion::DualStack` by default
+    /// - ip_version: IpVersion
+    ///
+    /// It filters the resolved addresses, including the ones provided by DnsResolver.
+    pub fn with_ip_version_preference(self, ip_version: IpVersion) -> Self {
+        Self { ip_version, ..self }
+    }
+
+    /// Resolve the host to a specific address, without editing `/etc/hosts`
+    /// - host: the hostname, such as `api.example.com`
+    /// - addr: the address to connect
+    ///
+    /// It takes precedence over DnsResolver, and the `Host` header is kept as the original name.
+    /// The port of addr is ignored, since the port in url is used.
+    pub fn with_host_override(self, host: impl ToString, addr: impl Into<SocketAddr>) -> Self {
+        let mut s = self;
+        s.host_overrides.push((host.to_string(), addr.into()));
+        s
+    }
+
+    /// Trust the root certificates in a PEM bundle, in addition to the system ones
+    /// - path: the path of PEM file, which may contain many certificates
+    ///
+    /// `ApiError::InvalidCertificate` will be returned, if the file can't be read or parsed.
+    pub fn with_ca_bundle_path(self, path: impl AsRef<Path>) -> ApiResult<Self> {
+        let path = path.as_ref();
+        let invalid = |e: &dyn std::fmt::Display| {
+            ApiError::InvalidCertificate(format!("{} => {}", path.display(), e))
+        };
+        let pem = std::fs::read(path).map_err(|e| invalid(&e))?;
+        let certificates = Certificate::from_pem_bundle(&pem).map_err(|e| invalid(&e))?;
+        if certificates.is_empty() {
+            return Err(invalid(&"no certificate found"));
+        }
+        let mut s = self;
+        s.root_certificates.extend(certificates);
+        Ok(s)
+    }
+
     /// Set the UrlRewriter
     /// - resolver: UrlRewriter
     pub fn with_rewriter<T>(self, rewriter: T) -> Self
@@ -97,14 +493,112 @@ impl ApiBuilder {
         }
     }
 
-    /// Add initialiser
+    /// Set the JsonPolicy, which is used to serialize json payload of requests
+    /// - policy: JsonPolicy
+    pub fn with_json_policy(self, policy: JsonPolicy) -> Self {
+        Self {
+            json_policy: Some(Arc::new(policy)),
+            ..self
+        }
+    }
+
+    /// Set whether to unwrap the json response which is encoded as json string, which is `false` by default
+    /// - double_json_decode: unwrap or not
+    ///
+    /// It's designed for the misbehaving upstream, see `DoubleJsonDecode` for details.
+    pub fn with_double_json_decode(self, double_json_decode: bool) -> Self {
+        Self {
+            double_json_decode,
+            ..self
+        }
+    }
+
+    /// Set whether to coalesce the identical in-flight GET requests, which is `false` by default
+    /// - single_flight: coalesce or not
+    ///
+    /// The concurrent GET requests with the same url and headers share one network call, and all callers receive the cloned result.
+    /// The requests with different headers, such as `Authorization`, `Cookie` or `Accept`, are not coalesced.
+    pub fn with_single_flight(self, single_flight: bool) -> Self {
+        Self {
+            single_flight,
+            ..self
+        }
+    }
+
+    /// Set the Clock, which is `SystemClock` by default
+    /// - clock: Clock
+    ///
+    /// It's used by the timestamps of signatures, the delays of `RetryMiddleware` and `with_respond_async`, and the expiry of `CachedCredentials`.
+    /// Use `ManualClock` in tests to advance time deterministically.
+    pub fn with_clock<T>(self, clock: T) -> Self
+    where
+        T: Clock,
+    {
+        Self {
+            clock: Some(Arc::new(clock)),
+            ..self
+        }
+    }
+
+    /// Set `Date` header of all requests automatically, in the format of RFC 7231
+    /// - enabled: whether to set `Date` header
+    ///
+    /// It's applied when the request is sent, with the time of `Clock` set by `with_clock`,
+    /// so each retry, hedged request and poll gets a fresh value.
+    /// The `Date` header set by `req.header()`, `req.headers()` or `with_method_defaults` is kept.
+    pub fn with_auto_date(self, enabled: bool) -> Self {
+        Self {
+            auto_date: enabled,
+            ..self
+        }
+    }
+
+    /// Add initialiser with default priority (0)
     /// - initialiser: Reqwest Initialiser
+    ///
+    /// The initialisers will be applied in ascending order of priority.
+    /// For the same priority, they will be applied in the order they were added.
     pub fn with_initialiser<T>(self, initialiser: T) -> Self
+    where
+        T: Initialiser,
+    {
+        self.with_initialiser_priority(initialiser, 0)
+    }
+
+    /// Add initialiser with priority
+    /// - initialiser: Reqwest Initialiser
+    /// - priority: the initialiser with lower priority will be applied earlier
+    pub fn with_initialiser_priority<T>(self, initialiser: T, priority: i32) -> Self
     where
         T: Initialiser,
     {
         let mut s = self;
-        s.initialisers.push(Arc::new(initialiser));
+        s.initialisers.push((priority, Arc::new(initialiser)));
+        s
+    }
+
+    /// Add many initialisers with default priority (0), in declared order
+    /// - initialisers: Reqwest Initialisers
+    pub fn with_initialisers<I>(self, initialisers: I) -> Self
+    where
+        I: IntoIterator<Item = Arc<dyn Initialiser>>,
+    {
+        let mut s = self;
+        s.initialisers
+            .extend(initialisers.into_iter().map(|initialiser| (0, initialiser)));
+        s
+    }
+
+    /// Add async initialiser, whose Initialiser is loaded before the first request
+    /// - initialiser: AsyncInitialiser
+    ///
+    /// Use `build_async` to load it eagerly, and fail fast.
+    pub fn with_async_initialiser<T>(self, initialiser: T) -> Self
+    where
+        T: AsyncInitialiser,
+    {
+        let mut s = self;
+        s.async_initialisers.push(Arc::new(initialiser));
         s
     }
 
@@ -119,55 +613,259 @@ impl ApiBuilder {
         s
     }
 
+    /// Get the options which are ignored by the shared Reqwest Client
+    fn ignored_by_shared_client(&self) -> Vec<&'static str> {
+        let mut ignored = vec![];
+        if self.user_agent != DEFAULT_USER_AGENT {
+            ignored.push("with_user_agent");
+        }
+        if cfg!(any(feature = "gzip", feature = "deflate")) && !self.auto_decompress {
+            ignored.push("with_auto_decompress");
+        }
+        if self.accept_encoding.is_some() {
+            ignored.push("with_accept_encoding");
+        }
+        if !self.base_headers.is_empty() {
+            ignored.push("with_base_headers_from_env");
+        }
+        if self.accept_language.is_some() {
+            ignored.push("with_accept_language");
+        }
+        if !self.root_certificates.is_empty() {
+            ignored.push("with_ca_bundle_path");
+        }
+        if self.max_connections_per_host.is_some() {
+            ignored.push("with_max_connections_per_host");
+        }
+        if self.tcp_keepalive.is_some() {
+            ignored.push("with_tcp_keepalive");
+        }
+        if self.pool_idle_timeout.is_some() {
+            ignored.push("with_pool_idle_timeout");
+        }
+        if self.local_address.is_some() {
+            ignored.push("with_local_address");
+        }
+        if self.ip_version != IpVersion::default() {
+            ignored.push("with_ip_version_preference");
+        }
+        if !self.host_overrides.is_empty() {
+            ignored.push("with_host_override");
+        }
+        if self.resolver.is_some() {
+            ignored.push("with_resolver");
+        }
+        ignored
+    }
+
     /// Build an instance of ApiCore
     pub fn build(self) -> ApiCore {
-        let client = match self.resolver.clone() {
-            Some(r) => self.client.dns_resolver(Arc::new(r)),
-            None => self.client,
+        if self.shared_client.is_some() {
+            for option in self.ignored_by_shared_client() {
+                log::warn!("`{}` is ignored by the shared client", option);
+            }
+        }
+        #[cfg(feature = "gzip")]
+        let gzip = self.should_decompress("gzip");
+        #[cfg(feature = "deflate")]
+        let deflate = self.should_decompress("deflate");
+        let client = self.client.user_agent(self.user_agent);
+        #[cfg(feature = "gzip")]
+        let client = client.gzip(gzip);
+        #[cfg(feature = "deflate")]
+        let client = client.deflate(deflate);
+        let client = match self.accept_encoding {
+            Some(encodings) => {
+                let value = if encodings.is_empty() {
+                    "identity".to_string()
+                } else {
+                    encodings.join(", ")
+                };
+                let mut headers = HeaderMap::new();
+                if let Ok(value) = HeaderValue::from_str(&value) {
+                    headers.insert(ACCEPT_ENCODING, value);
+                }
+                client.default_headers(headers)
+            }
+            None => client,
+        };
+        let client = if self.base_headers.is_empty() {
+            client
+        } else {
+            client.default_headers(self.base_headers)
         };
-        let mut client = reqwest_middleware::ClientBuilder::new(client.build().unwrap());
+        let client = match self
+            .accept_language
+            .as_deref()
+            .and_then(|language| HeaderValue::from_str(language).ok())
+        {
+            Some(value) => {
+                let mut headers = HeaderMap::new();
+                headers.insert(ACCEPT_LANGUAGE, value);
+                client.default_headers(headers)
+            }
+            None => client,
+        };
+        let client = self
+            .root_certificates
+            .into_iter()
+            .fold(client, |client, cert| client.add_root_certificate(cert));
+        let client = match self.max_connections_per_host {
+            Some(max) => client.pool_max_idle_per_host(max),
+            None => client,
+        };
+        let client = match self.tcp_keepalive {
+            Some(interval) => client.tcp_keepalive(interval),
+            None => client,
+        };
+        let client = match self.pool_idle_timeout {
+            Some(timeout) => client.pool_idle_timeout(timeout),
+            None => client,
+        };
+        let client = match self.local_address {
+            Some(addr) => client.local_address(addr),
+            None => client,
+        };
+        let pool_counter = PoolCounter::default();
+        let resolver = self
+            .resolver
+            .clone()
+            .unwrap_or_else(ReqwestDnsResolver::system)
+            .with_ip_version(self.ip_version)
+            .with_counter(pool_counter.clone());
+        let client = client.dns_resolver(Arc::new(resolver));
+        let client = self
+            .host_overrides
+            .iter()
+            .fold(client, |client, (host, addr)| client.resolve(host, *addr));
+        let client = match self.shared_client.as_deref() {
+            Some(shared) => shared.clone(),
+            None => client.build().unwrap(),
+        };
+        let mut client = reqwest_middleware::ClientBuilder::new(client);
 
         // Apply middleware in correct order
         client = client.with(RequestTraceIdMiddleware);
+        client = client.with(RespondAsyncMiddleware);
         // client = client.with(RewriteHostMiddleware);
         for middleware in self.middlewares {
             client = client.with_arc(middleware);
         }
+        if self.auto_date {
+            client = client.with(AutoDateMiddleware);
+        }
         if self.authenticator.is_some() {
             client = client.with(AuthenticateMiddleware);
         }
         client = client.with(LogMiddleware);
+        if let Some(max) = self.max_connections_per_host {
+            client = client.with(HostLimitMiddleware::new(max));
+        }
+        #[cfg(feature = "stats")]
+        let stats_counter = StatsCounter::default();
+        #[cfg(feature = "stats")]
+        {
+            client = client.with(RequestStatsMiddleware(stats_counter.clone()));
+        }
+        client = client.with(MockMiddleware);
+        client = client.with(PoolStatsMiddleware(pool_counter.clone()));
 
         // Apply initialisers
+        // LogConfig, JsonPolicy, DoubleJsonDecode, SingleFlight, ConcurrencyLimit and Clock go first, then others in ascending order of priority
         if let Some(logger) = self.logger {
             client = client.with_arc_init(logger);
         }
-        for initialiser in self.initialisers {
+        if let Some(policy) = self.json_policy {
+            client = client.with_arc_init(policy);
+        }
+        if self.double_json_decode {
+            client = client.with_init(DoubleJsonDecode);
+        }
+        if !self.auto_decompress {
+            client = client.with_init(NoDecompress);
+        }
+        if let Some(len) = self.max_decoded_len {
+            client = client.with_init(MaxDecodedLen(len));
+        }
+        if self.single_flight {
+            client = client.with_init(SingleFlight::default());
+        }
+        if let Some(max) = self.max_concurrency {
+            client = client.with_init(ConcurrencyLimit::new(max));
+        }
+        if let Some(clock) = self.clock.clone() {
+            client = client.with_init(SharedClock(clock));
+        }
+        let mut initialisers = self.initialisers;
+        initialisers.sort_by_key(|(priority, _)| *priority);
+        for (_, initialiser) in initialisers {
             client = client.with_arc_init(initialiser);
         }
 
         ApiCore {
             client: client.build(),
+            shared_client: self.shared_client,
             base_url: self.base_url,
+            absolute_url: self.absolute_url,
+            path_prefix: self.path_prefix,
+            strict_path_join: self.strict_path_join,
+            default_timeout: self.default_timeout,
+            method_defaults: Arc::new(self.method_defaults),
+            async_initialisers: Arc::new(AsyncInitialisers::new(self.async_initialisers)),
             rewriter: self.rewriter,
             resolver: self.resolver,
             authenticator: self.authenticator,
+            pool_counter,
+            #[cfg(feature = "stats")]
+            stats_counter,
+            replaced: Arc::default(),
         }
     }
+
+    /// Build an instance of ApiCore, and load the AsyncInitialisers eagerly
+    ///
+    /// It fails if any AsyncInitialiser fails to load.
+    pub async fn build_async(self) -> ApiResult<ApiCore> {
+        let core = self.build();
+        core.async_initialisers.load().await?;
+        Ok(core)
+    }
 }
 
 /// This struct is used to create HTTP request
+#[derive(Clone)]
 pub struct ApiCore {
     /// Reqwest Client
     client: Client,
+    /// The Reqwest Client shared with other apis
+    shared_client: Option<Arc<reqwest::Client>>,
     /// Base url for target api
     base_url: Url,
+    /// The policy of absolute URL in request path
+    absolute_url: AbsoluteUrlPolicy,
+    /// The prefix of relative paths
+    path_prefix: Option<String>,
+    /// Whether to join the base url and path verbatim
+    strict_path_join: bool,
+    /// The default timeout of requests
+    default_timeout: Option<Duration>,
+    /// The default headers of each HTTP method
+    method_defaults: Arc<HashMap<Method, HeaderMap>>,
+    /// The holder of AsyncInitialisers
+    async_initialisers: Arc<AsyncInitialisers>,
     /// The holder of ReqwestUrlRewriter
     rewriter: Option<ReqwestUrlRewriter>,
     /// The holder of ReqwestDnsResolver
     resolver: Option<ReqwestDnsResolver>,
     /// The holder of ApiAuthenticator
     authenticator: Option<Arc<dyn ApiAuthenticator>>,
+    /// The counter of connection pool
+    pool_counter: PoolCounter,
+    /// The counter of requests
+    #[cfg(feature = "stats")]
+    stats_counter: StatsCounter,
+    /// The ApiCore which replaces this one by `reset`, shared by the clones
+    replaced: Arc<RwLock<Option<Arc<ApiCore>>>>,
 }
 
 impl std::fmt::Debug for ApiCore {
@@ -185,20 +883,46 @@ impl std::fmt::Debug for ApiCore {
         if let Some(s) = self.authenticator.as_ref() {
             d = d.field("authenticator", &s.type_name());
         }
+        if !self.async_initialisers.is_empty() {
+            d = d.field("async_initialisers", &self.async_initialisers.type_names());
+        }
         d.finish()
     }
 }
 
 impl ApiCore {
+    /// Replace the config of this ApiCore, such as hot config reloads
+    /// - core: the ApiCore with new config
+    ///
+    /// Each request takes the config when it's built, so the in-flight requests
+    /// finish on the old config, and the new ones use the new config.
+    pub fn reset(&self, core: ApiCore) {
+        *self.replaced.write().unwrap() = Some(Arc::new(core));
+    }
+
+    /// Get the ApiCore which replaces this one by `reset`, if any
+    fn replaced(&self) -> Option<Arc<ApiCore>> {
+        self.replaced.read().unwrap().clone()
+    }
+
+    /// Copy the current config, which is not affected by `reset` of this one
+    fn snapshot(&self) -> Self {
+        let core = match self.replaced() {
+            Some(core) => core.as_ref().clone(),
+            None => self.clone(),
+        };
+        Self {
+            replaced: Arc::default(),
+            ..core
+        }
+    }
+
     /// Create a new ApiCore with a different base_url
     pub fn rebase(&self, base_url: impl IntoUrl) -> ApiResult<Self> {
         let base_url = base_url.into_url().map_err(ApiError::InvalidUrl)?;
         Ok(Self {
-            client: self.client.clone(),
             base_url,
-            rewriter: self.rewriter.clone(),
-            resolver: self.resolver.clone(),
-            authenticator: self.authenticator.clone(),
+            ..self.snapshot()
         })
     }
 
@@ -209,11 +933,8 @@ impl ApiCore {
         T: UrlRewriter,
     {
         Self {
-            client: self.client.clone(),
-            base_url: self.base_url.clone(),
             rewriter: Some(ReqwestUrlRewriter::new(rewriter)),
-            resolver: self.resolver.clone(),
-            authenticator: self.authenticator.clone(),
+            ..self.snapshot()
         }
     }
 
@@ -224,11 +945,8 @@ impl ApiCore {
         T: DnsResolver,
     {
         Self {
-            client: self.client.clone(),
-            base_url: self.base_url.clone(),
-            rewriter: self.rewriter.clone(),
             resolver: Some(ReqwestDnsResolver::new(resolver)),
-            authenticator: self.authenticator.clone(),
+            ..self.snapshot()
         }
     }
 
@@ -248,11 +966,37 @@ impl ApiCore {
         T: ApiAuthenticator,
     {
         Self {
-            client: self.client.clone(),
-            base_url: self.base_url.clone(),
-            rewriter: self.rewriter.clone(),
-            resolver: self.resolver.clone(),
             authenticator: Some(Arc::new(authenticator)),
+            ..self.snapshot()
+        }
+    }
+
+    /// Get the statistics of connection pool
+    ///
+    /// The new connections are not counted, if the Client is set by `with_shared_client`.
+    pub fn pool_stats(&self) -> PoolStats {
+        match self.replaced() {
+            Some(core) => core.pool_stats(),
+            None => self.pool_counter.stats(),
+        }
+    }
+
+    /// Get the statistics of requests, such as the number of requests and the latency summary
+    ///
+    /// It's only available with the `stats` feature, which is designed for performance regression tests.
+    #[cfg(feature = "stats")]
+    pub fn request_stats(&self) -> RequestStats {
+        match self.replaced() {
+            Some(core) => core.request_stats(),
+            None => self.stats_counter.stats(),
+        }
+    }
+
+    /// Get the shared Reqwest Client, if set by `with_shared_client`
+    pub fn shared_client(&self) -> Option<Arc<reqwest::Client>> {
+        match self.replaced() {
+            Some(core) => core.shared_client(),
+            None => self.shared_client.clone(),
         }
     }
 
@@ -269,28 +1013,164 @@ impl ApiCore {
     }
 
     /// Build a new request url
-    /// - path: relative path to base_url
+    /// - path: relative path to base_url, or absolute URL
+    ///
+    /// The absolute URL (`http://` or `https://`) will be used verbatim, according to AbsoluteUrlPolicy.
+    /// The relative path is joined as configured by `with_strict_path_join`.
     ///
     /// Return error when failed to retrieve valid endpoint from ApiRouter
     pub async fn build_url(&self, path: impl AsRef<str>) -> ApiResult<Url> {
-        let base = self.build_base_url().await?;
-        Ok(base.merge_path(path.as_ref()))
+        let replaced = self.replaced();
+        let core = replaced.as_deref().unwrap_or(self);
+        let path = path.as_ref();
+        if let Some(url) = core.parse_absolute_url(path)? {
+            return Ok(url);
+        }
+        let base = core.build_base_url().await?;
+        let base = match core.path_prefix.as_ref() {
+            Some(prefix) => base.merge_path(prefix),
+            None => base,
+        };
+        if core.strict_path_join {
+            Ok(base.merge_path_strict(path))
+        } else {
+            Ok(base.merge_path(path))
+        }
+    }
+
+    /// Parse the absolute URL in path, and check it by AbsoluteUrlPolicy
+    /// - path: relative path to base_url, or absolute URL
+    fn parse_absolute_url(&self, path: &str) -> ApiResult<Option<Url>> {
+        if !is_absolute_url(path) {
+            return Ok(None);
+        }
+        let url = into_url(path)?;
+        let allowed = match self.absolute_url {
+            AbsoluteUrlPolicy::Allow => true,
+            AbsoluteUrlPolicy::SameHost => {
+                url.host_str() == self.base_url.host_str()
+                    && url.port_or_known_default() == self.base_url.port_or_known_default()
+            }
+            AbsoluteUrlPolicy::Deny => false,
+        };
+        if allowed {
+            Ok(Some(url))
+        } else {
+            Err(ApiError::UrlNotAllowed(url.to_string()))
+        }
     }
 
     /// Build a new HTTP request
     /// - method: HTTP method
     /// - path: relative path to base_url
+    ///
+    /// The `Host` header provided by DnsResolver will be applied, unless path is an absolute URL.
+    /// The method not allowed by DnsResolver is rejected by `ApiError::MethodNotAllowedByRouter`.
+    /// The ApiAuthenticator is attached as an extension, and applied to the final url when sending.
     pub async fn build_request(
         &self,
         method: Method,
         path: impl AsRef<str>,
     ) -> ApiResult<RequestBuilder> {
-        let url = self.build_url(path.as_ref()).await?;
-        let req = self.client.request(method, url);
+        let replaced = self.replaced();
+        let core = replaced.as_deref().unwrap_or(self);
+        if let Some(resolver) = core.resolver.as_ref() {
+            if !resolver.allows(&method) {
+                return Err(ApiError::MethodNotAllowedByRouter(method));
+            }
+        }
+        let path = path.as_ref();
+        let url = core.build_url(path).await?;
+        let defaults = core.method_defaults.get(&method).cloned();
+        let mut req = core.client.request(method, url);
+        if let Some(timeout) = core.default_timeout {
+            req = req.timeout(timeout);
+        }
+        if let Some(headers) = defaults {
+            req = req.headers(headers);
+        }
+        req = core.async_initialisers.init(req).await?;
+        if !is_absolute_url(path) {
+            if let Some(host) = core.resolver.as_ref().and_then(|r| r.host_header()) {
+                req = req.header(HOST, host);
+            }
+        }
 
-        match self.authenticator.clone() {
+        match core.authenticator.clone() {
             Some(authenticator) => Ok(req.with_extension(authenticator)),
             None => Ok(req),
         }
     }
+
+    /// Build a GET request to follow the link in json response, such as `_links.next.href` of HAL
+    /// - body: the json response
+    /// - pointer: JSON Pointer to the link, such as `/_links/next/href`
+    ///
+    /// The link is resolved against `Content-Location` in `__headers__` if present, or the base url with path prefix,
+    /// as a directory. So `users?page=2` and `/v1/users?page=2` are both resolved to `http://host/v1/users?page=2`
+    /// with base url `http://host/v1`. The link under base url is built as relative path,
+    /// and others as absolute URL according to AbsoluteUrlPolicy.
+    pub async fn follow_link(&self, body: &Value, pointer: &str) -> ApiResult<RequestBuilder> {
+        let replaced = self.replaced();
+        let core = replaced.as_deref().unwrap_or(self);
+        let href = body
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .ok_or_else(|| ApiError::InvalidLink(format!("no link at {}", pointer)))?;
+        let invalid = |e: url::ParseError| ApiError::InvalidLink(format!("{} => {}", href, e));
+
+        let mut base = match core.path_prefix.as_ref() {
+            Some(prefix) => core.base_url.clone().merge_path(prefix),
+            None => core.base_url.clone(),
+        };
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        let location = body
+            .pointer("/__headers__/content-location")
+            .and_then(Value::as_str);
+        let document = match location {
+            Some(location) => base.join(location).map_err(invalid)?,
+            None => base.clone(),
+        };
+        let mut url = document.join(href).map_err(invalid)?;
+        url.set_fragment(None);
+
+        match url.as_str().strip_prefix(base.as_str()) {
+            Some(path) => core.build_request(Method::GET, format!("/{}", path)).await,
+            None => core.build_request(Method::GET, url.as_str()).await,
+        }
+    }
+
+    /// Build the final url of request without sending it, such as a pre-signed URL
+    /// - method: HTTP method
+    /// - path: relative path to base_url, or absolute URL
+    ///
+    /// The ApiAuthenticator will be applied, so the token carried by `Carrier::QueryParam`
+    /// will be included. The headers added by ApiAuthenticator are dropped.
+    pub async fn sign_url(&self, method: Method, path: impl AsRef<str>) -> ApiResult<Url> {
+        let replaced = self.replaced();
+        let core = replaced.as_deref().unwrap_or(self);
+        let mut req = core.build_request(method, path).await?;
+        let extensions = std::mem::take(req.extensions());
+        let req = req.build().map_err(ApiError::BuildRequest)?;
+        let req = match core.authenticator.as_ref() {
+            Some(authenticator) => authenticator.authenticate(req, &extensions).await?,
+            None => req,
+        };
+        Ok(req.url().clone())
+    }
+}
+
+/// Check whether the path is an absolute URL (`http://` or `https://`)
+/// - path: relative path to base_url, or absolute URL
+fn is_absolute_url(path: &str) -> bool {
+    let lower = path.get(..8).unwrap_or(path).to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// Convert to Url
+/// - url: the value to convert
+fn into_url(url: impl IntoUrl) -> ApiResult<Url> {
+    url.into_url().map_err(ApiError::InvalidUrl)
 }
@@ -1,4 +1,5 @@
 use base64::{engine::general_purpose, DecodeError, Engine};
+use hmac::{Hmac, Mac};
 use md5::{Digest, Md5};
 use sha1::Sha1;
 use sha2::Sha256;
@@ -72,6 +73,28 @@ pub fn sha256_base64(input: impl AsRef<[u8]>) -> String {
     encode_base64(sha256_raw(input))
 }
 
+/// Calc HMAC-SHA256, and encode via hex
+pub fn hmac_sha256(key: impl AsRef<[u8]>, input: impl AsRef<[u8]>) -> String {
+    hex::encode(hmac_sha256_raw(key, input))
+}
+
+/// Calc HMAC-SHA256
+pub fn hmac_sha256_raw(key: impl AsRef<[u8]>, input: impl AsRef<[u8]>) -> impl AsRef<[u8]> {
+    let mut mac = new_hmac_sha256(key);
+    mac.update(input.as_ref());
+    mac.finalize().into_bytes()
+}
+
+/// Calc HMAC-SHA256, and encode via base64
+pub fn hmac_sha256_base64(key: impl AsRef<[u8]>, input: impl AsRef<[u8]>) -> String {
+    encode_base64(hmac_sha256_raw(key, input))
+}
+
+/// Create HMAC-SHA256 instance, which accepts key of any length
+pub(crate) fn new_hmac_sha256(key: impl AsRef<[u8]>) -> Hmac<Sha256> {
+    Hmac::<Sha256>::new_from_slice(key.as_ref()).expect("HMAC accepts key of any length")
+}
+
 /// Encode base64
 pub fn encode_base64(input: impl AsRef<[u8]>) -> String {
     general_purpose::STANDARD.encode(input)
@@ -123,4 +146,13 @@ mod tests {
             output
         );
     }
+
+    #[test]
+    fn test_hmac_sha256() {
+        let output = hmac_sha256("key", "The quick brown fox jumps over the lazy dog");
+        assert_eq!(
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+            output
+        );
+    }
 }
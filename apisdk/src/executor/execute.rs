@@ -1,12 +1,22 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, convert::Infallible, future::Future, pin::pin};
 
-use reqwest::{header::CONTENT_TYPE, Response, ResponseBuilderExt};
-use serde::Serialize;
+use futures::future::{select, Either};
+
+use hyper::body::Bytes;
+use reqwest::{
+    header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, ETAG},
+    Body, Response, ResponseBuilderExt, StatusCode,
+};
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
+use tokio::sync::{mpsc::Receiver, OwnedSemaphorePermit};
 
 use crate::{
-    get_default_log_level, ApiError, ApiResult, FormLike, IntoFilter, LogConfig, Logger, MimeType,
-    MockServer, RequestBuilder, RequestId, RequestTraceIdMiddleware, Responder, ResponseBody,
+    get_boundary, get_default_log_level, parse_multipart, ApiError, ApiResult, CancellationToken,
+    ConcurrencyLimit, ContentEncoding, DoubleJsonDecode, FormLike, GrpcWebJson, IntoFilter, Json,
+    JsonArrayStream, JsonPolicy, LineStream, LogConfig, Logger, MaxDecodedLen, MimeType,
+    NdjsonStream, NoDecompress, RequestBuilder, RequestId, RequestTraceIdMiddleware, ResponseBody,
+    ResponseRenameAll, SingleFlight,
 };
 
 /// This struct is used to build RequestConfig internally by macros.
@@ -47,11 +57,18 @@ impl RequestConfigurator {
     fn build(self, req: &mut RequestBuilder) -> (Logger, bool) {
         let extensions = req.extensions();
 
-        let log_filter = extensions
-            .get::<LogConfig>()
+        let config = extensions.get::<LogConfig>();
+        let log_filter = config
             .map(|config| config.level)
             .or(self.log_filter)
             .unwrap_or(get_default_log_level());
+        let max_body_len = config.and_then(|config| config.max_body_len);
+        let redact = config
+            .map(|config| config.redact.clone())
+            .unwrap_or_default();
+        let response_headers = config
+            .map(|config| config.response_headers.clone())
+            .unwrap_or_default();
 
         let request_id = extensions
             .get::<RequestId>()
@@ -59,7 +76,10 @@ impl RequestConfigurator {
             .unwrap_or_default();
 
         (
-            Logger::new(self.log_target, log_filter, request_id),
+            Logger::new(self.log_target, log_filter, request_id)
+                .with_max_body_len(max_body_len)
+                .with_redact(redact)
+                .with_response_headers(response_headers),
             self.require_headers,
         )
     }
@@ -68,7 +88,7 @@ impl RequestConfigurator {
 /// Send request
 /// - req: used to build request
 /// - config: control the send process
-pub async fn send(mut req: RequestBuilder, config: RequestConfigurator) -> ApiResult<ResponseBody> {
+pub async fn send(mut req: RequestBuilder, config: RequestConfigurator) -> ApiResult<DecodedBody> {
     // Inject extensions
     req = RequestTraceIdMiddleware::inject_extension(req);
     let (logger, require_headers) = config.build(&mut req);
@@ -87,26 +107,57 @@ pub async fn send_json<I>(
     mut req: RequestBuilder,
     json: &I,
     config: RequestConfigurator,
-) -> ApiResult<ResponseBody>
+) -> ApiResult<DecodedBody>
 where
     I: Serialize + ?Sized,
 {
-    req = req.json(json);
+    // Apply JsonPolicy if provided
+    let policy = req.extensions().get::<JsonPolicy>().cloned();
+    let payload = match policy {
+        Some(policy) => {
+            Some(policy.apply(serde_json::to_value(json).map_err(ApiError::EncodeJson)?))
+        }
+        None => None,
+    };
+    req = match payload.as_ref() {
+        Some(payload) => req.json(payload),
+        None => req.json(json),
+    };
 
     // Inject extensions
     req = RequestTraceIdMiddleware::inject_extension(req);
     let (logger, require_headers) = config.build(&mut req);
     if logger.is_enabled() {
-        req = req.with_extension(
-            logger
-                .clone()
-                .with_json(serde_json::to_value(json).unwrap_or_default()),
-        );
+        let payload = payload.unwrap_or_else(|| serde_json::to_value(json).unwrap_or_default());
+        req = req.with_extension(logger.clone().with_json(payload));
     }
 
     send_and_parse(req, logger, require_headers).await
 }
 
+/// Send request with JSON payload, and deserialize JSON response
+/// - req: used to build request
+/// - json: request payload
+/// - config: control the send process
+///
+/// The errors of each phase are distinguishable:
+/// - serialize payload: `ApiError::EncodeJson`
+/// - send request: `ApiError::Reqwest`, `ApiError::HttpClientStatus`, `ApiError::HttpServerStatus`, etc.
+/// - deserialize response: `ApiError::DecodeJson`, `ApiError::IncompatibleContentType`, etc.
+pub async fn send_json_to<I, T>(
+    req: RequestBuilder,
+    json: &I,
+    config: RequestConfigurator,
+) -> ApiResult<T>
+where
+    I: Serialize + ?Sized,
+    T: 'static + DeserializeOwned,
+{
+    let json = serde_json::to_value(json).map_err(ApiError::EncodeJson)?;
+    let body = send_json(req, &json, config).await?;
+    body.extract(Json::try_parse)
+}
+
 /// Send request with xml payload
 /// - req: used to build request
 /// - form: request payload
@@ -115,7 +166,7 @@ pub async fn send_xml<I>(
     mut req: RequestBuilder,
     xml: &I,
     config: RequestConfigurator,
-) -> ApiResult<ResponseBody>
+) -> ApiResult<DecodedBody>
 where
     I: Serialize + ?Sized,
 {
@@ -140,19 +191,18 @@ pub async fn send_form<I>(
     mut req: RequestBuilder,
     form: I,
     config: RequestConfigurator,
-) -> ApiResult<ResponseBody>
+) -> ApiResult<DecodedBody>
 where
     I: FormLike,
 {
+    let form = form.resolve();
     let is_multipart = form.is_multipart();
     let meta = form.get_meta();
 
     if is_multipart {
-        if let Some(multipart) = form.get_multipart() {
-            req = req.multipart(multipart)
-        }
-    } else if let Some(form) = form.get_form() {
-        req = req.form(&form);
+        req = multipart_body(req, form)?;
+    } else if let Some(fields) = form.get_form_fields() {
+        req = req.form(&fields);
     };
 
     // Inject extensions
@@ -178,13 +228,13 @@ pub async fn send_multipart<I>(
     mut req: RequestBuilder,
     form: I,
     config: RequestConfigurator,
-) -> ApiResult<ResponseBody>
+) -> ApiResult<DecodedBody>
 where
     I: FormLike,
 {
-    let form = form.get_multipart().ok_or(ApiError::MultipartForm)?;
+    let form = form.resolve();
     let meta = form.get_meta();
-    req = req.multipart(form);
+    req = multipart_body(req, form)?;
 
     // Inject extensions
     req = RequestTraceIdMiddleware::inject_extension(req);
@@ -196,6 +246,55 @@ where
     send_and_parse(req, logger, require_headers).await
 }
 
+/// Set the multipart form as request body, which is encoded with the custom boundary if present
+/// - req: used to build request
+/// - form: the resolved multipart form
+fn multipart_body<I>(req: RequestBuilder, form: I) -> ApiResult<RequestBuilder>
+where
+    I: FormLike,
+{
+    match form.get_boundary() {
+        Some(boundary) => {
+            let body = form
+                .encode_multipart(&boundary)
+                .ok_or(ApiError::MultipartForm)?;
+            Ok(req
+                .header(
+                    CONTENT_TYPE,
+                    format!("multipart/form-data; boundary={}", boundary),
+                )
+                .body(body))
+        }
+        None => Ok(req.multipart(form.get_multipart().ok_or(ApiError::MultipartForm)?)),
+    }
+}
+
+/// This struct holds the decoded response body, which is used to extract result internally by macros.
+#[derive(Debug)]
+pub struct DecodedBody {
+    /// The decoded response body
+    body: ResponseBody,
+    /// Helper to log messages
+    logger: Logger,
+}
+
+impl DecodedBody {
+    /// Extract result from response body, and log the body at debug level if failed
+    /// - extract: convert response body to result
+    pub fn extract<T, F>(self, extract: F) -> ApiResult<T>
+    where
+        F: FnOnce(ResponseBody) -> ApiResult<T>,
+    {
+        let Self { body, logger } = self;
+        let backup = logger.is_debug_enabled().then(|| body.clone());
+        extract(body).inspect_err(|e| {
+            if let Some(body) = backup {
+                logger.log_extract_error(&body, e);
+            }
+        })
+    }
+}
+
 /// Send request, and get raw response
 /// - req: used to build request
 /// - config: control the send process
@@ -210,43 +309,227 @@ pub async fn send_raw(mut req: RequestBuilder, config: RequestConfigurator) -> A
     send_and_unparse(req, logger).await
 }
 
+/// Send request, and check whether the status of response is success, without parsing body
+/// - req: used to build request
+/// - config: control the send process
+pub async fn probe(mut req: RequestBuilder, config: RequestConfigurator) -> ApiResult<bool> {
+    req = RequestTraceIdMiddleware::inject_extension(req);
+
+    let (logger, _) = config.build(&mut req);
+    if logger.is_enabled() {
+        req = req.with_extension(logger.clone());
+    }
+
+    let res = send_and_unparse(req, logger).await?;
+    Ok(res.status().is_success())
+}
+
+/// Attach the items as newline-delimited json payload (content-type = application/x-ndjson)
+/// - req: used to build request
+/// - items: the items to serialize, one per line
+///
+/// The items are serialized eagerly, so the request could still be retried or duplicated.
+pub fn ndjson_body<I>(req: RequestBuilder, items: I) -> ApiResult<RequestBuilder>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut body = vec![];
+    for item in items {
+        serde_json::to_writer(&mut body, &item).map_err(ApiError::EncodeJson)?;
+        body.push(b'\n');
+    }
+    Ok(req.header(CONTENT_TYPE, "application/x-ndjson").body(body))
+}
+
+/// Attach the precomputed bytes as payload, with the explicit content type
+/// - req: used to build request
+/// - bytes: the serialized payload, which is sent as is
+/// - content_type: the value of `Content-Type`
+///
+/// No serialization is involved, so it suits passthrough or proxy, e.g. `application/cbor` or `application/protobuf`.
+pub fn bytes_body<B>(req: RequestBuilder, bytes: B, content_type: impl AsRef<str>) -> RequestBuilder
+where
+    B: Into<Body>,
+{
+    req.header(CONTENT_TYPE, content_type.as_ref()).body(bytes)
+}
+
+/// Attach the chunks received from channel as streaming payload (transfer-encoding = chunked)
+/// - req: used to build request
+/// - receiver: the receiver of chunks, the request body ends once all senders are dropped
+///
+/// A bounded channel provides backpressure, since the next chunk is only received after the previous one is written.
+/// The body could not be cloned, so the request will not be retried or duplicated.
+pub fn channel_body<T>(req: RequestBuilder, receiver: Receiver<T>) -> RequestBuilder
+where
+    T: Into<Bytes> + Send + 'static,
+{
+    let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+        let chunk = receiver.recv().await?;
+        Some((Ok::<_, Infallible>(chunk.into()), receiver))
+    });
+    req.body(Body::wrap_stream(stream))
+}
+
+/// Send request, and decode response as newline-delimited json stream
+/// - req: used to build request
+/// - config: control the send process
+pub async fn send_ndjson<T>(
+    mut req: RequestBuilder,
+    config: RequestConfigurator,
+) -> ApiResult<NdjsonStream<T>> {
+    req = RequestTraceIdMiddleware::inject_extension(req);
+
+    let (logger, _) = config.build(&mut req);
+    if logger.is_enabled() {
+        req = req.with_extension(logger.clone());
+    }
+
+    let grpc = req.extensions().contains::<GrpcWebJson>();
+    let res = send_and_unparse(req, logger.clone()).await?;
+    let res = check_status(res, &logger, grpc).await?;
+    Ok(NdjsonStream::new(res))
+}
+
+/// Send request, and decode response as a stream of text lines
+/// - req: used to build request
+/// - config: control the send process
+pub async fn send_lines(
+    mut req: RequestBuilder,
+    config: RequestConfigurator,
+) -> ApiResult<LineStream> {
+    req = RequestTraceIdMiddleware::inject_extension(req);
+
+    let (logger, _) = config.build(&mut req);
+    if logger.is_enabled() {
+        req = req.with_extension(logger.clone());
+    }
+
+    let grpc = req.extensions().contains::<GrpcWebJson>();
+    let res = send_and_unparse(req, logger.clone()).await?;
+    let res = check_status(res, &logger, grpc).await?;
+    Ok(LineStream::new(res))
+}
+
+/// Send request, and decode the elements of json array response as stream
+/// - req: used to build request
+/// - config: control the send process
+pub async fn send_json_array<T>(
+    mut req: RequestBuilder,
+    config: RequestConfigurator,
+) -> ApiResult<JsonArrayStream<T>> {
+    req = RequestTraceIdMiddleware::inject_extension(req);
+
+    let (logger, _) = config.build(&mut req);
+    if logger.is_enabled() {
+        req = req.with_extension(logger.clone());
+    }
+
+    let grpc = req.extensions().contains::<GrpcWebJson>();
+    let res = send_and_unparse(req, logger.clone()).await?;
+    let res = check_status(res, &logger, grpc).await?;
+    Ok(JsonArrayStream::new(res))
+}
+
+/// Check the status code of response
+/// - res: HTTP response
+/// - logger: helper to log messages
+/// - grpc: try to parse `google.rpc.Status` from error body
+async fn check_status(res: Response, logger: &Logger, grpc: bool) -> ApiResult<Response> {
+    let status = res.status();
+    if status == StatusCode::PRECONDITION_FAILED {
+        // Keep the current ETag, so the caller could refetch-and-retry
+        let etag = res
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        let e = ApiError::PreconditionFailed(etag);
+        logger.log_error(&e);
+        return Err(e);
+    }
+    if status.is_client_error() || status.is_server_error() {
+        let grpc_error = if grpc {
+            let json = res.json::<Value>().await.ok();
+            json.and_then(|json| GrpcWebJson::parse_status(&json))
+        } else {
+            None
+        };
+        let e = grpc_error.unwrap_or_else(|| ApiError::from_status(status));
+        logger.log_error(&e);
+        return Err(e);
+    }
+    Ok(res)
+}
+
 /// Send request, and return unparsed response
 /// - req: the request to send
 /// - logger: helper to log messages
-async fn send_and_unparse(mut req: RequestBuilder, logger: Logger) -> ApiResult<Response> {
-    let extensions = req.extensions();
-
-    // Mock
-    if let Some(mock) = extensions.get::<MockServer>().cloned() {
-        let req = req.build().map_err(ApiError::BuildRequest)?;
-        logger.log_mock_request_and_response(&req, mock.type_name());
-        let url = req.url().clone();
-        match mock.handle(req).await {
-            Ok(body) => {
-                logger.log_mock_response_body(&body);
-                let (content_type, text) = match body {
-                    ResponseBody::Json(json) => (MimeType::Json, json.to_string()),
-                    ResponseBody::Xml(xml) => (MimeType::Xml, xml),
-                    ResponseBody::Text(text) => (MimeType::Text, text),
-                };
-                let res = hyper::Response::builder()
-                    .url(url)
-                    .header(CONTENT_TYPE, content_type.to_string())
-                    .body(text)
-                    .map_err(|_| {
-                        ApiError::Middleware(anyhow::format_err!("Failed to build response"))
-                    })?;
-                return Ok(Response::from(res));
-            }
-            Err(e) => {
-                logger.log_error(&e);
-                return Err(ApiError::Middleware(e));
-            }
+async fn send_and_unparse(mut req: RequestBuilder, mut logger: Logger) -> ApiResult<Response> {
+    start_timer(&mut req, &mut logger);
+    let token = req.extensions().get::<CancellationToken>().cloned();
+    cancellable(token, &logger, send_and_receive(req, logger.clone())).await
+}
+
+/// Send request, and receive the response
+/// - req: the request to send
+/// - logger: helper to log messages
+async fn send_and_receive(mut req: RequestBuilder, logger: Logger) -> ApiResult<Response> {
+    let _permit = acquire_permit(&mut req).await?;
+    let res = match req.send().await {
+        Ok(res) => res,
+        Err(e) => {
+            let e = ApiError::from(e);
+            logger.log_error(&e);
+            return Err(e);
         }
+    };
+    Ok(res)
+}
+
+/// Run the future, and abort it once the `CancellationToken` is cancelled
+/// - token: the optional token
+/// - logger: helper to log messages
+/// - fut: the future to run
+async fn cancellable<T>(
+    token: Option<CancellationToken>,
+    logger: &Logger,
+    fut: impl Future<Output = ApiResult<T>>,
+) -> ApiResult<T> {
+    let token = match token {
+        Some(token) => token,
+        None => return fut.await,
+    };
+
+    match select(token.cancelled(), pin!(fut)).await {
+        Either::Left(_) => {
+            let e = ApiError::Cancelled;
+            logger.log_error(&e);
+            Err(e)
+        }
+        Either::Right((result, _)) => result,
+    }
+}
+
+/// Wait for the permit of `ConcurrencyLimit`, which should be held until the response is received
+/// - req: the request to send
+async fn acquire_permit(req: &mut RequestBuilder) -> ApiResult<Option<OwnedSemaphorePermit>> {
+    let limit = req.extensions().get::<ConcurrencyLimit>().cloned();
+    match limit {
+        Some(limit) => limit.acquire().await.map(Some),
+        None => Ok(None),
     }
+}
 
-    let res = req.send().await?;
-    Ok(res)
+/// Restart the timer of logger, right before the request is sent
+/// - req: the request to send
+/// - logger: helper to log messages
+fn start_timer(req: &mut RequestBuilder, logger: &mut Logger) {
+    logger.restart();
+    if let Some(logger) = req.extensions().get_mut::<Logger>() {
+        logger.restart();
+    }
 }
 
 /// Send request, and parse response as desired type
@@ -254,43 +537,97 @@ async fn send_and_unparse(mut req: RequestBuilder, logger: Logger) -> ApiResult<
 /// - logger: helper to log messages
 /// - require_headers: should zip headers into response body
 async fn send_and_parse(
+    mut req: RequestBuilder,
+    mut logger: Logger,
+    require_headers: bool,
+) -> ApiResult<DecodedBody> {
+    start_timer(&mut req, &mut logger);
+    let token = req.extensions().get::<CancellationToken>().cloned();
+    let flight = req
+        .extensions()
+        .get::<SingleFlight>()
+        .cloned()
+        .and_then(|flight| Some((SingleFlight::key_of(&req, require_headers)?, flight)));
+    let call = send_and_decode(req, logger.clone(), require_headers);
+    let body = match flight {
+        Some((key, flight)) => cancellable(token, &logger, flight.run(key, call)).await?,
+        None => cancellable(token, &logger, call).await?,
+    };
+    Ok(DecodedBody { body, logger })
+}
+
+/// Send request, and decode response body
+/// - req: the request to send
+/// - logger: helper to log messages
+/// - require_headers: should zip headers into response body
+async fn send_and_decode(
     mut req: RequestBuilder,
     logger: Logger,
     require_headers: bool,
 ) -> ApiResult<ResponseBody> {
-    let extensions = req.extensions();
-
-    // Mock
-    if let Some(mock) = extensions.get::<MockServer>().cloned() {
-        let req = req.build().map_err(ApiError::BuildRequest)?;
-        logger.log_mock_request_and_response(&req, mock.type_name());
-        match mock.handle(req).await {
-            Ok(body) => {
-                logger.log_mock_response_body(&body);
-                return Ok(body);
-            }
-            Err(e) => {
-                logger.log_error(&e);
-                return Err(ApiError::Middleware(e));
-            }
-        }
-    }
-
     // Send the request
-    let res = req.send().await?;
+    let grpc = req.extensions().contains::<GrpcWebJson>();
+    let double_json = req.extensions().get::<DoubleJsonDecode>().copied();
+    let rename_all = req.extensions().get::<ResponseRenameAll>().copied();
+    let no_decompress = req.extensions().contains::<NoDecompress>();
+    let max_decoded_len = req
+        .extensions()
+        .get::<MaxDecodedLen>()
+        .copied()
+        .unwrap_or_default();
+    let _permit = acquire_permit(&mut req).await?;
+    let res = match req.send().await {
+        Ok(res) => res,
+        Err(e) => {
+            let e = ApiError::from(e);
+            logger.log_error(&e);
+            return Err(e);
+        }
+    };
 
     // Check status code
-    let status = res.status();
-    let res = if status.is_client_error() || status.is_server_error() {
-        let e = if status.is_client_error() {
-            ApiError::HttpClientStatus(status.as_u16(), status.to_string())
-        } else {
-            ApiError::HttpServerStatus(status.as_u16(), status.to_string())
-        };
-        logger.log_error(&e);
-        return Err(e);
-    } else {
-        res
+    let res = check_status(res, &logger, grpc).await?;
+    logger.log_response_headers(res.headers());
+
+    // Nothing to parse, such as 202 / 204 or empty 200
+    // The body is read to tell, since chunked body has no content-length
+    let res = match read_content(res).await {
+        Ok(Some(res)) => res,
+        Ok(None) => {
+            logger.log_response_empty();
+            return Ok(ResponseBody::Empty);
+        }
+        Err(e) => {
+            logger.log_error(&e);
+            return Err(e);
+        }
+    };
+
+    // Dispatch on content-encoding, before parsing payload
+    // The advertised encodings have been decoded by reqwest, and the header is removed,
+    // so decode the rest here, such as gzip without `gzip` feature, br or zstd,
+    // unless the compressed payload is required by `NoDecompress`
+    let content_encoding = res
+        .headers()
+        .get(CONTENT_ENCODING)
+        .map(|v| String::from_utf8_lossy(v.as_bytes()).to_string());
+    let res = match content_encoding {
+        Some(content_encoding) => match ContentEncoding::parse_list(&content_encoding) {
+            Ok(encodings) if encodings.is_empty() => res,
+            Ok(_) if no_decompress => return parse_as_bytes(res, content_encoding, logger).await,
+            Ok(encodings) => match decode_content(res, &encodings, max_decoded_len).await {
+                Ok(res) => res,
+                Err(e) => {
+                    logger.log_error(&e);
+                    return Err(e);
+                }
+            },
+            Err(e) => {
+                logger.log_error(&e);
+                return Err(e);
+            }
+        },
+        None => res,
     };
 
     // Check content-type, and parse payload
@@ -301,9 +638,20 @@ async fn send_and_parse(
         .map(MimeType::from)
         .unwrap_or(MimeType::Text);
     match content_type {
-        MimeType::Json => parse_as_json(res, content_type, logger, require_headers).await,
+        MimeType::Json => {
+            parse_as_json(
+                res,
+                content_type,
+                logger,
+                require_headers,
+                double_json,
+                rename_all,
+            )
+            .await
+        }
         MimeType::Xml => parse_as_xml(res, content_type, logger).await,
-        MimeType::Text => parse_as_text(res, content_type, logger).await,
+        MimeType::Text | MimeType::Form => parse_as_text(res, content_type, logger).await,
+        MimeType::Multipart(_) => parse_as_multipart(res, content_type, logger).await,
         _ => Err(ApiError::UnsupportedContentType(content_type)),
     }
 }
@@ -314,6 +662,8 @@ async fn parse_as_json(
     content_type: MimeType,
     logger: Logger,
     require_headers: bool,
+    double_json: Option<DoubleJsonDecode>,
+    rename_all: Option<ResponseRenameAll>,
 ) -> ApiResult<ResponseBody> {
     // Extract HTTP headers from response
     let headers = if require_headers {
@@ -341,6 +691,16 @@ async fn parse_as_json(
         }
     };
 
+    // Unwrap the json string, which is encoded once more
+    if let Some(double_json) = double_json {
+        json = double_json.apply(json);
+    }
+
+    // Rename the fields, before the headers are injected
+    if let Some(rename_all) = rename_all {
+        json = rename_all.apply(json);
+    }
+
     // Inject headers as `__headers__` field into payload
     // Extractor could parse the `__headers__` field if required
     if let Some(headers) = headers {
@@ -397,3 +757,159 @@ async fn parse_as_text(
 
     Ok(ResponseBody::Text(text))
 }
+
+/// Decode response body, and rebuild the response with decoded payload
+/// - res: the response to decode
+/// - encodings: the content codings, in applied order
+/// - limit: the max length of decoded payload
+async fn decode_content(
+    res: Response,
+    encodings: &[ContentEncoding],
+    limit: MaxDecodedLen,
+) -> ApiResult<Response> {
+    if let Some(encoding) = encodings.iter().find(|e| !e.is_supported()) {
+        return Err(ApiError::UnsupportedContentEncoding(encoding.to_string()));
+    }
+
+    let status = res.status();
+    let version = res.version();
+    let url = res.url().clone();
+    let mut headers = res.headers().clone();
+    let content_type = || {
+        headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(MimeType::from)
+            .unwrap_or(MimeType::Text)
+    };
+
+    // Read response, and remove the codings in reverse order
+    let mut data = res
+        .bytes()
+        .await
+        .map_err(|e| ApiError::DecodeResponse(content_type(), e.to_string()))?
+        .to_vec();
+    for encoding in encodings.iter().rev() {
+        data = encoding.decode(&data, limit.0).map_err(|e| {
+            ApiError::DecodeResponse(
+                content_type(),
+                format!("Failed to decode {}: {}", encoding, e),
+            )
+        })?;
+    }
+
+    // Rebuild the response with decoded body
+    headers.remove(CONTENT_ENCODING);
+    headers.remove(CONTENT_LENGTH);
+    let mut rebuilt = hyper::Response::builder()
+        .status(status)
+        .version(version)
+        .url(url)
+        .body(data)
+        .unwrap_or_default();
+    *rebuilt.headers_mut() = headers;
+    Ok(Response::from(rebuilt))
+}
+
+/// Read response body, and rebuild the response with the buffered payload
+///
+/// Return `None` if the body is empty
+async fn read_content(res: Response) -> ApiResult<Option<Response>> {
+    if res.status() == StatusCode::NO_CONTENT || res.content_length() == Some(0) {
+        return Ok(None);
+    }
+
+    let status = res.status();
+    let version = res.version();
+    let url = res.url().clone();
+    let headers = res.headers().clone();
+    let data = res.bytes().await.map_err(|e| {
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(MimeType::from)
+            .unwrap_or(MimeType::Text);
+        ApiError::DecodeResponse(content_type, e.to_string())
+    })?;
+    if data.is_empty() {
+        return Ok(None);
+    }
+
+    let mut rebuilt = hyper::Response::builder()
+        .status(status)
+        .version(version)
+        .url(url)
+        .body(data)
+        .unwrap_or_default();
+    *rebuilt.headers_mut() = headers;
+    Ok(Some(Response::from(rebuilt)))
+}
+
+/// Parse response body to raw bytes, which are not decoded
+async fn parse_as_bytes(
+    res: Response,
+    content_encoding: String,
+    logger: Logger,
+) -> ApiResult<ResponseBody> {
+    let content_type = res
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+
+    // Read response
+    let data = match res.bytes().await {
+        Ok(bytes) => bytes.to_vec(),
+        Err(e) => {
+            let e = ApiError::DecodeResponse(MimeType::from(content_type.as_str()), e.to_string());
+            logger.log_error(&e);
+            return Err(e);
+        }
+    };
+    logger.log_response_bytes(Some(&content_encoding), &data);
+
+    Ok(ResponseBody::Bytes {
+        content_type,
+        content_encoding: Some(content_encoding),
+        data,
+    })
+}
+
+/// Parse response body to multipart
+async fn parse_as_multipart(
+    res: Response,
+    content_type: MimeType,
+    logger: Logger,
+) -> ApiResult<ResponseBody> {
+    // Retrieve boundary from content-type
+    let boundary = match res
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(get_boundary)
+    {
+        Some(boundary) => boundary,
+        None => {
+            let e = ApiError::DecodeResponse(content_type, "Missing boundary".to_string());
+            logger.log_error(&e);
+            return Err(e);
+        }
+    };
+
+    // Decode response, and split into parts
+    let parts = match res.bytes().await {
+        Ok(bytes) => parse_multipart(&boundary, &bytes),
+        Err(e) => Err(ApiError::DecodeResponse(content_type, e.to_string())),
+    };
+    match parts {
+        Ok(parts) => {
+            logger.log_response_multipart(&parts);
+            Ok(ResponseBody::Multipart(parts))
+        }
+        Err(e) => {
+            logger.log_error(&e);
+            Err(e)
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
 
-use reqwest::multipart::{Form, Part};
+use reqwest::{
+    header::{HeaderMap, HeaderValue, CONTENT_TYPE},
+    multipart::{Form, Part},
+};
 use serde_json::Value;
 
 /// This trait provides form related functions
@@ -11,8 +14,37 @@ pub trait FormLike {
     fn get_meta(&self) -> HashMap<String, String>;
     /// Treat the form as an urlencoded form
     fn get_form(self) -> Option<HashMap<String, String>>;
+    /// Treat the form as an urlencoded form, whose fields are kept in order
+    ///
+    /// The ordered forms, such as `Vec<(K, V)>`, will keep the repeated keys.
+    fn get_form_fields(self) -> Option<Vec<(String, String)>>
+    where
+        Self: Sized,
+    {
+        self.get_form().map(|form| form.into_iter().collect())
+    }
     /// Treat the form as a multipart form
     fn get_multipart(self) -> Option<Form>;
+    /// Get the custom boundary of multipart form, which is random if `None`
+    fn get_boundary(&self) -> Option<String> {
+        None
+    }
+    /// Encode the multipart form with the custom boundary
+    ///
+    /// Return `None` if the form could not be encoded.
+    fn encode_multipart(self, _boundary: &str) -> Option<Vec<u8>>
+    where
+        Self: Sized,
+    {
+        None
+    }
+    /// Evaluate the lazy fields, which is called once when sending
+    fn resolve(self) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
 }
 
 impl<K, V> FormLike for &[(K, V)]
@@ -40,6 +72,40 @@ where
         Some(form)
     }
 
+    fn get_form_fields(self) -> Option<Vec<(String, String)>> {
+        Some(
+            self.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    fn get_multipart(self) -> Option<Form> {
+        None
+    }
+}
+
+impl<K, V> FormLike for Vec<(K, V)>
+where
+    K: ToString,
+    V: ToString,
+{
+    fn is_multipart(&self) -> bool {
+        false
+    }
+
+    fn get_meta(&self) -> HashMap<String, String> {
+        self.as_slice().get_meta()
+    }
+
+    fn get_form(self) -> Option<HashMap<String, String>> {
+        self.as_slice().get_form()
+    }
+
+    fn get_form_fields(self) -> Option<Vec<(String, String)>> {
+        self.as_slice().get_form_fields()
+    }
+
     fn get_multipart(self) -> Option<Form> {
         None
     }
@@ -208,17 +274,177 @@ impl MultipartFormOps for Form {
     }
 }
 
+/// This struct represents a part of `MultipartForm`, whose content type could be set explicitly
+///
+/// Unlike `reqwest::multipart::Part`, it could be encoded with the custom boundary.
+#[derive(Debug, Clone)]
+pub struct MultipartPart {
+    /// The payload
+    data: Vec<u8>,
+    /// The value of `Content-Type`
+    content_type: Option<String>,
+    /// The file name
+    file_name: Option<String>,
+}
+
+impl MultipartPart {
+    /// Create a text part
+    /// - value: the text
+    pub fn text(value: impl ToString) -> Self {
+        Self::bytes(value.to_string())
+    }
+
+    /// Create a binary part
+    /// - data: the payload
+    pub fn bytes(data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            data: data.into(),
+            content_type: None,
+            file_name: None,
+        }
+    }
+
+    /// Set the content type of part, such as `application/json`
+    /// - content_type: the value of `Content-Type`
+    pub fn with_content_type(self, content_type: impl ToString) -> Self {
+        Self {
+            content_type: Some(content_type.to_string()),
+            ..self
+        }
+    }
+
+    /// Set the file name of part
+    /// - file_name: the file name
+    pub fn with_file_name(self, file_name: impl ToString) -> Self {
+        Self {
+            file_name: Some(file_name.to_string()),
+            ..self
+        }
+    }
+
+    /// Convert to `reqwest::multipart::Part`
+    fn into_part(self) -> Part {
+        let mut part = Part::bytes(self.data);
+        if let Some(file_name) = self.file_name {
+            part = part.file_name(file_name);
+        }
+        if let Some(value) = self
+            .content_type
+            .and_then(|v| HeaderValue::from_str(&v).ok())
+        {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, value);
+            part = part.headers(headers);
+        }
+        part
+    }
+
+    /// Encode the part with its name
+    /// - name: the field name
+    /// - buf: the buffer to write
+    fn encode(&self, name: &str, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(b"Content-Disposition: form-data; name=\"");
+        buf.extend_from_slice(escape_quoted(name).as_bytes());
+        buf.extend_from_slice(b"\"");
+        if let Some(file_name) = &self.file_name {
+            buf.extend_from_slice(b"; filename=\"");
+            buf.extend_from_slice(escape_quoted(file_name).as_bytes());
+            buf.extend_from_slice(b"\"");
+        }
+        buf.extend_from_slice(b"\r\n");
+        if let Some(content_type) = &self.content_type {
+            buf.extend_from_slice(b"Content-Type: ");
+            buf.extend_from_slice(content_type.as_bytes());
+            buf.extend_from_slice(b"\r\n");
+        }
+        buf.extend_from_slice(b"\r\n");
+        buf.extend_from_slice(&self.data);
+        buf.extend_from_slice(b"\r\n");
+    }
+}
+
+/// Escape the value of quoted string in `Content-Disposition`
+fn escape_quoted(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// This enum represents a field of `MultipartForm`
+#[derive(Debug)]
+enum MultipartField {
+    /// The part which could be encoded with the custom boundary
+    Typed(MultipartPart),
+    /// The opaque `reqwest::multipart::Part`
+    Reqwest(Part),
+}
+
 /// This struct wraps `reqwest::multipart::Form`
+///
+/// The boundary is random by default, and could be set by `with_boundary`.
+///
+/// # Examples
+///
+/// ```
+/// let form = MultipartForm::new()
+///     .with_boundary("my-boundary")
+///     .text("name", "file.json")
+///     .with_part("file", MultipartPart::bytes(data).with_content_type("application/json"));
+/// ```
 #[derive(Debug, Default)]
 pub struct MultipartForm {
     meta: HashMap<String, String>,
-    form: Form,
+    fields: Vec<(Cow<'static, str>, MultipartField)>,
+    boundary: Option<String>,
 }
 
 impl MultipartForm {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Set the boundary, instead of the random one
+    /// - boundary: the boundary
+    ///
+    /// The `reqwest::multipart::Part` added by `part` could not be encoded with the custom boundary,
+    /// so use `with_part` instead, or `ApiError::MultipartForm` will be returned when sending.
+    pub fn with_boundary(self, boundary: impl ToString) -> Self {
+        Self {
+            boundary: Some(boundary.to_string()),
+            ..self
+        }
+    }
+
+    /// Add a part, whose content type could be set explicitly
+    /// - name: the field name
+    /// - part: MultipartPart
+    pub fn with_part<T>(self, name: T, part: MultipartPart) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let Self {
+            mut meta,
+            mut fields,
+            boundary,
+        } = self;
+        let name = name.into();
+        meta.insert(
+            name.to_string(),
+            format!(
+                "MultipartPart {{ len: {}, content_type: {:?}, file_name: {:?} }}",
+                part.data.len(),
+                part.content_type,
+                part.file_name
+            ),
+        );
+        fields.push((name, MultipartField::Typed(part)));
+        Self {
+            meta,
+            fields,
+            boundary,
+        }
+    }
 }
 
 impl FormLike for MultipartForm {
@@ -235,7 +461,36 @@ impl FormLike for MultipartForm {
     }
 
     fn get_multipart(self) -> Option<Form> {
-        Some(self.form)
+        let form = self
+            .fields
+            .into_iter()
+            .fold(Form::new(), |form, (name, field)| match field {
+                MultipartField::Typed(part) => form.part(name, part.into_part()),
+                MultipartField::Reqwest(part) => form.part(name, part),
+            });
+        Some(form)
+    }
+
+    fn get_boundary(&self) -> Option<String> {
+        self.boundary.clone()
+    }
+
+    fn encode_multipart(self, boundary: &str) -> Option<Vec<u8>> {
+        let mut buf = vec![];
+        for (name, field) in &self.fields {
+            let part = match field {
+                MultipartField::Typed(part) => part,
+                MultipartField::Reqwest(_) => return None,
+            };
+            buf.extend_from_slice(b"--");
+            buf.extend_from_slice(boundary.as_bytes());
+            buf.extend_from_slice(b"\r\n");
+            part.encode(name, &mut buf);
+        }
+        buf.extend_from_slice(b"--");
+        buf.extend_from_slice(boundary.as_bytes());
+        buf.extend_from_slice(b"--\r\n");
+        Some(buf)
     }
 }
 
@@ -245,23 +500,82 @@ impl MultipartFormOps for MultipartForm {
         T: Into<Cow<'static, str>>,
         U: Into<Cow<'static, str>>,
     {
-        let Self { mut meta, mut form } = self;
+        let Self {
+            mut meta,
+            mut fields,
+            boundary,
+        } = self;
         let name = name.into();
         let value = value.into();
         meta.insert(name.to_string(), value.to_string());
-        form = form.text(name, value);
-        Self { meta, form }
+        fields.push((name, MultipartField::Typed(MultipartPart::text(value))));
+        Self {
+            meta,
+            fields,
+            boundary,
+        }
     }
 
     fn part<T>(self, name: T, part: Part) -> Self
     where
         T: Into<Cow<'static, str>>,
     {
-        let Self { mut meta, mut form } = self;
+        let Self {
+            mut meta,
+            mut fields,
+            boundary,
+        } = self;
         let name = name.into();
         meta.insert(name.to_string(), format!("{:?}", part));
-        form = form.part(name, part);
-        Self { meta, form }
+        fields.push((name, MultipartField::Reqwest(part)));
+        Self {
+            meta,
+            fields,
+            boundary,
+        }
+    }
+}
+
+/// This struct holds the closure to produce the value of form field when sending,
+/// such as timestamps, signatures and environment variables
+#[derive(Clone)]
+pub struct LazyValue(Arc<dyn Fn() -> String + Send + Sync>);
+
+impl LazyValue {
+    /// Create a new instance
+    /// - f: the closure to produce value
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    /// Create a new instance, which reads the environment variable
+    /// - key: the name of environment variable, whose value is empty if not present
+    pub fn env(key: impl ToString) -> Self {
+        let key = key.to_string();
+        Self::new(move || std::env::var(&key).unwrap_or_default())
+    }
+
+    /// Produce the value
+    pub fn get(&self) -> String {
+        (self.0)()
+    }
+}
+
+impl<F> From<F> for LazyValue
+where
+    F: Fn() -> String + Send + Sync + 'static,
+{
+    fn from(f: F) -> Self {
+        Self::new(f)
+    }
+}
+
+impl std::fmt::Debug for LazyValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LazyValue")
     }
 }
 
@@ -272,6 +586,7 @@ where
     T: FormLike + MultipartFormOps,
 {
     map: HashMap<Cow<'static, str>, Cow<'static, str>>,
+    lazy: Vec<(Cow<'static, str>, LazyValue)>,
     form: Option<T>,
 }
 
@@ -279,6 +594,26 @@ impl DynamicForm {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Add a data field, whose value is produced when sending
+    /// - name: the field name
+    /// - value: LazyValue, or closure
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let form = DynamicForm::new()
+    ///     .text_with("timestamp", || now().to_string())
+    ///     .text_with("token", LazyValue::env("API_TOKEN"));
+    /// ```
+    pub fn text_with<T>(self, name: T, value: impl Into<LazyValue>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let mut s = self;
+        s.lazy.push((name.into(), value.into()));
+        s
+    }
 }
 
 impl MultipartFormOps for DynamicForm {
@@ -287,19 +622,24 @@ impl MultipartFormOps for DynamicForm {
         T: Into<Cow<'static, str>>,
         U: Into<Cow<'static, str>>,
     {
-        let Self { mut map, form } = self;
+        let Self {
+            mut map,
+            lazy,
+            form,
+        } = self;
         map.insert(name.into(), value.into());
-        Self { map, form }
+        Self { map, lazy, form }
     }
 
     fn part<T>(self, name: T, part: Part) -> Self
     where
         T: Into<Cow<'static, str>>,
     {
-        let Self { map, form } = self;
+        let Self { map, lazy, form } = self;
         let form = form.unwrap_or_default().part(name, part);
         Self {
             map,
+            lazy,
             form: Some(form),
         }
     }
@@ -315,15 +655,19 @@ impl FormLike for DynamicForm {
         self.map.iter().for_each(|(k, v)| {
             meta.insert(k.to_string(), v.to_string());
         });
+        self.lazy.iter().for_each(|(k, v)| {
+            meta.insert(k.to_string(), format!("{:?}", v));
+        });
         meta
     }
 
     fn get_form(self) -> Option<HashMap<String, String>> {
-        match self.form {
+        let s = self.resolve();
+        match s.form {
             Some(_) => None,
             None => {
                 let mut form = HashMap::new();
-                for (k, v) in self.map {
+                for (k, v) in s.map {
                     form.insert(k.to_string(), v.to_string());
                 }
                 Some(form)
@@ -332,14 +676,31 @@ impl FormLike for DynamicForm {
     }
 
     fn get_multipart(self) -> Option<Form> {
-        let mut form = self
+        let s = self.resolve();
+        let mut form = s
             .form
             .unwrap_or_default()
             .get_multipart()
             .unwrap_or_default();
-        for (k, v) in self.map {
+        for (k, v) in s.map {
             form = form.text(k, v);
         }
         Some(form)
     }
+
+    fn resolve(self) -> Self {
+        let Self {
+            mut map,
+            lazy,
+            form,
+        } = self;
+        for (k, v) in lazy {
+            map.insert(k, Cow::Owned(v.get()));
+        }
+        Self {
+            map,
+            lazy: vec![],
+            form,
+        }
+    }
 }
@@ -29,10 +29,14 @@ macro_rules! _function_path {
 ///     - send the request, parse response as xml, then use quick_xml to deserialize it
 /// - `send!(req, Text)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send the request, parse response as text, then use FromStr to deserialize it
+/// - `send!(req, Form)` -> `impl Future<Output = ApiResult<T>>`
+///     - send the request, parse response as form-urlencoded, then use serde_urlencoded to deserialize it
 /// - `send!(req, OtherType)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send the request, parse response as json, and use `OtherType` as JsonExtractor
 /// - `send!(req, Json<OtherType>)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send the request, parse response as json, and use `OtherType` as JsonExtractor
+/// - `send!(req, |body| ...)` -> `impl Future<Output = ApiResult<T>>`
+///     - send the request, verify response status, and use the closure `FnOnce(ResponseBody) -> ApiResult<T>` to extract result
 ///
 /// ### Built-in JsonExtractors
 ///
@@ -75,6 +79,28 @@ macro_rules! send {
             Ok(())
         }
     };
+    ($req:expr, |$($closure:tt)+) => {
+        $crate::_send_with!(
+            $req,
+            (|$($closure)+),
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            )
+        )
+    };
+    ($req:expr, move |$($closure:tt)+) => {
+        $crate::_send_with!(
+            $req,
+            (move |$($closure)+),
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            )
+        )
+    };
     ($req:expr, Body) => {
         async {
             $crate::__internal::send(
@@ -86,7 +112,7 @@ macro_rules! send {
                 ),
             )
             .await
-            .and_then(|c| c.try_into())
+            .and_then(|c| c.extract(|c| c.try_into()))
         }
     };
     ($req:expr, Json) => {
@@ -98,6 +124,9 @@ macro_rules! send {
     ($req:expr, Text) => {
         $crate::send!($req, $crate::Text, ())
     };
+    ($req:expr, Form) => {
+        $crate::send!($req, $crate::Form, ())
+    };
     ($req:expr, $parser:ty, ()) => {
         async {
             let result = $crate::__internal::send(
@@ -109,7 +138,7 @@ macro_rules! send {
                 ),
             )
             .await?;
-            <$parser>::try_parse(result)
+            result.extract(|result| <$parser>::try_parse(result))
         }
     };
     ($req:expr, Json<$ve:ty>) => {
@@ -130,8 +159,10 @@ macro_rules! send {
                 ),
             )
             .await?;
-            let result = <$parser>::try_parse::<$ve>(result)?;
-            <$ve>::try_extract(result)
+            result.extract(|result| {
+                let result = <$parser>::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
         }
     };
 }
@@ -150,11 +181,31 @@ macro_rules! _send_with {
             Ok(())
         }
     };
+    ($req:expr, (|$($closure:tt)+), $config:expr) => {
+        async {
+            $crate::__internal::send(
+                $req,
+                $config.merge($crate::_function_path!(), false),
+            )
+            .await?
+            .extract(|$($closure)+)
+        }
+    };
+    ($req:expr, (move |$($closure:tt)+), $config:expr) => {
+        async {
+            $crate::__internal::send(
+                $req,
+                $config.merge($crate::_function_path!(), false),
+            )
+            .await?
+            .extract(move |$($closure)+)
+        }
+    };
     ($req:expr, Body, $config:expr) => {
         async {
             $crate::__internal::send($req, $config.merge($crate::_function_path!(), true))
                 .await
-                .and_then(|c| c.try_into())
+                .and_then(|c| c.extract(|c| c.try_into()))
         }
     };
     ($req:expr, Json, $config:expr) => {
@@ -166,12 +217,15 @@ macro_rules! _send_with {
     ($req:expr, Text, $config:expr) => {
         $crate::_send_with!($req, $crate::Text, (), $config)
     };
+    ($req:expr, Form, $config:expr) => {
+        $crate::_send_with!($req, $crate::Form, (), $config)
+    };
     ($req:expr, $parser:ty, (), $config:expr) => {
         async {
             let result =
                 $crate::__internal::send($req, $config.merge($crate::_function_path!(), false))
                     .await?;
-            <$parser>::try_parse(result)
+            result.extract(|result| <$parser>::try_parse(result))
         }
     };
     ($req:expr, Json<$ve:ty>, $config:expr) => {
@@ -188,8 +242,10 @@ macro_rules! _send_with {
                 $config.merge($crate::_function_path!(), <$ve>::require_headers()),
             )
             .await?;
-            let result = <$parser>::try_parse::<$ve>(result)?;
-            <$ve>::try_extract(result)
+            result.extract(|result| {
+                let result = <$parser>::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
         }
     };
 }
@@ -210,10 +266,14 @@ macro_rules! _send_with {
 ///     - send the request, parse response as xml, then use quick_xml to deserialize it
 /// - `send_json!(req, json, Text)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send the request, parse response as text, then use FromStr to deserialize it
+/// - `send_json!(req, json, Form)` -> `impl Future<Output = ApiResult<T>>`
+///     - send the request, parse response as form-urlencoded, then use serde_urlencoded to deserialize it
 /// - `send_json!(req, json, OtherType)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send json, parse response as json, and use `OtherType` as JsonExtractor
 /// - `send_json!(req, json, Json<OtherType>)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send json, parse response as json, and use `OtherType` as JsonExtractor
+/// - `send_json!(req, json, |body| ...)` -> `impl Future<Output = ApiResult<T>>`
+///     - send json, verify response status, and use the closure `FnOnce(ResponseBody) -> ApiResult<T>` to extract result
 ///
 /// # Examples
 ///
@@ -225,6 +285,13 @@ macro_rules! _send_with {
 /// let res: TypeOfResponse = send_json!(req, data).await?;
 /// ```
 ///
+/// The payload is sent with any HTTP method, such as `DELETE` with a query body required by Elasticsearch.
+///
+/// ```
+/// let req = client.delete("/index/_query").await?;
+/// let res: TypeOfResponse = send_json!(req, query).await?;
+/// ```
+///
 /// Please reference `send` for more information
 #[macro_export]
 macro_rules! send_json {
@@ -246,6 +313,28 @@ macro_rules! send_json {
             Ok(())
         }
     };
+    ($req:expr, $json:expr, |$($closure:tt)+) => {
+        $crate::_send_json_with!(
+            $req, $json,
+            (|$($closure)+),
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            )
+        )
+    };
+    ($req:expr, $json:expr, move |$($closure:tt)+) => {
+        $crate::_send_json_with!(
+            $req, $json,
+            (move |$($closure)+),
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            )
+        )
+    };
     ($req:expr, $json:expr, Body) => {
         async {
             $crate::__internal::send_json(
@@ -258,7 +347,7 @@ macro_rules! send_json {
                 ),
             )
             .await
-            .and_then(|c| c.try_into())
+            .and_then(|c| c.extract(|c| c.try_into()))
         }
     };
     ($req:expr, $json:expr, Json) => {
@@ -270,6 +359,9 @@ macro_rules! send_json {
     ($req:expr, $json:expr, Text) => {
         $crate::send_json!($req, $json, $crate::Text, ())
     };
+    ($req:expr, $json:expr, Form) => {
+        $crate::send_json!($req, $json, $crate::Form, ())
+    };
     ($req:expr, $json:expr, $parser:ty, ()) => {
         async {
             let result = $crate::__internal::send_json(
@@ -282,7 +374,7 @@ macro_rules! send_json {
                 ),
             )
             .await?;
-            <$parser>::try_parse(result)
+            result.extract(|result| <$parser>::try_parse(result))
         }
     };
     ($req:expr, $json:expr, Json<$ve:ty>) => {
@@ -304,8 +396,10 @@ macro_rules! send_json {
                 ),
             )
             .await?;
-            let result = <$parser>::try_parse::<$ve>(result)?;
-            <$ve>::try_extract(result)
+            result.extract(|result| {
+                let result = <$parser>::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
         }
     };
 }
@@ -328,6 +422,28 @@ macro_rules! _send_json_with {
             Ok(())
         }
     };
+    ($req:expr, $json:expr, (|$($closure:tt)+), $config:expr) => {
+        async {
+            $crate::__internal::send_json(
+                $req,
+                &($json),
+                $config.merge($crate::_function_path!(), false),
+            )
+            .await?
+            .extract(|$($closure)+)
+        }
+    };
+    ($req:expr, $json:expr, (move |$($closure:tt)+), $config:expr) => {
+        async {
+            $crate::__internal::send_json(
+                $req,
+                &($json),
+                $config.merge($crate::_function_path!(), false),
+            )
+            .await?
+            .extract(move |$($closure)+)
+        }
+    };
     ($req:expr, $json:expr, Body, $config:expr) => {
         async {
             $crate::__internal::send_json(
@@ -336,7 +452,7 @@ macro_rules! _send_json_with {
                 $config.merge($crate::_function_path!(), true),
             )
             .await
-            .and_then(|c| c.try_into())
+            .and_then(|c| c.extract(|c| c.try_into()))
         }
     };
     ($req:expr, $json:expr, Json, $config:expr) => {
@@ -348,6 +464,9 @@ macro_rules! _send_json_with {
     ($req:expr, $json:expr, Text, $config:expr) => {
         $crate::_send_json_with!($req, $json, $crate::Text, (), $config)
     };
+    ($req:expr, $json:expr, Form, $config:expr) => {
+        $crate::_send_json_with!($req, $json, $crate::Form, (), $config)
+    };
     ($req:expr, $json:expr, $parser:ty, (), $config:expr) => {
         async {
             let result = $crate::__internal::send_json(
@@ -356,7 +475,7 @@ macro_rules! _send_json_with {
                 $config.merge($crate::_function_path!(), false),
             )
             .await?;
-            <$parser>::try_parse(result)
+            result.extract(|result| <$parser>::try_parse(result))
         }
     };
     ($req:expr, $json:expr, Json<$ve:ty>, $config:expr) => {
@@ -388,12 +507,68 @@ macro_rules! _send_json_with {
                 $config.merge($crate::_function_path!(), <$ve>::require_headers()),
             )
             .await?;
-            let result = <$parser>::try_parse::<$ve>(result)?;
-            <$ve>::try_extract(result)
+            result.extract(|result| {
+                let result = <$parser>::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
         }
     };
 }
 
+/// Send the payload as JSON, and deserialize JSON response to `T`
+///
+/// Unlike `send_json`, the errors of each phase are distinguishable:
+/// - `ApiError::EncodeJson`: failed to serialize payload
+/// - `ApiError::Reqwest` / `ApiError::HttpClientStatus` / `ApiError::HttpServerStatus` / ...: failed to send request
+/// - `ApiError::DecodeJson` / `ApiError::IncompatibleContentType` / ...: failed to deserialize response
+///
+/// # Forms
+///
+/// - `send_json_to!(req, json)` -> `impl Future<Output = ApiResult<T>>`
+///     - serialize json, send it, and deserialize response as `T`, which is inferred
+/// - `send_json_to!(req, json, T)` -> `impl Future<Output = ApiResult<T>>`
+///     - serialize json, send it, and deserialize response as `T`
+///
+/// # Examples
+///
+/// ```
+/// let data = json!({
+///     "key": "value"
+/// });
+/// let req = client.post("/path/api").await?;
+/// let res: TypeOfResponse = send_json_to!(req, data).await?;
+/// ```
+#[macro_export]
+macro_rules! send_json_to {
+    ($req:expr, $json:expr) => {
+        $crate::send_json_to!($req, $json, _)
+    };
+    ($req:expr, $json:expr, $t:ty) => {
+        $crate::__internal::send_json_to::<_, $t>(
+            $req,
+            &($json),
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            ),
+        )
+    };
+}
+
+/// Internal macro
+#[macro_export]
+#[doc(hidden)]
+macro_rules! _send_json_to_with {
+    ($req:expr, $json:expr, $t:ty, $config:expr) => {
+        $crate::__internal::send_json_to::<_, $t>(
+            $req,
+            &($json),
+            $config.merge($crate::_function_path!(), false),
+        )
+    };
+}
+
 /// Send the payload as XML, which will be serialized by quick_xml
 ///
 /// # Forms
@@ -410,10 +585,14 @@ macro_rules! _send_json_with {
 ///     - send the request, parse response as xml, then use quick_xml to deserialize it
 /// - `send_xml!(req, xml, Text)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send the request, parse response as text, then use FromStr to deserialize it
+/// - `send_xml!(req, xml, Form)` -> `impl Future<Output = ApiResult<T>>`
+///     - send the request, parse response as form-urlencoded, then use serde_urlencoded to deserialize it
 /// - `send_xml!(req, xml, OtherType)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send xml, parse response as json, and use `OtherType` as JsonExtractor
 /// - `send_xml!(req, xml, Json<OtherType>)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send xml, parse response as json, and use `OtherType` as JsonExtractor
+/// - `send_xml!(req, xml, |body| ...)` -> `impl Future<Output = ApiResult<T>>`
+///     - send xml, verify response status, and use the closure `FnOnce(ResponseBody) -> ApiResult<T>` to extract result
 ///
 /// # Examples
 ///
@@ -449,6 +628,28 @@ macro_rules! send_xml {
             Ok(())
         }
     };
+    ($req:expr, $xml:expr, |$($closure:tt)+) => {
+        $crate::_send_xml_with!(
+            $req, $xml,
+            (|$($closure)+),
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            )
+        )
+    };
+    ($req:expr, $xml:expr, move |$($closure:tt)+) => {
+        $crate::_send_xml_with!(
+            $req, $xml,
+            (move |$($closure)+),
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            )
+        )
+    };
     ($req:expr, $xml:expr, Body) => {
         async {
             $crate::__internal::send_xml(
@@ -461,7 +662,7 @@ macro_rules! send_xml {
                 ),
             )
             .await
-            .and_then(|c| c.try_into())
+            .and_then(|c| c.extract(|c| c.try_into()))
         }
     };
     ($req:expr, $xml:expr, Json) => {
@@ -473,6 +674,9 @@ macro_rules! send_xml {
     ($req:expr, $xml:expr, Text) => {
         $crate::send_xml!($req, $xml, $crate::Text, ())
     };
+    ($req:expr, $xml:expr, Form) => {
+        $crate::send_xml!($req, $xml, $crate::Form, ())
+    };
     ($req:expr, $xml:expr, $parser:ty, ()) => {
         async {
             let result = $crate::__internal::send_xml(
@@ -485,7 +689,7 @@ macro_rules! send_xml {
                 ),
             )
             .await?;
-            <$parser>::try_parse(result)
+            result.extract(|result| <$parser>::try_parse(result))
         }
     };
     ($req:expr, $xml:expr, Json<$ve:ty>) => {
@@ -507,8 +711,10 @@ macro_rules! send_xml {
                 ),
             )
             .await?;
-            let result = <$parser>::try_parse::<$ve>(result)?;
-            <$ve>::try_extract(result)
+            result.extract(|result| {
+                let result = <$parser>::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
         }
     };
 }
@@ -531,6 +737,28 @@ macro_rules! _send_xml_with {
             Ok(())
         }
     };
+    ($req:expr, $xml:expr, (|$($closure:tt)+), $config:expr) => {
+        async {
+            $crate::__internal::send_xml(
+                $req,
+                &($xml),
+                $config.merge($crate::_function_path!(), false),
+            )
+            .await?
+            .extract(|$($closure)+)
+        }
+    };
+    ($req:expr, $xml:expr, (move |$($closure:tt)+), $config:expr) => {
+        async {
+            $crate::__internal::send_xml(
+                $req,
+                &($xml),
+                $config.merge($crate::_function_path!(), false),
+            )
+            .await?
+            .extract(move |$($closure)+)
+        }
+    };
     ($req:expr, $xml:expr, Body, $config:expr) => {
         async {
             $crate::__internal::send_xml(
@@ -539,7 +767,7 @@ macro_rules! _send_xml_with {
                 $config.merge($crate::_function_path!(), true),
             )
             .await
-            .and_then(|c| c.try_into())
+            .and_then(|c| c.extract(|c| c.try_into()))
         }
     };
     ($req:expr, $xml:expr, Json, $config:expr) => {
@@ -551,6 +779,9 @@ macro_rules! _send_xml_with {
     ($req:expr, $xml:expr, Text, $config:expr) => {
         $crate::_send_xml_with!($req, $xml, $crate::Text, (), $config)
     };
+    ($req:expr, $xml:expr, Form, $config:expr) => {
+        $crate::_send_xml_with!($req, $xml, $crate::Form, (), $config)
+    };
     ($req:expr, $xml:expr, $parser:ty, (), $config:expr) => {
         async {
             let result = $crate::__internal::send_xml(
@@ -559,7 +790,7 @@ macro_rules! _send_xml_with {
                 $config.merge($crate::_function_path!(), false),
             )
             .await?;
-            <$parser>::try_parse(result)
+            result.extract(|result| <$parser>::try_parse(result))
         }
     };
     ($req:expr, $xml:expr, Json<$ve:ty>, $config:expr) => {
@@ -591,8 +822,10 @@ macro_rules! _send_xml_with {
                 $config.merge($crate::_function_path!(), <$ve>::require_headers()),
             )
             .await?;
-            let result = <$parser>::try_parse::<$ve>(result)?;
-            <$ve>::try_extract(result)
+            result.extract(|result| {
+                let result = <$parser>::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
         }
     };
 }
@@ -613,10 +846,14 @@ macro_rules! _send_xml_with {
 ///     - send the request, parse response as xml, then use quick_xml to deserialize it
 /// - `send_form!(req, form, Text)`-> `impl Future<Output = ApiResult<T>>`
 ///     - send the request, parse response as text, then use FromStr to deserialize it
+/// - `send_form!(req, form, Form)`-> `impl Future<Output = ApiResult<T>>`
+///     - send the request, parse response as form-urlencoded, then use serde_urlencoded to deserialize it
 /// - `send_form!(req, form, OtherType)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send form, parse response as json, and use `OtherType` as JsonExtractor
 /// - `send_form!(req, form, Json<OtherType>)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send form, parse response as json, and use `OtherType` as JsonExtractor
+/// - `send_form!(req, form, |body| ...)` -> `impl Future<Output = ApiResult<T>>`
+///     - send form, verify response status, and use the closure `FnOnce(ResponseBody) -> ApiResult<T>` to extract result
 ///
 /// # Examples
 ///
@@ -662,6 +899,28 @@ macro_rules! send_form {
             Ok(())
         }
     };
+    ($req:expr, $form:expr, |$($closure:tt)+) => {
+        $crate::_send_form_with!(
+            $req, $form,
+            (|$($closure)+),
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            )
+        )
+    };
+    ($req:expr, $form:expr, move |$($closure:tt)+) => {
+        $crate::_send_form_with!(
+            $req, $form,
+            (move |$($closure)+),
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            )
+        )
+    };
     ($req:expr, $form:expr, Body) => {
         async {
             $crate::__internal::send_form(
@@ -674,7 +933,7 @@ macro_rules! send_form {
                 ),
             )
             .await
-            .and_then(|c| c.try_into())
+            .and_then(|c| c.extract(|c| c.try_into()))
         }
     };
     ($req:expr, $form:expr, Json) => {
@@ -686,6 +945,9 @@ macro_rules! send_form {
     ($req:expr, $form:expr, Text) => {
         $crate::send_form!($req, $form, $crate::Text, ())
     };
+    ($req:expr, $form:expr, Form) => {
+        $crate::send_form!($req, $form, $crate::Form, ())
+    };
     ($req:expr, $form:expr, $parser:ty, ()) => {
         async {
             let result = $crate::__internal::send_form(
@@ -698,7 +960,7 @@ macro_rules! send_form {
                 ),
             )
             .await?;
-            <$parser>::try_parse(result)
+            result.extract(|result| <$parser>::try_parse(result))
         }
     };
     ($req:expr, $form:expr, Json<$ve:ty>) => {
@@ -720,8 +982,10 @@ macro_rules! send_form {
                 ),
             )
             .await?;
-            let result = <$parser>::try_parse::<$ve>(result)?;
-            <$ve>::try_extract(result)
+            result.extract(|result| {
+                let result = <$parser>::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
         }
     };
 }
@@ -744,6 +1008,28 @@ macro_rules! _send_form_with {
             Ok(())
         }
     };
+    ($req:expr, $form:expr, (|$($closure:tt)+), $config:expr) => {
+        async {
+            $crate::__internal::send_form(
+                $req,
+                $form,
+                $config.merge($crate::_function_path!(), false),
+            )
+            .await?
+            .extract(|$($closure)+)
+        }
+    };
+    ($req:expr, $form:expr, (move |$($closure:tt)+), $config:expr) => {
+        async {
+            $crate::__internal::send_form(
+                $req,
+                $form,
+                $config.merge($crate::_function_path!(), false),
+            )
+            .await?
+            .extract(move |$($closure)+)
+        }
+    };
     ($req:expr, $form:expr, Body, $config:expr) => {
         async {
             $crate::__internal::send_form(
@@ -752,7 +1038,7 @@ macro_rules! _send_form_with {
                 $config.merge($crate::_function_path!(), true),
             )
             .await
-            .and_then(|c| c.try_into())
+            .and_then(|c| c.extract(|c| c.try_into()))
         }
     };
     ($req:expr, $form:expr, Json, $config:expr) => {
@@ -764,6 +1050,9 @@ macro_rules! _send_form_with {
     ($req:expr, $form:expr, Text, $config:expr) => {
         $crate::_send_form_with!($req, $form, $crate::Text, (), $config)
     };
+    ($req:expr, $form:expr, Form, $config:expr) => {
+        $crate::_send_form_with!($req, $form, $crate::Form, (), $config)
+    };
     ($req:expr, $form:expr, $parser:ty, (), $config:expr) => {
         async {
             let result = $crate::__internal::send_form(
@@ -772,7 +1061,7 @@ macro_rules! _send_form_with {
                 $config.merge($crate::_function_path!(), false),
             )
             .await?;
-            <$parser>::try_parse(result)
+            result.extract(|result| <$parser>::try_parse(result))
         }
     };
     ($req:expr, $form:expr, Json<$ve:ty>, $config:expr) => {
@@ -804,8 +1093,10 @@ macro_rules! _send_form_with {
                 $config.merge($crate::_function_path!(), <$ve>::require_headers()),
             )
             .await?;
-            let result = <$parser>::try_parse::<$ve>(result)?;
-            <$ve>::try_extract(result)
+            result.extract(|result| {
+                let result = <$parser>::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
         }
     };
 }
@@ -826,10 +1117,14 @@ macro_rules! _send_form_with {
 ///     - send the request, parse response as xml, then use quick_xml to deserialize it
 /// - `send_multipart!(req, form, Text)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send the request, parse response as text, then use FromStr to deserialize it
+/// - `send_multipart!(req, form, Form)` -> `impl Future<Output = ApiResult<T>>`
+///     - send the request, parse response as form-urlencoded, then use serde_urlencoded to deserialize it
 /// - `send_multipart!(req, form, OtherType)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send form, parse response as json, and use `OtherType` as JsonExtractor
 /// - `send_multipart!(req, form, Json<OtherType>)` -> `impl Future<Output = ApiResult<T>>`
 ///     - send form, parse response as json, and use `OtherType` as JsonExtractor
+/// - `send_multipart!(req, form, |body| ...)` -> `impl Future<Output = ApiResult<T>>`
+///     - send form, verify response status, and use the closure `FnOnce(ResponseBody) -> ApiResult<T>` to extract result
 ///
 /// # Examples
 ///
@@ -866,6 +1161,28 @@ macro_rules! send_multipart {
             Ok(())
         }
     };
+    ($req:expr, $form:expr, |$($closure:tt)+) => {
+        $crate::_send_multipart_with!(
+            $req, $form,
+            (|$($closure)+),
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            )
+        )
+    };
+    ($req:expr, $form:expr, move |$($closure:tt)+) => {
+        $crate::_send_multipart_with!(
+            $req, $form,
+            (move |$($closure)+),
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            )
+        )
+    };
     ($req:expr, $form:expr, Body) => {
         async {
             $crate::__internal::send_multipart(
@@ -878,7 +1195,7 @@ macro_rules! send_multipart {
                 ),
             )
             .await
-            .and_then(|c| c.try_into())
+            .and_then(|c| c.extract(|c| c.try_into()))
         }
     };
     ($req:expr, $form:expr, Json) => {
@@ -890,6 +1207,9 @@ macro_rules! send_multipart {
     ($req:expr, $form:expr, Text) => {
         $crate::send_multipart!($req, $form, $crate::Text, ())
     };
+    ($req:expr, $form:expr, Form) => {
+        $crate::send_multipart!($req, $form, $crate::Form, ())
+    };
     ($req:expr, $form:expr, $parser:ty, ()) => {
         async {
             let result = $crate::__internal::send_multipart(
@@ -902,7 +1222,7 @@ macro_rules! send_multipart {
                 ),
             )
             .await?;
-            <$parser>::try_parse(result)
+            result.extract(|result| <$parser>::try_parse(result))
         }
     };
     ($req:expr, $form:expr, Json<$ve:ty>) => {
@@ -924,8 +1244,10 @@ macro_rules! send_multipart {
                 ),
             )
             .await?;
-            let result = <$parser>::try_parse::<$ve>(result)?;
-            <$ve>::try_extract(result)
+            result.extract(|result| {
+                let result = <$parser>::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
         }
     };
 }
@@ -948,6 +1270,28 @@ macro_rules! _send_multipart_with {
             Ok(())
         }
     };
+    ($req:expr, $form:expr, (|$($closure:tt)+), $config:expr) => {
+        async {
+            $crate::__internal::send_multipart(
+                $req,
+                $form,
+                $config.merge($crate::_function_path!(), false),
+            )
+            .await?
+            .extract(|$($closure)+)
+        }
+    };
+    ($req:expr, $form:expr, (move |$($closure:tt)+), $config:expr) => {
+        async {
+            $crate::__internal::send_multipart(
+                $req,
+                $form,
+                $config.merge($crate::_function_path!(), false),
+            )
+            .await?
+            .extract(move |$($closure)+)
+        }
+    };
     ($req:expr, $form:expr, Body, $config:expr) => {
         async {
             $crate::__internal::send_multipart(
@@ -956,7 +1300,7 @@ macro_rules! _send_multipart_with {
                 $config.merge($crate::_function_path!(), true),
             )
             .await
-            .and_then(|c| c.try_into())
+            .and_then(|c| c.extract(|c| c.try_into()))
         }
     };
     ($req:expr, $form:expr, Json, $config:expr) => {
@@ -968,6 +1312,9 @@ macro_rules! _send_multipart_with {
     ($req:expr, $form:expr, Text, $config:expr) => {
         $crate::_send_multipart_with!($req, $form, $crate::Text, (), $config)
     };
+    ($req:expr, $form:expr, Form, $config:expr) => {
+        $crate::_send_multipart_with!($req, $form, $crate::Form, (), $config)
+    };
     ($req:expr, $form:expr, $parser:ty, (), $config:expr) => {
         async {
             let result = $crate::__internal::send_multipart(
@@ -976,7 +1323,7 @@ macro_rules! _send_multipart_with {
                 $config.merge($crate::_function_path!(), false),
             )
             .await?;
-            <$parser>::try_parse(result)
+            result.extract(|result| <$parser>::try_parse(result))
         }
     };
     ($req:expr, $form:expr, Json<$ve:ty>, $config:expr) => {
@@ -1008,8 +1355,10 @@ macro_rules! _send_multipart_with {
                 $config.merge($crate::_function_path!(), <$ve>::require_headers()),
             )
             .await?;
-            let result = <$parser>::try_parse::<$ve>(result)?;
-            <$ve>::try_extract(result)
+            result.extract(|result| {
+                let result = <$parser>::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
         }
     };
 }
@@ -1034,6 +1383,315 @@ macro_rules! send_raw {
     };
 }
 
+/// Internal macro
+#[macro_export]
+#[doc(hidden)]
+macro_rules! _send_raw_with {
+    ($req:expr, $config:expr) => {
+        $crate::__internal::send_raw($req, $config.merge($crate::_function_path!(), false))
+    };
+}
+
+/// Send and decode response as newline-delimited json stream
+///
+/// # Forms
+///
+/// - `send_ndjson!(req)` -> `impl Future<Output = ApiResult<apisdk::NdjsonStream<T>>>`
+///     - send request, verify response status, and decode each line of response as `T`
+/// - `send_ndjson!(req, items)` -> `impl Future<Output = ApiResult<T>>`
+///     - send items as newline-delimited json (application/x-ndjson), and parse response as json or xml based on response
+/// - `send_ndjson!(req, items, ...)` -> `impl Future<Output = ApiResult<T>>`
+///     - send items as newline-delimited json, and extract response in the same way as `send!(req, ...)`
+///
+/// # Examples
+///
+/// ```
+/// let req = client.get("/path/api").await?;
+/// let mut stream = send_ndjson!(req).await?;
+/// while let Some(item) = stream.next().await {
+///     let item: TypeOfItem = item?;
+/// }
+///
+/// let events = vec![json!({"id": 1}), json!({"id": 2})];
+/// let req = client.post("/path/ingest").await?;
+/// let res: TypeOfResponse = send_ndjson!(req, events).await?;
+/// ```
+#[macro_export]
+macro_rules! send_ndjson {
+    ($req:expr) => {
+        $crate::__internal::send_ndjson(
+            $req,
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            ),
+        )
+    };
+    ($req:expr, $items:expr $(, $($extractor:tt)+)?) => {
+        async {
+            let req = $crate::__internal::ndjson_body($req, $items)?;
+            $crate::send!(req $(, $($extractor)+)?).await
+        }
+    };
+}
+
+/// Internal macro
+#[macro_export]
+#[doc(hidden)]
+macro_rules! _send_ndjson_with {
+    ($req:expr, $config:expr) => {
+        $crate::__internal::send_ndjson($req, $config.merge($crate::_function_path!(), false))
+    };
+}
+
+/// Send and decode response as a stream of text lines
+///
+/// The response is decoded incrementally, so the whole body will not be buffered.
+///
+/// # Forms
+///
+/// - `send_lines!(req)` -> `impl Future<Output = ApiResult<apisdk::LineStream>>`
+///     - send request, verify response status, and decode each line of response as `String`
+///
+/// # Examples
+///
+/// ```
+/// let req = client.get("/path/logs").await?;
+/// let mut stream = send_lines!(req).await?;
+/// while let Some(line) = stream.next().await {
+///     let line: String = line?;
+/// }
+/// ```
+#[macro_export]
+macro_rules! send_lines {
+    ($req:expr) => {
+        $crate::__internal::send_lines(
+            $req,
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            ),
+        )
+    };
+}
+
+/// Internal macro
+#[macro_export]
+#[doc(hidden)]
+macro_rules! _send_lines_with {
+    ($req:expr, $config:expr) => {
+        $crate::__internal::send_lines($req, $config.merge($crate::_function_path!(), false))
+    };
+}
+
+/// Send the precomputed bytes as payload, with the explicit content type
+///
+/// The bytes are sent as is, without JSON / form serialization.
+///
+/// # Forms
+///
+/// - `send_bytes!(req, bytes, content_type)` -> `impl Future<Output = ApiResult<T>>`
+///     - send bytes as request body, and parse response as json or xml based on response
+/// - `send_bytes!(req, bytes, content_type, ...)` -> `impl Future<Output = ApiResult<T>>`
+///     - send bytes as request body, and extract response in the same way as `send!(req, ...)`
+///
+/// # Examples
+///
+/// ```
+/// let payload: Vec<u8> = encode_as_cbor(&data)?;
+/// let req = client.post("/path/api").await?;
+/// let res: TypeOfResponse = send_bytes!(req, payload, "application/cbor").await?;
+/// ```
+#[macro_export]
+macro_rules! send_bytes {
+    ($req:expr, $bytes:expr, $content_type:expr $(, $($extractor:tt)+)?) => {
+        async {
+            let req = $crate::__internal::bytes_body($req, $bytes, $content_type);
+            $crate::send!(req $(, $($extractor)+)?).await
+        }
+    };
+}
+
+/// Send the chunks received from channel as streaming payload
+///
+/// The request body ends once all senders are dropped.
+/// Use a bounded channel to get backpressure, since the chunks are only received as fast as they are written.
+///
+/// # Forms
+///
+/// - `send_channel_body!(req, receiver)` -> `impl Future<Output = ApiResult<T>>`
+///     - send chunks as request body, and parse response as json or xml based on response
+/// - `send_channel_body!(req, receiver, ...)` -> `impl Future<Output = ApiResult<T>>`
+///     - send chunks as request body, and extract response in the same way as `send!(req, ...)`
+///
+/// # Examples
+///
+/// ```
+/// let (tx, rx) = tokio::sync::mpsc::channel::<Vec<u8>>(16);
+/// tokio::spawn(async move {
+///     for line in lines {
+///         tx.send(line).await.ok();
+///     }
+/// });
+/// let req = client.post("/path/logs").await?;
+/// let res: TypeOfResponse = send_channel_body!(req, rx).await?;
+/// ```
+#[macro_export]
+macro_rules! send_channel_body {
+    ($req:expr, $receiver:expr $(, $($extractor:tt)+)?) => {
+        async {
+            let req = $crate::__internal::channel_body($req, $receiver);
+            $crate::send!(req $(, $($extractor)+)?).await
+        }
+    };
+}
+
+/// Send the payload, and decode the elements of json array response as stream
+///
+/// The elements are emitted as they arrive, so the whole array will not be buffered.
+///
+/// # Forms
+///
+/// - `send_json_array!(req)` -> `impl Future<Output = ApiResult<apisdk::JsonArrayStream<serde_json::Value>>>`
+///     - send request, verify response status, and decode each element of response as `serde_json::Value`
+/// - `send_json_array!(req, T)` -> `impl Future<Output = ApiResult<apisdk::JsonArrayStream<T>>>`
+///     - send request, verify response status, and decode each element of response as `T`
+///
+/// # Examples
+///
+/// ```
+/// let req = client.get("/path/api").await?;
+/// let mut stream = send_json_array!(req).await?;
+/// while let Some(item) = stream.next().await {
+///     let item: serde_json::Value = item?;
+/// }
+/// ```
+#[macro_export]
+macro_rules! send_json_array {
+    ($req:expr) => {
+        $crate::send_json_array!($req, $crate::serde_json::Value)
+    };
+    ($req:expr, $t:ty) => {
+        $crate::__internal::send_json_array::<$t>(
+            $req,
+            $crate::__internal::RequestConfigurator::new(
+                $crate::_function_path!(),
+                None::<bool>,
+                false,
+            ),
+        )
+    };
+}
+
+/// Internal macro
+#[macro_export]
+#[doc(hidden)]
+macro_rules! _send_json_array_with {
+    ($req:expr, $t:ty, $config:expr) => {
+        $crate::__internal::send_json_array::<$t>(
+            $req,
+            $config.merge($crate::_function_path!(), false),
+        )
+    };
+}
+
+/// Send request, and validate the json response against JSON Schema before deserializing it
+///
+/// # Forms
+///
+/// - `send_with_schema_validation!(req, schema)` -> `impl Future<Output = ApiResult<T>>`
+///     - send request, validate response, then use serde_json to deserialize it
+/// - `send_with_schema_validation!(req, schema, OtherType)` -> `impl Future<Output = ApiResult<T>>`
+///     - send request, validate response, and use `OtherType` as JsonExtractor
+///
+/// The `schema` is compiled by `apisdk::jsonschema::JSONSchema::compile`, and could be reused.
+/// The violations are returned as `ApiError::SchemaValidation`, each with the JSON Pointer to the invalid value.
+///
+/// It requires the `schema` feature.
+///
+/// # Examples
+///
+/// ```
+/// let schema = JSONSchema::compile(&json!({"type": "object", "required": ["id"]}))?;
+/// let req = client.get("/path/api").await?;
+/// let res: TypeOfResponse = send_with_schema_validation!(req, schema).await?;
+/// ```
+#[cfg(feature = "schema")]
+#[macro_export]
+macro_rules! send_with_schema_validation {
+    ($req:expr, $schema:expr) => {
+        async {
+            let result = $crate::__internal::send(
+                $req,
+                $crate::__internal::RequestConfigurator::new(
+                    $crate::_function_path!(),
+                    None::<bool>,
+                    false,
+                ),
+            )
+            .await?;
+            result.extract(|result| {
+                $crate::validate_schema(&$schema, &result)?;
+                $crate::Json::try_parse(result)
+            })
+        }
+    };
+    ($req:expr, $schema:expr, $ve:ty) => {
+        async {
+            use $crate::JsonExtractor;
+            let result = $crate::__internal::send(
+                $req,
+                $crate::__internal::RequestConfigurator::new(
+                    $crate::_function_path!(),
+                    None::<bool>,
+                    <$ve>::require_headers(),
+                ),
+            )
+            .await?;
+            result.extract(|result| {
+                $crate::validate_schema(&$schema, &result)?;
+                let result = $crate::Json::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
+        }
+    };
+}
+
+/// Internal macro
+#[cfg(feature = "schema")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! _send_with_schema_validation_with {
+    ($req:expr, $schema:expr, Json<$ve:ty>, $config:expr) => {
+        async {
+            use $crate::JsonExtractor;
+            let result = $crate::__internal::send(
+                $req,
+                $config.merge($crate::_function_path!(), <$ve>::require_headers()),
+            )
+            .await?;
+            result.extract(|result| {
+                $crate::validate_schema(&$schema, &result)?;
+                let result = $crate::Json::try_parse::<$ve>(result)?;
+                <$ve>::try_extract(result)
+            })
+        }
+    };
+    ($req:expr, $schema:expr, $config:expr) => {
+        async {
+            let result =
+                $crate::__internal::send($req, $config.merge($crate::_function_path!(), false))
+                    .await?;
+            result.extract(|result| {
+                $crate::validate_schema(&$schema, &result)?;
+                $crate::Json::try_parse(result)
+            })
+        }
+    };
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -1,18 +1,33 @@
 mod execute;
 mod form;
 mod macros;
+mod poll;
+mod tus;
 
 pub use form::*;
+pub use poll::ChangeStream;
+pub use tus::TusSource;
 // pub use macros::*;
 
 /// Internal struct & functions
 #[doc(hidden)]
 pub mod __internal {
+    pub use super::execute::bytes_body;
+    pub use super::execute::channel_body;
+    pub use super::execute::ndjson_body;
+    pub use super::execute::probe;
     pub use super::execute::send;
     pub use super::execute::send_form;
     pub use super::execute::send_json;
+    pub use super::execute::send_json_array;
+    pub use super::execute::send_json_to;
+    pub use super::execute::send_lines;
     pub use super::execute::send_multipart;
+    pub use super::execute::send_ndjson;
     pub use super::execute::send_raw;
     pub use super::execute::send_xml;
+    pub use super::execute::DecodedBody;
     pub use super::execute::RequestConfigurator;
+    pub use super::poll::poll_changes;
+    pub use super::tus::tus_upload;
 }
@@ -0,0 +1,141 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{Stream, StreamExt};
+use reqwest::{
+    header::{ETAG, IF_NONE_MATCH},
+    Method, StatusCode,
+};
+use serde_json::Value;
+
+use super::execute::{send_raw, RequestConfigurator};
+use crate::{ApiCore, ApiError, ApiResult, MimeType};
+
+/// This struct is a stream of json responses, which are emitted only when the payload changes.
+///
+/// The first response is always emitted. After that, the path is polled by `GET` at the interval,
+/// and the response is emitted only if its json differs from the previous one, regardless of the order of object keys.
+/// If the server returns `ETag`, it's sent back by `If-None-Match`, so `304 Not Modified` saves the transfer.
+///
+/// A failed poll yields an error item, and the polling continues. Drop the stream to stop polling.
+///
+/// # Examples
+///
+/// ```
+/// let mut changes = api.poll_changes("/config", Duration::from_secs(30));
+/// while let Some(config) = changes.next().await {
+///     let config: Value = config?;
+/// }
+/// ```
+pub struct ChangeStream {
+    /// The json responses
+    inner: Pin<Box<dyn Stream<Item = ApiResult<Value>> + Send>>,
+}
+
+impl std::fmt::Debug for ChangeStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChangeStream").finish_non_exhaustive()
+    }
+}
+
+impl Stream for ChangeStream {
+    type Item = ApiResult<Value>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+/// The state of polling
+struct Poller {
+    /// The api core to build requests
+    core: Arc<ApiCore>,
+    /// The relative path
+    path: String,
+    /// The interval between polls
+    interval: Duration,
+    /// The ETag of last response
+    etag: Option<String>,
+    /// The last emitted payload
+    last: Option<Value>,
+    /// Indicate whether the first poll has been sent
+    started: bool,
+}
+
+impl Poller {
+    /// Poll until the payload changes, or the poll fails
+    async fn next_change(&mut self) -> ApiResult<Value> {
+        loop {
+            if self.started {
+                tokio::time::sleep(self.interval).await;
+            }
+            self.started = true;
+            if let Some(json) = self.poll_once().await? {
+                return Ok(json);
+            }
+        }
+    }
+
+    /// Send a conditional GET, and return the payload if changed
+    async fn poll_once(&mut self) -> ApiResult<Option<Value>> {
+        let mut req = self.core.build_request(Method::GET, &self.path).await?;
+        if let Some(etag) = self.etag.as_ref() {
+            req = req.header(IF_NONE_MATCH, etag);
+        }
+        let res = send_raw(
+            req,
+            RequestConfigurator::new(module_path!(), None::<bool>, false),
+        )
+        .await?;
+
+        let status = res.status();
+        if status == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if status.is_client_error() || status.is_server_error() {
+            return Err(ApiError::from_status(status));
+        }
+        self.etag = res
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        let json: Value = res
+            .json()
+            .await
+            .map_err(|e| ApiError::DecodeResponse(MimeType::Json, e.to_string()))?;
+
+        // The objects are compared by keys, even if `preserve_order` keeps the keys unsorted
+        if self.last.as_ref() == Some(&json) {
+            return Ok(None);
+        }
+        self.last = Some(json.clone());
+        Ok(Some(json))
+    }
+}
+
+/// Poll the path, and emit the json response only when it changes
+/// - core: the api core to build requests
+/// - path: relative path
+/// - interval: the interval between polls
+pub fn poll_changes(core: Arc<ApiCore>, path: String, interval: Duration) -> ChangeStream {
+    let poller = Poller {
+        core,
+        path,
+        interval,
+        etag: None,
+        last: None,
+        started: false,
+    };
+    let inner = futures::stream::unfold(poller, |mut poller| async move {
+        let item = poller.next_change().await;
+        Some((item, poller))
+    });
+    ChangeStream {
+        inner: inner.boxed(),
+    }
+}
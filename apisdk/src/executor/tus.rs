@@ -0,0 +1,163 @@
+use std::{io::SeekFrom, sync::Arc, time::Duration};
+
+use reqwest::{
+    header::{HeaderName, HeaderValue, CONTENT_TYPE, LOCATION},
+    Method, Response, StatusCode,
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+use super::execute::{send_raw, RequestConfigurator};
+use crate::{ApiCore, ApiError, ApiResult, Url};
+
+/// The version of Tus protocol
+const TUS_VERSION: HeaderValue = HeaderValue::from_static("1.0.0");
+/// The header of Tus protocol version
+const TUS_RESUMABLE: HeaderName = HeaderName::from_static("tus-resumable");
+/// The header of total size
+const UPLOAD_LENGTH: HeaderName = HeaderName::from_static("upload-length");
+/// The header of uploaded size
+const UPLOAD_OFFSET: HeaderName = HeaderName::from_static("upload-offset");
+/// The content type of chunks
+const OFFSET_OCTET_STREAM: &str = "application/offset+octet-stream";
+
+/// How many times to resume without any progress
+const MAX_RESUMES: u32 = 3;
+/// The base delay before resuming
+const RESUME_DELAY: Duration = Duration::from_millis(200);
+
+/// This trait is implemented by the source of `tus_upload`, such as `tokio::fs::File`
+pub trait TusSource: AsyncRead + AsyncSeek + Unpin + Send {}
+
+impl<T> TusSource for T where T: AsyncRead + AsyncSeek + Unpin + Send {}
+
+/// Upload the source by Tus protocol, and return the URL of the upload
+/// - core: the api core to build requests
+/// - path: relative path to create the upload
+/// - file: the source to upload
+/// - chunk_size: the max size of each `PATCH` request
+///
+/// The upload is created by `POST` with `Upload-Length`, and the chunks are sent by `PATCH` with `Upload-Offset`.
+/// If a chunk fails with a retryable error or `409 Conflict`, the offset is queried by `HEAD`,
+/// and the upload resumes from there. It gives up after 3 resumes without any progress.
+pub async fn tus_upload<R>(
+    core: Arc<ApiCore>,
+    path: String,
+    mut file: R,
+    chunk_size: usize,
+) -> ApiResult<Url>
+where
+    R: TusSource,
+{
+    let chunk_size = chunk_size.max(1);
+    let length = file.seek(SeekFrom::End(0)).await.map_err(io_error)?;
+    let location = create(&core, &path, length).await?;
+
+    let mut offset = 0;
+    let mut resumes = 0;
+    let mut chunk = vec![0; chunk_size];
+    while offset < length {
+        file.seek(SeekFrom::Start(offset)).await.map_err(io_error)?;
+        let size = chunk_size.min((length - offset) as usize);
+        file.read_exact(&mut chunk[..size])
+            .await
+            .map_err(io_error)?;
+
+        let e = match patch(&core, &location, offset, chunk[..size].to_vec()).await {
+            Ok(next) if next > offset => {
+                offset = next;
+                resumes = 0;
+                continue;
+            }
+            Ok(next) => ApiError::Upload(format!("no progress at offset {}", next)),
+            Err(e) => e,
+        };
+        if !is_resumable(&e) || resumes >= MAX_RESUMES {
+            return Err(e);
+        }
+        resumes += 1;
+        log::debug!("Resume tus upload {} after error: {}", location, e);
+        tokio::time::sleep(RESUME_DELAY * resumes).await;
+        match head(&core, &location).await {
+            Ok(next) => offset = next,
+            Err(e) if is_resumable(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(location)
+}
+
+/// Create the upload, and return its URL
+async fn create(core: &ApiCore, path: &str, length: u64) -> ApiResult<Url> {
+    let req = core
+        .build_request(Method::POST, path)
+        .await?
+        .header(TUS_RESUMABLE, TUS_VERSION)
+        .header(UPLOAD_LENGTH, length);
+    let res = send(req).await?;
+    let location = res
+        .headers()
+        .get(LOCATION)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| ApiError::Upload("missing Location".to_string()))?;
+    core.build_url(path)
+        .await?
+        .join(location)
+        .map_err(|e| ApiError::Upload(format!("invalid Location: {}", e)))
+}
+
+/// Send a chunk at the offset, and return the offset reported by server
+async fn patch(core: &ApiCore, location: &Url, offset: u64, chunk: Vec<u8>) -> ApiResult<u64> {
+    let req = core
+        .build_request(Method::PATCH, location.as_str())
+        .await?
+        .header(TUS_RESUMABLE, TUS_VERSION)
+        .header(UPLOAD_OFFSET, offset)
+        .header(CONTENT_TYPE, OFFSET_OCTET_STREAM)
+        .body(chunk);
+    let res = send(req).await?;
+    upload_offset(&res)
+}
+
+/// Query the offset of the upload
+async fn head(core: &ApiCore, location: &Url) -> ApiResult<u64> {
+    let req = core
+        .build_request(Method::HEAD, location.as_str())
+        .await?
+        .header(TUS_RESUMABLE, TUS_VERSION);
+    let res = send(req).await?;
+    upload_offset(&res)
+}
+
+/// Send the request, and check the status of response
+async fn send(req: crate::RequestBuilder) -> ApiResult<Response> {
+    let res = send_raw(
+        req,
+        RequestConfigurator::new(module_path!(), None::<bool>, false),
+    )
+    .await?;
+    let status = res.status();
+    if status.is_client_error() || status.is_server_error() {
+        return Err(ApiError::from_status(status));
+    }
+    Ok(res)
+}
+
+/// Parse `Upload-Offset` of response
+fn upload_offset(res: &Response) -> ApiResult<u64> {
+    res.headers()
+        .get(UPLOAD_OFFSET)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| ApiError::Upload("missing Upload-Offset".to_string()))
+}
+
+/// Check whether the upload could be resumed after the error
+fn is_resumable(e: &ApiError) -> bool {
+    e.is_retryable() || e.status() == Some(StatusCode::CONFLICT.as_u16())
+}
+
+/// Convert the error of reading source
+fn io_error(e: std::io::Error) -> ApiError {
+    ApiError::Upload(e.to_string())
+}
@@ -0,0 +1,107 @@
+use std::{any::type_name, future::Future, sync::Arc};
+
+use async_trait::async_trait;
+use tokio::sync::OnceCell;
+
+use crate::{ApiResult, Initialiser, RequestBuilder};
+
+/// This trait is used to initialise requests with the state loaded asynchronously,
+/// such as the secrets from a vault.
+///
+/// The loaded `Initialiser` is applied to each request in `build_request`,
+/// after the ones set by `with_initialiser`.
+///
+/// # Examples
+///
+/// ```
+/// let api = XxxApi::builder()
+///     .with_async_initialiser(|| async {
+///         let secret = load_secret().await?;
+///         Ok(move |req: RequestBuilder| req.header("X-Secret", secret.as_str()))
+///     })
+///     .build_async()
+///     .await?;
+/// ```
+#[async_trait]
+pub trait AsyncInitialiser: 'static + Send + Sync {
+    /// Get type_name, used in Debug
+    fn type_name(&self) -> &str {
+        type_name::<Self>()
+    }
+
+    /// Load the Initialiser
+    ///
+    /// It's called once, either by `build_async` or before the first request.
+    /// It will be called again by the next request, if failed.
+    async fn load(&self) -> ApiResult<Box<dyn Initialiser>>;
+}
+
+/// Implement AsyncInitialiser for async function / closure
+#[async_trait]
+impl<F, Fut, I> AsyncInitialiser for F
+where
+    F: 'static + Send + Sync,
+    F: Fn() -> Fut,
+    Fut: Future<Output = ApiResult<I>> + Send,
+    I: Initialiser,
+{
+    async fn load(&self) -> ApiResult<Box<dyn Initialiser>> {
+        let initialiser = self().await?;
+        Ok(Box::new(initialiser))
+    }
+}
+
+/// This struct is used to hold AsyncInitialisers, and the loaded Initialisers
+#[derive(Default)]
+pub(crate) struct AsyncInitialisers {
+    /// The AsyncInitialisers, in declared order
+    inner: Vec<Arc<dyn AsyncInitialiser>>,
+    /// The loaded Initialisers
+    loaded: OnceCell<Vec<Box<dyn Initialiser>>>,
+}
+
+impl AsyncInitialisers {
+    /// Create a new instance
+    /// - inner: the AsyncInitialisers
+    pub fn new(inner: Vec<Arc<dyn AsyncInitialiser>>) -> Self {
+        Self {
+            inner,
+            loaded: OnceCell::new(),
+        }
+    }
+
+    /// Check whether there is no AsyncInitialiser
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Get the type names, used in Debug
+    pub fn type_names(&self) -> Vec<&str> {
+        self.inner.iter().map(|i| i.type_name()).collect()
+    }
+
+    /// Load all Initialisers, unless they have been loaded
+    pub async fn load(&self) -> ApiResult<&[Box<dyn Initialiser>]> {
+        let loaded = self
+            .loaded
+            .get_or_try_init(|| async {
+                let mut loaded = Vec::with_capacity(self.inner.len());
+                for initialiser in &self.inner {
+                    loaded.push(initialiser.load().await?);
+                }
+                ApiResult::Ok(loaded)
+            })
+            .await?;
+        Ok(loaded)
+    }
+
+    /// Apply the loaded Initialisers to request
+    /// - req: the request to initialise
+    pub async fn init(&self, req: RequestBuilder) -> ApiResult<RequestBuilder> {
+        if self.is_empty() {
+            return Ok(req);
+        }
+        let loaded = self.load().await?;
+        Ok(loaded.iter().fold(req, |req, i| i.init(req)))
+    }
+}
@@ -1,18 +1,26 @@
-use std::{any::type_name, num::ParseIntError, string::FromUtf8Error, sync::Arc, time::SystemTime};
+use std::{
+    any::type_name,
+    num::ParseIntError,
+    string::FromUtf8Error,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
 
 use async_trait::async_trait;
 use base64::DecodeError;
+use hmac::Mac;
 use reqwest::{
     header::{HeaderName, HeaderValue, AUTHORIZATION},
-    Request, Response,
+    Method, Request, Response, Url,
 };
 use reqwest_middleware::Next;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio::sync::Mutex;
 
 use crate::{
     digest::{self, decode_base64},
-    Extensions, Middleware,
+    get_clock, Clock, Extensions, Middleware, SystemClock,
 };
 
 /// This middleware is used to authenticate the request
@@ -58,6 +66,11 @@ where
 }
 
 /// This trait is used to authenticate request
+///
+/// It's applied when the request is being sent, after the url has been finalized by
+/// UrlRewriter / DnsResolver in `build_request`, and by the middlewares set by `with_middleware`.
+/// So the signature could cover the final host, path and query of each request.
+/// The payload has been serialized as well, so the body could be hashed by `SignableRequest`.
 #[async_trait]
 pub trait ApiAuthenticator: TokenGenerator {
     /// Get type_name, used in Debug
@@ -83,6 +96,37 @@ pub trait ApiAuthenticator: TokenGenerator {
     }
 }
 
+/// This trait is used to inspect the body of request to sign
+///
+/// # Examples
+///
+/// ```
+/// async fn generate_token(&self, req: &Request) -> Result<String, reqwest_middleware::Error> {
+///     let body = req.body_sha256().unwrap_or_default();
+///     Ok(digest::hmac_sha256(&self.secret, format!("{}\n{}\n{}", req.method(), req.url().path(), body)))
+/// }
+/// ```
+pub trait SignableRequest {
+    /// Get the serialized body, which is empty if there's no body
+    ///
+    /// `None` is returned for the streaming body, such as `channel_body`, which can't be read before sending.
+    fn body_bytes(&self) -> Option<&[u8]>;
+
+    /// Get the hex-encoded SHA-256 digest of body, see `body_bytes`
+    fn body_sha256(&self) -> Option<String> {
+        self.body_bytes().map(digest::sha256)
+    }
+}
+
+impl SignableRequest for Request {
+    fn body_bytes(&self) -> Option<&[u8]> {
+        match self.body() {
+            Some(body) => body.as_bytes(),
+            None => Some(&[]),
+        }
+    }
+}
+
 #[async_trait]
 impl TokenGenerator for Box<dyn ApiAuthenticator> {
     async fn generate_token(&self, req: &Request) -> Result<String, reqwest_middleware::Error> {
@@ -138,21 +182,24 @@ impl Carrier {
     pub fn apply(&self, req: Request, token: impl ToString) -> Request {
         let mut req = req;
         let token = token.to_string();
+        // Mark the value as sensitive, so that it will not be logged
+        let sensitive = |value: String| {
+            let mut value = HeaderValue::try_from(value).unwrap();
+            value.set_sensitive(true);
+            value
+        };
         match self {
             Carrier::BearerAuth => {
-                req.headers_mut().insert(
-                    AUTHORIZATION,
-                    HeaderValue::try_from(format!("Bearer {}", token)).unwrap(),
-                );
+                req.headers_mut()
+                    .insert(AUTHORIZATION, sensitive(format!("Bearer {}", token)));
             }
             Carrier::SchemalessAuth => {
-                req.headers_mut()
-                    .insert(AUTHORIZATION, HeaderValue::try_from(token).unwrap());
+                req.headers_mut().insert(AUTHORIZATION, sensitive(token));
             }
             Carrier::Header(name) => {
                 req.headers_mut().append(
                     HeaderName::try_from(name.as_str()).unwrap(),
-                    HeaderValue::try_from(token).unwrap(),
+                    sensitive(token),
                 );
             }
             Carrier::QueryParam(name) => {
@@ -244,8 +291,175 @@ impl WithCarrier for AccessTokenAuth {
     }
 }
 
+/// This struct is used to sign request by using credentials from an async provider, such as IMDS or Vault.
+///
+/// The credentials are cached within the TTL. When they are about to expire (see `with_refresh_before`),
+/// one request refreshes them, while others keep using the cached ones.
+/// If the refresh fails, the cached ones are still used until they expire, and the refresh is retried by the next request.
+/// The concurrent refreshes are single-flighted, so the provider is called once at a time.
+/// The expiry is measured by the `Clock` set by `with_clock`.
+///
+/// # Examples
+///
+/// ```
+/// struct VaultProvider;
+///
+/// #[async_trait]
+/// impl TokenGenerator for VaultProvider {
+///     async fn generate_token(&self, _req: &Request) -> Result<String, reqwest_middleware::Error> {
+///         // fetch from Vault
+///     }
+/// }
+///
+/// let api = XxxApi::builder()
+///     .with_authenticator(CachedCredentials::new(VaultProvider, Duration::from_secs(300)))
+///     .build();
+/// ```
+pub struct CachedCredentials {
+    /// Fetch the credentials
+    provider: Arc<dyn TokenGenerator>,
+    /// How long the credentials keep valid
+    ttl: Duration,
+    /// How long before expiry to refresh the credentials
+    refresh_before: Duration,
+    carrier: Carrier,
+    /// The cached credentials, and when they expire
+    cached: RwLock<Option<(String, SystemTime)>>,
+    /// Single-flight the refreshes
+    refreshing: Mutex<()>,
+}
+
+impl std::fmt::Debug for CachedCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedCredentials")
+            .field("ttl", &self.ttl)
+            .field("refresh_before", &self.refresh_before)
+            .field("carrier", &self.carrier)
+            .finish()
+    }
+}
+
+impl CachedCredentials {
+    /// Create a new instance
+    /// - provider: fetch the credentials asynchronously
+    /// - ttl: how long the credentials keep valid
+    ///
+    /// The credentials are refreshed within the last 10% of TTL by default.
+    pub fn new(provider: impl TokenGenerator, ttl: Duration) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            ttl,
+            refresh_before: ttl / 10,
+            carrier: Carrier::default(),
+            cached: RwLock::new(None),
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    /// Set how long before expiry to refresh the credentials
+    /// - refresh_before: the duration before expiry
+    pub fn with_refresh_before(self, refresh_before: Duration) -> Self {
+        Self {
+            refresh_before,
+            ..self
+        }
+    }
+
+    /// Get the cached credentials, and whether they should be refreshed
+    /// - now: the current time
+    fn get_cached(&self, now: SystemTime) -> Option<(String, bool)> {
+        match self.cached.read().unwrap().as_ref() {
+            Some((token, expires_at)) if now < *expires_at => {
+                let stale = now + self.refresh_before >= *expires_at;
+                Some((token.clone(), stale))
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the credentials, which are refreshed if expired or stale
+    /// - req: HTTP request
+    /// - clock: the clock to measure the expiry
+    async fn get_token(
+        &self,
+        req: &Request,
+        clock: &dyn Clock,
+    ) -> Result<String, reqwest_middleware::Error> {
+        let _guard = match self.get_cached(clock.now()) {
+            Some((token, false)) => return Ok(token),
+            // Keep using the cached credentials, if they are being refreshed
+            Some((token, true)) => match self.refreshing.try_lock() {
+                Ok(guard) => guard,
+                Err(_) => return Ok(token),
+            },
+            None => self.refreshing.lock().await,
+        };
+
+        // The credentials may have been refreshed by others
+        if let Some((token, false)) = self.get_cached(clock.now()) {
+            return Ok(token);
+        }
+        let token = match self.provider.generate_token(req).await {
+            Ok(token) => token,
+            // Keep using the stale credentials until they expire, and retry the refresh later
+            Err(e) => match self.get_cached(clock.now()) {
+                Some((token, _)) => {
+                    log::warn!("Failed to refresh credentials: {}", e);
+                    return Ok(token);
+                }
+                None => return Err(e),
+            },
+        };
+        *self.cached.write().unwrap() = Some((token.clone(), clock.now() + self.ttl));
+        Ok(token)
+    }
+}
+
+#[async_trait]
+impl ApiAuthenticator for CachedCredentials {
+    fn get_carrier(&self) -> &Carrier {
+        &self.carrier
+    }
+
+    async fn authenticate(
+        &self,
+        req: Request,
+        extensions: &Extensions,
+    ) -> Result<Request, reqwest_middleware::Error> {
+        let token = self.get_token(&req, get_clock(extensions).as_ref()).await?;
+        Ok(self.get_carrier().apply(req, token))
+    }
+}
+
+#[async_trait]
+impl TokenGenerator for CachedCredentials {
+    async fn generate_token(&self, req: &Request) -> Result<String, reqwest_middleware::Error> {
+        self.get_token(req, &SystemClock).await
+    }
+}
+
+impl WithCarrier for CachedCredentials {
+    fn with_carrier(self, carrier: Carrier) -> Self {
+        Self { carrier, ..self }
+    }
+
+    fn with_header_name(self, name: impl ToString) -> Self {
+        Self {
+            carrier: Carrier::Header(name.to_string()),
+            ..self
+        }
+    }
+
+    fn with_query_param(self, name: impl ToString) -> Self {
+        Self {
+            carrier: Carrier::QueryParam(name.to_string()),
+            ..self
+        }
+    }
+}
+
 /// Hash algorithm
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HashAlgorithm {
     Md5,
     Sha1,
@@ -369,16 +583,21 @@ impl ApiAuthenticator for HashedTokenAuth {
     fn get_carrier(&self) -> &Carrier {
         &self.carrier
     }
+
+    async fn authenticate(
+        &self,
+        req: Request,
+        extensions: &Extensions,
+    ) -> Result<Request, reqwest_middleware::Error> {
+        let token = self.generate_token_at(get_clock(extensions).unix_timestamp());
+        Ok(self.get_carrier().apply(req, token))
+    }
 }
 
 #[async_trait]
 impl TokenGenerator for HashedTokenAuth {
     async fn generate_token(&self, _req: &Request) -> Result<String, reqwest_middleware::Error> {
-        let timestamp = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        Ok(self.generate_token_at(timestamp))
+        Ok(self.generate_token_at(SystemClock.unix_timestamp()))
     }
 }
 
@@ -402,6 +621,158 @@ impl WithCarrier for HashedTokenAuth {
     }
 }
 
+/// This struct is used to sign request by query params, such as S3-style pre-signed URLs.
+///
+/// # Sign algorithm
+///
+/// ```
+/// expires = UNIX_TIMESTAMP (in second) + expires_in
+/// query = sorted(query params + X-Access-Key + X-Expires), joined by `&`
+/// signature = hex(hmac_sha256($secret, $method + "\n" + $path + "\n" + $query))
+/// url = url + "&X-Access-Key=...&X-Expires=...&X-Signature=..."
+/// ```
+///
+/// The signature covers method, path and all query params, so the url can't be reused for other requests.
+/// Use `api.sign_url()` to generate the pre-signed URL without sending the request, and `verify` on the server side.
+///
+/// # Examples
+///
+/// ```
+/// let api = XxxApi::builder()
+///     .with_authenticator(PresignedUrlAuth::new("key", "secret", Duration::from_secs(300)))
+///     .build();
+/// let url = api.sign_url(Method::GET, "/files/1").await?;
+/// ```
+#[derive(Debug)]
+pub struct PresignedUrlAuth {
+    access_key: String,
+    secret: String,
+    expires_in: Duration,
+    access_key_param: String,
+    expires_param: String,
+    signature_param: String,
+}
+
+impl PresignedUrlAuth {
+    /// Create a new instance
+    /// - access_key: the access key, which is carried in query
+    /// - secret: the secret to sign
+    /// - expires_in: how long the signed url keeps valid
+    pub fn new(access_key: impl ToString, secret: impl ToString, expires_in: Duration) -> Self {
+        Self {
+            access_key: access_key.to_string(),
+            secret: secret.to_string(),
+            expires_in,
+            access_key_param: "X-Access-Key".to_string(),
+            expires_param: "X-Expires".to_string(),
+            signature_param: "X-Signature".to_string(),
+        }
+    }
+
+    /// Set the names of query params, which are `X-Access-Key`, `X-Expires` and `X-Signature` by default
+    /// - access_key: the name of access key param
+    /// - expires: the name of expires param
+    /// - signature: the name of signature param
+    pub fn with_param_names(
+        self,
+        access_key: impl ToString,
+        expires: impl ToString,
+        signature: impl ToString,
+    ) -> Self {
+        Self {
+            access_key_param: access_key.to_string(),
+            expires_param: expires.to_string(),
+            signature_param: signature.to_string(),
+            ..self
+        }
+    }
+
+    /// Verify the signed url, which is neither tampered nor expired
+    /// - method: HTTP method
+    /// - url: the signed url
+    pub fn verify(&self, method: &Method, url: &Url) -> bool {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.verify_at(method, url, now)
+    }
+
+    /// Verify the signed url at the time
+    fn verify_at(&self, method: &Method, url: &Url, now: u64) -> bool {
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v.to_string())
+        };
+        if param(&self.access_key_param).as_deref() != Some(self.access_key.as_str()) {
+            return false;
+        }
+        match param(&self.expires_param).and_then(|e| e.parse::<u64>().ok()) {
+            Some(expires) if expires >= now => {}
+            _ => return false,
+        }
+        let Some(signature) = param(&self.signature_param).and_then(|s| hex::decode(s).ok()) else {
+            return false;
+        };
+        let mut mac = digest::new_hmac_sha256(&self.secret);
+        mac.update(self.string_to_sign(method, url).as_bytes());
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    /// Build the string to sign, the signature param is excluded
+    fn string_to_sign(&self, method: &Method, url: &Url) -> String {
+        let mut query: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(name, _)| name != self.signature_param.as_str())
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        query.sort();
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(query)
+            .finish();
+        format!("{}\n{}\n{}", method, url.path(), query)
+    }
+
+    /// Sign the request at the time
+    fn sign_at(&self, req: Request, now: u64) -> Request {
+        let mut req = req;
+        let expires = now + self.expires_in.as_secs();
+        req.url_mut()
+            .query_pairs_mut()
+            .append_pair(&self.access_key_param, &self.access_key)
+            .append_pair(&self.expires_param, &expires.to_string());
+        let signature =
+            digest::hmac_sha256(&self.secret, self.string_to_sign(req.method(), req.url()));
+        req.url_mut()
+            .query_pairs_mut()
+            .append_pair(&self.signature_param, &signature);
+        req
+    }
+}
+
+#[async_trait]
+impl TokenGenerator for PresignedUrlAuth {
+    /// Generate the signature of request, whose query should contain access key and expires
+    async fn generate_token(&self, req: &Request) -> Result<String, reqwest_middleware::Error> {
+        Ok(digest::hmac_sha256(
+            &self.secret,
+            self.string_to_sign(req.method(), req.url()),
+        ))
+    }
+}
+
+#[async_trait]
+impl ApiAuthenticator for PresignedUrlAuth {
+    async fn authenticate(
+        &self,
+        req: Request,
+        extensions: &Extensions,
+    ) -> Result<Request, reqwest_middleware::Error> {
+        Ok(self.sign_at(req, get_clock(extensions).unix_timestamp()))
+    }
+}
+
 /// Token Error
 #[derive(Debug, Error)]
 pub enum TokenError {
@@ -512,3 +883,33 @@ impl TryFrom<String> for ParsedHashedToken {
         Self::parse(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::{Method, Request, Url};
+
+    use super::PresignedUrlAuth;
+
+    #[test]
+    fn test_presigned_url_expiry() {
+        let auth = PresignedUrlAuth::new("key", "secret", Duration::from_secs(60));
+        let url = Url::parse("http://localhost/files/1?b=2&a=1").unwrap();
+        let req = auth.sign_at(Request::new(Method::GET, url), 1000);
+        let url = req.url();
+
+        assert!(auth.verify_at(&Method::GET, url, 1000));
+        assert!(auth.verify_at(&Method::GET, url, 1060));
+        assert!(!auth.verify_at(&Method::GET, url, 1061));
+        assert!(!auth.verify_at(&Method::PUT, url, 1000));
+
+        let mut tampered = url.clone();
+        tampered.set_query(Some(
+            &url.query()
+                .unwrap()
+                .replace("X-Expires=1060", "X-Expires=9999"),
+        ));
+        assert!(!auth.verify_at(&Method::GET, &tampered, 1000));
+    }
+}
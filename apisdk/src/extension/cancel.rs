@@ -0,0 +1,151 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+/// This extension is used to cancel the in-flight request
+///
+/// Once the token is cancelled, the request will be aborted, and `ApiError::Cancelled` will be returned.
+///
+/// # Example
+///
+/// ```
+/// let token = CancellationToken::new();
+/// let req = client.get("/path").await?;
+/// let req = req.with_extension(token.clone());
+///
+/// // cancel the request from somewhere else
+/// token.cancel();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    /// The shared state
+    inner: Arc<CancelState>,
+}
+
+/// The shared state of `CancellationToken`
+#[derive(Debug, Default)]
+struct CancelState {
+    /// Indicate whether the token is cancelled
+    cancelled: AtomicBool,
+    /// The wakers of pending futures, by the id of future
+    wakers: Mutex<HashMap<u64, Waker>>,
+    /// The id of next future
+    next_id: AtomicU64,
+}
+
+impl CancellationToken {
+    /// Create a new instance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the token, and wake up all pending futures
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        let wakers: Vec<Waker> = self
+            .inner
+            .wakers
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(_, w)| w)
+            .collect();
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Check the token is cancelled or not
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Get a future, which will be resolved when the token is cancelled
+    pub fn cancelled(&self) -> WaitForCancellation {
+        WaitForCancellation {
+            token: self.clone(),
+            id: self.inner.next_id.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+/// This future will be resolved when the related `CancellationToken` is cancelled
+///
+/// Its waker is unregistered once it's dropped.
+#[derive(Debug)]
+pub struct WaitForCancellation {
+    /// The related token
+    token: CancellationToken,
+    /// The id to register the waker
+    id: u64,
+}
+
+impl Future for WaitForCancellation {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        let mut wakers = self.token.inner.wakers.lock().unwrap();
+        match wakers.get_mut(&self.id) {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            Some(waker) => *waker = cx.waker().clone(),
+            None => {
+                wakers.insert(self.id, cx.waker().clone());
+            }
+        }
+        drop(wakers);
+
+        // Check again, in case the token is cancelled before the waker is registered
+        if self.token.is_cancelled() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for WaitForCancellation {
+    fn drop(&mut self) {
+        let waker = self.token.inner.wakers.lock().unwrap().remove(&self.id);
+        drop(waker);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, pin::pin, task::Context};
+
+    use futures::task::noop_waker;
+
+    use super::CancellationToken;
+
+    #[test]
+    fn test_waker_removed_on_drop() {
+        let token = CancellationToken::new();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        for _ in 0..10 {
+            let mut fut = pin!(token.cancelled());
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+            assert!(fut.as_mut().poll(&mut cx).is_pending());
+            assert_eq!(1, token.inner.wakers.lock().unwrap().len());
+        }
+        assert!(token.inner.wakers.lock().unwrap().is_empty());
+
+        let mut fut = pin!(token.cancelled());
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        token.cancel();
+        assert!(fut.as_mut().poll(&mut cx).is_ready());
+        assert!(token.inner.wakers.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,330 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use reqwest::{
+    header::{HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE},
+    Request, Response, StatusCode,
+};
+use reqwest_middleware::Next;
+
+use crate::{digest, Extensions, Middleware, SignableRequest};
+
+/// This struct holds a challenge of `WWW-Authenticate` header
+///
+/// ```text
+/// WWW-Authenticate: Digest realm="api", qop="auth", nonce="dcd98b7102dd", opaque="5ccc069c403e"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    /// The auth-scheme, such as `Digest`
+    pub scheme: String,
+    /// The auth-params, whose names are in lowercase
+    pub params: HashMap<String, String>,
+}
+
+impl Challenge {
+    /// Get the auth-param
+    /// - name: the name of param, in lowercase
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(|v| v.as_str())
+    }
+
+    /// Parse the challenges of one header value, which may contain several challenges
+    /// - value: the value of `WWW-Authenticate` header
+    pub fn parse(value: &str) -> Vec<Challenge> {
+        let mut challenges: Vec<Challenge> = vec![];
+        let mut chars = value.chars().peekable();
+        loop {
+            // Skip the separators
+            while chars.next_if(|c| *c == ',' || c.is_whitespace()).is_some() {}
+            let mut token = String::new();
+            while let Some(c) = chars.next_if(|c| *c != '=' && *c != ',' && !c.is_whitespace()) {
+                token.push(c);
+            }
+            if token.is_empty() {
+                break;
+            }
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+            // A token without `=` starts a new challenge
+            if chars.next_if_eq(&'=').is_none() {
+                challenges.push(Challenge {
+                    scheme: token,
+                    params: HashMap::new(),
+                });
+                continue;
+            }
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            let mut value = String::new();
+            if chars.next_if_eq(&'"').is_some() {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => value.extend(chars.next()),
+                        c => value.push(c),
+                    }
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| *c != ',' && !c.is_whitespace()) {
+                    value.push(c);
+                }
+            }
+            // The params before any scheme are ignored
+            if let Some(challenge) = challenges.last_mut() {
+                challenge.params.insert(token.to_lowercase(), value);
+            }
+        }
+        challenges
+    }
+}
+
+/// This trait is used to compute the credentials for the challenge, see `ChallengeAuthMiddleware`
+pub trait ChallengeHandler: 'static + Send + Sync {
+    /// Get the auth-scheme to handle, such as `Digest`, which is case-insensitive
+    fn scheme(&self) -> &str;
+
+    /// Compute the value of `Authorization` header
+    /// - challenge: the challenge of scheme
+    /// - req: the request to retry
+    ///
+    /// Return `None` if the challenge is not supported
+    fn respond(&self, challenge: &Challenge, req: &Request) -> Option<String>;
+}
+
+/// This middleware is used to negotiate auth with the challenge of `WWW-Authenticate` header.
+///
+/// When the response is `401 Unauthorized` with a challenge of the scheme, the request is retried once
+/// with the credentials computed by `ChallengeHandler`, such as `DigestAuth`.
+/// The request whose body could not be cloned is sent only once.
+///
+/// # Examples
+///
+/// ```
+/// let api = XxxApi::builder()
+///     .with_middleware(ChallengeAuthMiddleware::new(DigestAuth::new("user", "password")))
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct ChallengeAuthMiddleware {
+    /// The handler
+    handler: Arc<dyn ChallengeHandler>,
+}
+
+impl std::fmt::Debug for ChallengeAuthMiddleware {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChallengeAuthMiddleware")
+            .field("scheme", &self.handler.scheme())
+            .finish()
+    }
+}
+
+impl ChallengeAuthMiddleware {
+    /// Create a new instance
+    /// - handler: compute the credentials for the challenge
+    pub fn new(handler: impl ChallengeHandler) -> Self {
+        Self {
+            handler: Arc::new(handler),
+        }
+    }
+
+    /// Compute the value of `Authorization` header from the response
+    fn authorize(&self, res: &Response, req: &Request) -> Option<HeaderValue> {
+        let credentials = res
+            .headers()
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(Challenge::parse)
+            .filter(|c| c.scheme.eq_ignore_ascii_case(self.handler.scheme()))
+            .find_map(|c| self.handler.respond(&c, req))?;
+        // Mark the value as sensitive, so that it will not be logged
+        let mut value = HeaderValue::try_from(credentials).ok()?;
+        value.set_sensitive(true);
+        Some(value)
+    }
+}
+
+#[async_trait]
+impl Middleware for ChallengeAuthMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        let mut retry = match req.try_clone() {
+            Some(retry) => retry,
+            None => return next.run(req, extensions).await,
+        };
+
+        let res = next.clone().run(req, extensions).await?;
+        if res.status() != StatusCode::UNAUTHORIZED {
+            return Ok(res);
+        }
+        match self.authorize(&res, &retry) {
+            Some(value) => {
+                log::debug!("Retry with {} auth", self.handler.scheme());
+                retry.headers_mut().insert(AUTHORIZATION, value);
+                next.run(retry, extensions).await
+            }
+            None => Ok(res),
+        }
+    }
+}
+
+/// This struct is used to compute the credentials of HTTP Digest auth (RFC 7616)
+///
+/// The algorithms `MD5`, `MD5-sess`, `SHA-256` and `SHA-256-sess` are supported,
+/// with the qop of `auth`, `auth-int` or none.
+#[derive(Debug, Clone)]
+pub struct DigestAuth {
+    /// The username
+    username: String,
+    /// The password
+    password: String,
+}
+
+impl DigestAuth {
+    /// Create a new instance
+    /// - username: the username
+    /// - password: the password
+    pub fn new(username: impl ToString, password: impl ToString) -> Self {
+        Self {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    /// Compute the value of `Authorization` header with the client nonce
+    /// - challenge: the challenge of `Digest`
+    /// - req: the request to retry
+    /// - cnonce: the client nonce
+    pub fn respond_with_cnonce(
+        &self,
+        challenge: &Challenge,
+        req: &Request,
+        cnonce: &str,
+    ) -> Option<String> {
+        let realm = challenge.param("realm").unwrap_or_default();
+        let nonce = challenge.param("nonce")?;
+        let algorithm = challenge.param("algorithm").unwrap_or("MD5");
+        let (sha256, session) = match algorithm.to_uppercase().as_str() {
+            "MD5" => (false, false),
+            "MD5-SESS" => (false, true),
+            "SHA-256" => (true, false),
+            "SHA-256-SESS" => (true, true),
+            _ => return None,
+        };
+        let hash = |input: &[u8]| match sha256 {
+            true => digest::sha256(input),
+            false => digest::md5(input),
+        };
+
+        // Prefer `auth` to `auth-int`, since the latter requires the whole body
+        let qop = match challenge.param("qop") {
+            Some(qop) => {
+                let qops: Vec<&str> = qop.split(',').map(|q| q.trim()).collect();
+                if qops.contains(&"auth") {
+                    Some("auth")
+                } else if qops.contains(&"auth-int") {
+                    Some("auth-int")
+                } else {
+                    return None;
+                }
+            }
+            None => None,
+        };
+
+        let uri = match req.url().query() {
+            Some(query) => format!("{}?{}", req.url().path(), query),
+            None => req.url().path().to_string(),
+        };
+        let nc = "00000001";
+
+        let mut ha1 = hash(format!("{}:{}:{}", self.username, realm, self.password).as_bytes());
+        if session {
+            ha1 = hash(format!("{}:{}:{}", ha1, nonce, cnonce).as_bytes());
+        }
+        let ha2 = match qop {
+            Some("auth-int") => {
+                let body = hash(req.body_bytes()?);
+                hash(format!("{}:{}:{}", req.method(), uri, body).as_bytes())
+            }
+            _ => hash(format!("{}:{}", req.method(), uri).as_bytes()),
+        };
+        let response = match qop {
+            Some(qop) => {
+                hash(format!("{}:{}:{}:{}:{}:{}", ha1, nonce, nc, cnonce, qop, ha2).as_bytes())
+            }
+            None => hash(format!("{}:{}:{}", ha1, nonce, ha2).as_bytes()),
+        };
+
+        let mut credentials = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}, response=\"{}\"",
+            self.username, realm, nonce, uri, algorithm, response
+        );
+        if let Some(qop) = qop {
+            credentials.push_str(&format!(", qop={}, nc={}, cnonce=\"{}\"", qop, nc, cnonce));
+        }
+        if let Some(opaque) = challenge.param("opaque") {
+            credentials.push_str(&format!(", opaque=\"{}\"", opaque));
+        }
+        Some(credentials)
+    }
+}
+
+impl ChallengeHandler for DigestAuth {
+    fn scheme(&self) -> &str {
+        "Digest"
+    }
+
+    fn respond(&self, challenge: &Challenge, req: &Request) -> Option<String> {
+        self.respond_with_cnonce(challenge, req, &nanoid::nanoid!())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{Method, Url};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_challenges() {
+        let challenges = Challenge::parse(
+            r#"Negotiate, Digest realm="a, b", QOP="auth,auth-int", nonce=abc, Basic realm="x""#,
+        );
+        assert_eq!(3, challenges.len());
+        assert_eq!("Negotiate", challenges[0].scheme);
+        assert!(challenges[0].params.is_empty());
+        assert_eq!("Digest", challenges[1].scheme);
+        assert_eq!(Some("a, b"), challenges[1].param("realm"));
+        assert_eq!(Some("auth,auth-int"), challenges[1].param("qop"));
+        assert_eq!(Some("abc"), challenges[1].param("nonce"));
+        assert_eq!("Basic", challenges[2].scheme);
+        assert_eq!(Some("x"), challenges[2].param("realm"));
+    }
+
+    #[test]
+    fn test_digest_rfc7616() {
+        // The example of RFC 7616, section 3.9.1
+        let challenge = Challenge::parse(
+            r#"Digest realm="http-auth@example.org", qop="auth, auth-int", algorithm=MD5, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#,
+        )
+        .remove(0);
+        let req = Request::new(
+            Method::GET,
+            Url::parse("http://www.example.org/dir/index.html").unwrap(),
+        );
+        let credentials = DigestAuth::new("Mufasa", "Circle of Life")
+            .respond_with_cnonce(
+                &challenge,
+                &req,
+                "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ",
+            )
+            .unwrap();
+        assert!(credentials.contains(r#"response="8ca523f5e9506fed4657c9700eebdbec""#));
+        assert!(credentials.contains("qop=auth, nc=00000001"));
+        assert!(credentials.contains(r#"opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#));
+    }
+}
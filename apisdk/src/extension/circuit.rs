@@ -0,0 +1,206 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use reqwest::{Request, Response};
+use reqwest_middleware::Next;
+
+use crate::{ApiError, Extensions, Middleware};
+
+/// This enum represents the state of circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent
+    Closed,
+    /// Requests are rejected with `ApiError::CircuitOpen`, without being sent
+    Open,
+    /// The open duration has elapsed, and only one request is sent as a trial
+    HalfOpen,
+}
+
+/// The predicate to decide whether the failure should trip the circuit
+type TripPredicate = Arc<dyn Fn(&ApiError) -> bool + Send + Sync>;
+
+/// The counters of circuit
+#[derive(Debug, Default)]
+struct Circuit {
+    /// The number of consecutive failures
+    failures: u32,
+    /// When the circuit was opened
+    opened_at: Option<Instant>,
+    /// Whether the trial request is being sent
+    probing: bool,
+}
+
+/// This struct marks the trial request, and allows another trial when dropped
+struct Probe {
+    /// The counters
+    circuit: Arc<Mutex<Circuit>>,
+}
+
+impl Drop for Probe {
+    fn drop(&mut self) {
+        self.circuit.lock().unwrap().probing = false;
+    }
+}
+
+/// This middleware is used to stop sending requests to an unhealthy service.
+///
+/// The circuit is opened after `threshold` consecutive failures, and then the requests fail fast with `ApiError::CircuitOpen`.
+/// Once the open duration has elapsed, a trial request is sent: the circuit is closed if it succeeds, or opened again if not.
+/// The other requests are still rejected, until the trial request is done.
+///
+/// By default, server errors (5xx), connection errors and timeouts trip the circuit.
+/// Use `with_trip_on` to classify the failures in another way.
+///
+/// # Examples
+///
+/// ```
+/// let breaker = CircuitBreakerMiddleware::new(5, Duration::from_secs(30))
+///     .with_trip_on(|e: &ApiError| matches!(e.status(), Some(429 | 500 | 502 | 504)));
+/// let api = XxxApi::builder().with_middleware(breaker.clone()).build();
+/// // ... send requests
+/// let state = breaker.state();
+/// ```
+#[derive(Clone)]
+pub struct CircuitBreakerMiddleware {
+    /// The number of consecutive failures to open the circuit
+    threshold: u32,
+    /// How long the circuit keeps open
+    open_duration: Duration,
+    /// Decide whether the failure should trip the circuit
+    trip_on: TripPredicate,
+    /// The counters
+    circuit: Arc<Mutex<Circuit>>,
+}
+
+impl Debug for CircuitBreakerMiddleware {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CircuitBreakerMiddleware")
+            .field("threshold", &self.threshold)
+            .field("open_duration", &self.open_duration)
+            .field("state", &self.state())
+            .finish()
+    }
+}
+
+impl CircuitBreakerMiddleware {
+    /// Create a new instance
+    /// - threshold: the number of consecutive failures to open the circuit
+    /// - open_duration: how long the circuit keeps open
+    pub fn new(threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            open_duration,
+            trip_on: Arc::new(is_trip_worthy),
+            circuit: Arc::new(Mutex::new(Circuit::default())),
+        }
+    }
+
+    /// Set the predicate to decide whether the failure should trip the circuit
+    /// - trip_on: return `true` if the failure should be counted
+    pub fn with_trip_on<F>(self, trip_on: F) -> Self
+    where
+        F: Fn(&ApiError) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            trip_on: Arc::new(trip_on),
+            ..self
+        }
+    }
+
+    /// Get the current state
+    pub fn state(&self) -> CircuitState {
+        let circuit = self.circuit.lock().unwrap();
+        match circuit.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.open_duration => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Close the circuit, and reset the counters
+    pub fn reset(&self) {
+        *self.circuit.lock().unwrap() = Circuit::default();
+    }
+
+    /// Check whether the request could be sent
+    ///
+    /// Return `Some(Probe)` if the request is sent as a trial, or `ApiError::CircuitOpen` if rejected.
+    fn acquire(&self) -> Result<Option<Probe>, ApiError> {
+        let mut circuit = self.circuit.lock().unwrap();
+        match circuit.opened_at {
+            None => Ok(None),
+            Some(opened_at) if opened_at.elapsed() < self.open_duration => {
+                Err(ApiError::CircuitOpen)
+            }
+            Some(_) if circuit.probing => Err(ApiError::CircuitOpen),
+            Some(_) => {
+                circuit.probing = true;
+                Ok(Some(Probe {
+                    circuit: self.circuit.clone(),
+                }))
+            }
+        }
+    }
+
+    /// Update the counters with the outcome of request
+    /// - tripped: whether the request failed with a trip-worthy error
+    fn record(&self, tripped: bool) {
+        let mut circuit = self.circuit.lock().unwrap();
+        if tripped {
+            circuit.failures = circuit.failures.saturating_add(1);
+            if circuit.failures >= self.threshold || circuit.opened_at.is_some() {
+                circuit.opened_at = Some(Instant::now());
+            }
+        } else {
+            *circuit = Circuit {
+                probing: circuit.probing,
+                ..Default::default()
+            };
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for CircuitBreakerMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        let _probe = self.acquire()?;
+
+        match next.run(req, extensions).await {
+            Ok(res) => {
+                let status = res.status();
+                let tripped = if status.is_client_error() || status.is_server_error() {
+                    (self.trip_on)(&ApiError::from_status(status))
+                } else {
+                    false
+                };
+                self.record(tripped);
+                Ok(res)
+            }
+            Err(e) => {
+                let e = ApiError::from(e);
+                self.record((self.trip_on)(&e));
+                Err(e.into())
+            }
+        }
+    }
+}
+
+/// The default predicate, which trips on server errors, connection errors and timeouts
+/// - e: the failure
+fn is_trip_worthy(e: &ApiError) -> bool {
+    match e {
+        ApiError::Reqwest(e) => e.is_connect() || e.is_timeout(),
+        ApiError::Connection(..) | ApiError::Timeout => true,
+        _ => matches!(e.status(), Some(500..=599)),
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use reqwest_middleware::{RequestBuilder, RequestInitialiser};
+
+use crate::Extensions;
+
+/// This trait is used to read the current time and wait, see `with_clock`
+///
+/// It's used by the timestamps of signatures, the delays of `RetryMiddleware` and `with_respond_async`, and the expiry of `CachedCredentials`,
+/// so the tests could advance time deterministically by `ManualClock`.
+#[async_trait]
+pub trait Clock: 'static + Send + Sync {
+    /// Get the current time
+    fn now(&self) -> SystemTime;
+
+    /// Wait for the duration
+    /// - duration: how long to wait
+    async fn sleep(&self, duration: Duration);
+
+    /// Get the current UNIX timestamp, in second
+    fn unix_timestamp(&self) -> u64 {
+        self.now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+}
+
+/// This struct reads the system time, and waits by `tokio::time::sleep`, which is the default `Clock`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await
+    }
+}
+
+/// This struct is a `Clock` which only moves when advanced, designed for tests
+///
+/// The `sleep` returns immediately, after advancing the time by the duration.
+/// The cloned clocks share the same time.
+///
+/// # Examples
+///
+/// ```
+/// let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000));
+/// let api = XxxApi::builder().with_clock(clock.clone()).build();
+/// clock.advance(Duration::from_secs(60));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    /// The current time
+    now: Arc<Mutex<SystemTime>>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new(SystemTime::now())
+    }
+}
+
+impl ManualClock {
+    /// Create a new instance
+    /// - start: the initial time
+    pub fn new(start: SystemTime) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(start)),
+        }
+    }
+
+    /// Move the time forward
+    /// - duration: how long to advance
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+#[async_trait]
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+        tokio::task::yield_now().await
+    }
+}
+
+/// This struct holds the `Clock` set by `with_clock`, and attaches it to each request
+#[derive(Clone)]
+pub(crate) struct SharedClock(pub Arc<dyn Clock>);
+
+impl RequestInitialiser for SharedClock {
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        req.with_extension(self.clone())
+    }
+}
+
+/// Get the `Clock` of request, or `SystemClock` if not set by `with_clock`
+/// - extensions: the extensions of request
+pub fn get_clock(extensions: &Extensions) -> Arc<dyn Clock> {
+    match extensions.get::<SharedClock>() {
+        Some(clock) => clock.0.clone(),
+        None => Arc::new(SystemClock),
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+use reqwest_middleware::RequestBuilder;
+
+use crate::Extensions;
+
+/// This struct is used to hold caller-provided context of request, such as tenant id.
+///
+/// The value is kept by its type, so it won't conflict with other extensions of the same type.
+/// It's cloned into the duplicated requests, such as retries and hedged requests (see `DuplicateOps`).
+///
+/// # Examples
+///
+/// ### attach context
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let req = req.with_context(TenantId("t1".to_string()));
+/// ```
+///
+/// ### read context in middleware
+///
+/// ```
+/// async fn handle(&self, req: Request, extensions: &mut Extensions, next: Next<'_>) -> Result<Response> {
+///     if let Some(tenant) = RequestContext::<TenantId>::get(extensions) {
+///         // ...
+///     }
+///     next.run(req, extensions).await
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestContext<T>(pub T);
+
+impl<T> RequestContext<T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Get the context value from extensions
+    /// - extensions: the extensions of request
+    pub fn get(extensions: &Extensions) -> Option<&T> {
+        let value = extensions
+            .get::<RequestContexts>()?
+            .0
+            .get(&TypeId::of::<T>())?;
+        (**value).as_any().downcast_ref()
+    }
+
+    /// Get the mutable context value from extensions
+    /// - extensions: the extensions of request
+    pub fn get_mut(extensions: &mut Extensions) -> Option<&mut T> {
+        let value = extensions
+            .get_mut::<RequestContexts>()?
+            .0
+            .get_mut(&TypeId::of::<T>())?;
+        (**value).as_any_mut().downcast_mut()
+    }
+}
+
+/// This trait is used to clone the context values of any type
+trait ContextValue: Any + Send + Sync {
+    /// Clone the value
+    fn clone_box(&self) -> Box<dyn ContextValue>;
+    /// Cast to `Any`, to get the concrete value
+    fn as_any(&self) -> &dyn Any;
+    /// Cast to mutable `Any`, to get the concrete value
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T> ContextValue for T
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn clone_box(&self) -> Box<dyn ContextValue> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// This struct holds the context values of request, by their types
+#[derive(Default)]
+pub(crate) struct RequestContexts(HashMap<TypeId, Box<dyn ContextValue>>);
+
+impl Clone for RequestContexts {
+    fn clone(&self) -> Self {
+        Self(
+            self.0
+                .iter()
+                .map(|(id, value)| (*id, (**value).clone_box()))
+                .collect(),
+        )
+    }
+}
+
+/// This trait provides context related functions
+pub trait ContextOps {
+    /// Attach a context value, which could be read by middlewares via `RequestContext::get`
+    /// - value: the context value
+    fn with_context<T>(self, value: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static;
+}
+
+impl ContextOps for RequestBuilder {
+    fn with_context<T>(mut self, value: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let extensions = self.extensions();
+        let value: Box<dyn ContextValue> = Box::new(value);
+        match extensions.get_mut::<RequestContexts>() {
+            Some(contexts) => {
+                contexts.0.insert(TypeId::of::<T>(), value);
+            }
+            None => {
+                let mut contexts = RequestContexts::default();
+                contexts.0.insert(TypeId::of::<T>(), value);
+                extensions.insert(contexts);
+            }
+        }
+        self
+    }
+}
@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use reqwest::{
+    header::{HeaderValue, DATE},
+    Request, Response,
+};
+use reqwest_middleware::Next;
+
+use crate::{get_clock, Extensions, Middleware};
+
+/// This middleware is used to set `Date` header, if enabled by `with_auto_date`
+///
+/// It goes after the user middlewares, so each retry, hedged request and poll gets the time when it's sent.
+/// The `Date` header set by the request is kept.
+pub(crate) struct AutoDateMiddleware;
+
+#[async_trait]
+impl Middleware for AutoDateMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        if !req.headers().contains_key(DATE) {
+            let date = httpdate::fmt_http_date(get_clock(extensions).now());
+            if let Ok(value) = HeaderValue::from_str(&date) {
+                req.headers_mut().insert(DATE, value);
+            }
+        }
+        next.run(req, extensions).await
+    }
+}
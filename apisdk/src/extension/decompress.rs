@@ -0,0 +1,67 @@
+use reqwest_middleware::{RequestBuilder, RequestInitialiser};
+
+/// This extension is used to keep the compressed response as `ResponseBody::Bytes`,
+/// together with its `Content-Encoding`, instead of decoding it before parsing.
+///
+/// # Examples
+///
+/// ### apply to all requests
+///
+/// ```
+/// let client = XxxApi::builder().with_auto_decompress(false).build();
+/// ```
+///
+/// ### apply to single request
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let req = req.with_extension(NoDecompress);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoDecompress;
+
+impl RequestInitialiser for NoDecompress {
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        req.with_extension(*self)
+    }
+}
+
+/// This extension is used to limit the length of response decoded by apisdk (e.g. `br` or `zstd`),
+/// which prevents decompression bombs.
+///
+/// The response fails with `ApiError::DecodeResponse` once the limit is exceeded.
+/// The gzip/deflate response decoded by reqwest while streaming is not limited (see `with_auto_decompress`).
+///
+/// # Examples
+///
+/// ### apply to all requests
+///
+/// ```
+/// let client = XxxApi::builder().with_max_decoded_len(1024 * 1024).build();
+/// ```
+///
+/// ### apply to single request
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let req = req.with_extension(MaxDecodedLen(1024 * 1024));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxDecodedLen(pub usize);
+
+impl MaxDecodedLen {
+    /// The default limit, which is 64 MiB
+    pub const DEFAULT: usize = 64 * 1024 * 1024;
+}
+
+impl Default for MaxDecodedLen {
+    fn default() -> Self {
+        Self(Self::DEFAULT)
+    }
+}
+
+impl RequestInitialiser for MaxDecodedLen {
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        req.with_extension(*self)
+    }
+}
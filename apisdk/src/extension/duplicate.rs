@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use reqwest_middleware::RequestBuilder;
+
+use crate::{
+    ApiAuthenticator, CancellationToken, DoubleJsonDecode, Extensions, GrpcWebJson, JsonPolicy,
+    LogConfig, Logger, MockServer, NoDecompress, NoMock, Priority, RequestContexts, RequestId,
+    RespondAsync, ResponseRenameAll, SharedClock, SingleFlight, TraceId,
+};
+
+/// This trait is used to duplicate a configured request, such as retries and speculative requests
+pub trait DuplicateOps: Sized {
+    /// Try to duplicate the request, together with the extensions provided by this crate
+    ///
+    /// Return `None` when the body could not be cloned, such as streaming body.
+    ///
+    /// Unlike `RequestBuilder::try_clone()`, `LogConfig`, `MockServer`, `RequestId`, `RequestContext` and other
+    /// extensions of this crate are preserved. Other extensions inserted by `with_extension` are dropped.
+    fn try_duplicate(&mut self) -> Option<Self>;
+}
+
+impl DuplicateOps for RequestBuilder {
+    fn try_duplicate(&mut self) -> Option<Self> {
+        let mut dup = self.try_clone()?;
+        RequestExtensions::copy_from(self.extensions()).insert_into(dup.extensions());
+        Some(dup)
+    }
+}
+
+/// Declare the extensions provided by this crate, and generate `RequestExtensions` to carry them
+macro_rules! request_extensions {
+    ($($name:ident: $ty:ty,)+) => {
+        /// This struct holds the per-request extensions provided by this crate
+        ///
+        /// It's the single place to register a new extension,
+        /// so that retries, hedged requests and other duplicates keep it.
+        #[derive(Clone, Default)]
+        pub(crate) struct RequestExtensions {
+            $($name: Option<$ty>,)+
+        }
+
+        impl RequestExtensions {
+            /// Copy the extensions provided by this crate
+            /// - extensions: the extensions of original request
+            pub(crate) fn copy_from(extensions: &Extensions) -> Self {
+                Self {
+                    $($name: extensions.get::<$ty>().cloned(),)+
+                }
+            }
+
+            /// Insert the carried extensions
+            /// - extensions: the extensions of duplicated request
+            pub(crate) fn insert_into(self, extensions: &mut Extensions) {
+                $(
+                    if let Some(value) = self.$name {
+                        extensions.insert(value);
+                    }
+                )+
+            }
+        }
+    };
+}
+
+request_extensions! {
+    authenticator: Arc<dyn ApiAuthenticator>,
+    log_config: LogConfig,
+    logger: Logger,
+    json_policy: JsonPolicy,
+    double_json: DoubleJsonDecode,
+    rename_all: ResponseRenameAll,
+    single_flight: SingleFlight,
+    mock: MockServer,
+    no_mock: NoMock,
+    grpc: GrpcWebJson,
+    no_decompress: NoDecompress,
+    respond_async: RespondAsync,
+    cancellation: CancellationToken,
+    request_id: RequestId,
+    trace_id: TraceId,
+    shared_clock: SharedClock,
+    priority: Priority,
+    contexts: RequestContexts,
+}
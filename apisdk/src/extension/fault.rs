@@ -0,0 +1,157 @@
+use std::{sync::Mutex, time::Duration};
+
+use async_trait::async_trait;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use reqwest::{Request, Response, StatusCode};
+use reqwest_middleware::Next;
+
+use crate::{ApiError, Extensions, Matcher, Middleware, TransportError};
+
+/// This enum represents the fault to inject
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Delay the request, and then send it
+    Delay(Duration),
+    /// Fail with HTTP status, without sending the request
+    Status(u16),
+    /// Fail with transport-level error, without sending the request
+    Transport(TransportError),
+}
+
+impl Fault {
+    /// Build the ApiError, unless it's a delay
+    fn to_error(&self) -> Option<ApiError> {
+        match self {
+            Self::Delay(_) => None,
+            Self::Status(code) => {
+                let status =
+                    StatusCode::from_u16(*code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                Some(ApiError::from_status(status))
+            }
+            Self::Transport(e) => Some(ApiError::Connection(*e)),
+        }
+    }
+}
+
+/// This struct decides which requests will be injected with the fault
+#[derive(Debug, Clone)]
+pub struct FaultRule {
+    /// The requests to match
+    matcher: Matcher,
+    /// The probability to inject, between `0.0` and `1.0`
+    probability: f64,
+    /// The fault to inject
+    fault: Fault,
+}
+
+impl FaultRule {
+    /// Create a new instance, which injects the fault into all requests
+    /// - fault: the fault to inject
+    pub fn new(fault: Fault) -> Self {
+        Self {
+            matcher: Matcher::new(),
+            probability: 1.0,
+            fault,
+        }
+    }
+
+    /// Only inject into the matched requests
+    /// - matcher: Matcher
+    pub fn with_matcher(self, matcher: Matcher) -> Self {
+        Self { matcher, ..self }
+    }
+
+    /// Only inject into the requests with the path
+    /// - path: the suffix of url path
+    pub fn with_path(self, path: impl ToString) -> Self {
+        Self {
+            matcher: self.matcher.path(path),
+            ..self
+        }
+    }
+
+    /// Set the probability to inject
+    /// - probability: between `0.0` and `1.0`, and `NaN` is treated as `0.0`
+    pub fn with_probability(self, probability: f64) -> Self {
+        let probability = if probability.is_nan() {
+            0.0
+        } else {
+            probability.clamp(0.0, 1.0)
+        };
+        Self {
+            probability,
+            ..self
+        }
+    }
+}
+
+/// This middleware is used to inject faults (delays and errors) into requests, such as chaos testing.
+///
+/// The rules are evaluated in declared order. The delays are accumulated,
+/// and the first error stops the request from being sent.
+/// It's deterministic with the same seed and the same sequence of requests.
+///
+/// # Examples
+///
+/// ```
+/// let faults = FaultInjectionMiddleware::new(42)
+///     .with_rule(FaultRule::new(Fault::Delay(Duration::from_millis(200))).with_probability(0.1))
+///     .with_rule(FaultRule::new(Fault::Status(503)).with_path("/orders").with_probability(0.05));
+/// let api = XxxApi::builder().with_middleware(faults).build();
+/// ```
+pub struct FaultInjectionMiddleware {
+    /// The rules
+    rules: Vec<FaultRule>,
+    /// The seeded random generator
+    rng: Mutex<StdRng>,
+}
+
+impl FaultInjectionMiddleware {
+    /// Create a new instance without any rule
+    /// - seed: the seed of random generator
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rules: vec![],
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// Add a rule
+    /// - rule: FaultRule
+    pub fn with_rule(self, rule: FaultRule) -> Self {
+        let mut s = self;
+        s.rules.push(rule);
+        s
+    }
+
+    /// Pick the faults to inject
+    /// - req: HTTP request
+    fn pick(&self, req: &Request) -> Vec<&Fault> {
+        let mut rng = self.rng.lock().unwrap();
+        self.rules
+            .iter()
+            .filter(|rule| rule.matcher.matches(req))
+            .filter(|rule| rng.gen_bool(rule.probability))
+            .map(|rule| &rule.fault)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Middleware for FaultInjectionMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        for fault in self.pick(&req) {
+            if let Fault::Delay(delay) = fault {
+                tokio::time::sleep(*delay).await;
+            } else if let Some(e) = fault.to_error() {
+                return Err(e.into());
+            }
+        }
+        next.run(req, extensions).await
+    }
+}
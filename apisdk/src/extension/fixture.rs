@@ -0,0 +1,163 @@
+use std::{cmp::Reverse, collections::BTreeMap, path::Path};
+
+use async_trait::async_trait;
+use reqwest::{Method, Request};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{ApiError, ApiResult, Matcher, Responder, ResponseBody, Unmatched};
+
+/// This struct describes the request to match in fixture
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FixtureRequest {
+    /// The expected HTTP method
+    method: Option<String>,
+    /// The expected suffix of url path
+    path: Option<String>,
+    /// The expected query parameters
+    query: BTreeMap<String, String>,
+    /// Reject unexpected query parameters
+    exact_query: bool,
+}
+
+/// This enum describes the response to reply in fixture
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FixtureResponse {
+    /// Reply json payload
+    Json(Value),
+    /// Reply xml payload
+    Xml(String),
+    /// Reply text payload
+    Text(String),
+}
+
+/// This struct describes a fixture file
+#[derive(Debug, Deserialize)]
+struct Fixture {
+    /// The request to match
+    #[serde(default)]
+    request: FixtureRequest,
+    /// The response to reply
+    response: FixtureResponse,
+}
+
+/// This struct holds a loaded fixture
+#[derive(Debug)]
+struct Route {
+    /// The file name of fixture
+    name: String,
+    /// The request matcher
+    matcher: Matcher,
+    /// The response payload
+    payload: ResponseBody,
+}
+
+/// This struct is used to reply the requests by using fixture files.
+///
+/// Each `*.json` file in the directory describes a request matcher and a response:
+///
+/// ```json
+/// {
+///     "request": { "method": "GET", "path": "/users", "query": { "page": "2" }, "exact_query": false },
+///     "response": { "json": { "users": [] } }
+/// }
+/// ```
+///
+/// All fields of `request` are optional, and `response` is one of `json`, `xml` or `text`.
+///
+/// When more than one fixture matches, the most specific one wins:
+/// - the longer `path` goes first
+/// - then the one with more `query` parameters
+/// - then the one with `method`
+/// - then the one with `exact_query`
+/// - otherwise, the one whose file name sorts first
+#[derive(Debug)]
+pub(crate) struct FixtureResponder {
+    /// The routes, the more specific one goes first
+    routes: Vec<Route>,
+}
+
+impl FixtureResponder {
+    /// Load fixtures from directory
+    /// - dir: the directory of fixture files, which is not searched recursively
+    pub fn load(dir: &Path) -> ApiResult<Self> {
+        let read_error = |e: std::io::Error| {
+            ApiError::Other(format!(
+                "Failed to read fixtures `{}`: {}",
+                dir.display(),
+                e
+            ))
+        };
+        let mut files = vec![];
+        for entry in std::fs::read_dir(dir).map_err(read_error)? {
+            let path = entry.map_err(read_error)?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+                files.push(path);
+            }
+        }
+        files.sort();
+
+        let mut routes = files
+            .iter()
+            .map(|path| load_fixture(path))
+            .collect::<ApiResult<Vec<_>>>()?;
+        // The sort is stable, so the file name breaks the ties
+        routes.sort_by_key(|r| Reverse(r.matcher.specificity()));
+        Ok(Self { routes })
+    }
+}
+
+#[async_trait]
+impl Responder for FixtureResponder {
+    async fn handle(&self, req: Request) -> anyhow::Result<ResponseBody> {
+        match self.routes.iter().find(|r| r.matcher.matches(&req)) {
+            Some(route) => {
+                log::debug!("Reply by fixture {}", route.name);
+                Ok(route.payload.clone())
+            }
+            None => Err(Unmatched::new("fixture", &req).into()),
+        }
+    }
+}
+
+/// Load a fixture file
+/// - path: the path of fixture file
+fn load_fixture(path: &Path) -> ApiResult<Route> {
+    let invalid =
+        |e: String| ApiError::Other(format!("Invalid fixture `{}`: {}", path.display(), e));
+    let content = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+    let fixture: Fixture = serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+
+    let request = fixture.request;
+    let mut matcher = Matcher::new();
+    if let Some(method) = request.method {
+        let method = Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|e| invalid(e.to_string()))?;
+        matcher = matcher.method(method);
+    }
+    if let Some(path) = request.path {
+        matcher = matcher.path(path);
+    }
+    for (name, value) in request.query {
+        matcher = matcher.query(name, value);
+    }
+    if request.exact_query {
+        matcher = matcher.exact_query();
+    }
+
+    let payload = match fixture.response {
+        FixtureResponse::Json(json) => ResponseBody::Json(json),
+        FixtureResponse::Xml(xml) => ResponseBody::Xml(xml),
+        FixtureResponse::Text(text) => ResponseBody::Text(text),
+    };
+    Ok(Route {
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        matcher,
+        payload,
+    })
+}
@@ -0,0 +1,149 @@
+use reqwest_middleware::{RequestBuilder, RequestInitialiser};
+use serde_json::Value;
+
+use crate::ApiError;
+
+/// The status codes of gRPC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrpcCode {
+    Ok,
+    Cancelled,
+    Unknown,
+    InvalidArgument,
+    DeadlineExceeded,
+    NotFound,
+    AlreadyExists,
+    PermissionDenied,
+    ResourceExhausted,
+    FailedPrecondition,
+    Aborted,
+    OutOfRange,
+    Unimplemented,
+    Internal,
+    Unavailable,
+    DataLoss,
+    Unauthenticated,
+}
+
+impl GrpcCode {
+    /// Get the code by numeric value
+    /// - code: numeric value of status code
+    pub fn from_code(code: i64) -> Option<Self> {
+        let code = match code {
+            0 => Self::Ok,
+            1 => Self::Cancelled,
+            2 => Self::Unknown,
+            3 => Self::InvalidArgument,
+            4 => Self::DeadlineExceeded,
+            5 => Self::NotFound,
+            6 => Self::AlreadyExists,
+            7 => Self::PermissionDenied,
+            8 => Self::ResourceExhausted,
+            9 => Self::FailedPrecondition,
+            10 => Self::Aborted,
+            11 => Self::OutOfRange,
+            12 => Self::Unimplemented,
+            13 => Self::Internal,
+            14 => Self::Unavailable,
+            15 => Self::DataLoss,
+            16 => Self::Unauthenticated,
+            _ => return None,
+        };
+        Some(code)
+    }
+
+    /// Get the name of code, such as `NOT_FOUND`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ok => "OK",
+            Self::Cancelled => "CANCELLED",
+            Self::Unknown => "UNKNOWN",
+            Self::InvalidArgument => "INVALID_ARGUMENT",
+            Self::DeadlineExceeded => "DEADLINE_EXCEEDED",
+            Self::NotFound => "NOT_FOUND",
+            Self::AlreadyExists => "ALREADY_EXISTS",
+            Self::PermissionDenied => "PERMISSION_DENIED",
+            Self::ResourceExhausted => "RESOURCE_EXHAUSTED",
+            Self::FailedPrecondition => "FAILED_PRECONDITION",
+            Self::Aborted => "ABORTED",
+            Self::OutOfRange => "OUT_OF_RANGE",
+            Self::Unimplemented => "UNIMPLEMENTED",
+            Self::Internal => "INTERNAL",
+            Self::Unavailable => "UNAVAILABLE",
+            Self::DataLoss => "DATA_LOSS",
+            Self::Unauthenticated => "UNAUTHENTICATED",
+        }
+    }
+
+    /// Get the related HTTP status code
+    pub fn http_status(&self) -> u16 {
+        match self {
+            Self::Ok => 200,
+            Self::Cancelled => 499,
+            Self::InvalidArgument | Self::FailedPrecondition | Self::OutOfRange => 400,
+            Self::Unauthenticated => 401,
+            Self::PermissionDenied => 403,
+            Self::NotFound => 404,
+            Self::AlreadyExists | Self::Aborted => 409,
+            Self::ResourceExhausted => 429,
+            Self::Unimplemented => 501,
+            Self::Unavailable => 503,
+            Self::DeadlineExceeded => 504,
+            Self::Unknown | Self::Internal | Self::DataLoss => 500,
+        }
+    }
+}
+
+impl std::fmt::Display for GrpcCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// This extension is used to recognize `google.rpc.Status` error body of gRPC-Web JSON transcoding.
+///
+/// When the response status is not successful, and the body looks like `{code, message, details}`,
+/// `ApiError::Grpc` will be returned instead of `ApiError::HttpClientStatus` / `ApiError::HttpServerStatus`.
+///
+/// # Examples
+///
+/// ### apply to all requests
+///
+/// ```
+/// let client = XxxApi::builder().with_initialiser(GrpcWebJson).build();
+/// ```
+///
+/// ### apply to single request
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let req = req.with_extension(GrpcWebJson);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GrpcWebJson;
+
+impl GrpcWebJson {
+    /// Try to map `google.rpc.Status` to `ApiError::Grpc`
+    /// - json: the error body
+    pub fn parse_status(json: &Value) -> Option<ApiError> {
+        let code = json.get("code").and_then(Value::as_i64)?;
+        let code = GrpcCode::from_code(code)?;
+        let message = json.get("message").and_then(Value::as_str)?.to_string();
+        let details = match json.get("details") {
+            Some(Value::Array(details)) => details.clone(),
+            Some(Value::Null) | None => vec![],
+            Some(_) => return None,
+        };
+        Some(ApiError::Grpc {
+            code,
+            message,
+            details,
+        })
+    }
+}
+
+impl RequestInitialiser for GrpcWebJson {
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        req.with_extension(*self)
+    }
+}
@@ -0,0 +1,103 @@
+use std::{pin::pin, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use futures::future::{select, Either};
+use reqwest::{Method, Request, Response};
+use reqwest_middleware::Next;
+
+use crate::{Extensions, Middleware, RequestExtensions, UrlRewriter};
+
+/// This middleware is used to reduce tail latency by hedged requests.
+///
+/// If the response doesn't arrive within the delay, the same request is sent to a second endpoint,
+/// which is resolved by `UrlRewriter`, such as a `SocketAddr`.
+/// The first successful response wins, and the other request is cancelled.
+/// If one of them fails, the result of the other one is returned.
+///
+/// Only the idempotent methods (`GET`, `HEAD`, `OPTIONS`, `TRACE`, `PUT` and `DELETE`) are hedged,
+/// and the request whose body could not be cloned is sent as is.
+///
+/// The extensions provided by this crate are duplicated to the hedged request, but others are dropped.
+///
+/// # Examples
+///
+/// ```
+/// let backup: SocketAddr = "10.0.0.2:8080".parse()?;
+/// let api = XxxApi::builder()
+///     .with_middleware(HedgingMiddleware::new(Duration::from_millis(50), backup))
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct HedgingMiddleware {
+    /// How long to wait before sending the hedged request
+    delay: Duration,
+    /// Resolve the url of the second endpoint
+    endpoint: Arc<dyn UrlRewriter>,
+}
+
+impl HedgingMiddleware {
+    /// Create a new instance
+    /// - delay: how long to wait before sending the hedged request
+    /// - endpoint: rewrite the url to the second endpoint
+    pub fn new<T>(delay: Duration, endpoint: T) -> Self
+    where
+        T: UrlRewriter,
+    {
+        Self {
+            delay,
+            endpoint: Arc::new(endpoint),
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for HedgingMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        if !is_idempotent(req.method()) {
+            return next.run(req, extensions).await;
+        }
+        let mut hedge = match req.try_clone() {
+            Some(hedge) => hedge,
+            None => return next.run(req, extensions).await,
+        };
+        let mut hedge_extensions = Extensions::new();
+        RequestExtensions::copy_from(extensions).insert_into(&mut hedge_extensions);
+
+        // Wait for the primary request within the delay
+        let mut primary = pin!(next.clone().run(req, extensions));
+        let primary = match select(primary.as_mut(), pin!(tokio::time::sleep(self.delay))).await {
+            Either::Left((res, _)) => return res,
+            Either::Right(_) => primary,
+        };
+
+        // Send the hedged request, and race them
+        match self.endpoint.rewrite(hedge.url().clone()).await {
+            Ok(url) => *hedge.url_mut() = url,
+            Err(e) => {
+                log::warn!("Failed to resolve hedged endpoint: {}", e);
+                return primary.await;
+            }
+        }
+        log::debug!("Send hedged request to {}", hedge.url());
+        let secondary = pin!(next.run(hedge, &mut hedge_extensions));
+        match select(primary, secondary).await {
+            Either::Left((Ok(res), _)) | Either::Right((Ok(res), _)) => Ok(res),
+            Either::Left((Err(_), other)) => other.await,
+            Either::Right((Err(_), other)) => other.await,
+        }
+    }
+}
+
+/// Check whether the method is idempotent
+/// - method: HTTP method
+pub(crate) fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE | Method::PUT | Method::DELETE
+    )
+}
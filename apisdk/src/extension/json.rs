@@ -0,0 +1,296 @@
+use reqwest_middleware::{RequestBuilder, RequestInitialiser};
+use serde_json::{Map, Value};
+
+/// This enum represents how to rename the fields of json payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameRule {
+    /// camelCase
+    CamelCase,
+    /// PascalCase
+    PascalCase,
+    /// snake_case
+    SnakeCase,
+    /// kebab-case
+    KebabCase,
+}
+
+impl RenameRule {
+    /// Rename the field
+    /// - name: original field name
+    pub fn apply(&self, name: &str) -> String {
+        let words = split_words(name);
+        match self {
+            Self::CamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(i, w)| if i == 0 { w.clone() } else { capitalize(w) })
+                .collect(),
+            Self::PascalCase => words.iter().map(|w| capitalize(w)).collect(),
+            Self::SnakeCase => words.join("_"),
+            Self::KebabCase => words.join("-"),
+        }
+    }
+}
+
+/// Split the name into lowercase words, by `_`, `-` and case boundary
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c == '_' || c == '-' {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Make the first char uppercase
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// This struct is used to control how to serialize json payload of requests.
+/// It could be injected into request as an extension.
+///
+/// # Examples
+///
+/// ### apply to all requests
+///
+/// ```
+/// let client = XxxApi::builder()
+///     .with_json_policy(JsonPolicy::new().rename_all(RenameRule::CamelCase).skip_nulls())
+///     .build();
+/// ```
+///
+/// ### apply to single request
+///
+/// ```
+/// let req = client.post("/api/path").await?;
+/// let req = req.with_extension(JsonPolicy::new().skip_nulls());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct JsonPolicy {
+    /// Rename all fields
+    pub rename_all: Option<RenameRule>,
+    /// Remove all fields whose value is null
+    pub skip_nulls: bool,
+}
+
+impl JsonPolicy {
+    /// Create a new instance, which keeps payload as is
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rename all fields of objects
+    /// - rule: RenameRule
+    pub fn rename_all(self, rule: RenameRule) -> Self {
+        Self {
+            rename_all: Some(rule),
+            ..self
+        }
+    }
+
+    /// Remove all fields whose value is null
+    pub fn skip_nulls(self) -> Self {
+        Self {
+            skip_nulls: true,
+            ..self
+        }
+    }
+
+    /// Apply the policy to json payload, recursively
+    /// - json: json payload
+    pub fn apply(&self, json: Value) -> Value {
+        match json {
+            Value::Object(map) => {
+                let mut result = Map::new();
+                for (name, value) in map {
+                    if self.skip_nulls && value.is_null() {
+                        continue;
+                    }
+                    let name = match self.rename_all {
+                        Some(rule) => rule.apply(&name),
+                        None => name,
+                    };
+                    result.insert(name, self.apply(value));
+                }
+                Value::Object(result)
+            }
+            Value::Array(items) => Value::Array(items.into_iter().map(|v| self.apply(v)).collect()),
+            _ => json,
+        }
+    }
+}
+
+impl RequestInitialiser for JsonPolicy {
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        let mut req = req;
+        if req.extensions().contains::<JsonPolicy>() {
+            req
+        } else {
+            req.with_extension(self.clone())
+        }
+    }
+}
+
+/// This extension is used to decode the json response, which is encoded as a json string once more.
+///
+/// When the response body is a json string, such as `"{\"a\":1}"`, and the string itself is a json object or array,
+/// it's unwrapped once. Other strings are kept as is.
+///
+/// # Examples
+///
+/// ### apply to all requests
+///
+/// ```
+/// let client = XxxApi::builder().with_double_json_decode(true).build();
+/// ```
+///
+/// ### apply to single request
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let req = req.with_extension(DoubleJsonDecode);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DoubleJsonDecode;
+
+impl DoubleJsonDecode {
+    /// Unwrap the json string, if it contains json object or array
+    /// - json: the decoded response
+    pub fn apply(&self, json: Value) -> Value {
+        if let Value::String(text) = &json {
+            if let Ok(inner @ (Value::Object(_) | Value::Array(_))) =
+                serde_json::from_str::<Value>(text)
+            {
+                return inner;
+            }
+        }
+        json
+    }
+}
+
+impl RequestInitialiser for DoubleJsonDecode {
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        req.with_extension(*self)
+    }
+}
+
+/// This extension is used to rename the fields of json response, before it's deserialized by extractors.
+///
+/// It's the counterpart of `JsonPolicy::rename_all` for responses, such as to deserialize camelCase payload
+/// into snake_case structs without `#[serde(rename)]` attributes.
+/// The `__headers__` field injected for `JsonExtractor::require_headers` is kept as is.
+///
+/// # Examples
+///
+/// ### apply to all requests
+///
+/// ```
+/// let client = XxxApi::builder()
+///     .with_initialiser(ResponseRenameAll(RenameRule::SnakeCase))
+///     .build();
+/// ```
+///
+/// ### apply to single request
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let req = req.with_extension(ResponseRenameAll(RenameRule::SnakeCase));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseRenameAll(pub RenameRule);
+
+impl ResponseRenameAll {
+    /// Rename all fields of objects, recursively
+    /// - json: the decoded response
+    pub fn apply(&self, json: Value) -> Value {
+        JsonPolicy::new().rename_all(self.0).apply(json)
+    }
+}
+
+impl RequestInitialiser for ResponseRenameAll {
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        let mut req = req;
+        if req.extensions().contains::<ResponseRenameAll>() {
+            req
+        } else {
+            req.with_extension(*self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{DoubleJsonDecode, JsonPolicy, RenameRule, ResponseRenameAll};
+
+    #[test]
+    fn test_rename_rule() {
+        assert_eq!("userName", RenameRule::CamelCase.apply("user_name"));
+        assert_eq!("UserName", RenameRule::PascalCase.apply("user_name"));
+        assert_eq!("user_name", RenameRule::SnakeCase.apply("userName"));
+        assert_eq!("user-name", RenameRule::KebabCase.apply("UserName"));
+        assert_eq!("userId2", RenameRule::CamelCase.apply("user_id2"));
+    }
+
+    #[test]
+    fn test_apply() {
+        let policy = JsonPolicy::new()
+            .rename_all(RenameRule::CamelCase)
+            .skip_nulls();
+        let json = json!({
+            "user_name": "name",
+            "nick_name": null,
+            "tag_list": [{"tag_name": "a"}]
+        });
+        let expected = json!({
+            "userName": "name",
+            "tagList": [{"tagName": "a"}]
+        });
+        assert_eq!(expected, policy.apply(json));
+    }
+
+    #[test]
+    fn test_double_json_decode() {
+        let decode = DoubleJsonDecode;
+        assert_eq!(json!({"a": 1}), decode.apply(json!("{\"a\":1}")));
+        assert_eq!(json!([1, 2]), decode.apply(json!("[1,2]")));
+        assert_eq!(json!("123"), decode.apply(json!("123")));
+        assert_eq!(json!("text"), decode.apply(json!("text")));
+        assert_eq!(json!({"a": "{}"}), decode.apply(json!({"a": "{}"})));
+    }
+
+    #[test]
+    fn test_response_rename_all() {
+        let rename = ResponseRenameAll(RenameRule::SnakeCase);
+        let json = json!({
+            "userName": "name",
+            "tagList": [{"tagName": "a"}]
+        });
+        let expected = json!({
+            "user_name": "name",
+            "tag_list": [{"tag_name": "a"}]
+        });
+        assert_eq!(expected, rename.apply(json));
+    }
+}
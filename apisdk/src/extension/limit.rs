@@ -0,0 +1,100 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use reqwest::{Request, Response, Url};
+use reqwest_middleware::{Next, RequestBuilder, RequestInitialiser};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{ApiError, ApiResult, Extensions, Middleware};
+
+/// This middleware is used to limit the concurrent in-flight requests of each host.
+///
+/// The host is taken from the final url, which has been rewritten by `UrlRewriter` / `DnsResolver`,
+/// so the limit is applied to each endpoint.
+pub(crate) struct HostLimitMiddleware {
+    /// The max concurrent requests of each host
+    max_per_host: usize,
+    /// The semaphores of hosts
+    semaphores: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HostLimitMiddleware {
+    /// Create a new instance
+    /// - max_per_host: the max concurrent requests of each host
+    pub fn new(max_per_host: usize) -> Self {
+        Self {
+            max_per_host: max_per_host.max(1),
+            semaphores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get or create the semaphore of host
+    /// - url: the url of request
+    fn semaphore(&self, url: &Url) -> Arc<Semaphore> {
+        let key = format!(
+            "{}:{}",
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        );
+        let mut semaphores = self.semaphores.lock().unwrap();
+        semaphores
+            .entry(key)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_per_host)))
+            .clone()
+    }
+}
+
+#[async_trait]
+impl Middleware for HostLimitMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        let semaphore = self.semaphore(req.url());
+        let _permit = semaphore
+            .acquire_owned()
+            .await
+            .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))?;
+        next.run(req, extensions).await
+    }
+}
+
+/// This struct is used to limit the concurrent in-flight requests of the api, see `with_max_concurrency`
+///
+/// The permit is acquired by executor right before the request is sent, and held until the response is decoded,
+/// so it's released on completion, error and cancellation.
+#[derive(Clone)]
+pub(crate) struct ConcurrencyLimit {
+    /// The semaphore shared by all requests
+    semaphore: Arc<Semaphore>,
+}
+
+impl ConcurrencyLimit {
+    /// Create a new instance
+    /// - max: the max concurrent requests
+    pub fn new(max: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max.max(1))),
+        }
+    }
+
+    /// Wait for a permit
+    pub async fn acquire(&self) -> ApiResult<OwnedSemaphorePermit> {
+        self.semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| ApiError::Middleware(e.into()))
+    }
+}
+
+impl RequestInitialiser for ConcurrencyLimit {
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        req.with_extension(self.clone())
+    }
+}
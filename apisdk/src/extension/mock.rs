@@ -1,10 +1,80 @@
-use std::{any::type_name, sync::Arc};
+use std::{any::type_name, collections::HashMap, path::Path, sync::Arc};
 
 use async_trait::async_trait;
-use reqwest::Request;
-use reqwest_middleware::{RequestBuilder, RequestInitialiser};
+use reqwest::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE},
+    IntoUrl, Method, Request, Response, StatusCode, Url,
+};
+use reqwest_middleware::{Middleware, Next, RequestBuilder, RequestInitialiser};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use thiserror::Error;
 
-use crate::ResponseBody;
+use crate::{
+    ApiError, ApiResult, Extensions, FixtureResponder, MimeType, OpenApiResponder, ResponseBody,
+};
+
+/// This enum represents the payload of request. It could be used by Responder.
+#[derive(Debug)]
+pub enum RequestBody {
+    /// No payload
+    Empty,
+    /// Json (content-type = application/json)
+    Json(Value),
+    /// Xml (content-type = text/xml | application/xml)
+    Xml(String),
+    /// Form (content-type = application/x-www-form-urlencoded), the repeated keys are kept in order
+    Form(Vec<(String, String)>),
+    /// Text (content-type = text/plain | text/*)
+    Text(String),
+    /// Other buffered bytes
+    Bytes(Vec<u8>),
+    /// Streamed payload, such as multipart form, which could not be read
+    Stream,
+}
+
+impl RequestBody {
+    /// Parse the payload of request
+    /// - req: HTTP request
+    pub fn from_request(req: &Request) -> Self {
+        let body = match req.body() {
+            Some(body) => body,
+            None => return Self::Empty,
+        };
+        let bytes = match body.as_bytes() {
+            Some(bytes) => bytes,
+            None => return Self::Stream,
+        };
+
+        let content_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_lowercase();
+        match MimeType::from(content_type.as_str()) {
+            MimeType::Form => Self::Form(url::form_urlencoded::parse(bytes).into_owned().collect()),
+            MimeType::Json => match serde_json::from_slice(bytes) {
+                Ok(json) => Self::Json(json),
+                Err(_) => Self::Bytes(bytes.to_vec()),
+            },
+            MimeType::Xml => Self::Xml(String::from_utf8_lossy(bytes).to_string()),
+            MimeType::Text => Self::Text(String::from_utf8_lossy(bytes).to_string()),
+            MimeType::Multipart(_) | MimeType::Other(_) => Self::Bytes(bytes.to_vec()),
+        }
+    }
+
+    /// Parse json to target type
+    pub fn parse_json<T>(self) -> ApiResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        match self {
+            Self::Json(json) => serde_json::from_value(json).map_err(ApiError::DecodeJson),
+            _ => Err(ApiError::Other("Request body is not json".to_string())),
+        }
+    }
+}
 
 /// Reply a response to request. It should be used with MockServer.
 #[async_trait]
@@ -31,6 +101,255 @@ where
     }
 }
 
+/// This struct is used to wrap a function / closure, which requires the request body
+struct BodyResponder<F> {
+    inner: F,
+}
+
+#[async_trait]
+impl<F> Responder for BodyResponder<F>
+where
+    F: 'static + Send + Sync,
+    F: Fn(Request, RequestBody) -> anyhow::Result<ResponseBody>,
+{
+    fn type_name(&self) -> &str {
+        type_name::<F>()
+    }
+
+    async fn handle(&self, req: Request) -> anyhow::Result<ResponseBody> {
+        let body = RequestBody::from_request(&req);
+        (self.inner)(req, body)
+    }
+}
+
+/// This struct is used to wrap a function / closure, which requires the query parameters
+struct QueryResponder<F> {
+    inner: F,
+}
+
+#[async_trait]
+impl<F> Responder for QueryResponder<F>
+where
+    F: 'static + Send + Sync,
+    F: Fn(Request, HashMap<String, String>) -> anyhow::Result<ResponseBody>,
+{
+    fn type_name(&self) -> &str {
+        type_name::<F>()
+    }
+
+    async fn handle(&self, req: Request) -> anyhow::Result<ResponseBody> {
+        let query = parse_query(&req);
+        (self.inner)(req, query)
+    }
+}
+
+/// Parse the query parameters of request
+/// - req: HTTP request
+fn parse_query(req: &Request) -> HashMap<String, String> {
+    req.url().query_pairs().into_owned().collect()
+}
+
+/// Transport-level failure, which could be simulated by `MockServer::fail_with`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum TransportError {
+    /// The server refuses the connection
+    #[error("connection refused")]
+    ConnectionRefused,
+    /// The connection is reset by peer, such as in the middle of body
+    #[error("connection reset by peer")]
+    ConnectionReset,
+}
+
+/// This struct is used to simulate transport-level failures
+struct FailResponder {
+    error: TransportError,
+}
+
+#[async_trait]
+impl Responder for FailResponder {
+    async fn handle(&self, _req: Request) -> anyhow::Result<ResponseBody> {
+        Err(self.error.into())
+    }
+}
+
+/// The error of responder, when none of its routes matches the request, such as `MockRouter`
+///
+/// The request will be forwarded to the upstream, if `MockServer::with_passthrough` is set.
+#[derive(Debug, Clone, Error)]
+#[error("No {kind} matches {method} {url}")]
+pub struct Unmatched {
+    /// The kind of routes, such as `responder` or `fixture`
+    pub kind: &'static str,
+    /// HTTP method
+    pub method: Method,
+    /// The url of request
+    pub url: Url,
+}
+
+impl Unmatched {
+    /// Create a new instance
+    /// - kind: the kind of routes
+    /// - req: HTTP request
+    pub fn new(kind: &'static str, req: &Request) -> Self {
+        Self {
+            kind,
+            method: req.method().clone(),
+            url: req.url().clone(),
+        }
+    }
+}
+
+/// This struct is used to check whether a request should be handled by a responder
+///
+/// # Examples
+///
+/// ```
+/// let matcher = Matcher::new()
+///     .method(Method::GET)
+///     .path("/api/path")
+///     .query("page", "2");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Matcher {
+    /// The expected HTTP method
+    method: Option<Method>,
+    /// The expected suffix of url path
+    path: Option<String>,
+    /// The expected query parameters
+    query: Vec<(String, String)>,
+    /// Reject unexpected query parameters
+    exact_query: bool,
+}
+
+impl Matcher {
+    /// Create a new instance, which matches all requests
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match HTTP method
+    /// - method: HTTP method
+    pub fn method(self, method: Method) -> Self {
+        Self {
+            method: Some(method),
+            ..self
+        }
+    }
+
+    /// Match url path
+    /// - path: the suffix of url path
+    pub fn path(self, path: impl ToString) -> Self {
+        Self {
+            path: Some(path.to_string()),
+            ..self
+        }
+    }
+
+    /// Match query parameter
+    /// - name: param name
+    /// - value: param value
+    pub fn query(self, name: impl ToString, value: impl ToString) -> Self {
+        let mut s = self;
+        s.query.push((name.to_string(), value.to_string()));
+        s
+    }
+
+    /// Reject the request which contains query parameters other than expected
+    pub fn exact_query(self) -> Self {
+        Self {
+            exact_query: true,
+            ..self
+        }
+    }
+
+    /// Get the specificity, the more specific matcher has the greater one
+    ///
+    /// It's compared by the length of path, the number of query parameters, method and exact_query in order.
+    pub(crate) fn specificity(&self) -> (usize, usize, bool, bool) {
+        (
+            self.path.as_ref().map(|p| p.len()).unwrap_or_default(),
+            self.query.len(),
+            self.method.is_some(),
+            self.exact_query,
+        )
+    }
+
+    /// Check whether the request is matched
+    /// - req: HTTP request
+    pub fn matches(&self, req: &Request) -> bool {
+        if let Some(method) = self.method.as_ref() {
+            if req.method() != method {
+                return false;
+            }
+        }
+        if let Some(path) = self.path.as_ref() {
+            if !req.url().path().ends_with(path.as_str()) {
+                return false;
+            }
+        }
+
+        let query: Vec<(String, String)> = req.url().query_pairs().into_owned().collect();
+        if !self.query.iter().all(|expected| query.contains(expected)) {
+            return false;
+        }
+        if self.exact_query && !query.iter().all(|actual| self.query.contains(actual)) {
+            return false;
+        }
+        true
+    }
+}
+
+/// This struct is used to dispatch requests to different responders.
+/// The first responder whose `Matcher` matches the request will be used.
+///
+/// # Examples
+///
+/// ```
+/// let router = MockRouter::new()
+///     .route(Matcher::new().query("page", "1"), |_| {
+///         Ok(ResponseBody::Json(json!({"page": 1})))
+///     })
+///     .route(Matcher::new().query("page", "2"), |_| {
+///         Ok(ResponseBody::Json(json!({"page": 2})))
+///     });
+/// let req = req.with_extension(MockServer::new(router));
+/// ```
+#[derive(Default)]
+pub struct MockRouter {
+    /// The routes
+    routes: Vec<(Matcher, Arc<dyn Responder>)>,
+}
+
+impl MockRouter {
+    /// Create an empty router
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a route
+    /// - matcher: Matcher
+    /// - reply: Responder
+    pub fn route(self, matcher: Matcher, reply: impl Responder) -> Self {
+        let mut s = self;
+        s.routes.push((matcher, Arc::new(reply)));
+        s
+    }
+}
+
+#[async_trait]
+impl Responder for MockRouter {
+    async fn handle(&self, req: Request) -> anyhow::Result<ResponseBody> {
+        match self
+            .routes
+            .iter()
+            .find(|(matcher, _)| matcher.matches(&req))
+        {
+            Some((_, reply)) => reply.handle(req).await,
+            None => Err(Unmatched::new("responder", &req).into()),
+        }
+    }
+}
+
 /// This middleware is used to mock the response
 ///
 /// # Examples
@@ -48,6 +367,61 @@ where
 /// let res = send!(req).await
 /// ```
 ///
+/// ### mock with request body
+///
+/// ```
+/// let req = client.post("/api/path").await?;
+/// let req = req.with_extension(MockServer::with_body(|_, body| {
+///     // echo the request payload
+///     match body {
+///         RequestBody::Json(json) => Ok(ResponseBody::Json(json)),
+///         _ => Err(anyhow::format_err!("Unexpected body")),
+///     }
+/// }));
+/// let res = send_json!(req, json!({"key": "value"})).await
+/// ```
+///
+/// ### mock transport-level failure
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let req = req.with_extension(MockServer::fail_with(TransportError::ConnectionReset));
+/// // Err(ApiError::Connection(TransportError::ConnectionReset))
+/// let res = send!(req).await
+/// ```
+///
+/// ### mock HTTP status
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let req = req.with_extension(MockServer::new(|_| Ok(ResponseBody::Json(json!({})))).with_status(StatusCode::SERVICE_UNAVAILABLE));
+/// // Err(ApiError::HttpServerStatus(503, _))
+/// let res = send!(req).await
+/// ```
+///
+/// ### mock by OpenAPI document
+///
+/// ```
+/// let spec: serde_json::Value = serde_json::from_str(include_str!("openapi.json"))?;
+/// let req = req.with_extension(MockServer::from_openapi(&spec)?);
+/// let res = send!(req).await
+/// ```
+///
+/// ### mock by fixture files
+///
+/// ```
+/// let req = req.with_extension(MockServer::from_fixtures("tests/fixtures")?);
+/// let res = send!(req).await
+/// ```
+///
+/// ### mock some paths, and pass others through to a real server
+///
+/// ```
+/// let router = MockRouter::new().route(Matcher::new().path("/a"), |_| Ok(ResponseBody::Json(json!({}))));
+/// let req = req.with_extension(MockServer::new(router).with_passthrough("http://127.0.0.1:8080")?);
+/// let res = send!(req).await
+/// ```
+///
 /// ### mock all requests
 ///
 /// ```
@@ -62,6 +436,10 @@ where
 pub struct MockServer {
     /// Internal responder
     inner: Arc<dyn Responder>,
+    /// The status of response
+    status: StatusCode,
+    /// Forward the unmatched requests to upstream
+    passthrough: Option<Passthrough>,
 }
 
 impl MockServer {
@@ -69,10 +447,145 @@ impl MockServer {
     pub fn new(reply: impl Responder) -> Self {
         Self {
             inner: Arc::new(reply),
+            status: StatusCode::OK,
+            passthrough: None,
+        }
+    }
+
+    /// Create a new instance, whose responder will receive the parsed request body
+    pub fn with_body<F>(reply: F) -> Self
+    where
+        F: 'static + Send + Sync,
+        F: Fn(Request, RequestBody) -> anyhow::Result<ResponseBody>,
+    {
+        Self::new(BodyResponder { inner: reply })
+    }
+
+    /// Create a new instance, whose responder will receive the parsed query parameters
+    pub fn with_query<F>(reply: F) -> Self
+    where
+        F: 'static + Send + Sync,
+        F: Fn(Request, HashMap<String, String>) -> anyhow::Result<ResponseBody>,
+    {
+        Self::new(QueryResponder { inner: reply })
+    }
+
+    /// Create a new instance, which replies the operations of OpenAPI (3.x) document
+    /// - spec: the OpenAPI document in json
+    ///
+    /// The `example` of the successful response is used if provided, otherwise the payload is synthesized from the schema.
+    /// The request fails if no operation matches its method and path.
+    pub fn from_openapi(spec: &Value) -> ApiResult<Self> {
+        Ok(Self::new(OpenApiResponder::new(spec)?))
+    }
+
+    /// Create a new instance, which replies by the fixture files in directory
+    /// - dir: the directory of `*.json` fixtures, each describes a request matcher and a response
+    ///
+    /// The fixture looks like `{"request": {"method": "GET", "path": "/users", "query": {"page": "2"}}, "response": {"json": {...}}}`,
+    /// where all fields of `request` are optional, and `response` is one of `json`, `xml` or `text`.
+    ///
+    /// If more than one fixture matches, the most specific one wins: the longer `path`, then more `query` parameters,
+    /// then with `method`, then with `exact_query`, and finally the file name in order.
+    /// The request fails if no fixture matches.
+    pub fn from_fixtures(dir: impl AsRef<Path>) -> ApiResult<Self> {
+        Ok(Self::new(FixtureResponder::load(dir.as_ref())?))
+    }
+
+    /// Create a new instance, which always fails with the transport-level error
+    ///
+    /// The error will be surfaced as `ApiError::Connection`, rather than an HTTP status
+    pub fn fail_with(error: TransportError) -> Self {
+        Self::new(FailResponder { error })
+    }
+
+    /// Set the status of response, which is `200 OK` by default
+    /// - status: HTTP status code
+    pub fn with_status(self, status: StatusCode) -> Self {
+        Self { status, ..self }
+    }
+
+    /// Forward the unmatched requests to a real server, such as in partial integration tests
+    /// - base_url: the scheme, host and port of upstream, while the path and query of request are kept
+    ///
+    /// The request is unmatched, if the responder fails with `Unmatched`, such as `MockRouter`, fixtures and OpenAPI.
+    /// The response of upstream is returned as `ResponseBody`, with its status.
+    pub fn with_passthrough(self, base_url: impl IntoUrl) -> ApiResult<Self> {
+        let base_url = base_url.into_url().map_err(ApiError::InvalidUrl)?;
+        Ok(Self {
+            passthrough: Some(Passthrough {
+                base_url,
+                client: reqwest::Client::new(),
+            }),
+            ..self
+        })
+    }
+
+    /// Reply the request, or forward it to upstream if unmatched
+    /// - req: HTTP request
+    ///
+    /// Return the status and payload of response
+    async fn reply(&self, req: Request) -> anyhow::Result<(StatusCode, ResponseBody)> {
+        let forward = self.passthrough.as_ref().and_then(|_| req.try_clone());
+        match self.inner.handle(req).await {
+            Ok(body) => Ok((self.status, body)),
+            Err(e) if e.is::<Unmatched>() => match (self.passthrough.as_ref(), forward) {
+                (Some(passthrough), Some(req)) => passthrough.forward(req).await,
+                _ => Err(e),
+            },
+            Err(e) => Err(e),
         }
     }
 }
 
+/// This struct is used to forward the unmatched requests to upstream
+#[derive(Clone)]
+struct Passthrough {
+    /// The base url of upstream
+    base_url: Url,
+    /// The client to send requests, without any middleware
+    client: reqwest::Client,
+}
+
+impl Passthrough {
+    /// Send the request to upstream, and read the response as `ResponseBody`
+    /// - req: HTTP request
+    async fn forward(&self, req: Request) -> anyhow::Result<(StatusCode, ResponseBody)> {
+        let mut req = req;
+        let url = req.url_mut();
+        url.set_scheme(self.base_url.scheme())
+            .map_err(|_| anyhow::format_err!("Invalid scheme of {}", self.base_url))?;
+        url.set_host(self.base_url.host_str())?;
+        url.set_port(self.base_url.port())
+            .map_err(|_| anyhow::format_err!("Invalid port of {}", self.base_url))?;
+        log::debug!("Pass through {} {}", req.method(), req.url());
+
+        let res = self.client.execute(req).await?;
+        let status = res.status();
+        let header = |name| {
+            res.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+        let content_type = header(CONTENT_TYPE).unwrap_or_default();
+        let content_encoding = header(CONTENT_ENCODING);
+        let data = res.bytes().await?.to_vec();
+        let body = match (MimeType::from(content_type.as_str()), &content_encoding) {
+            _ if data.is_empty() => ResponseBody::Empty,
+            (MimeType::Json, None) => ResponseBody::Json(serde_json::from_slice(&data)?),
+            (MimeType::Xml, None) => ResponseBody::Xml(String::from_utf8(data)?),
+            (MimeType::Text | MimeType::Form, None) => ResponseBody::Text(String::from_utf8(data)?),
+            _ => ResponseBody::Bytes {
+                content_type,
+                content_encoding,
+                data,
+            },
+        };
+        Ok((status, body))
+    }
+}
+
 #[async_trait]
 impl Responder for MockServer {
     fn type_name(&self) -> &str {
@@ -80,8 +593,8 @@ impl Responder for MockServer {
     }
 
     async fn handle(&self, req: Request) -> anyhow::Result<ResponseBody> {
-        // Delegate to internal responder
-        self.inner.handle(req).await
+        // Delegate to internal responder, or upstream
+        self.reply(req).await.map(|(_, body)| body)
     }
 }
 
@@ -92,3 +605,83 @@ impl RequestInitialiser for MockServer {
         req.with_extension(self.clone())
     }
 }
+
+/// Get the `MockServer`, unless it's bypassed by `NoMock`
+/// - extensions: the extensions of request
+pub(crate) fn get_mock(extensions: &Extensions) -> Option<&MockServer> {
+    if extensions.get::<NoMock>().is_some() {
+        return None;
+    }
+    extensions.get::<MockServer>()
+}
+
+/// Convert the error returned by `MockServer`
+/// - e: the error, which may be a simulated `TransportError`
+/// - mock_name: the type name of responder, which is attached to other errors
+fn mock_error(e: anyhow::Error, mock_name: &str) -> ApiError {
+    match e.downcast::<TransportError>() {
+        Ok(e) => ApiError::Connection(e),
+        Err(e) => {
+            let context = format!("{} (mock: {})", e, mock_name);
+            ApiError::Middleware(e.context(context))
+        }
+    }
+}
+
+/// This middleware is used to reply by `MockServer`, instead of sending the request.
+///
+/// It goes last, so the mocked response will be observed by other middlewares on the way back.
+/// The timeout of request is applied to the responder, as if it's a real server.
+pub(crate) struct MockMiddleware;
+
+#[async_trait]
+impl Middleware for MockMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        let mock = match get_mock(extensions) {
+            Some(mock) => mock.clone(),
+            None => return next.run(req, extensions).await,
+        };
+        let url = req.url().clone();
+        let res = match req.timeout().copied() {
+            Some(timeout) => match tokio::time::timeout(timeout, mock.reply(req)).await {
+                Ok(res) => res,
+                Err(_) => return Err(ApiError::Timeout.into()),
+            },
+            None => mock.reply(req).await,
+        };
+        match res {
+            Ok((status, body)) => Ok(body.into_response(status, url)?),
+            Err(e) => Err(mock_error(e, mock.type_name()).into()),
+        }
+    }
+}
+
+/// This struct is used to bypass `MockServer`, the request will be sent to the real server
+///
+/// # Examples
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let req = req.with_extension(NoMock);
+/// // or
+/// let req = req.no_mock();
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoMock;
+
+/// This trait provides mock related functions
+pub trait MockOps {
+    /// Bypass `MockServer` for this request
+    fn no_mock(self) -> Self;
+}
+
+impl MockOps for RequestBuilder {
+    fn no_mock(self) -> Self {
+        self.with_extension(NoMock)
+    }
+}
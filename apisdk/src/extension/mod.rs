@@ -1,9 +1,63 @@
+mod async_init;
 mod auth;
+mod cancel;
+mod challenge;
+mod circuit;
+mod clock;
+mod context;
+mod date;
+mod decompress;
+mod duplicate;
+mod fault;
+mod fixture;
+mod grpc;
+mod hedge;
+mod json;
+mod limit;
 mod logger;
 mod mock;
+mod openapi;
+#[cfg(feature = "otel")]
+mod otel;
+mod pool;
+mod precondition;
+mod rate_limit;
+mod record;
+mod respond_async;
+mod retry;
+mod single_flight;
+#[cfg(feature = "stats")]
+mod stats;
 mod trace;
 
+pub use async_init::*;
 pub use auth::*;
+pub use cancel::*;
+pub use challenge::*;
+pub use circuit::*;
+pub use clock::*;
+pub use context::*;
+pub(crate) use date::*;
+pub use decompress::*;
+pub use duplicate::*;
+pub use fault::*;
+pub(crate) use fixture::*;
+pub use grpc::*;
+pub use hedge::*;
+pub use json::*;
+pub(crate) use limit::*;
 pub use logger::*;
 pub use mock::*;
+pub(crate) use openapi::*;
+#[cfg(feature = "otel")]
+pub use otel::*;
+pub use pool::*;
+pub use precondition::*;
+pub use rate_limit::*;
+pub use record::*;
+pub use respond_async::*;
+pub use retry::*;
+pub(crate) use single_flight::*;
+#[cfg(feature = "stats")]
+pub use stats::*;
 pub use trace::*;
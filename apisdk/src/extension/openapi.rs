@@ -0,0 +1,302 @@
+use async_trait::async_trait;
+use regex::Regex;
+use reqwest::{Method, Request};
+use serde_json::{json, Map, Value};
+
+use crate::{ApiError, ApiResult, Responder, ResponseBody, Unmatched};
+
+/// The max depth to resolve `$ref`, which prevents infinite recursion
+const MAX_DEPTH: usize = 8;
+
+/// The HTTP methods of OpenAPI operations
+const METHODS: [&str; 7] = ["get", "put", "post", "delete", "options", "head", "patch"];
+
+/// This struct holds the mocked response of an OpenAPI operation
+#[derive(Debug)]
+struct Operation {
+    /// The HTTP method
+    method: Method,
+    /// The path template, such as `/users/{id}`
+    template: String,
+    /// Match the suffix of url path
+    pattern: Regex,
+    /// The number of path parameters, the operation with fewer ones is more specific
+    params: usize,
+    /// The json payload, `None` if the response has no content
+    payload: Option<Value>,
+}
+
+/// This struct is used to reply the requests by using an OpenAPI (3.x) document.
+///
+/// The `example` of response is used if provided, otherwise the payload is synthesized from the schema.
+#[derive(Debug)]
+pub(crate) struct OpenApiResponder {
+    /// The operations, the more specific one goes first
+    operations: Vec<Operation>,
+}
+
+impl OpenApiResponder {
+    /// Create a new instance
+    /// - spec: the OpenAPI document in json
+    pub fn new(spec: &Value) -> ApiResult<Self> {
+        let paths = spec
+            .get("paths")
+            .and_then(|p| p.as_object())
+            .ok_or_else(|| ApiError::Other("Invalid OpenAPI document: no paths".to_string()))?;
+
+        let mut operations = vec![];
+        for (template, item) in paths {
+            let (pattern, params) = compile_template(template)?;
+            for method in METHODS {
+                let op = match item.get(method) {
+                    Some(op) => op,
+                    None => continue,
+                };
+                operations.push(Operation {
+                    method: Method::from_bytes(method.to_uppercase().as_bytes())
+                        .unwrap_or_default(),
+                    template: template.clone(),
+                    pattern: pattern.clone(),
+                    params,
+                    payload: mock_payload(spec, op),
+                });
+            }
+        }
+        operations.sort_by(|a, b| {
+            a.params
+                .cmp(&b.params)
+                .then(b.template.len().cmp(&a.template.len()))
+        });
+        Ok(Self { operations })
+    }
+}
+
+#[async_trait]
+impl Responder for OpenApiResponder {
+    async fn handle(&self, req: Request) -> anyhow::Result<ResponseBody> {
+        let path = req.url().path();
+        match self
+            .operations
+            .iter()
+            .find(|op| op.method == req.method() && op.pattern.is_match(path))
+        {
+            Some(op) => match op.payload.as_ref() {
+                Some(payload) => Ok(ResponseBody::Json(payload.clone())),
+                None => Ok(ResponseBody::Empty),
+            },
+            None => Err(Unmatched::new("OpenAPI operation", &req).into()),
+        }
+    }
+}
+
+/// Compile the path template into regex, which matches the suffix of url path
+/// - template: the path template, such as `/users/{id}`
+fn compile_template(template: &str) -> ApiResult<(Regex, usize)> {
+    let mut pattern = String::new();
+    let mut params = 0;
+    for segment in template.split('/').filter(|s| !s.is_empty()) {
+        pattern.push('/');
+        if segment.starts_with('{') && segment.ends_with('}') {
+            pattern.push_str("[^/]+");
+            params += 1;
+        } else {
+            pattern.push_str(&regex::escape(segment));
+        }
+    }
+    pattern.push_str("/?$");
+    let regex = Regex::new(&pattern)
+        .map_err(|e| ApiError::Other(format!("Invalid path `{}`: {}", template, e)))?;
+    Ok((regex, params))
+}
+
+/// Build the payload of the successful response
+/// - spec: the OpenAPI document
+/// - op: the operation object
+fn mock_payload(spec: &Value, op: &Value) -> Option<Value> {
+    let responses = op.get("responses")?.as_object()?;
+    let mut codes: Vec<&String> = responses.keys().filter(|c| c.starts_with('2')).collect();
+    codes.sort();
+    let response = match codes.first() {
+        Some(code) => &responses[code.as_str()],
+        None => responses.get("default")?,
+    };
+    let response = resolve(spec, response, 0)?;
+
+    let content = response.get("content")?.as_object()?;
+    let media = content
+        .iter()
+        .find(|(mime, _)| mime.contains("json"))
+        .or_else(|| content.iter().next())
+        .map(|(_, media)| media)?;
+
+    if let Some(example) = media.get("example") {
+        return Some(example.clone());
+    }
+    if let Some(example) = media
+        .get("examples")
+        .and_then(|e| e.as_object())
+        .and_then(|e| e.values().next())
+        .and_then(|e| resolve(spec, e, 0))
+        .and_then(|e| e.get("value"))
+    {
+        return Some(example.clone());
+    }
+    media
+        .get("schema")
+        .map(|schema| synthesize(spec, schema, 0))
+}
+
+/// Resolve `$ref` in the document, such as `#/components/schemas/User`
+/// - spec: the OpenAPI document
+/// - value: the object which may be a reference
+/// - depth: the depth of references
+fn resolve<'a>(spec: &'a Value, value: &'a Value, depth: usize) -> Option<&'a Value> {
+    match value.get("$ref").and_then(|r| r.as_str()) {
+        Some(reference) if depth < MAX_DEPTH => {
+            let pointer = reference.strip_prefix('#')?;
+            resolve(spec, spec.pointer(pointer)?, depth + 1)
+        }
+        Some(_) => None,
+        None => Some(value),
+    }
+}
+
+/// Synthesize a value from the schema
+/// - spec: the OpenAPI document
+/// - schema: the schema object
+/// - depth: the depth of nested schemas
+fn synthesize(spec: &Value, schema: &Value, depth: usize) -> Value {
+    if depth > MAX_DEPTH {
+        return Value::Null;
+    }
+    let schema = match resolve(spec, schema, 0) {
+        Some(schema) => schema,
+        None => return Value::Null,
+    };
+    if let Some(example) = schema.get("example").or_else(|| schema.get("default")) {
+        return example.clone();
+    }
+    if let Some(first) = schema
+        .get("enum")
+        .and_then(|e| e.as_array())
+        .and_then(|e| e.first())
+    {
+        return first.clone();
+    }
+    if let Some(all) = schema.get("allOf").and_then(|a| a.as_array()) {
+        let mut merged = Map::new();
+        for part in all {
+            if let Value::Object(map) = synthesize(spec, part, depth + 1) {
+                merged.extend(map);
+            }
+        }
+        return Value::Object(merged);
+    }
+    if let Some(first) = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(|a| a.as_array())
+        .and_then(|a| a.first())
+    {
+        return synthesize(spec, first, depth + 1);
+    }
+
+    let ty = match schema.get("type") {
+        Some(Value::String(ty)) => ty.as_str(),
+        // OpenAPI 3.1 allows type array, such as `["string", "null"]`
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(|t| t.as_str())
+            .find(|t| *t != "null")
+            .unwrap_or("null"),
+        _ if schema.get("properties").is_some() => "object",
+        _ if schema.get("items").is_some() => "array",
+        _ => "",
+    };
+    match ty {
+        "object" => {
+            let mut map = Map::new();
+            if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+                for (name, property) in properties {
+                    map.insert(name.clone(), synthesize(spec, property, depth + 1));
+                }
+            }
+            Value::Object(map)
+        }
+        "array" => match schema.get("items") {
+            Some(items) => json!([synthesize(spec, items, depth + 1)]),
+            None => json!([]),
+        },
+        "string" => {
+            let format = schema.get("format").and_then(|f| f.as_str());
+            let value = match format {
+                Some("date-time") => "1970-01-01T00:00:00Z",
+                Some("date") => "1970-01-01",
+                Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                Some("email") => "user@example.com",
+                Some("uri") | Some("url") => "https://example.com",
+                _ => "string",
+            };
+            Value::String(value.to_string())
+        }
+        "integer" => json!(schema
+            .get("minimum")
+            .and_then(|m| m.as_i64())
+            .unwrap_or_default()),
+        "number" => json!(schema
+            .get("minimum")
+            .and_then(|m| m.as_f64())
+            .unwrap_or_default()),
+        "boolean" => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{compile_template, synthesize};
+
+    #[test]
+    fn test_compile_template() {
+        let (regex, params) = compile_template("/users/{id}/posts").unwrap();
+        assert_eq!(1, params);
+        assert!(regex.is_match("/v1/users/42/posts"));
+        assert!(!regex.is_match("/v1/users/42/posts/1"));
+        assert!(!regex.is_match("/v1/users/posts"));
+    }
+
+    #[test]
+    fn test_synthesize() {
+        let spec = json!({
+            "components": {
+                "schemas": {
+                    "Tag": { "type": "string", "enum": ["a", "b"] }
+                }
+            }
+        });
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "minimum": 1 },
+                "score": { "type": "number" },
+                "active": { "type": "boolean" },
+                "created": { "type": "string", "format": "date-time" },
+                "tags": { "type": "array", "items": { "$ref": "#/components/schemas/Tag" } },
+                "note": { "type": ["string", "null"], "example": "hi" }
+            }
+        });
+        assert_eq!(
+            json!({
+                "id": 1,
+                "score": 0.0,
+                "active": false,
+                "created": "1970-01-01T00:00:00Z",
+                "tags": ["a"],
+                "note": "hi"
+            }),
+            synthesize(&spec, &schema, 0)
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::Instant;
+
+use async_trait::async_trait;
+use opentelemetry::{
+    metrics::{Counter, Histogram, Meter, Unit},
+    KeyValue,
+};
+use reqwest::{Request, Response};
+use reqwest_middleware::Next;
+
+use crate::{Extensions, Middleware};
+
+/// This middleware is used to emit request metrics via the OpenTelemetry metrics API
+///
+/// - `http.client.request.duration`: histogram of the duration in seconds
+/// - `http.client.requests`: counter of the requests
+///
+/// Both are recorded with `http.method`, `server.address` and `server.port`, plus `http.status_code` for responses or `error.type` for failures.
+///
+/// # Examples
+///
+/// ```
+/// let api = MyApi::builder()
+///     .with_middleware(OtelMetricsMiddleware::new(&global::meter("my-api")))
+///     .build();
+/// ```
+pub struct OtelMetricsMiddleware {
+    /// The histogram of durations
+    duration: Histogram<f64>,
+    /// The counter of requests
+    requests: Counter<u64>,
+}
+
+impl OtelMetricsMiddleware {
+    /// Create a new instance
+    /// - meter: the meter to create instruments
+    pub fn new(meter: &Meter) -> Self {
+        Self {
+            duration: meter
+                .f64_histogram("http.client.request.duration")
+                .with_description("Duration of HTTP client requests")
+                .with_unit(Unit::new("s"))
+                .init(),
+            requests: meter
+                .u64_counter("http.client.requests")
+                .with_description("Number of HTTP client requests")
+                .init(),
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for OtelMetricsMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        let mut attributes = vec![KeyValue::new("http.method", req.method().to_string())];
+        if let Some(host) = req.url().host_str() {
+            attributes.push(KeyValue::new("server.address", host.to_string()));
+        }
+        if let Some(port) = req.url().port_or_known_default() {
+            attributes.push(KeyValue::new("server.port", port as i64));
+        }
+
+        let start = Instant::now();
+        let res = next.run(req, extensions).await;
+        let elapsed = start.elapsed();
+
+        match &res {
+            Ok(res) => attributes.push(KeyValue::new(
+                "http.status_code",
+                res.status().as_u16() as i64,
+            )),
+            Err(reqwest_middleware::Error::Reqwest(e)) if e.is_timeout() => {
+                attributes.push(KeyValue::new("error.type", "timeout"))
+            }
+            Err(_) => attributes.push(KeyValue::new("error.type", "_OTHER")),
+        }
+        self.duration.record(elapsed.as_secs_f64(), &attributes);
+        self.requests.add(1, &attributes);
+        res
+    }
+}
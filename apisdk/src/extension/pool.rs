@@ -0,0 +1,68 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use async_trait::async_trait;
+use reqwest::{Request, Response};
+use reqwest_middleware::Next;
+
+use crate::{Extensions, Middleware};
+
+/// This struct holds the statistics of connection pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// The number of requests sent to network, excluding the mocked ones
+    pub requests: u64,
+    /// The number of new connections
+    pub new_connections: u64,
+    /// The number of requests sent on reused connections
+    pub reused_connections: u64,
+}
+
+/// This struct is used to count requests and new connections, shared by Client and ApiCore
+///
+/// reqwest doesn't expose the connector, so a new connection is approximated by a DNS lookup,
+/// which happens each time the connector establishes a connection.
+/// The hosts of IP literal or set by `with_host_override` are not looked up, so their connections are counted as reused.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PoolCounter {
+    /// The number of requests
+    requests: Arc<AtomicU64>,
+    /// The number of new connections
+    connections: Arc<AtomicU64>,
+}
+
+impl PoolCounter {
+    /// Count a new connection
+    pub fn connect(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the statistics
+    pub fn stats(&self) -> PoolStats {
+        let requests = self.requests.load(Ordering::Relaxed);
+        let new_connections = self.connections.load(Ordering::Relaxed);
+        PoolStats {
+            requests,
+            new_connections,
+            reused_connections: requests.saturating_sub(new_connections),
+        }
+    }
+}
+
+/// This middleware is used to count the requests sent to network
+pub(crate) struct PoolStatsMiddleware(pub PoolCounter);
+
+#[async_trait]
+impl Middleware for PoolStatsMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        self.0.requests.fetch_add(1, Ordering::Relaxed);
+        next.run(req, extensions).await
+    }
+}
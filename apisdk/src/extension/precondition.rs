@@ -0,0 +1,52 @@
+use reqwest::header::IF_MATCH;
+use reqwest_middleware::RequestBuilder;
+
+/// This trait provides helpers of optimistic concurrency control
+///
+/// If the resource has been changed since the ETag was fetched, the server responds `412 Precondition Failed`,
+/// which is mapped to `ApiError::PreconditionFailed`, so the caller could refetch the resource and retry.
+///
+/// # Examples
+///
+/// ```
+/// let req = client.put("/api/users/1").await?.with_if_match(etag).json(&user);
+/// match send!(req).await {
+///     Err(ApiError::PreconditionFailed(current)) => { /* refetch and retry */ }
+///     other => other,
+/// }
+/// ```
+pub trait PreconditionOps {
+    /// Set `If-Match` header, so the request is applied only if the resource still has the ETag
+    /// - etag: the ETag of resource, which is quoted if not yet, or `*` to match any
+    fn with_if_match(self, etag: impl AsRef<str>) -> Self;
+}
+
+impl PreconditionOps for RequestBuilder {
+    fn with_if_match(self, etag: impl AsRef<str>) -> Self {
+        self.header(IF_MATCH, quote_etag(etag.as_ref()))
+    }
+}
+
+/// Quote the ETag, unless it's quoted, weak or `*`
+/// - etag: the ETag of resource
+fn quote_etag(etag: &str) -> String {
+    let etag = etag.trim();
+    if etag == "*" || etag.starts_with('"') || etag.starts_with("W/\"") {
+        etag.to_string()
+    } else {
+        format!("\"{}\"", etag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::quote_etag;
+
+    #[test]
+    fn test_quote_etag() {
+        assert_eq!("\"v1\"", quote_etag("v1"));
+        assert_eq!("\"v1\"", quote_etag("\"v1\""));
+        assert_eq!("W/\"v1\"", quote_etag("W/\"v1\""));
+        assert_eq!("*", quote_etag("*"));
+    }
+}
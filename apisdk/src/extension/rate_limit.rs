@@ -0,0 +1,492 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap},
+    pin::pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use reqwest::{header::HeaderMap, Request, Response, Url};
+use reqwest_middleware::Next;
+use tokio::sync::Notify;
+
+use crate::{get_clock, Clock, Extensions, Middleware};
+
+/// This enum is used to decide how to parse the reset header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitReset {
+    /// Unix timestamp in seconds, such as GitHub and Twitter
+    EpochSeconds,
+    /// Seconds until the quota is reset, such as IETF `RateLimit` headers
+    DeltaSeconds,
+}
+
+/// This struct holds the names of rate-limit headers, since the conventions vary among APIs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitHeaders {
+    /// The header of request quota
+    pub limit: String,
+    /// The header of remaining requests
+    pub remaining: String,
+    /// The header of reset time
+    pub reset: String,
+    /// The format of reset header
+    pub reset_format: RateLimitReset,
+}
+
+impl Default for RateLimitHeaders {
+    fn default() -> Self {
+        Self::github()
+    }
+}
+
+impl RateLimitHeaders {
+    /// Create a new instance
+    /// - limit: the header of request quota
+    /// - remaining: the header of remaining requests
+    /// - reset: the header of reset time
+    /// - reset_format: the format of reset header
+    pub fn new(
+        limit: impl ToString,
+        remaining: impl ToString,
+        reset: impl ToString,
+        reset_format: RateLimitReset,
+    ) -> Self {
+        Self {
+            limit: limit.to_string().to_lowercase(),
+            remaining: remaining.to_string().to_lowercase(),
+            reset: reset.to_string().to_lowercase(),
+            reset_format,
+        }
+    }
+
+    /// `X-RateLimit-Limit`, `X-RateLimit-Remaining` and `X-RateLimit-Reset` (unix timestamp)
+    pub fn github() -> Self {
+        Self::new(
+            "x-ratelimit-limit",
+            "x-ratelimit-remaining",
+            "x-ratelimit-reset",
+            RateLimitReset::EpochSeconds,
+        )
+    }
+
+    /// `X-Rate-Limit-Limit`, `X-Rate-Limit-Remaining` and `X-Rate-Limit-Reset` (unix timestamp)
+    pub fn twitter() -> Self {
+        Self::new(
+            "x-rate-limit-limit",
+            "x-rate-limit-remaining",
+            "x-rate-limit-reset",
+            RateLimitReset::EpochSeconds,
+        )
+    }
+
+    /// `RateLimit-Limit`, `RateLimit-Remaining` and `RateLimit-Reset` (delta seconds)
+    pub fn ietf() -> Self {
+        Self::new(
+            "ratelimit-limit",
+            "ratelimit-remaining",
+            "ratelimit-reset",
+            RateLimitReset::DeltaSeconds,
+        )
+    }
+}
+
+/// This struct holds the rate-limit information parsed from response headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// The request quota in the current window
+    pub limit: Option<u64>,
+    /// The remaining requests in the current window
+    pub remaining: Option<u64>,
+    /// When the quota will be reset
+    pub reset: Option<SystemTime>,
+}
+
+impl RateLimitInfo {
+    /// Parse from response headers
+    /// - headers: HTTP headers
+    /// - names: the names of rate-limit headers
+    ///
+    /// Return `None` if none of the rate-limit headers is found.
+    pub fn from_headers(headers: &HeaderMap, names: &RateLimitHeaders) -> Option<Self> {
+        Self::parse(
+            |name| headers.get(name).and_then(|v| v.to_str().ok()),
+            names,
+            SystemTime::now(),
+        )
+    }
+
+    /// Parse by using the header getter
+    /// - get: get header value by name
+    /// - names: the names of rate-limit headers
+    /// - now: the current time, used by `RateLimitReset::DeltaSeconds`
+    fn parse<'a>(
+        get: impl Fn(&str) -> Option<&'a str>,
+        names: &RateLimitHeaders,
+        now: SystemTime,
+    ) -> Option<Self> {
+        let number = |name: &str| get(name).and_then(|v| v.trim().parse::<u64>().ok());
+        let limit = number(&names.limit);
+        let remaining = number(&names.remaining);
+        let reset = number(&names.reset).map(|secs| match names.reset_format {
+            RateLimitReset::EpochSeconds => UNIX_EPOCH + Duration::from_secs(secs),
+            RateLimitReset::DeltaSeconds => now + Duration::from_secs(secs),
+        });
+        if limit.is_none() && remaining.is_none() && reset.is_none() {
+            return None;
+        }
+        Some(Self {
+            limit,
+            remaining,
+            reset,
+        })
+    }
+
+    /// Check whether the quota is exhausted
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == Some(0)
+    }
+
+    /// Get the duration until the quota is reset
+    pub fn reset_after(&self) -> Option<Duration> {
+        self.reset
+            .and_then(|reset| reset.duration_since(SystemTime::now()).ok())
+    }
+}
+
+/// This enum is used to prioritise the requests waiting for rate-limit quota
+///
+/// # Examples
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let req = req.with_extension(Priority::High);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Background requests, which are served last
+    Low,
+    /// The default priority
+    #[default]
+    Normal,
+    /// User-facing requests, which are served first
+    High,
+}
+
+/// The requests waiting for quota of a host
+#[derive(Debug, Default)]
+struct WaitQueue {
+    /// The waiting requests, the first one has the highest priority and arrives first
+    waiters: BTreeSet<(Reverse<Priority>, u64)>,
+    /// The number of requests being served
+    in_flight: u64,
+    /// Notify waiters when a request is served or done
+    notify: Arc<Notify>,
+}
+
+impl WaitQueue {
+    /// Check whether there is any request waiting or being served
+    fn is_busy(&self) -> bool {
+        self.in_flight > 0 || !self.waiters.is_empty()
+    }
+}
+
+/// The quota of a host, which decides whether a request could be sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quota {
+    /// The quota is exhausted, and will be reset after the duration
+    Wait(Duration),
+    /// The max number of requests being served at the same time
+    Slots(u64),
+}
+
+/// This struct holds the position in WaitQueue, and leaves the queue when dropped
+struct Ticket {
+    /// The queues of hosts
+    queues: Arc<Mutex<HashMap<String, WaitQueue>>>,
+    /// The host key
+    key: String,
+    /// The position in queue
+    position: (Reverse<Priority>, u64),
+    /// Whether this one is being served
+    admitted: bool,
+}
+
+impl Ticket {
+    /// Be served if this one goes first and there is a free slot, or return the notify to wait
+    /// - slots: the max number of requests being served
+    fn try_admit(&mut self, slots: u64) -> Result<(), Arc<Notify>> {
+        let mut queues = self.queues.lock().unwrap();
+        let Some(queue) = queues.get_mut(&self.key) else {
+            return Ok(());
+        };
+        if queue.in_flight < slots && queue.waiters.first() == Some(&self.position) {
+            queue.waiters.remove(&self.position);
+            queue.in_flight += 1;
+            self.admitted = true;
+            // The next one may be served as well
+            queue.notify.notify_waiters();
+            return Ok(());
+        }
+        Err(queue.notify.clone())
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        let mut queues = self.queues.lock().unwrap();
+        if let Some(queue) = queues.get_mut(&self.key) {
+            if self.admitted {
+                queue.in_flight -= 1;
+            } else {
+                queue.waiters.remove(&self.position);
+            }
+            queue.notify.notify_waiters();
+            if !queue.is_busy() {
+                queues.remove(&self.key);
+            }
+        }
+    }
+}
+
+/// This middleware is used to throttle requests proactively, by using the rate-limit headers.
+///
+/// Once the quota of a host is exhausted, the subsequent requests will wait until it's reset.
+/// The wait is capped by `max_wait`, so the request will be sent anyway (and may get `429`).
+/// The time is read from the `Clock` set by `with_clock`.
+///
+/// The requests are served in the order of `Priority` set by `req.with_extension()`,
+/// and then in the order of arrival. No more requests than the remaining quota are served at the same time,
+/// so once the quota is reset, up to `limit` waiting requests are sent together.
+///
+/// # Examples
+///
+/// ```
+/// let limiter = RateLimitMiddleware::new(RateLimitHeaders::twitter());
+/// let api = XxxApi::builder().with_middleware(limiter.clone()).build();
+/// // ... send requests
+/// let info = limiter.get_info(&url);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimitMiddleware {
+    /// The names of rate-limit headers
+    names: RateLimitHeaders,
+    /// The max duration to wait
+    max_wait: Duration,
+    /// The latest information of each host
+    infos: Arc<Mutex<HashMap<String, RateLimitInfo>>>,
+    /// The waiting requests of each host
+    queues: Arc<Mutex<HashMap<String, WaitQueue>>>,
+    /// The sequence of arrival
+    seq: Arc<AtomicU64>,
+}
+
+impl RateLimitMiddleware {
+    /// Create a new instance, which waits for 60 seconds at most
+    /// - names: the names of rate-limit headers
+    pub fn new(names: RateLimitHeaders) -> Self {
+        Self {
+            names,
+            max_wait: Duration::from_secs(60),
+            infos: Arc::new(Mutex::new(HashMap::new())),
+            queues: Arc::new(Mutex::new(HashMap::new())),
+            seq: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Set the max duration to wait
+    /// - max_wait: the max duration
+    pub fn with_max_wait(self, max_wait: Duration) -> Self {
+        Self { max_wait, ..self }
+    }
+
+    /// Get the latest information of the host
+    /// - url: any url of the host
+    pub fn get_info(&self, url: &Url) -> Option<RateLimitInfo> {
+        self.infos.lock().unwrap().get(&host_key(url)).copied()
+    }
+
+    /// Get the quota of the host
+    /// - key: the host key
+    /// - now: the current time
+    fn quota(&self, key: &str, now: SystemTime) -> Quota {
+        let infos = self.infos.lock().unwrap();
+        let Some(info) = infos.get(key) else {
+            return Quota::Slots(u64::MAX);
+        };
+        match info.remaining {
+            Some(0) => match info
+                .reset
+                .and_then(|reset| reset.duration_since(now).ok())
+                .filter(|wait| !wait.is_zero())
+            {
+                Some(wait) => Quota::Wait(wait),
+                // The quota should have been reset, but the new remaining is unknown yet
+                None => Quota::Slots(info.limit.unwrap_or(1).max(1)),
+            },
+            Some(remaining) => Quota::Slots(remaining),
+            None => Quota::Slots(u64::MAX),
+        }
+    }
+
+    /// Join the queue of host
+    /// - key: the host key
+    /// - priority: the priority of request
+    fn enqueue(&self, key: &str, priority: Priority) -> Ticket {
+        let position = (Reverse(priority), self.seq.fetch_add(1, Ordering::Relaxed));
+        self.queues
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_default()
+            .waiters
+            .insert(position);
+        Ticket {
+            queues: self.queues.clone(),
+            key: key.to_string(),
+            position,
+            admitted: false,
+        }
+    }
+
+    /// Wait until the quota is available, and then take turns by priority
+    /// - ticket: the position in queue
+    /// - clock: the clock of request
+    async fn wait_turn(&self, ticket: &mut Ticket, clock: &dyn Clock) {
+        let deadline = clock.now() + self.max_wait;
+        loop {
+            let now = clock.now();
+            let slots = match self.quota(&ticket.key, now) {
+                // Waited long enough, so send it anyway
+                Quota::Wait(_) if now >= deadline => 1,
+                Quota::Wait(wait) => {
+                    let left = deadline.duration_since(now).unwrap_or_default();
+                    clock.sleep(wait.min(left)).await;
+                    continue;
+                }
+                Quota::Slots(slots) => slots,
+            };
+            let notify = match ticket.try_admit(slots) {
+                Ok(()) => return,
+                Err(notify) => notify,
+            };
+            let mut notified = pin!(notify.notified());
+            notified.as_mut().enable();
+            // Check again, in case of the notification is missed
+            if ticket.try_admit(slots).is_ok() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for RateLimitMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        let key = host_key(req.url());
+        let priority = extensions.get::<Priority>().copied().unwrap_or_default();
+        let clock = get_clock(extensions);
+        let mut ticket = self.enqueue(&key, priority);
+        self.wait_turn(&mut ticket, clock.as_ref()).await;
+
+        let res = next.run(req, extensions).await;
+        if let Ok(res) = res.as_ref() {
+            let headers = res.headers();
+            if let Some(info) = RateLimitInfo::parse(
+                |name| headers.get(name).and_then(|v| v.to_str().ok()),
+                &self.names,
+                clock.now(),
+            ) {
+                self.infos.lock().unwrap().insert(key, info);
+            }
+        }
+        drop(ticket);
+        res
+    }
+}
+
+/// Get the key of host
+/// - url: the url of request
+fn host_key(url: &Url) -> String {
+    format!(
+        "{}:{}",
+        url.host_str().unwrap_or_default(),
+        url.port_or_known_default().unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    use super::{RateLimitHeaders, RateLimitInfo};
+
+    #[test]
+    fn test_parse_github() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-RateLimit-Limit", HeaderValue::from_static("5000"));
+        headers.insert("X-RateLimit-Remaining", HeaderValue::from_static("4987"));
+        headers.insert("X-RateLimit-Reset", HeaderValue::from_static("1700000000"));
+
+        let info = RateLimitInfo::from_headers(&headers, &RateLimitHeaders::github()).unwrap();
+        assert_eq!(
+            RateLimitInfo {
+                limit: Some(5000),
+                remaining: Some(4987),
+                reset: Some(UNIX_EPOCH + Duration::from_secs(1700000000)),
+            },
+            info
+        );
+        assert!(!info.is_exhausted());
+        assert_eq!(None, info.reset_after());
+
+        // Other conventions are not matched
+        assert_eq!(
+            None,
+            RateLimitInfo::from_headers(&headers, &RateLimitHeaders::twitter())
+        );
+    }
+
+    #[test]
+    fn test_parse_twitter() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-rate-limit-limit", HeaderValue::from_static("900"));
+        headers.insert("x-rate-limit-remaining", HeaderValue::from_static("0"));
+
+        let info = RateLimitInfo::from_headers(&headers, &RateLimitHeaders::twitter()).unwrap();
+        assert_eq!(Some(900), info.limit);
+        assert_eq!(None, info.reset);
+        assert!(info.is_exhausted());
+    }
+
+    #[test]
+    fn test_parse_delta_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert("RateLimit-Limit", HeaderValue::from_static("100"));
+        headers.insert("RateLimit-Remaining", HeaderValue::from_static("invalid"));
+        headers.insert("RateLimit-Reset", HeaderValue::from_static("30"));
+
+        let now = SystemTime::now();
+        let info = RateLimitInfo::parse(
+            |name| headers.get(name).and_then(|v| v.to_str().ok()),
+            &RateLimitHeaders::ietf(),
+            now,
+        )
+        .unwrap();
+        assert_eq!(Some(100), info.limit);
+        assert_eq!(None, info.remaining);
+        assert_eq!(Some(now + Duration::from_secs(30)), info.reset);
+    }
+}
@@ -0,0 +1,314 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use reqwest::{
+    header::{HeaderMap, CONTENT_TYPE},
+    Request, Response, ResponseBuilderExt, Version,
+};
+use reqwest_middleware::Next;
+use serde_json::{json, Value};
+
+use crate::{Extensions, Middleware, RedactPolicy};
+
+/// The placeholder of redacted header value
+const REDACTED: &str = "[REDACTED]";
+
+/// This middleware is used to record the recent requests and responses, which could be exported as HAR 1.2.
+///
+/// It's opt-in and designed for debugging, since the response body will be buffered.
+/// The sensitive headers are redacted by `RedactPolicy`, which is shared with `LogConfig`.
+///
+/// # Examples
+///
+/// ```
+/// let recorder = RecordingMiddleware::new(100);
+/// let api = XxxApi::builder().with_middleware(recorder.clone()).build();
+/// // ... send requests
+/// let har = recorder.export_har();
+/// std::fs::write("apisdk.har", har.to_string())?;
+/// ```
+#[derive(Clone)]
+pub struct RecordingMiddleware {
+    /// The max number of entries to keep
+    capacity: usize,
+    /// The policy to redact sensitive headers
+    redact: RedactPolicy,
+    /// The recorded HAR entries, the oldest one goes first
+    entries: Arc<Mutex<VecDeque<Value>>>,
+}
+
+impl RecordingMiddleware {
+    /// Create a new instance
+    /// - capacity: the max number of entries to keep, the older ones will be dropped
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            redact: RedactPolicy::default(),
+            entries: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Set the policy to redact sensitive headers
+    /// - redact: RedactPolicy
+    pub fn with_redact(self, redact: RedactPolicy) -> Self {
+        Self { redact, ..self }
+    }
+
+    /// Get the number of recorded entries
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Check whether there is no recorded entry
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all recorded entries
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Export the recorded entries as HAR 1.2 json
+    pub fn export_har(&self) -> Value {
+        let entries: Vec<Value> = self.entries.lock().unwrap().iter().cloned().collect();
+        json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": "apisdk",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "entries": entries,
+            }
+        })
+    }
+
+    /// Append an entry, and drop the oldest one if full
+    /// - entry: HAR entry
+    fn push(&self, entry: Value) {
+        let mut entries = self.entries.lock().unwrap();
+        while entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Convert headers to HAR, with sensitive values redacted
+    /// - headers: HTTP headers
+    fn har_headers(&self, headers: &HeaderMap) -> Vec<Value> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if self.redact.is_sensitive(name, value) {
+                    REDACTED.to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).to_string()
+                };
+                json!({ "name": name.as_str(), "value": value })
+            })
+            .collect()
+    }
+
+    /// Convert request to HAR
+    /// - req: HTTP request
+    fn har_request(&self, req: &Request) -> Value {
+        let query: Vec<Value> = req
+            .url()
+            .query_pairs()
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+            .collect();
+        let mut request = json!({
+            "method": req.method().as_str(),
+            "url": req.url().as_str(),
+            "httpVersion": http_version(req.version()),
+            "cookies": [],
+            "headers": self.har_headers(req.headers()),
+            "queryString": query,
+            "headersSize": -1,
+            "bodySize": 0,
+        });
+        if let Some(bytes) = req.body().and_then(|body| body.as_bytes()) {
+            request["bodySize"] = json!(bytes.len());
+            request["postData"] = json!({
+                "mimeType": content_type(req.headers()),
+                "text": String::from_utf8_lossy(bytes),
+            });
+        } else if req.body().is_some() {
+            // Streamed body, such as multipart form, could not be read
+            request["bodySize"] = json!(-1);
+        }
+        request
+    }
+
+    /// Buffer the response body, and convert response to HAR
+    /// - res: HTTP response
+    async fn har_response(&self, res: Response) -> Result<(Response, Value), reqwest::Error> {
+        let status = res.status();
+        let version = res.version();
+        let headers = res.headers().clone();
+        let url = res.url().clone();
+        let bytes = res.bytes().await?;
+
+        let response = json!({
+            "status": status.as_u16(),
+            "statusText": status.canonical_reason().unwrap_or_default(),
+            "httpVersion": http_version(version),
+            "cookies": [],
+            "headers": self.har_headers(&headers),
+            "content": {
+                "size": bytes.len(),
+                "mimeType": content_type(&headers),
+                "text": String::from_utf8_lossy(&bytes),
+            },
+            "redirectURL": "",
+            "headersSize": -1,
+            "bodySize": bytes.len(),
+        });
+
+        // Rebuild the response with buffered body
+        let mut rebuilt = hyper::Response::builder()
+            .status(status)
+            .version(version)
+            .url(url)
+            .body(bytes)
+            .unwrap_or_default();
+        *rebuilt.headers_mut() = headers;
+        Ok((Response::from(rebuilt), response))
+    }
+}
+
+#[async_trait]
+impl Middleware for RecordingMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        let started = SystemTime::now();
+        let start = Instant::now();
+        let request = self.har_request(&req);
+
+        let (result, response) = match next.run(req, extensions).await {
+            Ok(res) => match self.har_response(res).await {
+                Ok((res, response)) => (Ok(res), response),
+                Err(e) => {
+                    let response = har_error(&e);
+                    (Err(reqwest_middleware::Error::Reqwest(e)), response)
+                }
+            },
+            Err(e) => {
+                let response = har_error(&e);
+                (Err(e), response)
+            }
+        };
+
+        let time = start.elapsed().as_secs_f64() * 1000.0;
+        self.push(json!({
+            "startedDateTime": format_rfc3339(started),
+            "time": time,
+            "request": request,
+            "response": response,
+            "cache": {},
+            "timings": {
+                "send": 0,
+                "wait": time,
+                "receive": 0,
+            },
+        }));
+
+        result
+    }
+}
+
+/// Build the HAR response for failed request, whose status is 0
+/// - e: the error
+fn har_error(e: impl std::fmt::Display) -> Value {
+    json!({
+        "status": 0,
+        "statusText": "",
+        "httpVersion": "",
+        "cookies": [],
+        "headers": [],
+        "content": {
+            "size": 0,
+            "mimeType": "",
+        },
+        "redirectURL": "",
+        "headersSize": -1,
+        "bodySize": -1,
+        "_error": e.to_string(),
+    })
+}
+
+/// Get the value of `Content-Type`
+/// - headers: HTTP headers
+fn content_type(headers: &HeaderMap) -> &str {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+}
+
+/// Get the HAR representation of HTTP version
+/// - version: HTTP version
+fn http_version(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_2 => "HTTP/2.0",
+        Version::HTTP_3 => "HTTP/3.0",
+        _ => "HTTP/1.1",
+    }
+}
+
+/// Format the time as RFC 3339 in UTC, such as `2024-01-02T03:04:05.678Z`
+/// - time: SystemTime
+fn format_rfc3339(time: SystemTime) -> String {
+    let duration = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = duration.as_secs();
+    let (days, secs_of_day) = ((secs / 86400) as i64, secs % 86400);
+
+    // Convert days since epoch to civil date
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        duration.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::format_rfc3339;
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!("1970-01-01T00:00:00.000Z", format_rfc3339(UNIX_EPOCH));
+        assert_eq!(
+            "2024-02-29T12:34:56.789Z",
+            format_rfc3339(UNIX_EPOCH + Duration::from_millis(1709210096789))
+        );
+    }
+}
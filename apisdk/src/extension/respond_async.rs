@@ -0,0 +1,138 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::{
+    header::{
+        HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, LOCATION,
+        PROXY_AUTHORIZATION, RETRY_AFTER, WWW_AUTHENTICATE,
+    },
+    Method, Request, Response, StatusCode,
+};
+use reqwest_middleware::{Next, RequestBuilder};
+
+use crate::{get_clock, ApiAuthenticator, ApiError, Extensions, Middleware};
+
+/// This struct is used to poll the long-running operation, which is set by `RespondAsyncOps::with_respond_async`
+#[derive(Debug, Clone, Copy)]
+pub struct RespondAsync {
+    /// The default interval between polls
+    pub interval: Duration,
+    /// The max time to wait for the operation
+    pub max_wait: Duration,
+}
+
+/// This trait provides helpers of long-running operations
+///
+/// The request is sent with `Prefer: respond-async`.
+/// If the server responds `202 Accepted` with `Location`, the location is polled by `GET`
+/// until it responds other than `202 Accepted`, and that response is returned as the result of request.
+///
+/// The delay between polls is taken from `Retry-After` (in seconds) if provided, or the interval otherwise.
+/// The headers of request are kept for each poll. When the location is in another origin,
+/// the credentials (e.g. `Authorization` and `Cookie`) are removed, and the poll is not signed by the authenticator.
+/// If the operation doesn't complete within the max wait, the request fails with `ApiError::Timeout`.
+///
+/// # Examples
+///
+/// ```
+/// let req = client.post("/api/reports").await?
+///     .with_respond_async(Duration::from_secs(1), Duration::from_secs(60));
+/// let report: Report = send_json!(req, params).await?;
+/// ```
+pub trait RespondAsyncOps {
+    /// Poll the long-running operation until it completes
+    /// - interval: the default interval between polls
+    /// - max_wait: the max time to wait for the operation
+    fn with_respond_async(self, interval: Duration, max_wait: Duration) -> Self;
+}
+
+impl RespondAsyncOps for RequestBuilder {
+    fn with_respond_async(self, interval: Duration, max_wait: Duration) -> Self {
+        self.header("Prefer", "respond-async")
+            .with_extension(RespondAsync { interval, max_wait })
+    }
+}
+
+/// This middleware is used to poll the long-running operation, if `RespondAsync` is set.
+///
+/// It goes before other middlewares, so each poll is authenticated, logged and mocked as a normal request.
+pub(crate) struct RespondAsyncMiddleware;
+
+#[async_trait]
+impl Middleware for RespondAsyncMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        let config = match extensions.get::<RespondAsync>() {
+            Some(config) => *config,
+            None => return next.run(req, extensions).await,
+        };
+
+        let clock = get_clock(extensions);
+        let start = clock.now();
+        let origin = req.url().origin();
+        let mut headers = req.headers().clone();
+        headers.remove(CONTENT_TYPE);
+        headers.remove(CONTENT_LENGTH);
+        let timeout = req.timeout().copied();
+        let authenticator = extensions.get::<Arc<dyn ApiAuthenticator>>().cloned();
+
+        let mut res = next.clone().run(req, extensions).await?;
+        while res.status() == StatusCode::ACCEPTED {
+            let url = match res
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| res.url().join(v).ok())
+            {
+                Some(url) => url,
+                None => break,
+            };
+            let delay = res
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(parse_retry_after)
+                .unwrap_or(config.interval);
+            let elapsed = clock.now().duration_since(start).unwrap_or_default();
+            if elapsed + delay > config.max_wait {
+                return Err(ApiError::Timeout.into());
+            }
+            clock.sleep(delay).await;
+
+            log::debug!("Poll long-running operation at {}", url);
+            let same_origin = url.origin() == origin;
+            let mut poll = Request::new(Method::GET, url);
+            *poll.headers_mut() = headers.clone();
+            if same_origin {
+                if let Some(authenticator) = authenticator.clone() {
+                    extensions.insert(authenticator);
+                }
+            } else {
+                remove_sensitive_headers(poll.headers_mut());
+                extensions.remove::<Arc<dyn ApiAuthenticator>>();
+            }
+            *poll.timeout_mut() = timeout;
+            res = next.clone().run(poll, extensions).await?;
+        }
+        Ok(res)
+    }
+}
+
+/// Remove the credentials before polling another origin, as the redirect policy of reqwest does
+/// - headers: the headers of poll
+fn remove_sensitive_headers(headers: &mut HeaderMap) {
+    headers.remove(AUTHORIZATION);
+    headers.remove(COOKIE);
+    headers.remove(PROXY_AUTHORIZATION);
+    headers.remove(WWW_AUTHENTICATE);
+}
+
+/// Parse `Retry-After` in seconds
+/// - value: the header value
+fn parse_retry_after(value: &HeaderValue) -> Option<Duration> {
+    let seconds = value.to_str().ok()?.trim().parse::<u64>().ok()?;
+    Some(Duration::from_secs(seconds))
+}
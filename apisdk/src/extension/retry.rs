@@ -0,0 +1,156 @@
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::{Request, Response};
+use reqwest_middleware::Next;
+
+use crate::{get_clock, is_idempotent, ApiError, Extensions, Middleware};
+
+/// The predicate to decide whether and when to retry
+type RetryPredicate = Arc<dyn Fn(&ApiError, u32) -> Option<Duration> + Send + Sync>;
+
+/// This struct decides whether the failed request should be retried, and how long to wait before that.
+///
+/// # Examples
+///
+/// ```
+/// // Retry the retryable errors 3 times, with 100ms between attempts
+/// let policy = RetryPolicy::new(3, Duration::from_millis(100));
+///
+/// // Retry 409 twice, with increasing delays
+/// let policy = RetryPolicy::retry_if(|e: &ApiError, attempt| match e.status() {
+///     Some(409) if attempt <= 2 => Some(Duration::from_millis(100 * attempt as u64)),
+///     _ => None,
+/// });
+///
+/// // Also retry POST and PATCH, which may cause duplicate side effects
+/// let policy = RetryPolicy::new(3, Duration::from_millis(100)).with_non_idempotent(true);
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Decide whether and when to retry
+    predicate: RetryPredicate,
+    /// Whether to retry the non-idempotent methods, such as POST and PATCH
+    non_idempotent: bool,
+}
+
+impl Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy").finish_non_exhaustive()
+    }
+}
+
+impl RetryPolicy {
+    /// Create a new instance, which retries the errors classified by `ApiError::is_retryable`
+    /// - max_retries: the max number of retries
+    /// - delay: how long to wait before each retry
+    pub fn new(max_retries: u32, delay: Duration) -> Self {
+        Self::retry_if(move |e: &ApiError, attempt| {
+            (attempt <= max_retries && e.is_retryable()).then_some(delay)
+        })
+    }
+
+    /// Create a new instance with fully custom retry decisions
+    /// - predicate: receive the failure and the number of failed attempts (starting from 1), and return the delay before next retry, or `None` to stop
+    pub fn retry_if<F>(predicate: F) -> Self
+    where
+        F: Fn(&ApiError, u32) -> Option<Duration> + Send + Sync + 'static,
+    {
+        Self {
+            predicate: Arc::new(predicate),
+            non_idempotent: false,
+        }
+    }
+
+    /// Set whether to retry the non-idempotent methods, such as POST and PATCH
+    /// - non_idempotent: `false` by default, so only GET, HEAD, OPTIONS, TRACE, PUT and DELETE are retried
+    pub fn with_non_idempotent(mut self, non_idempotent: bool) -> Self {
+        self.non_idempotent = non_idempotent;
+        self
+    }
+
+    /// Decide whether to retry
+    /// - e: the failure
+    /// - attempt: the number of failed attempts, starting from 1
+    ///
+    /// Return the delay before next retry, or `None` to stop
+    pub fn next_delay(&self, e: &ApiError, attempt: u32) -> Option<Duration> {
+        (self.predicate)(e, attempt)
+    }
+}
+
+/// This middleware is used to retry the failed requests according to `RetryPolicy`.
+///
+/// Both the transport errors and the error statuses (4xx and 5xx) are passed to the policy.
+/// When the policy gives up, the last response or error is returned as is.
+///
+/// The request whose body could not be cloned is sent only once.
+/// So is the request with a non-idempotent method (e.g. POST and PATCH), unless `RetryPolicy::with_non_idempotent` is set.
+/// The delays are waited by the `Clock` set by `with_clock`.
+///
+/// # Examples
+///
+/// ```
+/// let api = XxxApi::builder()
+///     .with_middleware(RetryMiddleware::new(RetryPolicy::new(3, Duration::from_millis(100))))
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryMiddleware {
+    /// The policy
+    policy: RetryPolicy,
+}
+
+impl RetryMiddleware {
+    /// Create a new instance
+    /// - policy: decide whether and when to retry
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self { policy }
+    }
+}
+
+#[async_trait]
+impl Middleware for RetryMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        if !self.policy.non_idempotent && !is_idempotent(req.method()) {
+            return next.run(req, extensions).await;
+        }
+
+        let clock = get_clock(extensions);
+        let mut attempt = 0;
+        let mut req = req;
+        loop {
+            let retry = match req.try_clone() {
+                Some(retry) => retry,
+                None => return next.run(req, extensions).await,
+            };
+            attempt += 1;
+
+            let res = next
+                .clone()
+                .run(req, extensions)
+                .await
+                .map_err(ApiError::from);
+            let delay = match &res {
+                Ok(res) if res.status().is_client_error() || res.status().is_server_error() => self
+                    .policy
+                    .next_delay(&ApiError::from_status(res.status()), attempt),
+                Ok(_) => None,
+                Err(e) => self.policy.next_delay(e, attempt),
+            };
+            match delay {
+                Some(delay) => {
+                    log::debug!("Retry #{} after {:?}", attempt, delay);
+                    clock.sleep(delay).await;
+                    req = retry;
+                }
+                None => return res.map_err(Into::into),
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    collections::HashMap,
+    future::Future,
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
+use reqwest::Method;
+use reqwest_middleware::{RequestBuilder, RequestInitialiser};
+
+use crate::{ApiError, ApiResult, ResponseBody};
+
+/// The in-flight call, which could be awaited by many callers
+type SharedCall = Shared<BoxFuture<'static, Result<ResponseBody, Arc<ApiError>>>>;
+
+/// This struct is used to coalesce the identical in-flight GET requests, see `with_single_flight`
+///
+/// The requests are keyed by method, url and headers, so the concurrent identical requests share one network call,
+/// and all callers receive the cloned result.
+#[derive(Clone, Default)]
+pub(crate) struct SingleFlight {
+    /// The in-flight calls, with the id of each call
+    calls: Arc<Mutex<HashMap<String, (u64, SharedCall)>>>,
+    /// The id of next call
+    next_id: Arc<AtomicU64>,
+}
+
+/// This struct is used to remove the in-flight call from the map when dropped
+///
+/// The call is removed only if it is still in the map, rather than a newer one with the same key.
+struct InFlight {
+    calls: Arc<Mutex<HashMap<String, (u64, SharedCall)>>>,
+    key: String,
+    id: u64,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let removed = {
+            let mut calls = self.calls.lock().unwrap();
+            match calls.get(&self.key) {
+                Some((id, _)) if *id == self.id => calls.remove(&self.key),
+                _ => None,
+            }
+        };
+        // Drop the call after unlock, since it may drop another `InFlight`
+        drop(removed);
+    }
+}
+
+impl SingleFlight {
+    /// Get the key of request, only the GET requests are coalesced
+    /// - req: the request to send
+    /// - require_headers: the response headers are zipped into body or not
+    pub(crate) fn key_of(req: &RequestBuilder, require_headers: bool) -> Option<String> {
+        let req = req.try_clone()?.build().ok()?;
+        if req.method() != Method::GET {
+            return None;
+        }
+
+        // The response may depend on the headers, such as `Authorization`, `Cookie` or `Accept`
+        let mut headers: Vec<_> = req
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_bytes()))
+            .collect();
+        headers.sort();
+        let mut hasher = DefaultHasher::new();
+        headers.hash(&mut hasher);
+
+        Some(format!(
+            "{} {} {:x} {}",
+            req.method(),
+            req.url(),
+            hasher.finish(),
+            require_headers
+        ))
+    }
+
+    /// Run the call, or join the in-flight one with the same key
+    /// - key: the key of request
+    /// - call: send the request, and decode response body
+    pub(crate) async fn run<F>(&self, key: String, call: F) -> ApiResult<ResponseBody>
+    where
+        F: Future<Output = ApiResult<ResponseBody>> + Send + 'static,
+    {
+        let (shared, _leader) = {
+            let mut calls = self.calls.lock().unwrap();
+            match calls.get(&key) {
+                Some((_, shared)) => {
+                    log::debug!("Join in-flight request: {}", key);
+                    (shared.clone(), None)
+                }
+                None => {
+                    let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                    let in_flight = || InFlight {
+                        calls: self.calls.clone(),
+                        key: key.clone(),
+                        id,
+                    };
+                    let finished = in_flight();
+                    let shared = async move {
+                        let result = call.await;
+                        // Later requests should start a new call
+                        drop(finished);
+                        result.map_err(Arc::new)
+                    }
+                    .boxed()
+                    .shared();
+                    // The call is not driven once the leader is dropped, unless others have joined it
+                    let leader = in_flight();
+                    calls.insert(key, (id, shared.clone()));
+                    (shared, Some(leader))
+                }
+            }
+        };
+
+        // The error is shared, unless there is only one caller
+        shared
+            .await
+            .map_err(|e| Arc::try_unwrap(e).unwrap_or_else(ApiError::Shared))
+    }
+}
+
+impl RequestInitialiser for SingleFlight {
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        req.with_extension(self.clone())
+    }
+}
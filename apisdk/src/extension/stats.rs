@@ -0,0 +1,154 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use reqwest::{Request, Response};
+use reqwest_middleware::Next;
+
+use crate::{Extensions, Middleware};
+
+/// This struct holds the summary of request latency
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencySummary {
+    /// The shortest latency
+    pub min: Duration,
+    /// The longest latency
+    pub max: Duration,
+    /// The average latency
+    pub mean: Duration,
+    /// The median latency
+    pub p50: Duration,
+    /// The 99th percentile latency
+    pub p99: Duration,
+}
+
+impl LatencySummary {
+    /// Summarize the latencies
+    /// - samples: the latencies of requests
+    fn of(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let total: Duration = sorted.iter().sum();
+        let percentile = |p: usize| sorted[((sorted.len() - 1) * p + 50) / 100];
+        Self {
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            mean: total / sorted.len() as u32,
+            p50: percentile(50),
+            p99: percentile(99),
+        }
+    }
+}
+
+/// This struct holds the statistics of requests, which is designed for performance regression tests
+///
+/// The mocked requests are counted as well, and the failed requests are counted without latency.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestStats {
+    /// The number of requests
+    pub requests: u64,
+    /// The number of requests which fail without response
+    pub failures: u64,
+    /// The size of request bodies, excluding the streaming ones
+    pub request_bytes: u64,
+    /// The size of response bodies, as declared by `Content-Length`
+    pub response_bytes: u64,
+    /// The latency until the response headers are received
+    pub latency: LatencySummary,
+}
+
+/// The samples of requests
+#[derive(Debug, Default)]
+struct Samples {
+    /// The number of requests
+    requests: u64,
+    /// The number of failures
+    failures: u64,
+    /// The size of request bodies
+    request_bytes: u64,
+    /// The size of response bodies
+    response_bytes: u64,
+    /// The latencies of successful requests
+    latencies: Vec<Duration>,
+}
+
+/// This struct is used to count requests, shared by Client and ApiCore
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StatsCounter {
+    /// The samples
+    samples: Arc<Mutex<Samples>>,
+}
+
+impl StatsCounter {
+    /// Get the statistics
+    pub fn stats(&self) -> RequestStats {
+        let samples = self.samples.lock().unwrap();
+        RequestStats {
+            requests: samples.requests,
+            failures: samples.failures,
+            request_bytes: samples.request_bytes,
+            response_bytes: samples.response_bytes,
+            latency: LatencySummary::of(&samples.latencies),
+        }
+    }
+}
+
+/// This middleware is used to count requests, bytes and latencies
+pub(crate) struct RequestStatsMiddleware(pub StatsCounter);
+
+#[async_trait]
+impl Middleware for RequestStatsMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        let request_bytes = req
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| bytes.len() as u64)
+            .unwrap_or_default();
+
+        let start = Instant::now();
+        let res = next.run(req, extensions).await;
+        let elapsed = start.elapsed();
+
+        let mut samples = self.0.samples.lock().unwrap();
+        samples.requests += 1;
+        samples.request_bytes += request_bytes;
+        match &res {
+            Ok(res) => {
+                samples.response_bytes += res.content_length().unwrap_or_default();
+                samples.latencies.push(elapsed);
+            }
+            Err(_) => samples.failures += 1,
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::LatencySummary;
+
+    #[test]
+    fn test_latency_summary() {
+        assert_eq!(LatencySummary::default(), LatencySummary::of(&[]));
+
+        let samples: Vec<_> = (1..=100).rev().map(Duration::from_millis).collect();
+        let summary = LatencySummary::of(&samples);
+        assert_eq!(Duration::from_millis(1), summary.min);
+        assert_eq!(Duration::from_millis(100), summary.max);
+        assert_eq!(Duration::from_micros(50500), summary.mean);
+        assert_eq!(Duration::from_millis(51), summary.p50);
+        assert_eq!(Duration::from_millis(99), summary.p99);
+    }
+}
@@ -1,6 +1,6 @@
 use serde::de::DeserializeOwned;
 
-use crate::{ApiResult, Json, ResponseBody, Xml};
+use crate::{ApiError, ApiResult, Json, MimeType, ResponseBody, Xml};
 
 /// This struct is used to parse response body to json or xml
 #[derive(Debug)]
@@ -13,8 +13,11 @@ impl Auto {
         T: 'static + DeserializeOwned,
     {
         match &body {
-            ResponseBody::Json(_) => Json::try_parse(body),
+            ResponseBody::Json(_) | ResponseBody::Empty => Json::try_parse(body),
             ResponseBody::Xml(_) | ResponseBody::Text(_) => Xml::try_parse(body),
+            ResponseBody::Multipart(_) | ResponseBody::Bytes { .. } => Err(
+                ApiError::IncompatibleContentType(MimeType::Json, body.mime_type()),
+            ),
         }
     }
 }
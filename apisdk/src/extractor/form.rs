@@ -0,0 +1,37 @@
+use std::any::TypeId;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{ApiError, ApiResult, MimeType, ResponseBody};
+
+/// This struct is used to parse response body as `application/x-www-form-urlencoded`
+///
+/// Some endpoints, e.g. legacy OAuth token endpoints, reply `access_token=...&expires_in=3600`.
+#[derive(Debug)]
+pub struct Form;
+
+impl Form {
+    /// Try to parse response
+    pub fn try_parse<T>(body: ResponseBody) -> ApiResult<T>
+    where
+        T: 'static + DeserializeOwned,
+    {
+        let type_id = TypeId::of::<T>();
+        if type_id == TypeId::of::<()>() {
+            return serde_json::from_value(Value::Null)
+                .map_err(|_| ApiError::Other("Impossible".to_string()));
+        }
+
+        match body {
+            ResponseBody::Text(text) => {
+                serde_urlencoded::from_str(&text).map_err(ApiError::DecodeForm)
+            }
+            ResponseBody::Empty => serde_urlencoded::from_str("").map_err(ApiError::DecodeForm),
+            _ => Err(ApiError::IncompatibleContentType(
+                MimeType::Form,
+                body.mime_type(),
+            )),
+        }
+    }
+}
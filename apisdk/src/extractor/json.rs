@@ -1,6 +1,9 @@
 use std::{any::TypeId, collections::HashMap};
 
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{
+    de::{DeserializeOwned, Error as _},
+    Deserialize, Deserializer, Serialize,
+};
 use serde_json::Value;
 
 use crate::{ApiError, ApiResult, MimeType};
@@ -52,6 +55,13 @@ impl Json {
                 log::debug!("Treat text as json for decoding");
                 Self::do_try_parse(text)
             }
+            ResponseBody::Empty if type_id == TypeId::of::<String>() => {
+                Self::do_try_parse(String::new())
+            }
+            // Treat empty as null, so that `Option<T>` could be `None`
+            ResponseBody::Empty => {
+                serde_json::from_value(Value::Null).map_err(ApiError::DecodeJson)
+            }
             _ => Err(ApiError::IncompatibleContentType(
                 MimeType::Json,
                 body.mime_type(),
@@ -113,6 +123,8 @@ impl Json {
 ///     - an alias of serde_json::Value
 /// - apisdk::CodeDataMessage
 ///     - parse `{code, data, message}` json payload, and return `data` field
+/// - apisdk::WithHeaders
+///     - separate `__headers__` from json payload, and return `WithHeaders<T>`
 pub trait JsonExtractor {
     /// The extractor needs response HTTP headers or not.
     fn require_headers() -> bool {
@@ -162,6 +174,10 @@ impl TryFrom<ResponseBody> for String {
             }
             ResponseBody::Xml(xml) => Ok(xml),
             ResponseBody::Text(text) => Ok(text),
+            ResponseBody::Empty => Ok(String::new()),
+            ResponseBody::Multipart(_) | ResponseBody::Bytes { .. } => Err(
+                ApiError::IncompatibleContentType(MimeType::Text, body.mime_type()),
+            ),
         }
     }
 }
@@ -301,6 +317,163 @@ impl JsonExtractor for CodeDataMessage {
     }
 }
 
+/// This struct is used to separate the HTTP headers from response payload.
+///
+/// The headers are taken from the injected `__headers__` field, and the rest of payload is parsed as `T`.
+///
+/// # Examples
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let res: WithHeaders<User> = send!(req, WithHeaders).await?;
+/// let etag = res.get_header("ETag");
+/// let user = res.body;
+/// ```
+#[derive(Debug)]
+pub struct WithHeaders<T = Value> {
+    /// The payload without `__headers__` field
+    pub body: T,
+    /// Hold all HTTP headers, whose names are in lowercase
+    headers: HashMap<String, String>,
+}
+
+impl<T> WithHeaders<T> {
+    /// Get any header, case-insensitively
+    /// - name: header name
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(|v| v.as_str())
+    }
+
+    /// Get all headers
+    pub fn headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+
+    /// Parse the headers into a typed struct, whose fields are named by lowercase header names
+    pub fn parse_headers<H>(&self) -> ApiResult<H>
+    where
+        H: DeserializeOwned,
+    {
+        let headers = serde_json::to_value(&self.headers).map_err(ApiError::DecodeJson)?;
+        serde_json::from_value(headers).map_err(ApiError::DecodeJson)
+    }
+
+    /// Split into body and headers
+    pub fn into_parts(self) -> (T, HashMap<String, String>) {
+        (self.body, self.headers)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for WithHeaders<T>
+where
+    T: DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut value = Value::deserialize(deserializer)?;
+        let headers = match &mut value {
+            Value::Object(map) => map.remove("__headers__"),
+            _ => None,
+        };
+        let headers: HashMap<String, String> = match headers {
+            Some(headers) => serde_json::from_value(headers).map_err(D::Error::custom)?,
+            None => HashMap::new(),
+        };
+        let headers = headers
+            .into_iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value))
+            .collect();
+        let body = serde_json::from_value(value).map_err(D::Error::custom)?;
+        Ok(Self { body, headers })
+    }
+}
+
+impl<T> TryFrom<ResponseBody> for WithHeaders<T>
+where
+    T: DeserializeOwned,
+{
+    type Error = ApiError;
+
+    fn try_from(body: ResponseBody) -> Result<Self, Self::Error> {
+        body.parse_json()
+    }
+}
+
+impl JsonExtractor for WithHeaders {
+    fn require_headers() -> bool {
+        true
+    }
+
+    fn try_extract<T>(self) -> ApiResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        // Put the headers back, so the result could be `WithHeaders<T>`
+        let mut value = self.body;
+        if let Value::Object(map) = &mut value {
+            let headers = serde_json::to_value(self.headers).map_err(ApiError::DecodeJson)?;
+            map.insert("__headers__".to_string(), headers);
+        }
+        serde_json::from_value(value).map_err(ApiError::DecodeJson)
+    }
+}
+
+/// This struct is used to keep the original json payload, along with the parsed result.
+///
+/// It's useful for audit logging, which stores the raw payload for later reprocessing.
+///
+/// # Examples
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let res: Raw<User> = send!(req).await?;
+/// let user = res.parsed;
+/// let raw = res.raw;
+/// ```
+#[derive(Debug)]
+pub struct Raw<T = Value> {
+    /// The parsed result
+    pub parsed: T,
+    /// The original json payload
+    pub raw: Value,
+}
+
+impl<T> Raw<T> {
+    /// Split into parsed result and raw payload
+    pub fn into_parts(self) -> (T, Value) {
+        (self.parsed, self.raw)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Raw<T>
+where
+    T: DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = Value::deserialize(deserializer)?;
+        let parsed = T::deserialize(&raw).map_err(D::Error::custom)?;
+        Ok(Self { parsed, raw })
+    }
+}
+
+impl<T> TryFrom<ResponseBody> for Raw<T>
+where
+    T: DeserializeOwned,
+{
+    type Error = ApiError;
+
+    fn try_from(body: ResponseBody) -> Result<Self, Self::Error> {
+        body.parse_json()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde::Deserialize;
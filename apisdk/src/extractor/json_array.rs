@@ -0,0 +1,297 @@
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{ready, Stream, StreamExt};
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::ndjson::response_chunks;
+use crate::{ApiError, ApiResult, MimeType};
+
+/// This struct is used to split a json array into top-level elements, which may cross chunks.
+///
+/// Only the bytes of the current element are buffered.
+#[derive(Debug, Default)]
+pub(crate) struct ArrayDecoder {
+    /// The opening `[` has been read
+    started: bool,
+    /// The closing `]` has been read
+    finished: bool,
+    /// The nesting depth inside current element
+    depth: usize,
+    /// Inside a string
+    in_string: bool,
+    /// The previous char is `\` inside a string
+    escaped: bool,
+    /// A `,` has been read, so the next element is required
+    expect_element: bool,
+    /// The incomplete element
+    buffer: Vec<u8>,
+    /// The complete elements
+    elements: VecDeque<Vec<u8>>,
+}
+
+impl ArrayDecoder {
+    /// Feed a chunk of bytes
+    /// - chunk: the received bytes
+    pub fn feed(&mut self, chunk: &[u8]) -> ApiResult<()> {
+        for b in chunk.iter().copied() {
+            if !self.started {
+                match b {
+                    b'[' => self.started = true,
+                    b if b.is_ascii_whitespace() => {}
+                    _ => return Err(Self::error("expect '[' at the beginning")),
+                }
+                continue;
+            }
+            if self.finished {
+                if !b.is_ascii_whitespace() {
+                    return Err(Self::error("unexpected data after ']'"));
+                }
+                continue;
+            }
+            if self.in_string {
+                self.buffer.push(b);
+                match b {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match b {
+                b'"' => {
+                    self.in_string = true;
+                    self.buffer.push(b);
+                }
+                b'{' | b'[' => {
+                    self.depth += 1;
+                    self.buffer.push(b);
+                }
+                b'}' | b']' if self.depth > 0 => {
+                    self.depth -= 1;
+                    self.buffer.push(b);
+                }
+                b']' => {
+                    if !self.buffer.is_empty() || self.expect_element {
+                        self.push_element()?;
+                    }
+                    self.finished = true;
+                }
+                b',' if self.depth == 0 => {
+                    self.push_element()?;
+                    self.expect_element = true;
+                }
+                b if b.is_ascii_whitespace() && self.buffer.is_empty() => {}
+                _ => self.buffer.push(b),
+            }
+        }
+        Ok(())
+    }
+
+    /// Verify the array is complete
+    pub fn finish(&mut self) -> ApiResult<()> {
+        if self.finished {
+            Ok(())
+        } else {
+            Err(Self::error("unexpected end of json array"))
+        }
+    }
+
+    /// Take the next complete element
+    pub fn next_element(&mut self) -> Option<Vec<u8>> {
+        self.elements.pop_front()
+    }
+
+    /// Queue current element
+    fn push_element(&mut self) -> ApiResult<()> {
+        if self.buffer.is_empty() {
+            return Err(Self::error("missing element"));
+        }
+        let element = std::mem::take(&mut self.buffer);
+        self.elements.push_back(element);
+        self.expect_element = false;
+        Ok(())
+    }
+
+    /// Build error
+    fn error(message: &str) -> ApiError {
+        ApiError::DecodeResponse(MimeType::Json, message.to_string())
+    }
+
+    /// Get the capacity of buffer
+    #[cfg(test)]
+    fn buffer_capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+}
+
+/// This struct is a stream of items, which are decoded from the elements of json array response.
+///
+/// Elements are emitted as they arrive, so the whole array is never buffered.
+/// A malformed element yields an error item, and the stream continues.
+/// A malformed array yields an error item, and the stream stops.
+///
+/// # Examples
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let mut stream = send_json_array!(req).await?;
+/// while let Some(item) = stream.next().await {
+///     let item: serde_json::Value = item?;
+/// }
+/// ```
+pub struct JsonArrayStream<T = Value> {
+    /// The chunks of response
+    chunks: Pin<Box<dyn Stream<Item = ApiResult<Vec<u8>>> + Send>>,
+    /// The array decoder
+    decoder: ArrayDecoder,
+    /// The error to yield, after all complete elements
+    error: Option<ApiError>,
+    /// Indicate the stream is finished
+    done: bool,
+    /// The type of item
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> std::fmt::Debug for JsonArrayStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonArrayStream")
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<T> JsonArrayStream<T> {
+    /// Create a new instance from response
+    /// - res: HTTP response
+    pub fn new(res: Response) -> Self {
+        Self::from_chunks(response_chunks(res))
+    }
+
+    /// Create a new instance from a stream of chunks
+    /// - chunks: the stream of chunks
+    pub fn from_chunks(chunks: impl Stream<Item = ApiResult<Vec<u8>>> + Send + 'static) -> Self {
+        Self {
+            chunks: chunks.boxed(),
+            decoder: ArrayDecoder::default(),
+            error: None,
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Stream for JsonArrayStream<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = ApiResult<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(element) = this.decoder.next_element() {
+                let item = serde_json::from_slice(&element).map_err(ApiError::DecodeJson);
+                return Poll::Ready(Some(item));
+            }
+            if this.done {
+                return Poll::Ready(this.error.take().map(Err));
+            }
+            let result = match ready!(this.chunks.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => this.decoder.feed(&chunk),
+                Some(Err(e)) => Err(e),
+                None => {
+                    this.done = true;
+                    this.decoder.finish()
+                }
+            };
+            if let Err(e) = result {
+                this.error = Some(e);
+                this.done = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, stream, StreamExt};
+    use serde_json::{json, Value};
+
+    use super::{ArrayDecoder, JsonArrayStream};
+
+    fn chunks(
+        input: &[&str],
+    ) -> impl futures::Stream<Item = crate::ApiResult<Vec<u8>>> + Send + 'static {
+        let chunks: Vec<_> = input.iter().map(|c| Ok(c.as_bytes().to_vec())).collect();
+        stream::iter(chunks)
+    }
+
+    #[test]
+    fn test_array_decoder() {
+        let mut decoder = ArrayDecoder::default();
+        decoder.feed(b" [1, \"a,]\\\"").unwrap();
+        assert_eq!(Some(b"1".to_vec()), decoder.next_element());
+        assert_eq!(None, decoder.next_element());
+        decoder.feed(b"\", {\"b\": [1, 2]}").unwrap();
+        assert_eq!(Some(b"\"a,]\\\"\"".to_vec()), decoder.next_element());
+        decoder.feed(b"]\n").unwrap();
+        assert_eq!(Some(b"{\"b\": [1, 2]}".to_vec()), decoder.next_element());
+        decoder.finish().unwrap();
+
+        let mut decoder = ArrayDecoder::default();
+        assert!(decoder.feed(b"[1,]").is_err());
+    }
+
+    #[test]
+    fn test_bounded_buffer() {
+        let mut decoder = ArrayDecoder::default();
+        decoder.feed(b"[").unwrap();
+        for i in 0..10000 {
+            decoder
+                .feed(format!("{{\"id\":{}}},", i).as_bytes())
+                .unwrap();
+            assert!(decoder.next_element().is_some());
+        }
+        assert!(decoder.buffer_capacity() < 64);
+    }
+
+    #[test]
+    fn test_stream() {
+        let stream =
+            JsonArrayStream::<Value>::from_chunks(chunks(&["[{\"id\":1},", "{\"id\":2}]"]));
+        let items: Vec<_> = block_on(stream.collect());
+        assert_eq!(2, items.len());
+        assert_eq!(&json!({"id": 2}), items[1].as_ref().unwrap());
+
+        let stream = JsonArrayStream::<Value>::from_chunks(chunks(&["[]"]));
+        let items: Vec<_> = block_on(stream.collect());
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn test_malformed_array() {
+        let stream = JsonArrayStream::<Value>::from_chunks(chunks(&["[1, 2"]));
+        let items: Vec<_> = block_on(stream.collect());
+        assert_eq!(2, items.len());
+        assert!(items[1].is_err());
+
+        let stream = JsonArrayStream::<Value>::from_chunks(chunks(&["[1, 2,]", "[3]"]));
+        let items: Vec<_> = block_on(stream.collect());
+        assert_eq!(3, items.len());
+        assert_eq!(&json!(2), items[1].as_ref().unwrap());
+        assert!(items[2].is_err());
+
+        let stream = JsonArrayStream::<Value>::from_chunks(chunks(&["{\"id\":1}"]));
+        let items: Vec<_> = block_on(stream.collect());
+        assert_eq!(1, items.len());
+        assert!(items[0].is_err());
+    }
+}
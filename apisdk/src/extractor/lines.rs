@@ -0,0 +1,140 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{ready, Stream, StreamExt};
+use reqwest::{header::TRANSFER_ENCODING, Response};
+
+use super::ndjson::{response_chunks, LineDecoder};
+use crate::{ApiError, ApiResult};
+
+/// This struct is a stream of text lines, which are decoded from response without buffering the whole body.
+///
+/// The line terminator (`\n` or `\r\n`) is stripped, and the blank lines are kept.
+/// A line which is not valid UTF-8 yields `ApiError::DecodeText`, and the stream continues.
+///
+/// # Examples
+///
+/// ```
+/// let req = client.get("/api/logs").await?;
+/// let mut stream = send_lines!(req).await?;
+/// while let Some(line) = stream.next().await {
+///     let line: String = line?;
+/// }
+/// ```
+pub struct LineStream {
+    /// The chunks of response
+    chunks: Pin<Box<dyn Stream<Item = ApiResult<Vec<u8>>> + Send>>,
+    /// The line decoder
+    decoder: LineDecoder,
+    /// The response is sent with `Transfer-Encoding: chunked`
+    chunked: bool,
+    /// Indicate the stream is finished
+    done: bool,
+}
+
+impl std::fmt::Debug for LineStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LineStream")
+            .field("chunked", &self.chunked)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl LineStream {
+    /// Create a new instance from response
+    /// - res: HTTP response
+    pub fn new(res: Response) -> Self {
+        let chunked = is_chunked(&res);
+        Self {
+            chunked,
+            ..Self::from_chunks(response_chunks(res))
+        }
+    }
+
+    /// Create a new instance from a stream of chunks
+    /// - chunks: the stream of chunks
+    pub fn from_chunks(chunks: impl Stream<Item = ApiResult<Vec<u8>>> + Send + 'static) -> Self {
+        Self {
+            chunks: chunks.boxed(),
+            decoder: LineDecoder::keep_blank(),
+            chunked: false,
+            done: false,
+        }
+    }
+
+    /// Check whether the response is sent with `Transfer-Encoding: chunked`
+    pub fn is_chunked(&self) -> bool {
+        self.chunked
+    }
+}
+
+impl Stream for LineStream {
+    type Item = ApiResult<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(line) = this.decoder.next_line() {
+                let line = String::from_utf8(line).map_err(|_| ApiError::DecodeText);
+                return Poll::Ready(Some(line));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+            match ready!(this.chunks.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => this.decoder.feed(&chunk),
+                Some(Err(e)) => {
+                    this.decoder.clear();
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                None => {
+                    this.decoder.finish();
+                    this.done = true;
+                }
+            }
+        }
+    }
+}
+
+/// Check whether the response is sent with `Transfer-Encoding: chunked`
+/// - res: HTTP response
+pub(crate) fn is_chunked(res: &Response) -> bool {
+    res.headers()
+        .get_all(TRANSFER_ENCODING)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|v| v.trim().eq_ignore_ascii_case("chunked"))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, stream, StreamExt};
+
+    use super::LineStream;
+
+    #[test]
+    fn test_line_stream() {
+        let chunks: Vec<_> = ["first li", "ne\r\n\nsecond", " line\nthird"]
+            .iter()
+            .map(|c| Ok(c.as_bytes().to_vec()))
+            .collect();
+        let stream = LineStream::from_chunks(stream::iter(chunks));
+        let lines: Vec<String> = block_on(stream.map(|l| l.unwrap()).collect());
+        assert_eq!(vec!["first line", "", "second line", "third"], lines);
+    }
+
+    #[test]
+    fn test_line_stream_invalid_utf8() {
+        let chunks = vec![Ok(b"ok\n\xff\xfe\nok\n".to_vec())];
+        let stream = LineStream::from_chunks(stream::iter(chunks));
+        let lines: Vec<_> = block_on(stream.collect());
+        assert_eq!(3, lines.len());
+        assert!(lines[1].is_err());
+        assert_eq!("ok", lines[2].as_ref().unwrap());
+    }
+}
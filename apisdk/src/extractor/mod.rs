@@ -1,14 +1,34 @@
-use hyper::header::HeaderValue;
+use std::io::Read;
+
+use hyper::header::{HeaderValue, CONTENT_ENCODING, CONTENT_TYPE};
+use reqwest::{Response, ResponseBuilderExt, StatusCode, Url};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 
 mod auto;
+mod form;
 mod json;
+mod json_array;
+mod lines;
+mod multipart;
+mod ndjson;
+mod page;
+#[cfg(feature = "schema")]
+mod schema;
 mod text;
 mod xml;
 
 pub use auto::*;
+pub use form::*;
 pub use json::*;
+pub use json_array::JsonArrayStream;
+pub use lines::LineStream;
+pub use multipart::ResponsePart;
+pub(crate) use multipart::{get_boundary, parse_multipart};
+pub use ndjson::NdjsonStream;
+pub use page::*;
+#[cfg(feature = "schema")]
+pub use schema::*;
 pub use text::*;
 pub use xml::*;
 
@@ -23,6 +43,10 @@ pub enum MimeType {
     Xml,
     /// Text (text/plain | text/*)
     Text,
+    /// Form (application/x-www-form-urlencoded)
+    Form,
+    /// Multipart (multipart/*), with subtype
+    Multipart(String),
     /// Other
     Other(String),
 }
@@ -33,6 +57,8 @@ impl std::fmt::Display for MimeType {
             Self::Json => write!(f, "application/json"),
             Self::Xml => write!(f, "application/xml"),
             Self::Text => write!(f, "text/plain"),
+            Self::Form => write!(f, "application/x-www-form-urlencoded"),
+            Self::Multipart(subtype) => write!(f, "multipart/{}", subtype),
             Self::Other(v) => write!(f, "{}", v),
         }
     }
@@ -53,6 +79,10 @@ impl From<&str> for MimeType {
             Self::Xml
         } else if value.starts_with("text/") {
             Self::Text
+        } else if value == "application/x-www-form-urlencoded" {
+            Self::Form
+        } else if let Some(subtype) = value.strip_prefix("multipart/") {
+            Self::Multipart(subtype.to_string())
         } else {
             Self::Other(value)
         }
@@ -66,8 +96,133 @@ impl From<MimeType> for HeaderValue {
     }
 }
 
+/// ContentEncoding (aka. content coding)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncoding {
+    /// No encoding (identity)
+    Identity,
+    /// Gzip (gzip | x-gzip)
+    Gzip,
+    /// Deflate (deflate)
+    Deflate,
+    /// Brotli (br)
+    Brotli,
+    /// Zstandard (zstd)
+    Zstd,
+}
+
+impl std::fmt::Display for ContentEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Identity => write!(f, "identity"),
+            Self::Gzip => write!(f, "gzip"),
+            Self::Deflate => write!(f, "deflate"),
+            Self::Brotli => write!(f, "br"),
+            Self::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+impl std::str::FromStr for ContentEncoding {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "identity" => Ok(Self::Identity),
+            "gzip" | "x-gzip" => Ok(Self::Gzip),
+            "deflate" => Ok(Self::Deflate),
+            "br" => Ok(Self::Brotli),
+            "zstd" => Ok(Self::Zstd),
+            _ => Err(ApiError::UnsupportedContentEncoding(s.trim().to_string())),
+        }
+    }
+}
+
+impl ContentEncoding {
+    /// Parse the value of `Content-Encoding`, which may list multiple codings in applied order
+    /// - value: the value of content-encoding
+    ///
+    /// Return `ApiError::UnsupportedContentEncoding` if any coding is unknown.
+    pub fn parse_list(value: &str) -> ApiResult<Vec<Self>> {
+        let mut encodings = vec![];
+        for coding in value.split(',') {
+            match coding.parse()? {
+                Self::Identity => {}
+                encoding => encodings.push(encoding),
+            }
+        }
+        Ok(encodings)
+    }
+
+    /// Get the feature required to decode this coding
+    fn feature(&self) -> Option<&'static str> {
+        match self {
+            Self::Identity => None,
+            Self::Gzip => Some("gzip"),
+            Self::Deflate => Some("deflate"),
+            Self::Brotli => Some("brotli"),
+            Self::Zstd => Some("zstd"),
+        }
+    }
+
+    /// Check whether this coding could be decoded, which depends on the enabled features
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Self::Identity => true,
+            Self::Gzip => cfg!(feature = "gzip"),
+            Self::Deflate => cfg!(feature = "deflate"),
+            Self::Brotli => cfg!(feature = "brotli"),
+            Self::Zstd => cfg!(feature = "zstd"),
+        }
+    }
+
+    /// Decode the payload in this coding
+    /// - data: the encoded payload
+    /// - limit: the max length of decoded payload
+    ///
+    /// It fails once the decoded payload exceeds `limit`, such as a decompression bomb,
+    /// or if the feature of this coding is not enabled.
+    pub fn decode(&self, data: &[u8], limit: usize) -> std::io::Result<Vec<u8>> {
+        let reader: Box<dyn Read + '_> = match self {
+            Self::Identity => Box::new(data),
+            #[cfg(feature = "gzip")]
+            Self::Gzip => Box::new(flate2::read::MultiGzDecoder::new(data)),
+            #[cfg(feature = "deflate")]
+            Self::Deflate => Box::new(flate2::read::ZlibDecoder::new(data)),
+            #[cfg(feature = "brotli")]
+            Self::Brotli => Box::new(brotli::Decompressor::new(data, 4096)),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => Box::new(
+                ruzstd::decoding::StreamingDecoder::new(data)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+            ),
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    format!(
+                        "`{}` feature is required",
+                        self.feature().unwrap_or_default()
+                    ),
+                ))
+            }
+        };
+
+        // Read one more byte, to tell whether the limit is exceeded
+        let mut decoded = vec![];
+        reader.take(limit as u64 + 1).read_to_end(&mut decoded)?;
+        if decoded.len() > limit {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("decoded payload exceeds {} bytes", limit),
+            ));
+        }
+        Ok(decoded)
+    }
+}
+
 /// This enum represents the payload of respones
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ResponseBody {
     /// Json (content-type = application/json)
     Json(Value),
@@ -75,6 +230,19 @@ pub enum ResponseBody {
     Xml(String),
     /// Text (content-type = text/plain | text/html | text/*)
     Text(String),
+    /// Multipart (content-type = multipart/mixed | multipart/related | multipart/*)
+    Multipart(Vec<ResponsePart>),
+    /// Raw bytes, such as compressed payload which is not decoded
+    Bytes {
+        /// The value of content-type
+        content_type: String,
+        /// The value of content-encoding
+        content_encoding: Option<String>,
+        /// The raw payload
+        data: Vec<u8>,
+    },
+    /// Empty, such as `204 No Content`, `Content-Length: 0` or empty chunked body
+    Empty,
 }
 
 impl ResponseBody {
@@ -84,7 +252,89 @@ impl ResponseBody {
             Self::Json(_) => MimeType::Json,
             Self::Xml(_) => MimeType::Xml,
             Self::Text(_) => MimeType::Text,
+            Self::Multipart(_) => MimeType::Multipart("mixed".to_string()),
+            Self::Bytes { content_type, .. } => MimeType::from(content_type.as_str()),
+            // The same as the response without content-type
+            Self::Empty => MimeType::Text,
+        }
+    }
+
+    /// Build a synthetic HTTP response, which could be returned by `Middleware` directly
+    /// - status: HTTP status code
+    /// - url: the url of request
+    pub fn into_response(self, status: StatusCode, url: Url) -> ApiResult<Response> {
+        let mut content_encoding = None;
+        let (content_type, payload) = match self {
+            Self::Json(json) => (
+                Some(MimeType::Json.to_string()),
+                json.to_string().into_bytes(),
+            ),
+            Self::Xml(xml) => (Some(MimeType::Xml.to_string()), xml.into_bytes()),
+            Self::Text(text) => (Some(MimeType::Text.to_string()), text.into_bytes()),
+            Self::Multipart(parts) => {
+                let boundary = nanoid::nanoid!();
+                (
+                    Some(format!("multipart/mixed; boundary={}", boundary)),
+                    multipart::encode_multipart(&boundary, &parts),
+                )
+            }
+            Self::Bytes {
+                content_type,
+                content_encoding: encoding,
+                data,
+            } => {
+                content_encoding = encoding;
+                (Some(content_type), data)
+            }
+            Self::Empty => (None, vec![]),
+        };
+        let mut res = hyper::Response::builder().status(status).url(url);
+        if let Some(content_type) = content_type {
+            res = res.header(CONTENT_TYPE, content_type);
+        }
+        if let Some(encoding) = content_encoding {
+            res = res.header(CONTENT_ENCODING, encoding);
         }
+        let res = res
+            .body(payload)
+            .map_err(|_| ApiError::Middleware(anyhow::format_err!("Failed to build response")))?;
+        Ok(Response::from(res))
+    }
+
+    /// Get the json value, unless it's not json
+    pub fn as_json(&self) -> Option<&Value> {
+        match self {
+            Self::Json(json) => Some(json),
+            _ => None,
+        }
+    }
+
+    /// Get the text, for both text and xml
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) | Self::Xml(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Convert to json value
+    ///
+    /// Return `ApiError::IncompatibleContentType` if it's not json
+    pub fn try_into_json(self) -> ApiResult<Value> {
+        match self {
+            Self::Json(json) => Ok(json),
+            _ => Err(ApiError::IncompatibleContentType(
+                MimeType::Json,
+                self.mime_type(),
+            )),
+        }
+    }
+
+    /// Convert to raw bytes, json / xml / text will be encoded as UTF-8
+    ///
+    /// Return `ApiError::IncompatibleContentType` if it's multipart
+    pub fn into_bytes(self) -> ApiResult<Vec<u8>> {
+        Vec::<u8>::try_from(self)
     }
 
     /// Parse json to target type
@@ -120,6 +370,24 @@ impl ResponseBody {
     }
 }
 
+impl TryFrom<ResponseBody> for Vec<u8> {
+    type Error = ApiError;
+
+    fn try_from(body: ResponseBody) -> Result<Self, Self::Error> {
+        match body {
+            ResponseBody::Json(json) => Ok(json.to_string().into_bytes()),
+            ResponseBody::Xml(xml) => Ok(xml.into_bytes()),
+            ResponseBody::Text(text) => Ok(text.into_bytes()),
+            ResponseBody::Bytes { data, .. } => Ok(data),
+            ResponseBody::Empty => Ok(vec![]),
+            ResponseBody::Multipart(_) => Err(ApiError::IncompatibleContentType(
+                MimeType::Other("application/octet-stream".to_string()),
+                body.mime_type(),
+            )),
+        }
+    }
+}
+
 /// This struct is used to parse response body to xml
 #[derive(Debug)]
 pub struct Body;
@@ -134,3 +402,106 @@ impl Body {
         T::try_from(body).map_err(|e| e.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ContentEncoding, ResponseBody, ResponsePart};
+    use crate::ApiError;
+
+    fn bytes() -> ResponseBody {
+        ResponseBody::Bytes {
+            content_type: "application/octet-stream".to_string(),
+            content_encoding: None,
+            data: vec![1, 2, 3],
+        }
+    }
+
+    fn multipart() -> ResponseBody {
+        ResponseBody::Multipart(vec![ResponsePart::new(Default::default(), "part")])
+    }
+
+    #[test]
+    fn test_as_json() {
+        let body = ResponseBody::Json(json!({"name": "apisdk"}));
+        assert_eq!(Some(&json!({"name": "apisdk"})), body.as_json());
+
+        assert_eq!(None, ResponseBody::Text("{}".to_string()).as_json());
+        assert_eq!(None, ResponseBody::Xml("<a/>".to_string()).as_json());
+        assert_eq!(None, bytes().as_json());
+        assert_eq!(None, multipart().as_json());
+    }
+
+    #[test]
+    fn test_as_text() {
+        assert_eq!(
+            Some("hello"),
+            ResponseBody::Text("hello".to_string()).as_text()
+        );
+        assert_eq!(
+            Some("<a/>"),
+            ResponseBody::Xml("<a/>".to_string()).as_text()
+        );
+
+        assert_eq!(None, ResponseBody::Json(json!("hello")).as_text());
+        assert_eq!(None, bytes().as_text());
+        assert_eq!(None, multipart().as_text());
+    }
+
+    #[test]
+    fn test_try_into_json() {
+        let body = ResponseBody::Json(json!([1, 2]));
+        assert_eq!(json!([1, 2]), body.try_into_json().unwrap());
+
+        let res = ResponseBody::Text("[1, 2]".to_string()).try_into_json();
+        assert!(matches!(res, Err(ApiError::IncompatibleContentType(..))));
+        let res = ResponseBody::Xml("<a/>".to_string()).try_into_json();
+        assert!(matches!(res, Err(ApiError::IncompatibleContentType(..))));
+        let res = bytes().try_into_json();
+        assert!(matches!(res, Err(ApiError::IncompatibleContentType(..))));
+        let res = multipart().try_into_json();
+        assert!(matches!(res, Err(ApiError::IncompatibleContentType(..))));
+    }
+
+    #[test]
+    fn test_into_bytes() {
+        let body = ResponseBody::Json(json!({"a": 1}));
+        assert_eq!(b"{\"a\":1}".to_vec(), body.into_bytes().unwrap());
+        let body = ResponseBody::Text("hello".to_string());
+        assert_eq!(b"hello".to_vec(), body.into_bytes().unwrap());
+        let body = ResponseBody::Xml("<a/>".to_string());
+        assert_eq!(b"<a/>".to_vec(), body.into_bytes().unwrap());
+        assert_eq!(vec![1, 2, 3], bytes().into_bytes().unwrap());
+        assert!(ResponseBody::Empty.into_bytes().unwrap().is_empty());
+
+        let res = multipart().into_bytes();
+        assert!(matches!(res, Err(ApiError::IncompatibleContentType(..))));
+    }
+
+    #[test]
+    fn test_parse_content_encoding() {
+        assert_eq!(
+            vec![ContentEncoding::Gzip],
+            ContentEncoding::parse_list("X-GZIP").unwrap()
+        );
+        assert_eq!(
+            vec![ContentEncoding::Deflate, ContentEncoding::Brotli],
+            ContentEncoding::parse_list("deflate, identity, br").unwrap()
+        );
+        assert!(ContentEncoding::parse_list("identity").unwrap().is_empty());
+        assert_eq!("zstd", ContentEncoding::Zstd.to_string());
+
+        let res = ContentEncoding::parse_list("gzip, x-custom");
+        assert!(matches!(res, Err(ApiError::UnsupportedContentEncoding(e)) if e == "x-custom"));
+    }
+
+    #[test]
+    fn test_decode_limit() {
+        let decoded = ContentEncoding::Identity.decode(b"hello", 5).unwrap();
+        assert_eq!(b"hello".to_vec(), decoded);
+
+        let res = ContentEncoding::Identity.decode(b"hello", 4);
+        assert_eq!(std::io::ErrorKind::InvalidData, res.unwrap_err().kind());
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+
+use crate::{ApiError, ApiResult, MimeType, ResponseBody};
+
+/// This struct represents a part of multipart response
+#[derive(Debug, Clone, Default)]
+pub struct ResponsePart {
+    /// Headers of the part, whose names are in lowercase
+    pub headers: HashMap<String, String>,
+    /// Raw bytes of the part
+    pub body: Vec<u8>,
+}
+
+impl ResponsePart {
+    /// Create a new instance
+    pub fn new(headers: HashMap<String, String>, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            headers,
+            body: body.into(),
+        }
+    }
+
+    /// Get any header
+    /// - name: header name, case-insensitive
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(|v| v.as_str())
+    }
+
+    /// Get the mime type of the part
+    pub fn mime_type(&self) -> MimeType {
+        self.get_header("content-type")
+            .map(MimeType::from)
+            .unwrap_or(MimeType::Text)
+    }
+
+    /// Treat the part as text
+    pub fn text(&self) -> ApiResult<String> {
+        String::from_utf8(self.body.clone()).map_err(|_| ApiError::DecodeText)
+    }
+
+    /// Parse the part as json
+    pub fn parse_json<T>(&self) -> ApiResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice(&self.body).map_err(ApiError::DecodeJson)
+    }
+}
+
+impl TryFrom<ResponseBody> for Vec<ResponsePart> {
+    type Error = ApiError;
+
+    fn try_from(body: ResponseBody) -> Result<Self, Self::Error> {
+        match body {
+            ResponseBody::Multipart(parts) => Ok(parts),
+            _ => Err(ApiError::IncompatibleContentType(
+                MimeType::Multipart("mixed".to_string()),
+                body.mime_type(),
+            )),
+        }
+    }
+}
+
+/// Get `boundary` parameter from the value of content-type
+/// - content_type: the value of content-type
+pub(crate) fn get_boundary(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if name.trim().eq_ignore_ascii_case("boundary") {
+            Some(value.trim().trim_matches('"').to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// Find the position of `needle` in `haystack`, start from `from`
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from > haystack.len() {
+        return None;
+    }
+    haystack[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|pos| pos + from)
+}
+
+/// Split a part into headers and body
+fn parse_part(raw: &[u8]) -> ResponsePart {
+    let (head, body) = match find(raw, b"\r\n\r\n", 0) {
+        Some(pos) => (&raw[..pos], &raw[pos + 4..]),
+        None if raw.starts_with(b"\r\n") => (&raw[..0], &raw[2..]),
+        None => (&raw[..0], raw),
+    };
+
+    let mut headers = HashMap::new();
+    for line in String::from_utf8_lossy(head).split("\r\n") {
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    ResponsePart::new(headers, body)
+}
+
+/// Split the multipart payload into parts
+/// - boundary: the boundary, without leading `--`
+/// - payload: the raw bytes of response
+pub(crate) fn parse_multipart(boundary: &str, payload: &[u8]) -> ApiResult<Vec<ResponsePart>> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let separator = format!("\r\n--{}", boundary).into_bytes();
+    let invalid = || {
+        ApiError::DecodeResponse(
+            MimeType::Multipart("mixed".to_string()),
+            "Invalid multipart payload".to_string(),
+        )
+    };
+
+    // Skip the preamble
+    let mut pos = find(payload, &delimiter, 0).ok_or_else(invalid)? + delimiter.len();
+
+    let mut parts = vec![];
+    loop {
+        // The close delimiter
+        if payload[pos..].starts_with(b"--") {
+            break;
+        }
+        // Skip the transport padding and line break after delimiter
+        pos = find(payload, b"\r\n", pos).ok_or_else(invalid)? + 2;
+
+        let end = find(payload, &separator, pos).ok_or_else(invalid)?;
+        parts.push(parse_part(&payload[pos..end]));
+        pos = end + separator.len();
+    }
+
+    Ok(parts)
+}
+
+/// Encode the parts as multipart payload
+/// - boundary: the boundary, without leading `--`
+/// - parts: the parts to encode
+pub(crate) fn encode_multipart(boundary: &str, parts: &[ResponsePart]) -> Vec<u8> {
+    let mut payload = vec![];
+    for part in parts {
+        payload.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        for (name, value) in part.headers.iter() {
+            payload.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+        }
+        payload.extend_from_slice(b"\r\n");
+        payload.extend_from_slice(&part.body);
+        payload.extend_from_slice(b"\r\n");
+    }
+    payload.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_multipart, get_boundary, parse_multipart};
+
+    const PAYLOAD: &str = "preamble\r\n\
+        --abc\r\n\
+        Content-Type: application/json\r\n\
+        \r\n\
+        {\"key\":\"value\"}\r\n\
+        --abc\r\n\
+        Content-Type: application/octet-stream\r\n\
+        Content-ID: <attachment>\r\n\
+        \r\n\
+        line1\r\nline2\r\n\
+        --abc--\r\n";
+
+    #[test]
+    fn test_get_boundary() {
+        assert_eq!(
+            Some("abc".to_string()),
+            get_boundary("multipart/related; type=\"application/json\"; boundary=\"abc\"")
+        );
+        assert_eq!(None, get_boundary("multipart/related"));
+    }
+
+    #[test]
+    fn test_parse_multipart() {
+        let parts = parse_multipart("abc", PAYLOAD.as_bytes()).unwrap();
+        assert_eq!(2, parts.len());
+        assert_eq!(
+            Some("application/json"),
+            parts[0].get_header("Content-Type")
+        );
+        assert_eq!(b"{\"key\":\"value\"}".to_vec(), parts[0].body);
+        assert_eq!(Some("<attachment>"), parts[1].get_header("content-id"));
+        assert_eq!(b"line1\r\nline2".to_vec(), parts[1].body);
+    }
+
+    #[test]
+    fn test_encode_multipart() {
+        let parts = parse_multipart("abc", PAYLOAD.as_bytes()).unwrap();
+        let payload = encode_multipart("xyz", &parts);
+        let decoded = parse_multipart("xyz", &payload).unwrap();
+        assert_eq!(parts.len(), decoded.len());
+        assert_eq!(parts[1].body, decoded[1].body);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse_multipart("abc", b"--abc\r\nno end").is_err());
+    }
+}
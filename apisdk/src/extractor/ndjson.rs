@@ -0,0 +1,241 @@
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{ready, Stream, StreamExt};
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+
+use crate::{ApiError, ApiResult};
+
+/// This struct is used to split bytes into lines, which may cross chunks
+#[derive(Debug, Default)]
+pub(crate) struct LineDecoder {
+    /// The incomplete line
+    buffer: Vec<u8>,
+    /// The complete lines
+    lines: VecDeque<Vec<u8>>,
+    /// Keep the blank lines
+    keep_blank: bool,
+}
+
+impl LineDecoder {
+    /// Create an instance, which keeps the blank lines
+    pub fn keep_blank() -> Self {
+        Self {
+            keep_blank: true,
+            ..Self::default()
+        }
+    }
+
+    /// Feed a chunk of bytes
+    /// - chunk: the received bytes
+    pub fn feed(&mut self, chunk: &[u8]) {
+        let mut rest = chunk;
+        while let Some(pos) = rest.iter().position(|b| *b == b'\n') {
+            self.buffer.extend_from_slice(&rest[..pos]);
+            let line = std::mem::take(&mut self.buffer);
+            self.push_line(line);
+            rest = &rest[pos + 1..];
+        }
+        self.buffer.extend_from_slice(rest);
+    }
+
+    /// Treat the remaining bytes as the last line
+    pub fn finish(&mut self) {
+        let line = std::mem::take(&mut self.buffer);
+        if !line.is_empty() {
+            self.push_line(line);
+        }
+    }
+
+    /// Drop all pending bytes and lines
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.lines.clear();
+    }
+
+    /// Take the next complete line
+    pub fn next_line(&mut self) -> Option<Vec<u8>> {
+        self.lines.pop_front()
+    }
+
+    /// Queue a line, blank lines are ignored unless `keep_blank`
+    fn push_line(&mut self, mut line: Vec<u8>) {
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if self.keep_blank || line.iter().any(|b| !b.is_ascii_whitespace()) {
+            self.lines.push_back(line);
+        }
+    }
+}
+
+/// Read the response body as a stream of chunks
+/// - res: HTTP response
+pub(crate) fn response_chunks(res: Response) -> impl Stream<Item = ApiResult<Vec<u8>>> + Send {
+    futures::stream::unfold(Some(res), |res| async move {
+        let mut res = res?;
+        match res.chunk().await {
+            Ok(Some(chunk)) => Some((Ok(chunk.to_vec()), Some(res))),
+            Ok(None) => None,
+            Err(e) => Some((Err(ApiError::from(e)), None)),
+        }
+    })
+}
+
+/// This struct is a stream of items, which are decoded from newline-delimited json response.
+///
+/// Each line will be deserialized into `T`. A malformed line yields an error item,
+/// and the stream continues by default. Use `fail_fast()` to stop at the first error.
+///
+/// # Examples
+///
+/// ```
+/// let req = client.get("/api/path").await?;
+/// let mut stream = send_ndjson!(req).await?;
+/// while let Some(item) = stream.next().await {
+///     let item: Item = item?;
+/// }
+/// ```
+pub struct NdjsonStream<T> {
+    /// The chunks of response
+    chunks: Pin<Box<dyn Stream<Item = ApiResult<Vec<u8>>> + Send>>,
+    /// The line decoder
+    decoder: LineDecoder,
+    /// Stop at the first error
+    fail_fast: bool,
+    /// Indicate the stream is finished
+    done: bool,
+    /// The type of item
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> std::fmt::Debug for NdjsonStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NdjsonStream")
+            .field("fail_fast", &self.fail_fast)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<T> NdjsonStream<T> {
+    /// Create a new instance from response
+    /// - res: HTTP response
+    pub fn new(res: Response) -> Self {
+        Self::from_chunks(response_chunks(res))
+    }
+
+    /// Create a new instance from a stream of chunks
+    /// - chunks: the stream of chunks
+    pub fn from_chunks(chunks: impl Stream<Item = ApiResult<Vec<u8>>> + Send + 'static) -> Self {
+        Self {
+            chunks: chunks.boxed(),
+            decoder: LineDecoder::default(),
+            fail_fast: false,
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Stop the stream at the first malformed line
+    pub fn fail_fast(self) -> Self {
+        Self {
+            fail_fast: true,
+            ..self
+        }
+    }
+}
+
+impl<T> Stream for NdjsonStream<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = ApiResult<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(line) = this.decoder.next_line() {
+                let item = serde_json::from_slice(&line).map_err(ApiError::DecodeJson);
+                if item.is_err() && this.fail_fast {
+                    this.decoder.clear();
+                    this.done = true;
+                }
+                return Poll::Ready(Some(item));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+            match ready!(this.chunks.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => this.decoder.feed(&chunk),
+                Some(Err(e)) => {
+                    this.decoder.clear();
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                None => {
+                    this.decoder.finish();
+                    this.done = true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, stream, StreamExt};
+    use serde::Deserialize;
+
+    use super::{LineDecoder, NdjsonStream};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        id: u32,
+    }
+
+    fn chunks(
+        input: &[&str],
+    ) -> impl futures::Stream<Item = crate::ApiResult<Vec<u8>>> + Send + 'static {
+        let chunks: Vec<_> = input.iter().map(|c| Ok(c.as_bytes().to_vec())).collect();
+        stream::iter(chunks)
+    }
+
+    #[test]
+    fn test_line_decoder() {
+        let mut decoder = LineDecoder::default();
+        decoder.feed(b"{\"id\":1}\r\n{\"id\"");
+        assert_eq!(Some(b"{\"id\":1}".to_vec()), decoder.next_line());
+        assert_eq!(None, decoder.next_line());
+        decoder.feed(b":2}\n\n");
+        assert_eq!(Some(b"{\"id\":2}".to_vec()), decoder.next_line());
+        decoder.feed(b"{\"id\":3}");
+        decoder.finish();
+        assert_eq!(Some(b"{\"id\":3}".to_vec()), decoder.next_line());
+        assert_eq!(None, decoder.next_line());
+    }
+
+    #[test]
+    fn test_malformed_line() {
+        let stream = NdjsonStream::<Item>::from_chunks(chunks(&["{\"id\":1}\nbad\n{\"id\":2}\n"]));
+        let items: Vec<_> = block_on(stream.collect());
+        assert_eq!(3, items.len());
+        assert!(items[1].is_err());
+        assert_eq!(&Item { id: 2 }, items[2].as_ref().unwrap());
+    }
+
+    #[test]
+    fn test_malformed_line_fail_fast() {
+        let stream =
+            NdjsonStream::<Item>::from_chunks(chunks(&["{\"id\":1}\nbad\n", "{\"id\":2}\n"]))
+                .fail_fast();
+        let items: Vec<_> = block_on(stream.collect());
+        assert_eq!(2, items.len());
+        assert!(items[1].is_err());
+    }
+}
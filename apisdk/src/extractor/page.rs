@@ -0,0 +1,183 @@
+use std::marker::PhantomData;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+/// This trait is used to declare the field names of paginated payload
+///
+/// # Examples
+///
+/// ```
+/// pub struct MyFields;
+///
+/// impl PageFields for MyFields {
+///     const ITEMS: &'static str = "list";
+///     const TOTAL: &'static str = "count";
+/// }
+///
+/// let res: Paginated<User, MyFields> = send!(req, CodeDataMessage).await?;
+/// ```
+pub trait PageFields {
+    /// The field of items
+    const ITEMS: &'static str = "items";
+    /// The field of total count
+    const TOTAL: &'static str = "total";
+    /// The field of current page
+    const PAGE: &'static str = "page";
+    /// The field of page size
+    const PAGE_SIZE: &'static str = "page_size";
+    /// The field of has_more flag
+    const HAS_MORE: &'static str = "has_more";
+}
+
+/// The default field names: `items`, `total`, `page`, `page_size` and `has_more`
+#[derive(Debug)]
+pub struct DefaultPageFields;
+
+impl PageFields for DefaultPageFields {}
+
+/// This struct is used to parse paginated payload, such as `{items, total, page}`.
+///
+/// It's usually used as result type, together with other extractors.
+///
+/// # Examples
+///
+/// ```
+/// async fn list_users(&self) -> ApiResult<Paginated<User>> {
+///     let req = client.get("/api/users").await?;
+///     send!(req, CodeDataMessage).await
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Paginated<T, F = DefaultPageFields> {
+    /// The items of current page
+    pub items: Vec<T>,
+    /// The total count of items
+    pub total: Option<u64>,
+    /// The current page
+    pub page: Option<u64>,
+    /// The page size
+    pub page_size: Option<u64>,
+    /// Whether there are more items.
+    ///
+    /// When the `has_more` field is absent, it will be calculated by `total`.
+    pub has_more: bool,
+    /// The field names
+    _fields: PhantomData<F>,
+}
+
+impl<T, F> Paginated<T, F> {
+    /// Create a new instance
+    pub fn new(items: Vec<T>, total: Option<u64>, page: Option<u64>) -> Self {
+        Self {
+            items,
+            total,
+            page,
+            page_size: None,
+            has_more: false,
+            _fields: PhantomData,
+        }
+    }
+
+    /// Check whether there are no items
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl<'de, T, F> Deserialize<'de> for Paginated<T, F>
+where
+    T: DeserializeOwned,
+    F: PageFields,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut map = Map::<String, Value>::deserialize(deserializer)?;
+        let items: Vec<T> = match map.remove(F::ITEMS) {
+            Some(Value::Null) | None => vec![],
+            Some(items) => serde_json::from_value(items).map_err(serde::de::Error::custom)?,
+        };
+        let number = |name: &str| map.get(name).and_then(as_u64);
+        let total = number(F::TOTAL);
+        let page = number(F::PAGE);
+        let page_size = number(F::PAGE_SIZE);
+
+        let has_more = match map.get(F::HAS_MORE).and_then(Value::as_bool) {
+            Some(has_more) => has_more,
+            None => match (total, page, page_size) {
+                (Some(total), Some(page), Some(size)) => page.saturating_mul(size) < total,
+                (Some(total), _, _) => (items.len() as u64) < total,
+                _ => false,
+            },
+        };
+
+        Ok(Self {
+            items,
+            total,
+            page,
+            page_size,
+            has_more,
+            _fields: PhantomData,
+        })
+    }
+}
+
+/// Read number, which may be encoded as string
+fn as_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{PageFields, Paginated};
+
+    struct CustomFields;
+
+    impl PageFields for CustomFields {
+        const ITEMS: &'static str = "list";
+        const TOTAL: &'static str = "count";
+        const PAGE: &'static str = "pageNo";
+        const PAGE_SIZE: &'static str = "pageSize";
+    }
+
+    #[test]
+    fn test_custom_fields() {
+        let page: Paginated<u32, CustomFields> = serde_json::from_value(json!({
+            "list": [1, 2],
+            "count": "5",
+            "pageNo": 1,
+            "pageSize": 2
+        }))
+        .unwrap();
+        assert_eq!(vec![1, 2], page.items);
+        assert_eq!(Some(5), page.total);
+        assert_eq!(Some(1), page.page);
+        assert!(page.has_more);
+    }
+
+    #[test]
+    fn test_has_more() {
+        let page: Paginated<u32> = serde_json::from_value(json!({
+            "items": [1, 2],
+            "total": 2
+        }))
+        .unwrap();
+        assert!(!page.has_more);
+
+        let page: Paginated<u32> = serde_json::from_value(json!({
+            "items": [],
+            "has_more": true
+        }))
+        .unwrap();
+        assert!(page.is_empty());
+        assert!(page.has_more);
+    }
+}
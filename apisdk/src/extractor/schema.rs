@@ -0,0 +1,86 @@
+use jsonschema::JSONSchema;
+use serde_json::Value;
+
+use crate::{ApiError, ApiResult, MimeType, ResponseBody, SchemaViolation};
+
+/// Validate the json response against JSON Schema, which is used by `send_with_schema_validation!`
+/// - schema: the compiled JSON Schema
+/// - body: the decoded response body
+///
+/// All violations are collected into `ApiError::SchemaValidation`.
+/// The `__headers__` field injected for `JsonExtractor::require_headers` is not validated.
+pub fn validate_schema(schema: &JSONSchema, body: &ResponseBody) -> ApiResult<()> {
+    let json = match body {
+        ResponseBody::Json(json) => json,
+        _ => {
+            return Err(ApiError::IncompatibleContentType(
+                MimeType::Json,
+                body.mime_type(),
+            ))
+        }
+    };
+
+    let stripped;
+    let json = match json {
+        Value::Object(map) if map.contains_key("__headers__") => {
+            let mut map = map.clone();
+            map.remove("__headers__");
+            stripped = Value::Object(map);
+            &stripped
+        }
+        _ => json,
+    };
+
+    let violations: Vec<SchemaViolation> = match schema.validate(json) {
+        Ok(()) => return Ok(()),
+        Err(errors) => errors
+            .map(|e| SchemaViolation {
+                pointer: e.instance_path.to_string(),
+                message: e.to_string(),
+            })
+            .collect(),
+    };
+    Err(ApiError::SchemaValidation(violations))
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonschema::JSONSchema;
+    use serde_json::json;
+
+    use super::validate_schema;
+    use crate::{ApiError, ResponseBody};
+
+    #[test]
+    fn test_validate_schema() {
+        let schema = JSONSchema::compile(&json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["id"],
+            "additionalProperties": false
+        }))
+        .unwrap();
+
+        let body = ResponseBody::Json(json!({ "id": 1, "__headers__": {} }));
+        assert!(validate_schema(&schema, &body).is_ok());
+
+        let body = ResponseBody::Json(json!({ "id": "1", "tags": [1] }));
+        match validate_schema(&schema, &body) {
+            Err(ApiError::SchemaValidation(violations)) => {
+                let pointers: Vec<_> = violations.iter().map(|v| v.pointer.as_str()).collect();
+                assert!(pointers.contains(&"/id"));
+                assert!(pointers.contains(&"/tags/0"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let body = ResponseBody::Text("id=1".to_string());
+        assert!(matches!(
+            validate_schema(&schema, &body),
+            Err(ApiError::IncompatibleContentType(..))
+        ));
+    }
+}
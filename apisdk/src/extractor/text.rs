@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use crate::{ApiError, ApiResult, ResponseBody};
+use crate::{ApiError, ApiResult, MimeType, ResponseBody};
 
 /// This struct is used to parse response body to text
 #[derive(Debug)]
@@ -16,6 +16,13 @@ impl Text {
             ResponseBody::Json(json) => json.to_string(),
             ResponseBody::Xml(xml) => xml,
             ResponseBody::Text(text) => text,
+            ResponseBody::Empty => String::new(),
+            ResponseBody::Multipart(_) | ResponseBody::Bytes { .. } => {
+                return Err(ApiError::IncompatibleContentType(
+                    MimeType::Text,
+                    body.mime_type(),
+                ))
+            }
         };
         T::from_str(&text).map_err(|_| ApiError::DecodeText)
     }
@@ -7,6 +7,7 @@ mod extension;
 mod extractor;
 mod result;
 mod url;
+pub mod webhook;
 
 pub use crate::core::*;
 pub use crate::executor::*;
@@ -24,15 +25,23 @@ pub use async_trait::async_trait;
 /// Re-export serde_json
 pub use serde_json;
 
+/// Re-export jsonschema, which is used by `send_with_schema_validation!`
+#[cfg(feature = "schema")]
+pub use jsonschema;
+
 // Re-export reqwest types
 pub use reqwest::dns;
 pub use reqwest::header;
 pub use reqwest::multipart;
+pub use reqwest::Certificate;
+/// Re-export from reqwest::Client, which could be shared by `with_shared_client`.
+pub use reqwest::Client as ReqwestClient;
 pub use reqwest::ClientBuilder;
 pub use reqwest::IntoUrl;
 pub use reqwest::Method;
 pub use reqwest::Request;
 pub use reqwest::Response;
+pub use reqwest::StatusCode;
 pub use reqwest::Url;
 
 // Re-export reqwest_middleware types
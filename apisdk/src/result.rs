@@ -1,7 +1,10 @@
+use std::sync::Arc;
+
+use reqwest::{Method, StatusCode};
 use serde_json::Value;
 use thiserror::Error;
 
-use crate::{MiddlewareError, MimeType};
+use crate::{GrpcCode, MiddlewareError, MimeType, TransportError};
 
 /// Api Error
 #[derive(Debug, Error)]
@@ -12,12 +15,27 @@ pub enum ApiError {
     /// Invalid URL
     #[error("Invalid URL: {0}")]
     InvalidUrl(reqwest::Error),
+    /// The absolute URL is not allowed by `AbsoluteUrlPolicy`
+    #[error("URL not allowed: {0}")]
+    UrlNotAllowed(String),
+    /// Invalid certificate, such as a malformed CA bundle
+    #[error("Invalid certificate: {0}")]
+    InvalidCertificate(String),
+    /// Invalid or missing environment variable, see `with_base_headers_from_env`
+    #[error("Invalid header from environment: {0}")]
+    EnvHeader(String),
+    /// The HTTP method is not allowed by the router, such as `ApiEndpoint::with_allowed_methods`
+    #[error("Method not allowed by router: {0}")]
+    MethodNotAllowedByRouter(Method),
     /// Build request error
     #[error("Build request error: {0}")]
     BuildRequest(reqwest::Error),
     /// Generic reqwest error
     #[error("Generic reqwest error: {0}")]
     Reqwest(reqwest::Error),
+    /// Connection error, simulated by `MockServer::fail_with`
+    #[error("Connection error: {0}")]
+    Connection(TransportError),
     /// Middleware error
     #[error("Middleware error: {0}")]
     Middleware(anyhow::Error),
@@ -30,9 +48,16 @@ pub enum ApiError {
     /// HTTP Server status error
     #[error("HTTP Server status error: [{0}] {1}")]
     HttpServerStatus(u16, String),
+    /// The precondition, such as `If-Match`, is not satisfied (`412 Precondition Failed`)
+    /// - 0: the current ETag of resource, if returned by server
+    #[error("Precondition failed: current ETag {0:?}")]
+    PreconditionFailed(Option<String>),
     /// Unsupported Content-Type
     #[error("Unsupported Content-Type: {0}")]
     UnsupportedContentType(MimeType),
+    /// Unsupported Content-Encoding
+    #[error("Unsupported Content-Encoding: {0}")]
+    UnsupportedContentEncoding(String),
     /// Incompatible Content-Type
     #[error("Incompatible Content-Type: perfer {0}, actual {1}")]
     IncompatibleContentType(MimeType, MimeType),
@@ -41,6 +66,9 @@ pub enum ApiError {
     /// - 1: message
     #[error("Decode response error: {0} => {1}")]
     DecodeResponse(MimeType, String),
+    /// Encode json error, when serializing request payload
+    #[error("Encode json error: {0}")]
+    EncodeJson(serde_json::Error),
     /// Decode json error
     #[error("Decode json error: {0}")]
     DecodeJson(#[from] serde_json::Error),
@@ -50,56 +78,181 @@ pub enum ApiError {
     /// Decode text error
     #[error("Decode text error")]
     DecodeText,
+    /// Decode form-urlencoded error, see `Form`
+    #[error("Decode form error: {0}")]
+    DecodeForm(#[from] serde_urlencoded::de::Error),
+    /// The link could not be found or resolved, see `follow_link`
+    #[error("Invalid link: {0}")]
+    InvalidLink(String),
+    /// Decode query string error, see `query_into`
+    #[error("Decode query error: {0}")]
+    DecodeQuery(String),
+    /// Resumable upload error, such as missing `Upload-Offset` or failing to read the source, see `tus_upload`
+    #[error("Upload error: {0}")]
+    Upload(String),
+    /// The json response violates JSON Schema, see `send_with_schema_validation!`
+    #[error("Schema validation error: {}", .0.iter().map(|v| v.to_string()).collect::<Vec<_>>().join("; "))]
+    SchemaValidation(Vec<SchemaViolation>),
     /// Illegal json
     #[error("Illegal json: {0}")]
     IllegalJson(Value),
     /// Service error
     #[error("Service error: {0} - {1:?}")]
     ServiceError(i64, Option<String>),
+    /// gRPC status error, parsed from `google.rpc.Status` body
+    #[error("gRPC status error: [{code}] {message}")]
+    Grpc {
+        /// The status code
+        code: GrpcCode,
+        /// The error message
+        message: String,
+        /// The error details
+        details: Vec<Value>,
+    },
+    /// Request is cancelled by `CancellationToken`
+    #[error("Request is cancelled")]
+    Cancelled,
+    /// Request is timed out, such as by `with_default_timeout` or `req.timeout()`
+    #[error("Request is timed out")]
+    Timeout,
+    /// Request is rejected by `CircuitBreakerMiddleware`, since the circuit is open
+    #[error("Circuit is open")]
+    CircuitOpen,
+    /// The error of coalesced request, which is shared by all callers, see `with_single_flight`
+    #[error("{0}")]
+    Shared(Arc<ApiError>),
     /// Other error
     #[error("Other error: {0}")]
     Other(String),
 }
 
+/// This struct represents a violation of JSON Schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// The JSON Pointer to the invalid value, such as `/items/0/id`, which is empty for the root
+    pub pointer: String,
+    /// The error message
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.pointer, self.message)
+        }
+    }
+}
+
 impl ApiError {
     /// Build ApiError by using `code` and `message`
     pub fn new(code: i64, message: impl ToString) -> Self {
         Self::ServiceError(code, Some(message.to_string()))
     }
 
+    /// Build ApiError by using HTTP status code
+    /// - status: the status of client error or server error
+    pub(crate) fn from_status(status: StatusCode) -> Self {
+        if status == StatusCode::PRECONDITION_FAILED {
+            Self::PreconditionFailed(None)
+        } else if status.is_client_error() {
+            Self::HttpClientStatus(status.as_u16(), status.to_string())
+        } else {
+            Self::HttpServerStatus(status.as_u16(), status.to_string())
+        }
+    }
+
     /// Try to retrieve `error_code`
     pub fn as_error_code(&self) -> i32 {
         match self {
             Self::ServiceDiscovery(..)
             | Self::InvalidUrl(..)
+            | Self::UrlNotAllowed(..)
+            | Self::InvalidCertificate(..)
+            | Self::EnvHeader(..)
             | Self::BuildRequest(..)
             | Self::Reqwest(..)
+            | Self::Connection(..)
             | Self::Middleware(..)
-            | Self::MultipartForm => 400,
+            | Self::MultipartForm
+            | Self::EncodeJson(..) => 400,
             Self::HttpClientStatus(c, _) => *c as i32,
             Self::HttpServerStatus(c, _) => *c as i32,
+            Self::PreconditionFailed(..) => 412,
+            Self::MethodNotAllowedByRouter(..) => 405,
             Self::UnsupportedContentType(..)
+            | Self::UnsupportedContentEncoding(..)
             | Self::IncompatibleContentType(..)
             | Self::DecodeResponse(..)
             | Self::DecodeJson(..)
             | Self::DecodeXml(..)
             | Self::DecodeText
+            | Self::DecodeForm(..)
+            | Self::DecodeQuery(..)
+            | Self::InvalidLink(..)
+            | Self::Upload(..)
+            | Self::SchemaValidation(..)
             | Self::IllegalJson(..) => 500,
             Self::ServiceError(c, _) => *c as i32,
+            Self::Grpc { code, .. } => code.http_status() as i32,
+            Self::Cancelled => 499,
+            Self::Timeout => 408,
+            Self::CircuitOpen => 503,
+            Self::Shared(e) => e.as_error_code(),
             Self::Other(..) => 500,
         }
     }
+
+    /// Get HTTP status code, if the error is caused by HTTP status
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::HttpClientStatus(c, _) | Self::HttpServerStatus(c, _) => Some(*c),
+            Self::Reqwest(e) => e.status().map(|s| s.as_u16()),
+            Self::PreconditionFailed(..) => Some(412),
+            Self::Grpc { code, .. } => Some(code.http_status()),
+            Self::Shared(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// Check whether the error is caused by timeout
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            Self::Reqwest(e) => e.is_timeout(),
+            Self::Timeout => true,
+            Self::Shared(e) => e.is_timeout(),
+            _ => matches!(self.status(), Some(408 | 504)),
+        }
+    }
+
+    /// Check whether the request could be retried
+    ///
+    /// - connection errors and timeouts
+    /// - `408 Request Timeout` and `429 Too Many Requests`
+    /// - server errors, except `501 Not Implemented`
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Reqwest(e) if e.is_connect() || e.is_timeout() => return true,
+            Self::Connection(..) | Self::Timeout => return true,
+            Self::Shared(e) => return e.is_retryable(),
+            _ => {}
+        }
+        match self.status() {
+            Some(408 | 429) => true,
+            Some(501) => false,
+            Some(c) => (500..600).contains(&c),
+            None => false,
+        }
+    }
 }
 
 impl From<reqwest::Error> for ApiError {
     fn from(e: reqwest::Error) -> Self {
-        if e.is_status() {
-            let status = e.status().unwrap_or_default();
-            if status.is_client_error() {
-                ApiError::HttpClientStatus(status.as_u16(), status.to_string())
-            } else {
-                ApiError::HttpServerStatus(status.as_u16(), status.to_string())
-            }
+        if e.is_timeout() {
+            ApiError::Timeout
+        } else if e.is_status() {
+            ApiError::from_status(e.status().unwrap_or_default())
         } else {
             ApiError::Reqwest(e)
         }
@@ -109,11 +262,227 @@ impl From<reqwest::Error> for ApiError {
 impl From<MiddlewareError> for ApiError {
     fn from(e: MiddlewareError) -> Self {
         match e {
-            MiddlewareError::Reqwest(e) => Self::Reqwest(e),
-            MiddlewareError::Middleware(e) => Self::Middleware(e),
+            MiddlewareError::Reqwest(e) => Self::from(e),
+            // Restore the ApiError raised by middlewares
+            MiddlewareError::Middleware(e) => match e.downcast::<ApiError>() {
+                Ok(e) => e,
+                Err(e) => Self::Middleware(e),
+            },
+        }
+    }
+}
+
+impl From<ApiError> for MiddlewareError {
+    fn from(e: ApiError) -> Self {
+        match e {
+            ApiError::Reqwest(e) => Self::Reqwest(e),
+            ApiError::Middleware(e) => Self::Middleware(e),
+            e => Self::Middleware(e.into()),
         }
     }
 }
 
 /// An alias of Result<T, ApiError
 pub type ApiResult<T> = Result<T, ApiError>;
+
+/// This trait provides combinators for post-processing `ApiResult`
+///
+/// # Examples
+///
+/// ```
+/// let users = api.list_users().await
+///     .log_on_error("users")
+///     .map_body(|res| res.users)
+///     .with_fallback(vec![]);
+/// ```
+pub trait ApiResultExt<T> {
+    /// Inspect the error, and keep the result unchanged
+    /// - f: the function to call with the error
+    fn on_error<F>(self, f: F) -> ApiResult<T>
+    where
+        F: FnOnce(&ApiError);
+
+    /// Map the success body, and keep the error unchanged
+    /// - f: the function to convert the body
+    fn map_body<U, F>(self, f: F) -> ApiResult<U>
+    where
+        F: FnOnce(T) -> U;
+
+    /// Get the success body, or the fallback value on error
+    /// - default: the fallback value
+    fn with_fallback(self, default: T) -> T;
+
+    /// Log the error as warn level, and keep the result unchanged
+    /// - target: the target of log
+    fn log_on_error(self, target: &str) -> ApiResult<T>;
+}
+
+impl<T> ApiResultExt<T> for ApiResult<T> {
+    fn on_error<F>(self, f: F) -> ApiResult<T>
+    where
+        F: FnOnce(&ApiError),
+    {
+        if let Err(e) = &self {
+            f(e);
+        }
+        self
+    }
+
+    fn map_body<U, F>(self, f: F) -> ApiResult<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        self.map(f)
+    }
+
+    fn with_fallback(self, default: T) -> T {
+        self.unwrap_or(default)
+    }
+
+    fn log_on_error(self, target: &str) -> ApiResult<T> {
+        self.on_error(|e| log::warn!(target: target, "{}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::{ApiError, ApiResult, ApiResultExt};
+    use crate::{GrpcCode, TransportError};
+
+    #[test]
+    fn test_status() {
+        assert_eq!(
+            Some(404),
+            ApiError::HttpClientStatus(404, "".to_string()).status()
+        );
+        assert_eq!(
+            Some(503),
+            ApiError::HttpServerStatus(503, "".to_string()).status()
+        );
+        assert_eq!(Some(412), ApiError::PreconditionFailed(None).status());
+        assert_eq!(None, ApiError::DecodeText.status());
+        assert_eq!(None, ApiError::Cancelled.status());
+    }
+
+    #[test]
+    fn test_is_timeout() {
+        assert!(ApiError::HttpClientStatus(408, "".to_string()).is_timeout());
+        assert!(ApiError::HttpServerStatus(504, "".to_string()).is_timeout());
+        assert!(!ApiError::HttpServerStatus(500, "".to_string()).is_timeout());
+        assert!(!ApiError::Other("".to_string()).is_timeout());
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(ApiError::HttpClientStatus(429, "".to_string()).is_retryable());
+        assert!(!ApiError::HttpClientStatus(400, "".to_string()).is_retryable());
+        assert!(ApiError::HttpServerStatus(503, "".to_string()).is_retryable());
+        assert!(!ApiError::HttpServerStatus(501, "".to_string()).is_retryable());
+        assert!(!ApiError::ServiceError(-1, None).is_retryable());
+        assert!(!ApiError::Cancelled.is_retryable());
+        assert!(ApiError::Connection(TransportError::ConnectionReset).is_retryable());
+    }
+
+    #[test]
+    fn test_shared() {
+        let shared = ApiError::Shared(Arc::new(ApiError::Timeout));
+        assert_eq!(408, shared.as_error_code());
+        assert!(shared.is_timeout());
+        assert!(shared.is_retryable());
+        assert_eq!("Request is timed out", shared.to_string());
+
+        let shared = ApiError::Shared(Arc::new(ApiError::HttpServerStatus(503, "".to_string())));
+        assert_eq!(Some(503), shared.status());
+    }
+
+    #[test]
+    fn test_grpc() {
+        let grpc = |code| ApiError::Grpc {
+            code,
+            message: "".to_string(),
+            details: vec![],
+        };
+        assert_eq!(Some(404), grpc(GrpcCode::NotFound).status());
+        assert!(grpc(GrpcCode::DeadlineExceeded).is_timeout());
+        assert!(grpc(GrpcCode::Unavailable).is_retryable());
+        assert!(grpc(GrpcCode::ResourceExhausted).is_retryable());
+        assert!(!grpc(GrpcCode::Unimplemented).is_retryable());
+        assert!(!grpc(GrpcCode::InvalidArgument).is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_connection_error() {
+        // Nothing should listen on port 1
+        let e = reqwest::Client::new()
+            .get("http://127.0.0.1:1/")
+            .send()
+            .await
+            .unwrap_err();
+        let e = ApiError::from(e);
+        assert!(matches!(e, ApiError::Reqwest(_)));
+        assert_eq!(None, e.status());
+        assert!(!e.is_timeout());
+        assert!(e.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_error() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let e = reqwest::Client::new()
+            .get(format!("http://{}/", addr))
+            .timeout(Duration::from_millis(50))
+            .send()
+            .await
+            .unwrap_err();
+        drop(listener);
+        let e = ApiError::from(e);
+        assert!(matches!(e, ApiError::Timeout));
+        assert!(e.is_timeout());
+        assert!(e.is_retryable());
+    }
+
+    fn ok() -> ApiResult<i32> {
+        Ok(1)
+    }
+
+    fn err() -> ApiResult<i32> {
+        Err(ApiError::HttpServerStatus(503, "unavailable".to_string()))
+    }
+
+    #[test]
+    fn test_on_error() {
+        let mut called = 0;
+        assert_eq!(1, ok().on_error(|_| called += 1).unwrap());
+        assert_eq!(0, called);
+
+        let mut status = None;
+        let res = err().on_error(|e| status = e.status());
+        assert!(matches!(res, Err(ApiError::HttpServerStatus(503, _))));
+        assert_eq!(Some(503), status);
+    }
+
+    #[test]
+    fn test_map_body() {
+        assert_eq!("1", ok().map_body(|v| v.to_string()).unwrap());
+
+        let res = err().map_body(|v| v.to_string());
+        assert!(matches!(res, Err(ApiError::HttpServerStatus(503, _))));
+    }
+
+    #[test]
+    fn test_with_fallback() {
+        assert_eq!(1, ok().with_fallback(0));
+        assert_eq!(0, err().with_fallback(0));
+    }
+
+    #[test]
+    fn test_log_on_error() {
+        assert_eq!(1, ok().log_on_error("apisdk").unwrap());
+
+        let res = err().log_on_error("apisdk");
+        assert!(matches!(res, Err(ApiError::HttpServerStatus(503, _))));
+    }
+}
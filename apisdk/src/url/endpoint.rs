@@ -0,0 +1,102 @@
+use std::net::SocketAddr;
+
+use async_trait::async_trait;
+use reqwest::Method;
+
+use crate::{DnsResolver, SocketAddrs};
+
+/// This struct is used to connect to a fixed address, while presenting specific TLS SNI and `Host` header.
+///
+/// It's useful for IP-based routing, such as connecting to a node of cluster directly.
+///
+/// # Examples
+///
+/// ```
+/// let endpoint = ApiEndpoint::new(([10, 0, 0, 1], 443))
+///     .with_sni("api.internal")
+///     .with_host_header("api.example.com");
+/// let client = XxxApi::builder().with_resolver(endpoint).build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApiEndpoint {
+    /// The address to connect
+    addr: SocketAddr,
+    /// The TLS SNI, which replaces the hostname of url
+    sni: Option<String>,
+    /// The value of `Host` header
+    host_header: Option<String>,
+    /// The allowed HTTP methods, all methods are allowed if `None`
+    allowed_methods: Option<Vec<Method>>,
+}
+
+impl ApiEndpoint {
+    /// Create a new instance
+    /// - addr: the address to connect
+    pub fn new(addr: impl Into<SocketAddr>) -> Self {
+        Self {
+            addr: addr.into(),
+            sni: None,
+            host_header: None,
+            allowed_methods: None,
+        }
+    }
+
+    /// Set the TLS SNI, which is the hostname of url by default
+    /// - sni: the server name
+    ///
+    /// The `Host` header will follow it, unless `with_host_header` is used.
+    pub fn with_sni(self, sni: impl ToString) -> Self {
+        Self {
+            sni: Some(sni.to_string()),
+            ..self
+        }
+    }
+
+    /// Set the `Host` header, which is the hostname of url by default
+    /// - host_header: the value of `Host` header
+    pub fn with_host_header(self, host_header: impl ToString) -> Self {
+        Self {
+            host_header: Some(host_header.to_string()),
+            ..self
+        }
+    }
+
+    /// Set the allowed HTTP methods, such as `GET` only for a read replica
+    /// - methods: the allowed methods
+    ///
+    /// The request with other methods fails early with `ApiError::MethodNotAllowedByRouter`.
+    pub fn with_allowed_methods(self, methods: impl IntoIterator<Item = Method>) -> Self {
+        Self {
+            allowed_methods: Some(methods.into_iter().collect()),
+            ..self
+        }
+    }
+
+    /// Get the address to connect
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+#[async_trait]
+impl DnsResolver for ApiEndpoint {
+    fn get_port(&self) -> Option<u16> {
+        Some(self.addr.port())
+    }
+
+    fn get_sni(&self) -> Option<&str> {
+        self.sni.as_deref()
+    }
+
+    fn get_host_header(&self) -> Option<&str> {
+        self.host_header.as_deref()
+    }
+
+    fn get_allowed_methods(&self) -> Option<&[Method]> {
+        self.allowed_methods.as_deref()
+    }
+
+    async fn resolve(&self, _name: &str) -> Option<SocketAddrs> {
+        Some(SocketAddrs::from(self.addr))
+    }
+}
@@ -0,0 +1,201 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::HashAlgorithm;
+
+/// Hash the input to a position on the ring
+fn position(algorithm: HashAlgorithm, input: impl AsRef<[u8]>) -> u64 {
+    let digest = algorithm.apply(input);
+    u64::from_str_radix(&digest[..16], 16).unwrap_or_default()
+}
+
+/// This struct implements consistent hashing with virtual nodes.
+///
+/// It can be used to build sticky routing, e.g. pick the same endpoint for the same key.
+/// When a node is added or removed, only the keys owned by that node will be remapped.
+///
+/// # Example
+///
+/// ```
+/// use apisdk::ConsistentHashRing;
+///
+/// let mut ring = ConsistentHashRing::new(100);
+/// ring.add("10.0.0.1");
+/// ring.add("10.0.0.2");
+/// let node = ring.get("user-1").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConsistentHashRing<T> {
+    /// The number of virtual nodes for each node
+    replicas: usize,
+    /// The hash algorithm
+    algorithm: HashAlgorithm,
+    /// The nodes, in order of addition
+    nodes: Vec<T>,
+    /// The ring, from hash to node
+    ring: BTreeMap<u64, T>,
+}
+
+impl<T> Default for ConsistentHashRing<T>
+where
+    T: Display + Clone + PartialEq,
+{
+    fn default() -> Self {
+        Self::new(160)
+    }
+}
+
+impl<T> ConsistentHashRing<T>
+where
+    T: Display + Clone + PartialEq,
+{
+    /// Create an empty ring, which uses md5 as hash
+    /// - replicas: the number of virtual nodes for each node
+    pub fn new(replicas: usize) -> Self {
+        Self::with_algorithm(replicas, HashAlgorithm::Md5)
+    }
+
+    /// Create an empty ring
+    /// - replicas: the number of virtual nodes for each node
+    /// - algorithm: the hash algorithm
+    pub fn with_algorithm(replicas: usize, algorithm: HashAlgorithm) -> Self {
+        Self {
+            replicas: replicas.max(1),
+            algorithm,
+            nodes: vec![],
+            ring: BTreeMap::new(),
+        }
+    }
+
+    /// Add a node, return false if it already exists
+    /// - node: the node to add
+    pub fn add(&mut self, node: T) -> bool {
+        if self.nodes.contains(&node) {
+            return false;
+        }
+        for i in 0..self.replicas {
+            let hash = position(self.algorithm, format!("{}#{}", node, i));
+            self.ring.entry(hash).or_insert_with(|| node.clone());
+        }
+        self.nodes.push(node);
+        true
+    }
+
+    /// Remove a node, return false if it doesn't exist
+    /// - node: the node to remove
+    pub fn remove(&mut self, node: &T) -> bool {
+        let Some(index) = self.nodes.iter().position(|n| n == node) else {
+            return false;
+        };
+        self.nodes.remove(index);
+        self.ring.retain(|_, n| n != node);
+
+        // Fill the positions which were occupied by the removed node on collision
+        for n in self.nodes.iter() {
+            for i in 0..self.replicas {
+                let hash = position(self.algorithm, format!("{}#{}", n, i));
+                self.ring.entry(hash).or_insert_with(|| n.clone());
+            }
+        }
+        true
+    }
+
+    /// Get the node which owns the key
+    /// - key: the key to locate
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Option<&T> {
+        let hash = position(self.algorithm, key);
+        self.ring
+            .range(hash..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map(|(_, node)| node)
+    }
+
+    /// Get all nodes, in order of addition
+    pub fn nodes(&self) -> &[T] {
+        &self.nodes
+    }
+
+    /// Get the number of nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check whether the ring is empty
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConsistentHashRing, HashAlgorithm};
+
+    fn keys() -> Vec<String> {
+        (0..1000).map(|i| format!("key-{}", i)).collect()
+    }
+
+    #[test]
+    fn test_empty() {
+        let ring = ConsistentHashRing::<String>::new(10);
+        assert!(ring.is_empty());
+        assert_eq!(None, ring.get("key"));
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut ring = ConsistentHashRing::new(10);
+        assert!(ring.add("a"));
+        assert!(!ring.add("a"));
+        assert!(ring.add("b"));
+        assert_eq!(2, ring.len());
+        assert!(ring.remove(&"a"));
+        assert!(!ring.remove(&"a"));
+        assert_eq!(&["b"], ring.nodes());
+        assert_eq!(Some(&"b"), ring.get("key"));
+    }
+
+    #[test]
+    fn test_stable_on_add() {
+        for algorithm in [
+            HashAlgorithm::Md5,
+            HashAlgorithm::Sha1,
+            HashAlgorithm::Sha256,
+        ] {
+            let mut ring = ConsistentHashRing::with_algorithm(100, algorithm);
+            ring.add("node-1");
+            ring.add("node-2");
+            ring.add("node-3");
+            let before: Vec<_> = keys().iter().map(|k| *ring.get(k).unwrap()).collect();
+
+            ring.add("node-4");
+            let after: Vec<_> = keys().iter().map(|k| *ring.get(k).unwrap()).collect();
+
+            let mut moved = 0;
+            for (b, a) in before.iter().zip(after.iter()) {
+                if b != a {
+                    // Keys can only move to the new node
+                    assert_eq!(&"node-4", a);
+                    moved += 1;
+                }
+            }
+            // Roughly 1/4 of keys should be moved
+            assert!(moved > 100 && moved < 400, "moved = {}", moved);
+        }
+    }
+
+    #[test]
+    fn test_stable_on_remove() {
+        let mut ring = ConsistentHashRing::new(100);
+        ring.add("node-1");
+        ring.add("node-2");
+        ring.add("node-3");
+        let before: Vec<_> = keys().iter().map(|k| *ring.get(k).unwrap()).collect();
+
+        ring.remove(&"node-2");
+        for (key, b) in keys().iter().zip(before.iter()) {
+            if b != &"node-2" {
+                assert_eq!(b, ring.get(key).unwrap());
+            }
+        }
+    }
+}
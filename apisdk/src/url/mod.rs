@@ -1,8 +1,14 @@
 use url::Url;
 
+mod endpoint;
+mod hash;
+mod query;
 mod resolver;
 mod rewriter;
 
+pub use endpoint::*;
+pub use hash::*;
+pub use query::*;
 pub use resolver::*;
 pub use rewriter::*;
 
@@ -16,19 +22,163 @@ pub use hickory::*;
 pub trait UrlOps {
     /// Merge path
     fn merge_path(self, path: &str) -> Self;
+    /// Merge path verbatim, with exactly one `/` between the url and path
+    fn merge_path_strict(self, path: &str) -> Self;
 }
 
 impl UrlOps for Url {
     /// Merge the url and path
-    /// - path: relative path
-    fn merge_path(mut self, path: &str) -> Self {
-        let base_path = self.path();
-        let new_path = match (base_path.ends_with('/'), path.starts_with('/')) {
-            (true, true) => format!("{}{}", base_path, &path[1..]),
-            (true, false) | (false, true) => format!("{}{}", base_path, path),
-            (false, false) => format!("{}/{}", base_path, path),
+    /// - path: relative path, which may carry query string
+    ///
+    /// The path will be percent-encoded, and the already-encoded chars will be kept as is.
+    fn merge_path(self, path: &str) -> Self {
+        merge_with(self, path, |base_path, path| {
+            match (base_path.ends_with('/'), path.starts_with('/')) {
+                (true, true) => format!("{}{}", base_path, &path[1..]),
+                (true, false) | (false, true) => format!("{}{}", base_path, path),
+                (false, false) => format!("{}/{}", base_path, path),
+            }
+        })
+    }
+
+    /// Merge the url and path verbatim
+    /// - path: relative path, which may carry query string
+    ///
+    /// All the trailing `/` of url and leading `/` of path are collapsed into exactly one `/`,
+    /// the trailing `/` of path is kept, and the empty path leaves the url as is.
+    fn merge_path_strict(self, path: &str) -> Self {
+        merge_with(self, path, |base_path, path| {
+            if path.is_empty() {
+                base_path.to_string()
+            } else {
+                format!(
+                    "{}/{}",
+                    base_path.trim_end_matches('/'),
+                    path.trim_start_matches('/')
+                )
+            }
+        })
+    }
+}
+
+/// Merge the url and path, and append the query string of path
+/// - url: the base url
+/// - path: relative path, which may carry query string
+/// - join: join the path of url and the encoded path
+fn merge_with(mut url: Url, path: &str, join: impl FnOnce(&str, &str) -> String) -> Url {
+    let (path, query) = match path.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (path, None),
+    };
+    let new_path = join(url.path(), &encode_path(path));
+    url.set_path(&new_path);
+    if let Some(query) = query.filter(|q| !q.is_empty()) {
+        let new_query = match url.query() {
+            Some(base_query) if !base_query.is_empty() => format!("{}&{}", base_query, query),
+            _ => query.to_string(),
         };
-        self.set_path(&new_path);
-        self
+        url.set_query(Some(&new_query));
+    }
+    url
+}
+
+/// Percent-encode the path, but keep `/` and valid `%XX` sequences
+/// - path: the path to encode
+fn encode_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut encoded = String::with_capacity(path.len());
+    for (i, b) in bytes.iter().copied().enumerate() {
+        let is_escaped = b == b'%'
+            && bytes.len() > i + 2
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit();
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => encoded.push(b as char),
+            b'-' | b'.' | b'_' | b'~' | b'/' | b':' | b'@' => encoded.push(b as char),
+            b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=' => {
+                encoded.push(b as char)
+            }
+            b'%' if is_escaped => encoded.push('%'),
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::UrlOps;
+
+    fn merge(base: &str, path: &str) -> String {
+        Url::parse(base).unwrap().merge_path(path).to_string()
+    }
+
+    #[test]
+    fn test_merge_path() {
+        assert_eq!("http://host/v1/users", merge("http://host/v1", "/users"));
+        assert_eq!("http://host/v1/users", merge("http://host/v1/", "users"));
+        assert_eq!("http://host/v1/users", merge("http://host/v1/", "/users"));
+    }
+
+    #[test]
+    fn test_merge_path_with_space() {
+        assert_eq!(
+            "http://host/v1/users/John%20Doe/tags%23",
+            merge("http://host/v1", "/users/John Doe/tags#")
+        );
+    }
+
+    #[test]
+    fn test_merge_path_pre_encoded() {
+        assert_eq!(
+            "http://host/v1/users/John%20Doe/100%25",
+            merge("http://host/v1", "/users/John%20Doe/100%")
+        );
+        assert_eq!(
+            "http://host/v1/%E4%B8%AD",
+            merge("http://host/v1", "/\u{4e2d}")
+        );
+    }
+
+    #[test]
+    fn test_merge_path_with_query() {
+        assert_eq!(
+            "http://host/v1/users?page=1&size=10",
+            merge("http://host/v1", "/users?page=1&size=10")
+        );
+        assert_eq!(
+            "http://host/v1/users?key=abc&page=1",
+            merge("http://host/v1?key=abc", "/users?page=1")
+        );
+        assert_eq!("http://host/v1/users", merge("http://host/v1", "/users?"));
+    }
+
+    #[test]
+    fn test_merge_path_strict() {
+        let merge = |base: &str, path: &str| Url::parse(base).unwrap().merge_path_strict(path);
+        assert_eq!(
+            "http://host/api/users",
+            merge("http://host/api/", "users").as_str()
+        );
+        assert_eq!(
+            "http://host/api/users",
+            merge("http://host/api", "/users").as_str()
+        );
+        assert_eq!(
+            "http://host/api/users",
+            merge("http://host/api//", "//users").as_str()
+        );
+        assert_eq!(
+            "http://host/api/users/",
+            merge("http://host/api", "users/").as_str()
+        );
+        assert_eq!("http://host/api", merge("http://host/api", "").as_str());
+        assert_eq!("http://host/api/", merge("http://host/api/", "").as_str());
+        assert_eq!(
+            "http://host/api/users/?page=1",
+            merge("http://host/api", "/users/?page=1").as_str()
+        );
     }
 }
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use serde::{
+    de::{
+        value::{Error, MapDeserializer, SeqDeserializer},
+        DeserializeOwned, Error as _, IntoDeserializer, Visitor,
+    },
+    forward_to_deserialize_any, Deserializer,
+};
+use url::Url;
+
+use crate::{ApiError, ApiResult};
+
+/// Parse the query string of url, such as the callback url of OAuth
+/// - url: the url to parse
+///
+/// The keys and values are percent-decoded, and `+` is decoded as space.
+/// The values of repeated key are kept in order.
+///
+/// # Examples
+///
+/// ```
+/// let url = Url::parse("https://app/callback?code=abc&scope=read&scope=write")?;
+/// let query = parse_query(&url);
+/// assert_eq!(vec!["read", "write"], query["scope"]);
+/// ```
+pub fn parse_query(url: &Url) -> HashMap<String, Vec<String>> {
+    let mut query: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, value) in url.query_pairs() {
+        query
+            .entry(key.into_owned())
+            .or_default()
+            .push(value.into_owned());
+    }
+    query
+}
+
+/// Parse the query string of url as desired type
+/// - url: the url to parse
+///
+/// The field of sequence type, such as `Vec<String>`, receives all values of repeated key,
+/// while the field of scalar type receives the last one.
+/// The numbers and booleans are parsed from text.
+///
+/// # Examples
+///
+/// ```
+/// #[derive(Deserialize)]
+/// struct Callback {
+///     code: String,
+///     state: Option<String>,
+///     scope: Vec<String>,
+/// }
+///
+/// let callback: Callback = query_into(&url)?;
+/// ```
+pub fn query_into<T>(url: &Url) -> ApiResult<T>
+where
+    T: DeserializeOwned,
+{
+    let query = parse_query(url)
+        .into_iter()
+        .map(|(key, values)| (key, QueryValue(values)));
+    T::deserialize(MapDeserializer::new(query)).map_err(|e| ApiError::DecodeQuery(e.to_string()))
+}
+
+/// The values of a query key
+struct QueryValue(Vec<String>);
+
+impl QueryValue {
+    /// Take the last value, which wins over the previous ones of repeated key
+    fn last(mut self) -> Result<String, Error> {
+        self.0
+            .pop()
+            .ok_or_else(|| Error::custom("missing query value"))
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for QueryValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Parse the last value from text
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let value = self.last()?;
+                visitor.$visit(value.parse().map_err(|_| {
+                    Error::custom(format!("invalid value: {:?}", value))
+                })?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for QueryValue {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.0.len() > 1 {
+            self.deserialize_seq(visitor)
+        } else {
+            visitor.visit_string(self.last()?)
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let values = self.0.into_iter().map(|value| QueryValue(vec![value]));
+        visitor.visit_seq(SeqDeserializer::new(values))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self.last()?.into_deserializer())
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 str string bytes byte_buf unit unit_struct newtype_struct
+        tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use url::Url;
+
+    use super::{parse_query, query_into};
+    use crate::ApiError;
+
+    #[derive(Debug, Deserialize)]
+    struct Callback {
+        code: String,
+        state: Option<String>,
+        scope: Vec<String>,
+        expires_in: u32,
+    }
+
+    #[test]
+    fn test_parse_query_repeated() {
+        let url = Url::parse("https://app/callback?scope=read&code=abc&scope=write").unwrap();
+        let query = parse_query(&url);
+        assert_eq!(vec!["read", "write"], query["scope"]);
+        assert_eq!(vec!["abc"], query["code"]);
+    }
+
+    #[test]
+    fn test_parse_query_decode() {
+        let url = Url::parse("https://app/callback?name=John%20Doe&q=a+b&k%3D=%E4%B8%AD").unwrap();
+        let query = parse_query(&url);
+        assert_eq!(vec!["John Doe"], query["name"]);
+        assert_eq!(vec!["a b"], query["q"]);
+        assert_eq!(vec!["\u{4e2d}"], query["k="]);
+    }
+
+    #[test]
+    fn test_query_into() {
+        let url =
+            Url::parse("https://app/callback?code=a%20b&scope=read&scope=write&expires_in=3600")
+                .unwrap();
+        let callback: Callback = query_into(&url).unwrap();
+        assert_eq!("a b", callback.code);
+        assert_eq!(None, callback.state);
+        assert_eq!(vec!["read", "write"], callback.scope);
+        assert_eq!(3600, callback.expires_in);
+
+        let url = Url::parse("https://app/callback?code=abc&scope=read&expires_in=x").unwrap();
+        let res: Result<Callback, _> = query_into(&url);
+        assert!(matches!(res, Err(ApiError::DecodeQuery(..))));
+    }
+}
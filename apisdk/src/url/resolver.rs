@@ -10,10 +10,13 @@ use hyper::{
     client::connect::dns::{GaiResolver, Name},
     service::Service,
 };
-use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::{
+    dns::{Addrs, Resolve, Resolving},
+    Method,
+};
 use url::Url;
 
-use crate::{ApiError, UrlRewriter};
+use crate::{ApiError, PoolCounter, UrlRewriter};
 
 pub(crate) type BoxError = Box<dyn std::error::Error + Send + Sync>;
 
@@ -61,6 +64,33 @@ impl From<SocketAddr> for SocketAddrs {
     }
 }
 
+/// This enum controls which IP version is used to connect
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpVersion {
+    /// Only connect to IPv4 addresses
+    V4,
+    /// Only connect to IPv6 addresses
+    V6,
+    /// Connect to both IPv4 and IPv6 addresses.
+    ///
+    /// When both are resolved, the connections are raced by Happy Eyeballs:
+    /// the family of first address is tried first, and the other family is tried after 300ms.
+    #[default]
+    DualStack,
+}
+
+impl IpVersion {
+    /// Check whether the address is allowed
+    /// - addr: the resolved address
+    pub fn accept(&self, addr: &SocketAddr) -> bool {
+        match self {
+            Self::V4 => addr.is_ipv4(),
+            Self::V6 => addr.is_ipv6(),
+            Self::DualStack => true,
+        }
+    }
+}
+
 /// This trait is used to performing DNS queries
 #[async_trait]
 pub trait DnsResolver: 'static + Send + Sync {
@@ -74,6 +104,24 @@ pub trait DnsResolver: 'static + Send + Sync {
         None
     }
 
+    /// Return `Some` if the hostname of url should be changed.
+    ///
+    /// The hostname is presented as TLS SNI, and is used as `Host` header unless `get_host_header` is provided.
+    /// It will be passed to `resolve`, so the connection still goes to the resolved address.
+    fn get_sni(&self) -> Option<&str> {
+        None
+    }
+
+    /// Return `Some` if `Host` header should be different from the hostname of url
+    fn get_host_header(&self) -> Option<&str> {
+        None
+    }
+
+    /// Return `Some` if only the specific HTTP methods are allowed, such as a read replica
+    fn get_allowed_methods(&self) -> Option<&[Method]> {
+        None
+    }
+
     /// Do DNS queries
     async fn resolve(&self, name: &str) -> Option<SocketAddrs>;
 }
@@ -121,6 +169,26 @@ where
 
 #[async_trait]
 impl DnsResolver for Box<dyn DnsResolver> {
+    fn get_scheme(&self) -> Option<&str> {
+        self.as_ref().get_scheme()
+    }
+
+    fn get_port(&self) -> Option<u16> {
+        self.as_ref().get_port()
+    }
+
+    fn get_sni(&self) -> Option<&str> {
+        self.as_ref().get_sni()
+    }
+
+    fn get_host_header(&self) -> Option<&str> {
+        self.as_ref().get_host_header()
+    }
+
+    fn get_allowed_methods(&self) -> Option<&[Method]> {
+        self.as_ref().get_allowed_methods()
+    }
+
     async fn resolve(&self, name: &str) -> Option<SocketAddrs> {
         self.as_ref().resolve(name).await
     }
@@ -149,6 +217,10 @@ pub(crate) struct ReqwestDnsResolver {
     /// The provided `DnsResolver`
     resolver: Arc<dyn DnsResolver>,
     fallback: FallbackResolver,
+    /// The IP version of resolved addresses
+    ip_version: IpVersion,
+    /// Count the lookups as new connections
+    counter: Option<PoolCounter>,
 }
 
 impl ReqwestDnsResolver {
@@ -160,22 +232,58 @@ impl ReqwestDnsResolver {
             type_name: type_name::<T>(),
             resolver: Arc::new(resolver),
             fallback: FallbackResolver(GaiResolver::new()),
+            ip_version: IpVersion::default(),
+            counter: None,
+        }
+    }
+
+    /// Create an instance, which only uses system DNS
+    pub fn system() -> Self {
+        Self::new(|_: &str| None)
+    }
+
+    /// Only keep the resolved addresses of IP version
+    pub fn with_ip_version(self, ip_version: IpVersion) -> Self {
+        Self { ip_version, ..self }
+    }
+
+    /// Count each lookup as a new connection
+    pub fn with_counter(self, counter: PoolCounter) -> Self {
+        Self {
+            counter: Some(counter),
+            ..self
         }
     }
 
     pub fn type_name(&self) -> &'static str {
         self.type_name
     }
+
+    /// Get the value of `Host` header, if it should be overridden
+    pub fn host_header(&self) -> Option<&str> {
+        self.resolver.get_host_header()
+    }
+
+    /// Check whether the HTTP method is allowed
+    pub fn allows(&self, method: &Method) -> bool {
+        self.resolver
+            .get_allowed_methods()
+            .is_none_or(|methods| methods.contains(method))
+    }
 }
 
 #[async_trait]
 impl UrlRewriter for ReqwestDnsResolver {
-    /// Rewrite url if scheme and/or port should be changed
+    /// Rewrite url if scheme, hostname and/or port should be changed
     async fn rewrite(&self, url: Url) -> Result<Url, ApiError> {
         let mut url = url;
         if let Some(scheme) = self.resolver.get_scheme() {
             let _ = url.set_scheme(scheme);
         }
+        if let Some(sni) = self.resolver.get_sni() {
+            url.set_host(Some(sni))
+                .map_err(|e| ApiError::Other(format!("Invalid SNI `{}`: {}", sni, e)))?;
+        }
         if let Some(port) = self.resolver.get_port() {
             let _ = url.set_port(Some(port));
         }
@@ -186,11 +294,24 @@ impl UrlRewriter for ReqwestDnsResolver {
 impl Resolve for ReqwestDnsResolver {
     fn resolve(&self, name: Name) -> Resolving {
         let me = self.clone();
+        if let Some(counter) = me.counter.as_ref() {
+            counter.connect();
+        }
         Box::pin(async move {
-            if let Some(addrs) = me.resolver.resolve(name.as_str()).await {
-                return Ok(addrs.iter);
+            let addrs = match me.resolver.resolve(name.as_str()).await {
+                Some(addrs) => addrs.iter,
+                None => me.fallback.resolve(name).await?,
+            };
+            let ip_version = me.ip_version;
+            if ip_version == IpVersion::DualStack {
+                return Ok(addrs);
+            }
+            let addrs: Vec<SocketAddr> = addrs.filter(|addr| ip_version.accept(addr)).collect();
+            if addrs.is_empty() {
+                let e = format!("No address of {:?} is resolved", ip_version);
+                return Err(BoxError::from(e));
             }
-            me.fallback.resolve(name).await
+            Ok(Box::new(addrs.into_iter()) as Addrs)
         })
     }
 }
@@ -0,0 +1,97 @@
+//! Sign and verify webhook payloads by using HMAC-SHA256.
+//!
+//! # Examples
+//!
+//! ```
+//! let signature = webhook::sign_payload(secret, &body);
+//! let req = req.header("X-Signature", format!("sha256={}", signature));
+//!
+//! // On the receiving side
+//! if !webhook::verify_payload(secret, &body, signature_header) {
+//!     return Err(ApiError::Other("Invalid signature".to_string()));
+//! }
+//! ```
+
+use hmac::Mac;
+
+use crate::digest::{hmac_sha256, new_hmac_sha256};
+
+/// The optional prefix of signature, such as `X-Hub-Signature-256: sha256=...`
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+/// Sign the payload, and encode the signature via hex
+/// - secret: the shared secret
+/// - body: the raw payload
+pub fn sign_payload(secret: impl AsRef<[u8]>, body: impl AsRef<[u8]>) -> String {
+    hmac_sha256(secret, body)
+}
+
+/// Verify the signature of payload in constant time
+/// - secret: the shared secret
+/// - body: the raw payload
+/// - signature: the signature encoded via hex, with optional `sha256=` prefix
+///
+/// Return `false` if the signature is malformed or mismatched.
+pub fn verify_payload(
+    secret: impl AsRef<[u8]>,
+    body: impl AsRef<[u8]>,
+    signature: impl AsRef<str>,
+) -> bool {
+    let signature = signature.as_ref().trim();
+    let signature = signature
+        .strip_prefix(SIGNATURE_PREFIX)
+        .unwrap_or(signature);
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    let mut mac = new_hmac_sha256(secret);
+    mac.update(body.as_ref());
+    mac.verify_slice(&signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sign_payload, verify_payload};
+
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &str = "Hello, World!";
+
+    #[test]
+    fn test_sign_payload() {
+        assert_eq!(
+            "757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17",
+            sign_payload(SECRET, BODY)
+        );
+    }
+
+    #[test]
+    fn test_verify_payload() {
+        let signature = sign_payload(SECRET, BODY);
+        assert!(verify_payload(SECRET, BODY, &signature));
+        assert!(verify_payload(
+            SECRET,
+            BODY,
+            format!("sha256={}", signature)
+        ));
+        assert!(verify_payload(SECRET, BODY, signature.to_uppercase()));
+    }
+
+    #[test]
+    fn test_verify_tampered_body() {
+        let signature = sign_payload(SECRET, BODY);
+        assert!(!verify_payload(SECRET, "Hello, World?", &signature));
+    }
+
+    #[test]
+    fn test_verify_wrong_secret() {
+        let signature = sign_payload(SECRET, BODY);
+        assert!(!verify_payload("wrong secret", BODY, &signature));
+    }
+
+    #[test]
+    fn test_verify_malformed_signature() {
+        assert!(!verify_payload(SECRET, BODY, "not hex"));
+        assert!(!verify_payload(SECRET, BODY, ""));
+        assert!(!verify_payload(SECRET, BODY, "757107ea"));
+    }
+}
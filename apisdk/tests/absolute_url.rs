@@ -0,0 +1,80 @@
+use apisdk::{send, AbsoluteUrlPolicy, ApiError, ApiResult, CodeDataMessage};
+
+use crate::common::{init_logger, start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn follow(&self, url: &str) -> ApiResult<Payload> {
+        let req = self.get(url).await?;
+        send!(req, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_absolute_url() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let url = api.build_url("https://example.com/v2/users?page=2").await?;
+    assert_eq!("https://example.com/v2/users?page=2", url.as_str());
+
+    // The base url is `http://localhost:3030/v1`
+    let res = api
+        .follow("http://127.0.0.1:3030/v1/path/json?page=2")
+        .await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!("/v1/path/json", res.path);
+    assert_eq!(Some("2"), res.query.get("page").map(|v| v.as_str()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_absolute_url_same_host() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder()
+        .with_absolute_url(AbsoluteUrlPolicy::SameHost)
+        .build();
+
+    let res = api
+        .follow("http://localhost:3030/v1/path/json?page=2")
+        .await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!("/v1/path/json", res.path);
+
+    let e = api
+        .follow("https://example.com/v1/path/json")
+        .await
+        .unwrap_err();
+    assert!(matches!(e, ApiError::UrlNotAllowed(_)));
+    let e = api
+        .follow("http://localhost:8080/v1/path/json")
+        .await
+        .unwrap_err();
+    assert!(matches!(e, ApiError::UrlNotAllowed(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_absolute_url_deny() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_absolute_url(AbsoluteUrlPolicy::Deny)
+        .build();
+
+    let e = api
+        .build_url("http://localhost:3030/v1/path/json")
+        .await
+        .unwrap_err();
+    assert!(matches!(e, ApiError::UrlNotAllowed(_)));
+
+    Ok(())
+}
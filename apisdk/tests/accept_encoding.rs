@@ -0,0 +1,88 @@
+use apisdk::{send, ApiResult, CodeDataMessage};
+#[cfg(feature = "gzip")]
+use apisdk::{ApiError, ResponseBody};
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[cfg(feature = "gzip")]
+#[derive(Debug)]
+struct RawBody(ResponseBody);
+
+#[cfg(feature = "gzip")]
+impl TryFrom<ResponseBody> for RawBody {
+    type Error = ApiError;
+
+    fn try_from(body: ResponseBody) -> Result<Self, Self::Error> {
+        Ok(Self(body))
+    }
+}
+
+impl TheApi {
+    async fn accept_encoding(&self) -> ApiResult<Option<String>> {
+        let req = self.get("/path/json").await?;
+        let data: Value = send!(req, CodeDataMessage).await?;
+        Ok(data["headers"]["accept-encoding"]
+            .as_str()
+            .map(|s| s.to_string()))
+    }
+
+    #[cfg(feature = "gzip")]
+    async fn touch_gzip(&self) -> ApiResult<RawBody> {
+        let req = self.get("/path/gzip").await?;
+        send!(req, Body).await
+    }
+}
+
+#[tokio::test]
+async fn test_accept_encoding_header() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder()
+        .with_accept_encoding(&["gzip", "br"])
+        .build();
+    assert_eq!(Some("gzip, br"), api.accept_encoding().await?.as_deref());
+
+    let api = TheApi::builder().with_accept_encoding(&[]).build();
+    assert_eq!(Some("identity"), api.accept_encoding().await?.as_deref());
+
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_accept_encoding_unadvertised() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    // The server always replies gzip, which is not advertised, but still decoded
+    let api = TheApi::builder().with_accept_encoding(&["br"]).build();
+
+    let RawBody(body) = api.touch_gzip().await?;
+    log::debug!("body = {:?}", body);
+    match body {
+        ResponseBody::Json(json) => assert_eq!(Some(true), json["data"]["compressed"].as_bool()),
+        _ => panic!("unexpected body: {:?}", body),
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_accept_encoding_advertised() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_accept_encoding(&["gzip"]).build();
+
+    let RawBody(body) = api.touch_gzip().await?;
+    log::debug!("body = {:?}", body);
+    assert!(matches!(body, ResponseBody::Json(_)));
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use apisdk::{
+    header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE},
+    send, ApiResult, CodeDataMessage, MockServer, Request, ResponseBody,
+};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn get_headers_with(&self, headers: HeaderMap) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?.headers(headers);
+        let data: Value = send!(req, CodeDataMessage).await?;
+        Ok(data["headers"].clone())
+    }
+}
+
+#[tokio::test]
+async fn test_accept_language() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_accept_language("fr-FR").build();
+
+    let headers = api.get_headers_with(HeaderMap::new()).await?;
+    assert_eq!("fr-FR", headers["accept-language"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_accept_language_override() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_accept_language("fr-FR").build();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("de-DE"));
+    let headers = api.get_headers_with(headers).await?;
+    assert_eq!("de-DE", headers["accept-language"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_accept_language_header() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().with_accept_language("fr-FR").build();
+
+    // The value set by `req.header()` replaces the default, rather than appended
+    let req = api
+        .get("/path/json")
+        .await?
+        .header(ACCEPT_LANGUAGE, "de-DE")
+        .with_extension(MockServer::new(|req: Request| {
+            let languages: Vec<_> = req
+                .headers()
+                .get_all(ACCEPT_LANGUAGE)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .collect();
+            Ok(ResponseBody::Json(json!({ "languages": languages })))
+        }));
+    let res: Value = send!(req).await?;
+    assert_eq!(json!(["de-DE"]), res["languages"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_accept_language_absent() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let headers = api.get_headers_with(HeaderMap::new()).await?;
+    assert!(headers.get("accept-language").is_none());
+
+    Ok(())
+}
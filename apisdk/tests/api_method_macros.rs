@@ -0,0 +1,176 @@
+#![cfg(not(feature = "tracing"))]
+
+use std::sync::Mutex;
+
+use apisdk::{api_method, send_raw, ApiResult, NdjsonStream, RequestId};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::{json, Value};
+
+use crate::common::{start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// This logger is used to capture all messages
+struct CaptureLogger {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.lines.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger {
+    lines: Mutex::new(vec![]),
+};
+
+/// Count the messages logged for the request
+/// - request_id: the id of request
+fn count_logs(request_id: &str) -> usize {
+    let prefix = format!("#[{}]", request_id);
+    let lines = LOGGER.lines.lock().unwrap();
+    lines
+        .iter()
+        .filter(|line| line.starts_with(&prefix))
+        .count()
+}
+
+impl TheApi {
+    async fn raw_logged(&self) -> ApiResult<()> {
+        let req = self.get("/path/json").await?;
+        let req = req.with_extension(RequestId::new("macros-logged"));
+        send_raw!(req).await?;
+        Ok(())
+    }
+
+    #[api_method(log = "off")]
+    async fn raw_off(&self) -> ApiResult<()> {
+        let req = self.get("/path/json").await?;
+        let req = req.with_extension(RequestId::new("macros-raw"));
+        send_raw!(req).await?;
+        Ok(())
+    }
+
+    #[api_method(log = "off")]
+    async fn json_to_off(&self) -> ApiResult<Value> {
+        let req = self.post("/path/echo").await?;
+        let req = req.with_extension(RequestId::new("macros-json-to"));
+        send_json_to!(req, json!({"id": 1}), Value).await
+    }
+
+    #[api_method(log = "off")]
+    async fn ndjson_off(&self) -> ApiResult<()> {
+        let req = self.get("/path/ndjson").await?;
+        let req = req.with_extension(RequestId::new("macros-ndjson"));
+        let _stream: NdjsonStream<Value> = send_ndjson!(req).await?;
+        Ok(())
+    }
+
+    #[api_method(log = "off")]
+    async fn ndjson_items_off(&self) -> ApiResult<()> {
+        let req = self.post("/path/bytes").await?;
+        let req = req.with_extension(RequestId::new("macros-ndjson-items"));
+        send_ndjson!(req, vec![json!({"id": 1})], ()).await
+    }
+
+    #[api_method(log = "off")]
+    async fn lines_off(&self) -> ApiResult<()> {
+        let req = self.get("/path/lines").await?;
+        let req = req.with_extension(RequestId::new("macros-lines"));
+        let _stream = send_lines!(req).await?;
+        Ok(())
+    }
+
+    #[api_method(log = "off")]
+    async fn bytes_off(&self) -> ApiResult<()> {
+        let req = self.post("/path/bytes").await?;
+        let req = req.with_extension(RequestId::new("macros-bytes"));
+        send_bytes!(req, b"bytes".to_vec(), "text/plain", ()).await
+    }
+
+    #[api_method(log = "off")]
+    async fn channel_body_off(&self) -> ApiResult<()> {
+        let req = self.post("/path/bytes").await?;
+        let req = req.with_extension(RequestId::new("macros-channel-body"));
+        let (tx, rx) = tokio::sync::mpsc::channel::<Vec<u8>>(1);
+        drop(tx);
+        send_channel_body!(req, rx, ()).await
+    }
+
+    #[api_method(log = "off")]
+    async fn json_array_off(&self) -> ApiResult<()> {
+        let req = self.get("/path/json-array/2").await?;
+        let req = req.with_extension(RequestId::new("macros-json-array"));
+        let _stream = send_json_array!(req).await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "schema")]
+    #[api_method(log = "off")]
+    async fn schema_validation_off(&self) -> ApiResult<()> {
+        let schema = apisdk::jsonschema::JSONSchema::compile(&json!({"type": "object"})).unwrap();
+        let req = self.get("/path/json").await?;
+        let req = req.with_extension(RequestId::new("macros-schema"));
+        let _: Value = send_with_schema_validation!(req, schema).await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "schema")]
+    #[api_method(log = "off")]
+    async fn schema_validation_as_off(&self) -> ApiResult<()> {
+        let schema = apisdk::jsonschema::JSONSchema::compile(&json!({"type": "object"})).unwrap();
+        let req = self.get("/path/json").await?;
+        let req = req.with_extension(RequestId::new("macros-schema-as"));
+        let _: Value = send_with_schema_validation!(req, schema, Value).await?;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_api_method_macros() -> ApiResult<()> {
+    let _ = log::set_logger(&LOGGER).map(|_| log::set_max_level(LevelFilter::Trace));
+    start_server().await;
+
+    let api = TheApi::default();
+
+    api.raw_logged().await?;
+    assert!(count_logs("macros-logged") > 0);
+
+    api.raw_off().await?;
+    api.json_to_off().await?;
+    api.ndjson_off().await?;
+    api.ndjson_items_off().await?;
+    api.lines_off().await?;
+    api.bytes_off().await?;
+    api.channel_body_off().await?;
+    api.json_array_off().await?;
+    #[cfg(feature = "schema")]
+    {
+        api.schema_validation_off().await?;
+        api.schema_validation_as_off().await?;
+    }
+    for request_id in [
+        "macros-raw",
+        "macros-json-to",
+        "macros-ndjson",
+        "macros-ndjson-items",
+        "macros-lines",
+        "macros-bytes",
+        "macros-channel-body",
+        "macros-json-array",
+        "macros-schema",
+        "macros-schema-as",
+    ] {
+        assert_eq!(0, count_logs(request_id), "{}", request_id);
+    }
+
+    Ok(())
+}
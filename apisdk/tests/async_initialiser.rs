@@ -0,0 +1,110 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use apisdk::{send, ApiError, ApiResult, CodeDataMessage, RequestBuilder};
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, TheApi, TheApiBuilder};
+
+#[allow(unused)]
+mod common;
+
+/// A fake vault, which fails for the first `failures` times
+#[derive(Clone, Default)]
+struct Vault {
+    failures: usize,
+    loads: Arc<AtomicUsize>,
+}
+
+impl Vault {
+    async fn fetch_secret(&self) -> ApiResult<String> {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let loads = self.loads.fetch_add(1, Ordering::SeqCst);
+        if loads < self.failures {
+            return Err(ApiError::Other("vault is sealed".to_string()));
+        }
+        Ok(format!("secret-{}", loads))
+    }
+
+    fn loads(&self) -> usize {
+        self.loads.load(Ordering::SeqCst)
+    }
+}
+
+fn build_api(vault: &Vault) -> TheApiBuilder {
+    let vault = vault.clone();
+    TheApi::builder().with_async_initialiser(move || {
+        let vault = vault.clone();
+        async move {
+            let secret = vault.fetch_secret().await?;
+            Ok(move |req: RequestBuilder| req.header("X-Secret", secret.as_str()))
+        }
+    })
+}
+
+impl TheApi {
+    async fn secret(&self) -> ApiResult<Option<String>> {
+        let req = self.get("/path/json").await?;
+        let data: Value = send!(req, CodeDataMessage).await?;
+        Ok(data["headers"]["x-secret"].as_str().map(|s| s.to_string()))
+    }
+}
+
+#[tokio::test]
+async fn test_async_initialiser() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let vault = Vault::default();
+    let api = build_api(&vault).build_async().await?;
+    assert_eq!(1, vault.loads());
+
+    assert_eq!(Some("secret-0"), api.secret().await?.as_deref());
+    assert_eq!(Some("secret-0"), api.secret().await?.as_deref());
+
+    // Loaded only once
+    assert_eq!(1, vault.loads());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_async_initialiser_build_failed() -> ApiResult<()> {
+    init_logger();
+
+    let vault = Vault {
+        failures: 1,
+        ..Default::default()
+    };
+    let res = build_api(&vault).build_async().await;
+    assert!(matches!(res, Err(ApiError::Other(_))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_async_initialiser_lazy() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let vault = Vault {
+        failures: 1,
+        ..Default::default()
+    };
+    let api = build_api(&vault).build();
+    assert_eq!(0, vault.loads());
+
+    // The first request fails, and the next one loads again
+    let res = api.secret().await;
+    assert!(matches!(res, Err(ApiError::Other(_))));
+    assert_eq!(Some("secret-1"), api.secret().await?.as_deref());
+    assert_eq!(Some("secret-1"), api.secret().await?.as_deref());
+    assert_eq!(2, vault.loads());
+
+    Ok(())
+}
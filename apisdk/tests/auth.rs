@@ -15,6 +15,16 @@ impl TheApi {
         let req = self.get("/path/json").await?;
         send!(req, CodeDataMessage).await
     }
+
+    async fn touch_bearer(&self, token: &str) -> ApiResult<Payload> {
+        let req = self.get("/path/json").await?.bearer_auth(token);
+        send!(req, CodeDataMessage).await
+    }
+
+    async fn touch_basic(&self, user: &str, pass: &str) -> ApiResult<Payload> {
+        let req = self.get("/path/json").await?.basic_auth(user, Some(pass));
+        send!(req, CodeDataMessage).await
+    }
 }
 
 #[tokio::test]
@@ -139,3 +149,51 @@ async fn test_hashed_token_auth() -> ApiResult<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_bearer_auth() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.touch_bearer("token").await?;
+    log::debug!("res = {:?}", res);
+    let auth = res.headers.get("authorization").unwrap();
+    assert_eq!("Bearer token", auth);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_basic_auth() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.touch_basic("user", "pass").await?;
+    log::debug!("res = {:?}", res);
+    let auth = res.headers.get("authorization").unwrap();
+    let expected = format!("Basic {}", general_purpose::STANDARD.encode("user:pass"));
+    assert_eq!(&expected, auth);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auth_is_redacted() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::default();
+
+    let req = api.get("/path/json").await?.bearer_auth("secret");
+    let req = req.build().unwrap();
+    assert!(!format!("{:?}", req).contains("secret"));
+
+    let req = api.get("/path/json").await?.build().unwrap();
+    let req = Carrier::BearerAuth.apply(req, "secret");
+    assert!(!format!("{:?}", req).contains("secret"));
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use apisdk::{
+    header::{HeaderMap, HeaderValue, DATE},
+    send, ApiResult, CodeDataMessage, ManualClock, MockServer, Request, ResponseBody,
+    RetryMiddleware, RetryPolicy, StatusCode,
+};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn get_headers_with(&self, headers: HeaderMap) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?.headers(headers);
+        let data: Value = send!(req, CodeDataMessage).await?;
+        Ok(data["headers"].clone())
+    }
+}
+
+#[tokio::test]
+async fn test_auto_date() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000));
+    let api = TheApi::builder()
+        .with_clock(clock.clone())
+        .with_auto_date(true)
+        .build();
+
+    let headers = api.get_headers_with(HeaderMap::new()).await?;
+    assert_eq!("Tue, 14 Nov 2023 22:13:20 GMT", headers["date"]);
+
+    clock.advance(Duration::from_secs(86400));
+    let headers = api.get_headers_with(HeaderMap::new()).await?;
+    assert_eq!("Wed, 15 Nov 2023 22:13:20 GMT", headers["date"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auto_date_system_clock() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_auto_date(true).build();
+
+    let headers = api.get_headers_with(HeaderMap::new()).await?;
+    let date = headers["date"].as_str().unwrap_or_default();
+    assert_eq!(29, date.len());
+    assert!(date.ends_with(" GMT"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auto_date_override() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_auto_date(true).build();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        DATE,
+        HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"),
+    );
+    let headers = api.get_headers_with(headers).await?;
+    assert_eq!("Sun, 06 Nov 1994 08:49:37 GMT", headers["date"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auto_date_header() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().with_auto_date(true).build();
+
+    // The value set by `req.header()` is kept, rather than appended
+    let req = api
+        .get("/path/json")
+        .await?
+        .header(DATE, "Sun, 06 Nov 1994 08:49:37 GMT")
+        .with_extension(MockServer::new(|req: Request| {
+            let dates: Vec<_> = req
+                .headers()
+                .get_all(DATE)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .collect();
+            Ok(ResponseBody::Json(json!({ "dates": dates })))
+        }));
+    let res: Value = send!(req).await?;
+    assert_eq!(json!(["Sun, 06 Nov 1994 08:49:37 GMT"]), res["dates"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auto_date_retry() -> ApiResult<()> {
+    init_logger();
+
+    let dates = Arc::new(Mutex::new(vec![]));
+    let mock = {
+        let dates = dates.clone();
+        MockServer::new(move |req: Request| {
+            let date = req.headers().get(DATE).cloned();
+            dates.lock().unwrap().extend(date);
+            Ok(ResponseBody::Json(json!({})))
+        })
+        .with_status(StatusCode::SERVICE_UNAVAILABLE)
+    };
+    let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000));
+    let api = TheApi::builder()
+        .with_clock(clock)
+        .with_auto_date(true)
+        .with_middleware(RetryMiddleware::new(RetryPolicy::new(
+            1,
+            Duration::from_secs(10),
+        )))
+        .with_initialiser(mock)
+        .build();
+
+    // Each attempt gets the time when it's sent
+    let req = api.get("/path/json").await?;
+    let res: ApiResult<Value> = send!(req).await;
+    assert!(res.is_err());
+    let dates = dates.lock().unwrap().clone();
+    assert_eq!(
+        vec![
+            "Tue, 14 Nov 2023 22:13:20 GMT",
+            "Tue, 14 Nov 2023 22:13:30 GMT"
+        ],
+        dates
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auto_date_absent() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let headers = api.get_headers_with(HeaderMap::new()).await?;
+    assert!(headers.get("date").is_none());
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use apisdk::{send, ApiError, ApiResult, CodeDataMessage, MissingEnvPolicy};
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn get_headers(&self) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?;
+        let data: Value = send!(req, CodeDataMessage).await?;
+        Ok(data["headers"].clone())
+    }
+}
+
+#[tokio::test]
+async fn test_base_headers_from_env() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    std::env::set_var("APISDK_TEST_API_TOKEN", "token-from-env");
+    let api = TheApi::builder()
+        .with_base_headers_from_env(
+            &[
+                ("APISDK_TEST_API_TOKEN", "X-Api-Token"),
+                ("APISDK_TEST_ABSENT", "X-Absent"),
+            ],
+            MissingEnvPolicy::Skip,
+        )?
+        .build();
+
+    let headers = api.get_headers().await?;
+    assert_eq!("token-from-env", headers["x-api-token"]);
+    assert!(headers.get("x-absent").is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_base_headers_from_env_missing() {
+    let res = TheApi::builder().with_base_headers_from_env(
+        &[("APISDK_TEST_MISSING", "X-Missing")],
+        MissingEnvPolicy::Error,
+    );
+    match res {
+        Err(ApiError::EnvHeader(message)) => assert!(message.contains("APISDK_TEST_MISSING")),
+        _ => panic!("missing env var should fail"),
+    }
+}
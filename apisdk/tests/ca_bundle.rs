@@ -0,0 +1,82 @@
+use std::{net::SocketAddr, path::PathBuf};
+
+use apisdk::{http_api, send, ApiError, ApiResult};
+use serde_json::{json, Value};
+use warp::Filter;
+
+use crate::common::init_logger;
+
+#[allow(unused)]
+mod common;
+
+#[http_api("https://localhost/v1")]
+#[derive(Debug, Clone)]
+pub struct TlsApi;
+
+impl TlsApi {
+    async fn hello(&self) -> ApiResult<Value> {
+        let req = self.get("/hello").await?;
+        send!(req).await
+    }
+}
+
+fn cert_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("certs")
+        .join(name)
+}
+
+/// Start a TLS server, whose certificate is signed by the CA in `bundle.pem`
+fn start_tls_server() -> SocketAddr {
+    let routes = warp::path!("v1" / "hello").map(|| warp::reply::json(&json!({"hello": "tls"})));
+    let (addr, server) = warp::serve(routes)
+        .tls()
+        .cert_path(cert_path("server.pem"))
+        .key_path(cert_path("server.key"))
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    addr
+}
+
+#[tokio::test]
+async fn test_ca_bundle_trusted() -> ApiResult<()> {
+    init_logger();
+    let addr = start_tls_server();
+
+    let api = TlsApiBuilder::new(format!("https://localhost:{}/v1", addr.port()))
+        .with_ca_bundle_path(cert_path("bundle.pem"))?
+        .build();
+
+    let res = api.hello().await?;
+    assert_eq!(json!({"hello": "tls"}), res);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ca_bundle_untrusted() -> ApiResult<()> {
+    init_logger();
+    let addr = start_tls_server();
+
+    let api = TlsApiBuilder::new(format!("https://localhost:{}/v1", addr.port())).build();
+
+    let res = api.hello().await;
+    assert!(matches!(res, Err(ApiError::Reqwest(_))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ca_bundle_invalid() {
+    init_logger();
+
+    let res = TlsApi::builder().with_ca_bundle_path(cert_path("server.key"));
+    match res {
+        Err(ApiError::InvalidCertificate(message)) => assert!(message.contains("server.key")),
+        _ => panic!("server.key should not be a valid CA bundle"),
+    }
+
+    let res = TlsApi::builder().with_ca_bundle_path(cert_path("missing.pem"));
+    assert!(matches!(res, Err(ApiError::InvalidCertificate(_))));
+}
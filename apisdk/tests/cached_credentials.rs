@@ -0,0 +1,147 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+
+use apisdk::{
+    send, ApiError, ApiResult, CachedCredentials, CodeDataMessage, ManualClock, TokenGenerator,
+};
+use async_trait::async_trait;
+use futures::future::join_all;
+use reqwest::Request;
+
+use crate::common::{init_logger, start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// Fetch credentials slowly, and count the calls
+#[derive(Default)]
+struct CountingProvider {
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl TokenGenerator for CountingProvider {
+    async fn generate_token(&self, _req: &Request) -> Result<String, reqwest_middleware::Error> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(format!("token-{}", call))
+    }
+}
+
+/// Fetch credentials once, and fail afterwards
+#[derive(Default)]
+struct FlakyProvider {
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl TokenGenerator for FlakyProvider {
+    async fn generate_token(&self, _req: &Request) -> Result<String, reqwest_middleware::Error> {
+        match self.calls.fetch_add(1, Ordering::SeqCst) {
+            0 => Ok("token-1".to_string()),
+            _ => Err(ApiError::Other("provider is down".to_string()).into()),
+        }
+    }
+}
+
+impl TheApi {
+    async fn touch_auth(&self) -> ApiResult<String> {
+        let req = self.get("/path/json").await?;
+        let res: Payload = send!(req, CodeDataMessage).await?;
+        Ok(res
+            .headers
+            .get("authorization")
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+fn build_api(ttl: Duration, refresh_before: Duration) -> (TheApi, Arc<AtomicUsize>) {
+    let provider = CountingProvider::default();
+    let calls = provider.calls.clone();
+    let auth = CachedCredentials::new(provider, ttl).with_refresh_before(refresh_before);
+    let api = TheApi::builder().with_authenticator(auth).build();
+    (api, calls)
+}
+
+#[tokio::test]
+async fn test_cached_credentials() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let (api, calls) = build_api(Duration::from_secs(60), Duration::from_secs(5));
+
+    // The concurrent requests share one fetch
+    let results = join_all((0..10).map(|_| api.touch_auth())).await;
+    for res in results {
+        assert_eq!("Bearer token-1", res?);
+    }
+    for _ in 0..5 {
+        assert_eq!("Bearer token-1", api.touch_auth().await?);
+    }
+    assert_eq!(1, calls.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_credentials_refresh() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let (api, calls) = build_api(Duration::from_millis(400), Duration::from_millis(200));
+    assert_eq!("Bearer token-1", api.touch_auth().await?);
+
+    // Refresh before expiry, while the concurrent requests keep using the cached credentials
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    let results = join_all((0..5).map(|_| api.touch_auth())).await;
+    let tokens: Vec<String> = results.into_iter().collect::<ApiResult<_>>()?;
+    assert!(tokens.contains(&"Bearer token-2".to_string()));
+    assert!(tokens
+        .iter()
+        .all(|t| t == "Bearer token-1" || t == "Bearer token-2"));
+    assert_eq!(2, calls.load(Ordering::SeqCst));
+    assert_eq!("Bearer token-2", api.touch_auth().await?);
+
+    // Refresh after expiry
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!("Bearer token-3", api.touch_auth().await?);
+    assert_eq!(3, calls.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_credentials_refresh_failed() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let provider = FlakyProvider::default();
+    let calls = provider.calls.clone();
+    let auth = CachedCredentials::new(provider, Duration::from_secs(60))
+        .with_refresh_before(Duration::from_secs(10));
+    let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000));
+    let api = TheApi::builder()
+        .with_authenticator(auth)
+        .with_clock(clock.clone())
+        .build();
+    assert_eq!("Bearer token-1", api.touch_auth().await?);
+
+    // The refresh fails, but the stale credentials are still valid
+    clock.advance(Duration::from_secs(55));
+    assert_eq!("Bearer token-1", api.touch_auth().await?);
+    assert_eq!("Bearer token-1", api.touch_auth().await?);
+    assert_eq!(3, calls.load(Ordering::SeqCst));
+
+    // The credentials are expired
+    clock.advance(Duration::from_secs(10));
+    assert!(api.touch_auth().await.is_err());
+    assert_eq!(4, calls.load(Ordering::SeqCst));
+
+    Ok(())
+}
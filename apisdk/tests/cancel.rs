@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+
+use apisdk::{
+    send, ApiError, ApiResult, CancellationToken, CodeDataMessage, MockServer, Responder,
+    ResponseBody,
+};
+use async_trait::async_trait;
+use reqwest::Request;
+use serde_json::json;
+
+use crate::common::{init_logger, start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// This responder replies after a long delay
+struct SlowResponder;
+
+#[async_trait]
+impl Responder for SlowResponder {
+    async fn handle(&self, _req: Request) -> anyhow::Result<ResponseBody> {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        Ok(ResponseBody::Json(json!({
+            "code": 0,
+            "data": {}
+        })))
+    }
+}
+
+impl TheApi {
+    async fn touch_slow(&self, token: &CancellationToken) -> ApiResult<Payload> {
+        let req = self.get("/path/json").await?;
+        let req = req
+            .with_extension(MockServer::new(SlowResponder))
+            .with_extension(token.clone());
+        send!(req, CodeDataMessage).await
+    }
+
+    async fn touch(&self, token: &CancellationToken) -> ApiResult<Payload> {
+        let req = self.get("/path/json").await?;
+        let req = req.with_extension(token.clone());
+        send!(req, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_cancel_slow_request() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        canceller.cancel();
+    });
+
+    let start = Instant::now();
+    let res = api.touch_slow(&token).await;
+    log::debug!("res = {:?}", res);
+    assert!(matches!(res, Err(ApiError::Cancelled)));
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancel_before_send() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let res = api.touch(&token).await;
+    log::debug!("res = {:?}", res);
+    assert!(matches!(res, Err(ApiError::Cancelled)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_not_cancelled() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let token = CancellationToken::new();
+    let res = api.touch(&token).await?;
+    log::debug!("res = {:?}", res);
+    assert!(!token.is_cancelled());
+
+    Ok(())
+}
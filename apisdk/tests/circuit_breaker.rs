@@ -0,0 +1,175 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use apisdk::{
+    send, ApiError, ApiResult, CircuitBreakerMiddleware, CircuitState, MockServer, ResponseBody,
+    StatusCode,
+};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn touch_status(&self, status: StatusCode, hits: Arc<AtomicUsize>) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?;
+        let req = req.with_extension(
+            MockServer::new(move |_| {
+                hits.fetch_add(1, Ordering::SeqCst);
+                Ok(ResponseBody::Json(json!({})))
+            })
+            .with_status(status),
+        );
+        send!(req).await
+    }
+
+    async fn touch_slow(&self) -> ApiResult<Value> {
+        let req = self.get("/path/slow").await?;
+        send!(req).await
+    }
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_default() -> ApiResult<()> {
+    init_logger();
+
+    let breaker = CircuitBreakerMiddleware::new(2, Duration::from_secs(60));
+    let api = TheApi::builder().with_middleware(breaker.clone()).build();
+    let hits = Arc::new(AtomicUsize::new(0));
+
+    // Client errors are ignored by default
+    for _ in 0..3 {
+        let res = api
+            .touch_status(StatusCode::TOO_MANY_REQUESTS, hits.clone())
+            .await;
+        assert!(matches!(res, Err(ApiError::HttpClientStatus(429, _))));
+    }
+    assert_eq!(CircuitState::Closed, breaker.state());
+
+    for _ in 0..2 {
+        let res = api
+            .touch_status(StatusCode::BAD_GATEWAY, hits.clone())
+            .await;
+        assert!(matches!(res, Err(ApiError::HttpServerStatus(502, _))));
+    }
+    assert_eq!(CircuitState::Open, breaker.state());
+
+    // Fail fast, without sending the request
+    let res = api.touch_status(StatusCode::OK, hits.clone()).await;
+    assert!(matches!(res, Err(ApiError::CircuitOpen)));
+    assert_eq!(5, hits.load(Ordering::SeqCst));
+
+    breaker.reset();
+    api.touch_status(StatusCode::OK, hits.clone()).await?;
+    assert_eq!(6, hits.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_trip_on() -> ApiResult<()> {
+    init_logger();
+
+    // Trip on 429 and other server errors, but ignore 503 during deploys
+    let breaker =
+        CircuitBreakerMiddleware::new(2, Duration::from_secs(60)).with_trip_on(|e: &ApiError| {
+            match e.status() {
+                Some(503) => false,
+                Some(c) => c == 429 || c >= 500,
+                None => false,
+            }
+        });
+    let api = TheApi::builder().with_middleware(breaker.clone()).build();
+    let hits = Arc::new(AtomicUsize::new(0));
+
+    for _ in 0..3 {
+        let res = api
+            .touch_status(StatusCode::SERVICE_UNAVAILABLE, hits.clone())
+            .await;
+        assert!(matches!(res, Err(ApiError::HttpServerStatus(503, _))));
+    }
+    assert_eq!(CircuitState::Closed, breaker.state());
+
+    for _ in 0..2 {
+        let res = api
+            .touch_status(StatusCode::TOO_MANY_REQUESTS, hits.clone())
+            .await;
+        assert!(matches!(res, Err(ApiError::HttpClientStatus(429, _))));
+    }
+    assert_eq!(CircuitState::Open, breaker.state());
+
+    let res = api.touch_status(StatusCode::OK, hits.clone()).await;
+    assert!(matches!(res, Err(ApiError::CircuitOpen)));
+    assert_eq!(5, hits.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_half_open() -> ApiResult<()> {
+    init_logger();
+
+    let breaker = CircuitBreakerMiddleware::new(1, Duration::from_millis(50));
+    let api = TheApi::builder().with_middleware(breaker.clone()).build();
+    let hits = Arc::new(AtomicUsize::new(0));
+
+    let _ = api
+        .touch_status(StatusCode::BAD_GATEWAY, hits.clone())
+        .await;
+    assert_eq!(CircuitState::Open, breaker.state());
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(CircuitState::HalfOpen, breaker.state());
+
+    // The trial request fails, so the circuit is opened again
+    let _ = api
+        .touch_status(StatusCode::BAD_GATEWAY, hits.clone())
+        .await;
+    assert_eq!(CircuitState::Open, breaker.state());
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    api.touch_status(StatusCode::OK, hits.clone()).await?;
+    assert_eq!(CircuitState::Closed, breaker.state());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_single_probe() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let breaker = CircuitBreakerMiddleware::new(1, Duration::from_millis(50));
+    let api = TheApi::builder().with_middleware(breaker.clone()).build();
+    let hits = Arc::new(AtomicUsize::new(0));
+
+    let _ = api
+        .touch_status(StatusCode::BAD_GATEWAY, hits.clone())
+        .await;
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(CircuitState::HalfOpen, breaker.state());
+
+    // Only the trial request is sent, and the others are rejected until it's done
+    let probe = tokio::spawn({
+        let api = api.clone();
+        async move { api.touch_slow().await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let res = api.touch_status(StatusCode::OK, hits.clone()).await;
+    assert!(matches!(res, Err(ApiError::CircuitOpen)));
+    assert_eq!(1, hits.load(Ordering::SeqCst));
+
+    probe.await.unwrap()?;
+    assert_eq!(CircuitState::Closed, breaker.state());
+    api.touch_status(StatusCode::OK, hits.clone()).await?;
+    assert_eq!(2, hits.load(Ordering::SeqCst));
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+use apisdk::{
+    send, ApiError, ApiResult, CachedCredentials, Clock, ManualClock, MockServer, Request,
+    ResponseBody, RetryMiddleware, RetryPolicy, StatusCode,
+};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// This clock records the durations to sleep
+#[derive(Debug, Clone, Default)]
+struct RecordingClock {
+    inner: ManualClock,
+    sleeps: Arc<Mutex<Vec<Duration>>>,
+}
+
+#[async_trait]
+impl Clock for RecordingClock {
+    fn now(&self) -> SystemTime {
+        self.inner.now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        self.sleeps.lock().unwrap().push(duration);
+        self.inner.sleep(duration).await
+    }
+}
+
+/// Reply the authorization header
+fn echo_auth() -> MockServer {
+    MockServer::new(|req: Request| {
+        let auth = req
+            .headers()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        Ok(ResponseBody::Json(json!({ "auth": auth })))
+    })
+}
+
+impl TheApi {
+    async fn touch_auth(&self) -> ApiResult<String> {
+        let req = self.get("/path/json").await?;
+        let res: Value = send!(req).await?;
+        Ok(res["auth"].as_str().unwrap_or_default().to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_clock_cached_credentials() -> ApiResult<()> {
+    init_logger();
+
+    let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let provider = {
+        let calls = calls.clone();
+        move || -> Result<String, reqwest_middleware::Error> {
+            Ok(format!(
+                "token-{}",
+                calls.fetch_add(1, Ordering::SeqCst) + 1
+            ))
+        }
+    };
+    let api = TheApi::builder()
+        .with_clock(clock.clone())
+        .with_initialiser(echo_auth())
+        .with_authenticator(
+            CachedCredentials::new(provider, Duration::from_secs(60))
+                .with_refresh_before(Duration::from_secs(5)),
+        )
+        .build();
+
+    assert_eq!("Bearer token-1", api.touch_auth().await?);
+
+    // Still fresh
+    clock.advance(Duration::from_secs(50));
+    assert_eq!("Bearer token-1", api.touch_auth().await?);
+    assert_eq!(1, calls.load(Ordering::SeqCst));
+
+    // About to expire
+    clock.advance(Duration::from_secs(5));
+    assert_eq!("Bearer token-2", api.touch_auth().await?);
+    assert_eq!(2, calls.load(Ordering::SeqCst));
+
+    // Expired
+    clock.advance(Duration::from_secs(60));
+    assert_eq!("Bearer token-3", api.touch_auth().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_clock_retry() -> ApiResult<()> {
+    init_logger();
+
+    let clock = RecordingClock::default();
+    let start = clock.now();
+    let policy = RetryPolicy::retry_if(|e: &ApiError, attempt| match e.status() {
+        Some(503) if attempt <= 3 => Some(Duration::from_secs(1 << (attempt - 1))),
+        _ => None,
+    });
+    let api = TheApi::builder()
+        .with_clock(clock.clone())
+        .with_middleware(RetryMiddleware::new(policy))
+        .with_initialiser(
+            MockServer::new(|_| Ok(ResponseBody::Json(json!({}))))
+                .with_status(StatusCode::SERVICE_UNAVAILABLE),
+        )
+        .build();
+
+    let req = api.get("/path/json").await?;
+    let res: ApiResult<Value> = send!(req).await;
+    assert!(matches!(res, Err(ApiError::HttpServerStatus(503, _))));
+
+    // Waited without real delays
+    assert_eq!(
+        vec![
+            Duration::from_secs(1),
+            Duration::from_secs(2),
+            Duration::from_secs(4)
+        ],
+        *clock.sleeps.lock().unwrap()
+    );
+    assert_eq!(
+        Duration::from_secs(7),
+        clock.now().duration_since(start).unwrap()
+    );
+
+    Ok(())
+}
@@ -1,6 +1,14 @@
 use apisdk::http_api;
 
 /// This a sample API
-#[http_api("http://localhost:3030/v1")]
+#[http_api(
+    "http://localhost:3030/v1",
+    follow_link,
+    sign_url,
+    reset,
+    pool_stats,
+    poll_changes,
+    tus_upload
+)]
 #[derive(Debug, Clone)]
 pub struct TheApi;
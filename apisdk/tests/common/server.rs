@@ -1,6 +1,10 @@
-use std::{collections::HashMap, time::Duration};
+use std::{collections::HashMap, io::Write, time::Duration};
 
-use apisdk::{header::HeaderMap, ApiError, ResponseBody};
+use apisdk::{digest, header::HeaderMap, ApiError, Challenge, ResponseBody};
+use flate2::{
+    write::{GzEncoder, ZlibEncoder},
+    Compression,
+};
 use futures::StreamExt;
 use serde::{de::DeserializeOwned, Deserialize};
 use serde_json::json;
@@ -41,7 +45,7 @@ pub async fn start_server() {
 }
 
 async fn do_start_server() {
-    tokio::spawn(async move {
+    let server = async move {
         let dump_json = warp::path!("v1" / "path" / "json")
             .and(warp::path::full())
             .and(warp::header::headers_cloned())
@@ -71,6 +75,52 @@ async fn do_start_server() {
             .and(warp::query())
             .and(warp::multipart::form())
             .and_then(handle_multipart);
+        let dump_related = warp::path!("v1" / "path" / "related").and_then(handle_related);
+        let dump_echo = warp::post()
+            .or(warp::delete())
+            .unify()
+            .and(warp::path!("v1" / "path" / "echo"))
+            .and(warp::body::json())
+            .and_then(handle_echo);
+        let dump_ndjson = warp::path!("v1" / "path" / "ndjson").and_then(handle_ndjson);
+        let dump_lines = warp::path!("v1" / "path" / "lines").and_then(handle_lines);
+        let dump_json_array =
+            warp::path!("v1" / "path" / "json-array" / usize).and_then(handle_json_array);
+        let dump_versioned = warp::path!("v1" / "path" / "versioned")
+            .and(warp::header::optional::<String>("if-match"))
+            .and_then(handle_versioned);
+        let health = warp::path!("v1" / "health").map(warp::reply);
+        let dump_empty = warp::path!("v1" / "path" / "empty")
+            .map(|| warp::reply::with_header(warp::reply(), "Content-Type", "application/json"));
+        let dump_empty_chunked =
+            warp::path!("v1" / "path" / "empty-chunked" / u16).and_then(handle_empty_chunked);
+        let accepted = warp::path!("v1" / "path" / "accepted")
+            .and(warp::query::<HashMap<String, String>>())
+            .map(|query: HashMap<String, String>| {
+                let location = query.get("location").cloned().unwrap_or_default();
+                warp::reply::with_header(
+                    warp::reply::with_status(warp::reply(), warp::http::StatusCode::ACCEPTED),
+                    "Location",
+                    location,
+                )
+            });
+        let unavailable = warp::path!("v1" / "unavailable")
+            .map(|| warp::reply::with_status("", warp::http::StatusCode::SERVICE_UNAVAILABLE));
+        let dump_slow = warp::path!("v1" / "path" / "slow").and_then(handle_slow);
+        let dump_gzip = warp::path!("v1" / "path" / "gzip").and_then(handle_gzip);
+        let dump_encoding =
+            warp::path!("v1" / "path" / "encoding" / String).and_then(handle_encoding);
+        let dump_grpc_error = warp::path!("v1" / "path" / "grpc-error").and_then(handle_grpc_error);
+        let dump_bytes = warp::post()
+            .and(warp::path!("v1" / "path" / "bytes"))
+            .and(warp::header::optional::<String>("content-type"))
+            .and(warp::body::bytes())
+            .and_then(handle_bytes);
+        let dump_digest = warp::path!("v1" / "path" / "digest")
+            .and(warp::method())
+            .and(warp::path::full())
+            .and(warp::header::optional::<String>("authorization"))
+            .and_then(handle_digest);
         let not_found = warp::path!("v1" / "not-found").and_then(handle_not_found);
 
         warp::serve(
@@ -79,10 +129,37 @@ async fn do_start_server() {
                 .or(dump_text)
                 .or(dump_form)
                 .or(dump_multipart)
+                .or(dump_related)
+                .or(dump_ndjson)
+                .or(dump_lines)
+                .or(dump_echo)
+                .or(dump_json_array)
+                .or(dump_versioned)
+                .or(dump_slow)
+                .or(health)
+                .or(dump_empty)
+                .or(dump_empty_chunked)
+                .or(accepted)
+                .or(unavailable)
+                .or(dump_gzip)
+                .or(dump_encoding)
+                .or(dump_grpc_error)
+                .or(dump_bytes)
+                .or(dump_digest)
                 .or(not_found),
         )
         .run(([127, 0, 0, 1], PORT))
         .await;
+    };
+
+    // Each `#[tokio::test]` owns its runtime, so the server gets a runtime of
+    // its own to outlive the test that happened to start it
+    std::thread::spawn(move || {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(server)
     });
 
     // Ensure the server is ready to work
@@ -212,6 +289,248 @@ async fn handle_multipart(
     Ok(warp::reply::json(&resp))
 }
 
+async fn handle_related() -> Result<impl Reply, warp::Rejection> {
+    warp::http::Response::builder()
+        .header(
+            "Content-Type",
+            "multipart/related; type=\"application/json\"; boundary=\"boundary-of-parts\"",
+        )
+        .body(
+            "--boundary-of-parts\r\n\
+            Content-Type: application/json\r\n\
+            \r\n\
+            {\"code\":0,\"data\":{\"hello\":\"world\"}}\r\n\
+            --boundary-of-parts\r\n\
+            Content-Type: application/octet-stream\r\n\
+            Content-ID: <attachment>\r\n\
+            \r\n\
+            binary\r\ncontent\r\n\
+            --boundary-of-parts--\r\n",
+        )
+        .map_err(|_| warp::reject())
+}
+
+async fn handle_echo(body: serde_json::Value) -> Result<impl Reply, warp::Rejection> {
+    let resp = json!({
+        "code": 0,
+        "message": "OK",
+        "data": body,
+    });
+    Ok(warp::reply::json(&resp))
+}
+
+async fn handle_lines() -> Result<impl Reply, warp::Rejection> {
+    // Split lines across chunks on purpose
+    let chunks = vec!["first li", "ne\r\n\nsecond", " line\nthird line"];
+    let stream = futures::stream::iter(chunks).then(|chunk| async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Ok::<_, std::convert::Infallible>(chunk)
+    });
+    warp::http::Response::builder()
+        .header("Content-Type", "text/plain")
+        .body(warp::hyper::Body::wrap_stream(stream))
+        .map_err(|_| warp::reject())
+}
+
+async fn handle_ndjson() -> Result<impl Reply, warp::Rejection> {
+    // Split lines across chunks on purpose
+    let chunks = vec![
+        "{\"id\":1,\"name\":\"a\"}\n{\"id\":2,",
+        "\"name\":\"b\"}\n{\"id\":3,\"na",
+        "me\":\"c\"}\n",
+    ];
+    let stream = futures::stream::iter(chunks).then(|chunk| async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Ok::<_, std::convert::Infallible>(chunk)
+    });
+    warp::http::Response::builder()
+        .header("Content-Type", "application/x-ndjson")
+        .body(warp::hyper::Body::wrap_stream(stream))
+        .map_err(|_| warp::reject())
+}
+
+async fn handle_empty_chunked(status: u16) -> Result<impl Reply, warp::Rejection> {
+    // The length of streaming body is unknown, so it is sent as chunked
+    let stream = futures::stream::empty::<Result<&str, std::convert::Infallible>>();
+    warp::http::Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(warp::hyper::Body::wrap_stream(stream))
+        .map_err(|_| warp::reject())
+}
+
+async fn handle_json_array(count: usize) -> Result<impl Reply, warp::Rejection> {
+    // Generate elements lazily, and split them across chunks on purpose
+    let chunks = (0..=count).map(move |i| match i {
+        0 => "[".to_string(),
+        i if i == count => format!("{{\"id\":{},\"name\":\"item\"}}]", i),
+        i => format!("{{\"id\":{},\"name\":\"item\"}},", i),
+    });
+    let stream = futures::stream::iter(chunks).map(Ok::<_, std::convert::Infallible>);
+    warp::http::Response::builder()
+        .header("Content-Type", "application/json")
+        .body(warp::hyper::Body::wrap_stream(stream))
+        .map_err(|_| warp::reject())
+}
+
+async fn handle_versioned(if_match: Option<String>) -> Result<impl Reply, warp::Rejection> {
+    // The current ETag of resource is always "v2"
+    let status = match if_match.as_deref() {
+        None | Some("*") | Some("\"v2\"") => warp::http::StatusCode::OK,
+        _ => warp::http::StatusCode::PRECONDITION_FAILED,
+    };
+    let resp = json!({ "etag": "v2" });
+    Ok(warp::reply::with_header(
+        warp::reply::with_status(warp::reply::json(&resp), status),
+        "ETag",
+        "\"v2\"",
+    ))
+}
+
+async fn handle_slow() -> Result<impl Reply, warp::Rejection> {
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let resp = json!({
+        "code": 0,
+        "message": "OK",
+        "data": {},
+    });
+    Ok(warp::reply::json(&resp))
+}
+
+async fn handle_gzip() -> Result<impl Reply, warp::Rejection> {
+    let resp = json!({
+        "code": 0,
+        "message": "OK",
+        "data": {"compressed": true},
+    });
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(resp.to_string().as_bytes())
+        .map_err(|_| warp::reject())?;
+    let body = encoder.finish().map_err(|_| warp::reject())?;
+    warp::http::Response::builder()
+        .header("Content-Type", "application/json")
+        .header("Content-Encoding", "gzip")
+        .body(body)
+        .map_err(|_| warp::reject())
+}
+
+/// Reply json with the content-encoding, only gzip and deflate are really compressed
+async fn handle_encoding(encoding: String) -> Result<impl Reply, warp::Rejection> {
+    let resp = json!({
+        "code": 0,
+        "message": "OK",
+        "data": {"encoding": encoding},
+    });
+    let payload = resp.to_string().into_bytes();
+    let body = match encoding.as_str() {
+        "gzip" => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&payload).map_err(|_| warp::reject())?;
+            encoder.finish().map_err(|_| warp::reject())?
+        }
+        "deflate" => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&payload).map_err(|_| warp::reject())?;
+            encoder.finish().map_err(|_| warp::reject())?
+        }
+        "br" => {
+            let mut encoded = Vec::new();
+            let mut encoder = brotli::CompressorWriter::new(&mut encoded, 4096, 5, 22);
+            encoder.write_all(&payload).map_err(|_| warp::reject())?;
+            drop(encoder);
+            encoded
+        }
+        "zstd" => ruzstd::encoding::compress_to_vec(
+            payload.as_slice(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        ),
+        _ => payload,
+    };
+    warp::http::Response::builder()
+        .header("Content-Type", "application/json")
+        .header("Content-Encoding", encoding)
+        .body(body)
+        .map_err(|_| warp::reject())
+}
+
+async fn handle_grpc_error() -> Result<impl Reply, warp::Rejection> {
+    let resp = json!({
+        "code": 5,
+        "message": "user not found",
+        "details": [{
+            "@type": "type.googleapis.com/google.rpc.ResourceInfo",
+            "resourceName": "users/1"
+        }]
+    });
+    Ok(warp::reply::with_status(
+        warp::reply::json(&resp),
+        warp::http::StatusCode::NOT_FOUND,
+    ))
+}
+
+async fn handle_bytes(
+    content_type: Option<String>,
+    body: warp::hyper::body::Bytes,
+) -> Result<impl Reply, warp::Rejection> {
+    let resp = json!({
+        "content_type": content_type,
+        "body": body.to_vec(),
+    });
+    Ok(warp::reply::json(&resp))
+}
+
+/// Require Digest auth of `Mufasa` / `Circle of Life`, see RFC 7616
+async fn handle_digest(
+    method: warp::http::Method,
+    path: FullPath,
+    authorization: Option<String>,
+) -> Result<impl Reply, warp::Rejection> {
+    const REALM: &str = "apisdk";
+    const NONCE: &str = "dcd98b7102dd2f0e8b11d0f600bfb0c093";
+    let verified = authorization
+        .as_deref()
+        .and_then(|auth| Challenge::parse(auth).pop())
+        .filter(|c| c.scheme == "Digest" && c.param("nonce") == Some(NONCE))
+        .filter(|c| {
+            let ha1 = digest::md5(format!("Mufasa:{}:Circle of Life", REALM));
+            let ha2 = digest::md5(format!("{}:{}", method, path.as_str()));
+            let response = digest::md5(format!(
+                "{}:{}:{}:{}:auth:{}",
+                ha1,
+                NONCE,
+                c.param("nc").unwrap_or_default(),
+                c.param("cnonce").unwrap_or_default(),
+                ha2
+            ));
+            c.param("response") == Some(response.as_str())
+        });
+    let Some(challenge) = verified else {
+        let challenge = format!(
+            "Digest realm=\"{}\", qop=\"auth\", algorithm=MD5, nonce=\"{}\", opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"",
+            REALM, NONCE
+        );
+        return warp::http::Response::builder()
+            .status(warp::http::StatusCode::UNAUTHORIZED)
+            .header("WWW-Authenticate", challenge)
+            .body(String::new())
+            .map_err(|_| warp::reject());
+    };
+    let resp = json!({
+        "code": 0,
+        "message": "OK",
+        "data": {
+            "username": challenge.param("username"),
+            "uri": challenge.param("uri"),
+            "opaque": challenge.param("opaque"),
+        },
+    });
+    warp::http::Response::builder()
+        .header("Content-Type", "application/json")
+        .body(resp.to_string())
+        .map_err(|_| warp::reject())
+}
+
 async fn handle_not_found() -> Result<String, warp::Rejection> {
     Err(warp::reject::not_found())
 }
@@ -0,0 +1,52 @@
+use std::time::{Duration, Instant};
+
+use apisdk::{send, ApiResult, CodeDataMessage};
+use futures::future::join_all;
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn slow(&self) -> ApiResult<Value> {
+        let req = self.get("/path/slow").await?;
+        send!(req, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_max_connections_per_host() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    // Each request takes 100ms, and only 2 requests are allowed at the same time
+    let api = TheApi::builder().with_max_connections_per_host(2).build();
+
+    let start = Instant::now();
+    let results = join_all((0..6).map(|_| api.slow())).await;
+    let elapsed = start.elapsed();
+    log::debug!("elapsed = {:?}", elapsed);
+    assert!(results.iter().all(|r| r.is_ok()));
+    assert!(elapsed >= Duration::from_millis(300));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_connections_per_host_unlimited() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let start = Instant::now();
+    let results = join_all((0..6).map(|_| api.slow())).await;
+    let elapsed = start.elapsed();
+    log::debug!("elapsed = {:?}", elapsed);
+    assert!(results.iter().all(|r| r.is_ok()));
+    assert!(elapsed < Duration::from_millis(300));
+
+    Ok(())
+}
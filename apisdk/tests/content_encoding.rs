@@ -0,0 +1,155 @@
+use apisdk::{send, ApiError, ApiResult, ResponseBody};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug)]
+struct RawBody(ResponseBody);
+
+impl TryFrom<ResponseBody> for RawBody {
+    type Error = ApiError;
+
+    fn try_from(body: ResponseBody) -> Result<Self, Self::Error> {
+        Ok(Self(body))
+    }
+}
+
+impl TheApi {
+    async fn touch_encoding(&self, encoding: &str) -> ApiResult<RawBody> {
+        let req = self.get(format!("/path/encoding/{}", encoding)).await?;
+        send!(req, Body).await
+    }
+}
+
+/// Check whether the body is decoded as json
+fn assert_json(body: ResponseBody, encoding: &str) {
+    match body {
+        ResponseBody::Json(json) => assert_eq!(encoding, json["data"]["encoding"]),
+        _ => panic!("unexpected body: {:?}", body),
+    }
+}
+
+#[tokio::test]
+async fn test_content_encoding_identity() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    // `br` is advertised, but the server replies `identity`
+    let api = TheApi::builder().with_accept_encoding(&["br"]).build();
+
+    let RawBody(body) = api.touch_encoding("identity").await?;
+    assert_json(body, "identity");
+
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_content_encoding_gzip() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let RawBody(body) = api.touch_encoding("gzip").await?;
+    assert_json(body, "gzip");
+
+    Ok(())
+}
+
+#[cfg(feature = "deflate")]
+#[tokio::test]
+async fn test_content_encoding_deflate() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let RawBody(body) = api.touch_encoding("deflate").await?;
+    assert_json(body, "deflate");
+
+    Ok(())
+}
+
+#[cfg(feature = "brotli")]
+#[tokio::test]
+async fn test_content_encoding_br() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let RawBody(body) = api.touch_encoding("br").await?;
+    assert_json(body, "br");
+
+    Ok(())
+}
+
+#[cfg(feature = "zstd")]
+#[tokio::test]
+async fn test_content_encoding_zstd() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let RawBody(body) = api.touch_encoding("zstd").await?;
+    assert_json(body, "zstd");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_content_encoding_unknown() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let res = api.touch_encoding("x-custom").await;
+    match res {
+        Err(ApiError::UnsupportedContentEncoding(encoding)) => assert_eq!("x-custom", encoding),
+        _ => panic!("unexpected result: {:?}", res),
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "brotli"))]
+#[tokio::test]
+async fn test_content_encoding_disabled() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let res = api.touch_encoding("br").await;
+    match res {
+        Err(ApiError::UnsupportedContentEncoding(encoding)) => assert_eq!("br", encoding),
+        _ => panic!("unexpected result: {:?}", res),
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "zstd")]
+#[tokio::test]
+async fn test_content_encoding_limit() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_max_decoded_len(16).build();
+
+    let res = api.touch_encoding("zstd").await;
+    assert!(matches!(res, Err(ApiError::DecodeResponse(..))));
+
+    // Override the limit for single request
+    let req = api.get("/path/encoding/zstd").await?;
+    let req = req.with_extension(apisdk::MaxDecodedLen::default());
+    let RawBody(body) = send!(req, Body).await?;
+    assert_json(body, "zstd");
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use apisdk::{
+    async_trait, header::HeaderValue, send, ApiResult, CodeDataMessage, ContextOps, DuplicateOps,
+    Extensions, Middleware, RequestContext,
+};
+use reqwest::{Request, Response};
+use reqwest_middleware::{Next, Result};
+use serde::Deserialize;
+
+use crate::common::{init_logger, start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Clone)]
+pub struct TenantId(String);
+
+pub struct TenantMiddleware;
+
+#[async_trait]
+impl Middleware for TenantMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        if let Some(tenant) = RequestContext::<TenantId>::get(extensions) {
+            if let Ok(value) = HeaderValue::from_str(&tenant.0) {
+                req.headers_mut().insert("X-Tenant-ID", value);
+            }
+        }
+        next.run(req, extensions).await
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Headers {
+    #[serde(default, rename = "x-tenant-id")]
+    pub x_tenant_id: String,
+}
+
+impl TheApi {
+    async fn touch(&self, tenant: Option<&str>) -> ApiResult<Payload<Headers>> {
+        let mut req = self.get("/path/json").await?;
+        if let Some(tenant) = tenant {
+            req = req.with_context(TenantId(tenant.to_string()));
+        }
+        send!(req, CodeDataMessage).await
+    }
+
+    async fn touch_duplicate(&self, tenant: &str) -> ApiResult<Payload<Headers>> {
+        let mut req = self.get("/path/json").await?;
+        req = req.with_context(TenantId(tenant.to_string()));
+        let dup = req.try_duplicate().expect("duplicate");
+        send!(dup, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_context() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_middleware(TenantMiddleware).build();
+
+    let res = api.touch(Some("tenant-1")).await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!("tenant-1", res.headers.x_tenant_id);
+
+    let res = api.touch(None).await?;
+    log::debug!("res = {:?}", res);
+    assert!(res.headers.x_tenant_id.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_context_duplicate() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_middleware(TenantMiddleware).build();
+
+    let res = api.touch_duplicate("tenant-2").await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!("tenant-2", res.headers.x_tenant_id);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_context_types() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().build();
+
+    let mut req = api.get("/path/json").await?;
+    req = req.with_context(TenantId("tenant-3".to_string()));
+    req = req.with_context(42u32);
+    let extensions = req.extensions();
+    assert_eq!(
+        Some("tenant-3"),
+        RequestContext::<TenantId>::get(extensions).map(|t| t.0.as_str())
+    );
+    if let Some(value) = RequestContext::<u32>::get_mut(extensions) {
+        *value += 1;
+    }
+    assert_eq!(Some(&43), RequestContext::<u32>::get(extensions));
+    assert_eq!(None, RequestContext::<String>::get(extensions));
+
+    Ok(())
+}
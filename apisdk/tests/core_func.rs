@@ -41,3 +41,22 @@ async fn test_via_core() -> ApiResult<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_build_url_encoding() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::default();
+
+    let url = api.core.build_url("/users/John Doe").await?;
+    assert_eq!("http://localhost:3030/v1/users/John%20Doe", url.as_str());
+
+    let url = api.core.build_url("/users/John%20Doe").await?;
+    assert_eq!("http://localhost:3030/v1/users/John%20Doe", url.as_str());
+
+    let url = api.core.build_url("/users?name=John Doe&page=1").await?;
+    assert_eq!("/v1/users", url.path());
+    assert_eq!(Some("name=John%20Doe&page=1"), url.query());
+
+    Ok(())
+}
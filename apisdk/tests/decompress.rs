@@ -0,0 +1,84 @@
+use std::io::Read;
+
+use apisdk::{send, ApiError, ApiResult, ResponseBody};
+use flate2::read::GzDecoder;
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug)]
+struct RawBody(ResponseBody);
+
+impl TryFrom<ResponseBody> for RawBody {
+    type Error = ApiError;
+
+    fn try_from(body: ResponseBody) -> Result<Self, Self::Error> {
+        Ok(Self(body))
+    }
+}
+
+impl TheApi {
+    async fn touch_gzip(&self) -> ApiResult<RawBody> {
+        let req = self.get("/path/gzip").await?;
+        send!(req, Body).await
+    }
+
+    async fn touch_gzip_bytes(&self) -> ApiResult<Vec<u8>> {
+        let req = self.get("/path/gzip").await?;
+        send!(req, Body).await
+    }
+}
+
+#[tokio::test]
+async fn test_auto_decompress_off() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_auto_decompress(false).build();
+
+    let RawBody(body) = api.touch_gzip().await?;
+    log::debug!("body = {:?}", body);
+    match body {
+        ResponseBody::Bytes {
+            content_type,
+            content_encoding,
+            data,
+        } => {
+            assert_eq!("application/json", content_type);
+            assert_eq!(Some("gzip"), content_encoding.as_deref());
+            // gzip magic number
+            assert_eq!(&[0x1f, 0x8b], &data[0..2]);
+
+            let mut text = String::new();
+            GzDecoder::new(data.as_slice())
+                .read_to_string(&mut text)
+                .unwrap();
+            let json: Value = serde_json::from_str(&text).unwrap();
+            assert_eq!(Some(true), json["data"]["compressed"].as_bool());
+        }
+        _ => panic!("unexpected body: {:?}", body),
+    }
+
+    let data = api.touch_gzip_bytes().await?;
+    assert_eq!(&[0x1f, 0x8b], &data[0..2]);
+
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_auto_decompress_on() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let RawBody(body) = api.touch_gzip().await?;
+    log::debug!("body = {:?}", body);
+    assert!(matches!(body, ResponseBody::Json(_)));
+
+    Ok(())
+}
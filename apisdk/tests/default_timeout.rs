@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use apisdk::{
+    async_trait, send, ApiError, ApiResult, MockServer, Request, Responder, ResponseBody,
+};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// This responder is used to simulate a slow server
+struct SlowResponder(Duration);
+
+#[async_trait]
+impl Responder for SlowResponder {
+    async fn handle(&self, _req: Request) -> anyhow::Result<ResponseBody> {
+        tokio::time::sleep(self.0).await;
+        Ok(ResponseBody::Json(json!({})))
+    }
+}
+
+impl TheApi {
+    async fn touch_slow_mock(&self, timeout: Option<Duration>) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?;
+        let req = req.with_extension(MockServer::new(SlowResponder(Duration::from_millis(200))));
+        let req = match timeout {
+            Some(timeout) => req.timeout(timeout),
+            None => req,
+        };
+        send!(req).await
+    }
+
+    async fn touch_slow(&self) -> ApiResult<Value> {
+        let req = self.get("/path/slow").await?;
+        send!(req).await
+    }
+}
+
+#[tokio::test]
+async fn test_default_timeout_mock() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_default_timeout(Duration::from_millis(50))
+        .build();
+
+    let res = api.touch_slow_mock(None).await;
+    assert!(matches!(res, Err(ApiError::Timeout)));
+
+    // The per-request timeout overrides the default one
+    let res = api
+        .touch_slow_mock(Some(Duration::from_millis(1000)))
+        .await?;
+    assert_eq!(json!({}), res);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_default_timeout_server() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    // The server replies after 100ms
+    let api = TheApi::builder()
+        .with_default_timeout(Duration::from_millis(20))
+        .build();
+
+    let res = api.touch_slow().await;
+    match res {
+        Err(e) => assert!(matches!(e, ApiError::Timeout) && e.is_timeout()),
+        Ok(_) => panic!("the request should be timed out"),
+    }
+
+    let api = TheApi::builder()
+        .with_default_timeout(Duration::from_secs(5))
+        .build();
+    api.touch_slow().await?;
+
+    Ok(())
+}
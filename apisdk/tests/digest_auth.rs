@@ -0,0 +1,91 @@
+use std::sync::{Arc, Mutex};
+
+use apisdk::{
+    send, ApiError, ApiResult, ChallengeAuthMiddleware, CodeDataMessage, DigestAuth, Middleware,
+    MiddlewareError, Request, Response,
+};
+use async_trait::async_trait;
+use reqwest_middleware::Next;
+use serde_json::Value;
+use task_local_extensions::Extensions;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// Record the `Authorization` header of each attempt
+#[derive(Default, Clone)]
+struct RecordAuth {
+    attempts: Arc<Mutex<Vec<Option<String>>>>,
+}
+
+#[async_trait]
+impl Middleware for RecordAuth {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, MiddlewareError> {
+        let auth = req
+            .headers()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        self.attempts.lock().unwrap().push(auth);
+        next.run(req, extensions).await
+    }
+}
+
+fn build_api(password: &str) -> (TheApi, RecordAuth) {
+    let record = RecordAuth::default();
+    let api = TheApi::builder()
+        .with_middleware(ChallengeAuthMiddleware::new(DigestAuth::new(
+            "Mufasa", password,
+        )))
+        .with_middleware(record.clone())
+        .build();
+    (api, record)
+}
+
+#[tokio::test]
+async fn test_digest_auth() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let (api, record) = build_api("Circle of Life");
+
+    let req = api.get("/path/digest").await?;
+    let res: Value = send!(req, CodeDataMessage).await?;
+    assert_eq!("Mufasa", res["username"]);
+    assert_eq!("/v1/path/digest", res["uri"]);
+    assert_eq!("5ccc069c403ebaf9f0171e9517f40e41", res["opaque"]);
+
+    // Challenged at first, then retried with the credentials
+    let attempts = record.attempts.lock().unwrap().clone();
+    assert_eq!(2, attempts.len());
+    assert!(attempts[0].is_none());
+    let auth = attempts[1].clone().unwrap_or_default();
+    assert!(auth.starts_with("Digest username=\"Mufasa\", realm=\"apisdk\""));
+    assert!(auth.contains("nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\""));
+    assert!(auth.contains("qop=auth, nc=00000001"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_digest_auth_rejected() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let (api, record) = build_api("wrong password");
+
+    // Retried only once
+    let req = api.get("/path/digest").await?;
+    let res: ApiResult<Value> = send!(req, CodeDataMessage).await;
+    assert!(matches!(res, Err(ApiError::HttpClientStatus(401, _))));
+    assert_eq!(2, record.attempts.lock().unwrap().len());
+
+    Ok(())
+}
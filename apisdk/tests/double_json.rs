@@ -0,0 +1,75 @@
+use apisdk::{send, ApiResult, DoubleJsonDecode, MockServer, ResponseBody};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct Data {
+    a: i32,
+    b: Vec<String>,
+}
+
+fn double_encoded() -> MockServer {
+    MockServer::new(|_| {
+        // The body is `"{\"a\":1,\"b\":[\"x\"]}"`
+        let inner = json!({ "a": 1, "b": ["x"] }).to_string();
+        Ok(ResponseBody::Json(Value::String(inner)))
+    })
+}
+
+#[tokio::test]
+async fn test_double_json_decode() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_double_json_decode(true)
+        .with_initialiser(double_encoded())
+        .build();
+
+    let req = api.get("/path/json").await?;
+    let data: Data = send!(req).await?;
+    assert_eq!(1, data.a);
+    assert_eq!(vec!["x"], data.b);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_double_json_decode_single_request() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().with_initialiser(double_encoded()).build();
+
+    let req = api.get("/path/json").await?;
+    let data: Value = send!(req).await?;
+    assert!(data.is_string());
+
+    let req = api.get("/path/json").await?;
+    let req = req.with_extension(DoubleJsonDecode);
+    let data: Value = send!(req).await?;
+    assert_eq!(json!({ "a": 1, "b": ["x"] }), data);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_double_json_decode_keep_plain_string() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_double_json_decode(true)
+        .with_initialiser(MockServer::new(|_| {
+            Ok(ResponseBody::Json(Value::String("42".to_string())))
+        }))
+        .build();
+
+    let req = api.get("/path/json").await?;
+    let data: Value = send!(req).await?;
+    assert_eq!(json!("42"), data);
+
+    Ok(())
+}
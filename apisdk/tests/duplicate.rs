@@ -0,0 +1,49 @@
+use std::sync::{Arc, Mutex};
+
+use apisdk::{send, ApiResult, DuplicateOps, MockServer, RequestId, ResponseBody};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[tokio::test]
+async fn test_duplicate_json() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let bodies = Arc::new(Mutex::new(vec![]));
+    let captured = bodies.clone();
+    let mock = MockServer::new(move |req: apisdk::Request| {
+        let body = req.body().and_then(|b| b.as_bytes()).unwrap_or_default();
+        captured.lock().unwrap().push(body.to_vec());
+        Ok(ResponseBody::Json(json!({"ok": true})))
+    });
+
+    let mut req = api
+        .post("/path/echo")
+        .await?
+        .json(&json!({"id": 1, "name": "n"}))
+        .with_extension(mock)
+        .with_extension(RequestId::new("req-1"));
+    let mut dup = req.try_duplicate().expect("json body should be cloneable");
+    assert_eq!(
+        "req-1",
+        dup.extensions().get::<RequestId>().unwrap().request_id
+    );
+
+    let res: Value = send!(req).await?;
+    assert_eq!(json!({"ok": true}), res);
+    let res: Value = send!(dup).await?;
+    assert_eq!(json!({"ok": true}), res);
+
+    let bodies = bodies.lock().unwrap();
+    assert_eq!(2, bodies.len());
+    assert!(!bodies[0].is_empty());
+    assert_eq!(bodies[0], bodies[1]);
+
+    Ok(())
+}
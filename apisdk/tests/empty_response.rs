@@ -0,0 +1,70 @@
+use apisdk::{send, ApiError, ApiResult, MockServer, ResponseBody, StatusCode};
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[tokio::test]
+async fn test_empty_response() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    // The empty 200 is not decoded as json, though its content-type is json
+    let req = api.get("/path/empty").await?;
+    let _: () = send!(req).await?;
+
+    let req = api.get("/path/empty").await?;
+    let res: Option<Value> = send!(req, Json).await?;
+    assert!(res.is_none());
+
+    let req = api.get("/path/empty").await?;
+    let res: String = send!(req, Text).await?;
+    assert_eq!("", res);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_empty_chunked_response() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    // There is no content-length for chunked body, though its content-type is json
+    for status in [200, 202] {
+        let req = api.get(format!("/path/empty-chunked/{}", status)).await?;
+        let empty: bool = send!(req, |body| Ok(matches!(body, ResponseBody::Empty))).await?;
+        assert!(empty);
+
+        let req = api.get(format!("/path/empty-chunked/{}", status)).await?;
+        let res: Option<Value> = send!(req, Json).await?;
+        assert!(res.is_none());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_content() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(
+            MockServer::new(|_| Ok(ResponseBody::Empty)).with_status(StatusCode::NO_CONTENT),
+        )
+        .build();
+
+    let req = api.delete("/users/1").await?;
+    let _: () = send!(req).await?;
+
+    let req = api.delete("/users/1").await?;
+    let res: ApiResult<Vec<Value>> = send!(req, Json).await;
+    assert!(matches!(res, Err(ApiError::DecodeJson(..))));
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::{net::SocketAddr, path::PathBuf};
+
+use apisdk::{http_api, send, ApiEndpoint, ApiError, ApiResult, CodeDataMessage, Method};
+use serde_json::{json, Value};
+use warp::Filter;
+
+use crate::common::{init_logger, start_server};
+
+#[allow(unused)]
+mod common;
+
+#[http_api("http://gateway/v1")]
+#[derive(Debug, Clone)]
+pub struct GatewayApi;
+
+impl GatewayApi {
+    async fn host(&self) -> ApiResult<String> {
+        let req = self.get("/path/json").await?;
+        let data: Value = send!(req, CodeDataMessage).await?;
+        Ok(data["headers"]["host"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_endpoint_host_header() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let endpoint = ApiEndpoint::new(([127, 0, 0, 1], 3030)).with_host_header("api.example.com");
+    let api = GatewayApi::builder().with_resolver(endpoint).build();
+    assert_eq!("api.example.com", api.host().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_endpoint_sni() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let endpoint = ApiEndpoint::new(([127, 0, 0, 1], 3030)).with_sni("api.internal");
+    let api = GatewayApi::builder().with_resolver(endpoint).build();
+    assert_eq!("api.internal:3030", api.host().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_endpoint_allowed_methods() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let replica = ApiEndpoint::new(([127, 0, 0, 1], 3030)).with_allowed_methods([Method::GET]);
+    let api = GatewayApi::builder().with_resolver(replica).build();
+    assert_eq!("gateway:3030", api.host().await?);
+
+    let res = api.post("/path/json").await;
+    match res {
+        Err(ApiError::MethodNotAllowedByRouter(method)) => assert_eq!(Method::POST, method),
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+
+    Ok(())
+}
+
+fn cert_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("certs")
+        .join(name)
+}
+
+/// Start a TLS server for `localhost`, which replies the `Host` header
+fn start_tls_server() -> SocketAddr {
+    let routes = warp::path!("v1" / "host")
+        .and(warp::header::<String>("host"))
+        .map(|host: String| warp::reply::json(&json!({ "host": host })));
+    let (addr, server) = warp::serve(routes)
+        .tls()
+        .cert_path(cert_path("server.pem"))
+        .key_path(cert_path("server.key"))
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    addr
+}
+
+#[tokio::test]
+async fn test_endpoint_tls_sni_and_host_header() -> ApiResult<()> {
+    init_logger();
+    let addr = start_tls_server();
+
+    // The certificate is issued to `localhost`, so the handshake requires the SNI
+    let endpoint = ApiEndpoint::new(addr)
+        .with_sni("localhost")
+        .with_host_header("api.example.com");
+    let api = GatewayApiBuilder::new("https://gateway/v1")
+        .with_ca_bundle_path(cert_path("bundle.pem"))?
+        .with_resolver(endpoint)
+        .build();
+
+    let req = api.get("/host").await?;
+    let res: Value = send!(req).await?;
+    assert_eq!(json!({"host": "api.example.com"}), res);
+
+    // Without SNI, the certificate doesn't match `gateway`
+    let api = GatewayApiBuilder::new("https://gateway/v1")
+        .with_ca_bundle_path(cert_path("bundle.pem"))?
+        .with_resolver(ApiEndpoint::new(addr).with_host_header("api.example.com"))
+        .build();
+
+    let req = api.get("/host").await?;
+    let res: ApiResult<Value> = send!(req).await;
+    assert!(res.is_err());
+
+    Ok(())
+}
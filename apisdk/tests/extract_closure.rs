@@ -0,0 +1,113 @@
+use apisdk::{api_method, send, send_json, ApiError, ApiResult, MockServer, ResponseBody};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, PartialEq)]
+struct Quota {
+    used: u64,
+    limit: u64,
+}
+
+/// The quirky convention: `{"status": "ok" | "fail", "payload": ..., "reason": ...}`
+fn quirky_mock() -> MockServer {
+    MockServer::new(|req: apisdk::Request| {
+        let body = if req.url().path().ends_with("/ok") {
+            json!({"status": "ok", "payload": {"used": 3, "limit": 10}})
+        } else {
+            json!({"status": "fail", "reason": "quota exceeded"})
+        };
+        Ok(ResponseBody::Json(body))
+    })
+}
+
+/// Map the quirky body to Quota
+fn extract_quota(body: ResponseBody) -> ApiResult<Quota> {
+    let json: Value = body.parse_json()?;
+    match json["status"].as_str() {
+        Some("ok") => Ok(Quota {
+            used: json["payload"]["used"].as_u64().unwrap_or_default(),
+            limit: json["payload"]["limit"].as_u64().unwrap_or_default(),
+        }),
+        _ => Err(ApiError::ServiceError(
+            -1,
+            json["reason"].as_str().map(|r| r.to_string()),
+        )),
+    }
+}
+
+impl TheApi {
+    async fn quota(&self, path: &str) -> ApiResult<Quota> {
+        let req = self.get(path).await?.with_extension(quirky_mock());
+        send!(req, |body| extract_quota(body)).await
+    }
+
+    #[api_method(log = "off")]
+    async fn quota_with_method(&self, path: &str) -> ApiResult<Quota> {
+        let req = self.get(path).await?.with_extension(quirky_mock());
+        send!(req, |body| extract_quota(body)).await
+    }
+
+    async fn echo_name(&self) -> ApiResult<String> {
+        let req = self.post("/path/echo").await?;
+        let field = "name".to_string();
+        send_json!(req, json!({"name": "apisdk"}), move |body: ResponseBody| {
+            let json: Value = body.parse_json()?;
+            json["data"][&field]
+                .as_str()
+                .map(|s| s.to_string())
+                .ok_or(ApiError::IllegalJson(json))
+        })
+        .await
+    }
+}
+
+#[tokio::test]
+async fn test_extract_closure() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let quota = api.quota("/quota/ok").await?;
+    assert_eq!(Quota { used: 3, limit: 10 }, quota);
+
+    let res = api.quota("/quota/fail").await;
+    match res {
+        Err(ApiError::ServiceError(-1, Some(reason))) => assert_eq!("quota exceeded", reason),
+        _ => panic!("unexpected result: {:?}", res),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_extract_closure_with_api_method() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let quota = api.quota_with_method("/quota/ok").await?;
+    assert_eq!(Quota { used: 3, limit: 10 }, quota);
+
+    let res = api.quota_with_method("/quota/fail").await;
+    assert!(matches!(res, Err(ApiError::ServiceError(-1, _))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_extract_closure_with_payload() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    assert_eq!("apisdk", api.echo_name().await?);
+
+    Ok(())
+}
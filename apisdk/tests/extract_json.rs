@@ -1,4 +1,4 @@
-use apisdk::{send, ApiError, ApiResult, CodeDataMessage, JsonExtractor};
+use apisdk::{send, ApiError, ApiResult, CodeDataMessage, JsonExtractor, WithHeaders};
 use serde::Deserialize;
 use serde_json::Value;
 
@@ -53,6 +53,18 @@ impl JsonExtractor for NoHeaders {
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct Reply {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplyHeaders {
+    #[serde(rename = "content-type")]
+    content_type: String,
+}
+
 impl TheApi {
     async fn get_json_2_string(&self) -> ApiResult<String> {
         let req = self.get("/path/json").await?;
@@ -88,6 +100,16 @@ impl TheApi {
         let req = self.get("/path/json").await?;
         send!(req, NoHeaders).await
     }
+
+    async fn extract_with_headers(&self) -> ApiResult<WithHeaders<Reply>> {
+        let req = self.get("/path/json").await?;
+        send!(req, WithHeaders).await
+    }
+
+    async fn extract_body_with_headers(&self) -> ApiResult<WithHeaders<Value>> {
+        let req = self.get("/path/json").await?;
+        send!(req, Body).await
+    }
 }
 
 #[tokio::test]
@@ -175,3 +197,27 @@ async fn test_extract_custom() -> ApiResult<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_extract_with_headers() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let res = api.extract_with_headers().await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!(0, res.body.code);
+    assert_eq!("OK", res.body.message);
+    assert_eq!(Some("application/json"), res.get_header("Content-Type"));
+
+    let headers: ReplyHeaders = res.parse_headers()?;
+    assert_eq!("application/json", headers.content_type);
+
+    let res = api.extract_body_with_headers().await?;
+    log::debug!("res = {:?}", res);
+    assert!(res.body.get("__headers__").is_none());
+    assert!(res.get_header("content-length").is_some());
+
+    Ok(())
+}
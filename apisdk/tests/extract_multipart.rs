@@ -0,0 +1,38 @@
+use apisdk::{send, ApiResult, ResponsePart};
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn get_related(&self) -> ApiResult<Vec<ResponsePart>> {
+        let req = self.get("/path/related").await?;
+        send!(req, Body).await
+    }
+}
+
+#[tokio::test]
+async fn test_extract_multipart_related() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let parts = api.get_related().await?;
+    log::debug!("parts = {:?}", parts);
+    assert_eq!(2, parts.len());
+
+    assert_eq!(
+        Some("application/json"),
+        parts[0].get_header("Content-Type")
+    );
+    let json: Value = parts[0].parse_json()?;
+    assert_eq!("world", json["data"]["hello"]);
+
+    assert_eq!(Some("<attachment>"), parts[1].get_header("Content-ID"));
+    assert_eq!(b"binary\r\ncontent".to_vec(), parts[1].body);
+
+    Ok(())
+}
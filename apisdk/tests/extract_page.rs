@@ -0,0 +1,54 @@
+use apisdk::{send, ApiResult, CodeDataMessage, MockServer, Paginated, ResponseBody};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Deserialize)]
+pub struct User {
+    pub id: u32,
+    pub name: String,
+}
+
+impl TheApi {
+    async fn list_users(&self) -> ApiResult<Paginated<User>> {
+        let req = self.get("/path/json").await?;
+        let req = req.with_extension(MockServer::new(|_| {
+            Ok(ResponseBody::Json(json!({
+                "code": 0,
+                "message": "OK",
+                "data": {
+                    "items": [
+                        {"id": 1, "name": "alice"},
+                        {"id": 2, "name": "bob"}
+                    ],
+                    "total": 5,
+                    "page": 1,
+                    "page_size": 2
+                }
+            })))
+        }));
+        send!(req, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_extract_paginated() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.list_users().await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!(2, res.items.len());
+    assert_eq!("bob", res.items[1].name);
+    assert_eq!(Some(5), res.total);
+    assert_eq!(Some(1), res.page);
+    assert!(res.has_more);
+
+    Ok(())
+}
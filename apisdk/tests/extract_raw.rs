@@ -0,0 +1,74 @@
+use apisdk::{send, ApiResult, CodeDataMessage, MockServer, Raw, ResponseBody};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct User {
+    id: i64,
+    name: String,
+}
+
+fn reply(payload: Value) -> MockServer {
+    MockServer::new(move |_| Ok(ResponseBody::Json(payload.clone())))
+}
+
+#[tokio::test]
+async fn test_extract_raw() -> ApiResult<()> {
+    init_logger();
+
+    let payload = json!({ "id": 1, "name": "alice", "extra": { "tags": ["a", "b"] } });
+    let api = TheApi::builder()
+        .with_initialiser(reply(payload.clone()))
+        .build();
+
+    let req = api.get("/users/1").await?;
+    let res: Raw<User> = send!(req).await?;
+    assert_eq!(1, res.parsed.id);
+    assert_eq!("alice", res.parsed.name);
+    assert_eq!(payload, res.raw);
+    assert_eq!(res.parsed.id, res.raw["id"]);
+    assert_eq!(res.parsed.name, res.raw["name"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_extract_raw_with_extractor() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(reply(json!({
+            "code": 0,
+            "data": { "id": 2, "name": "bob", "age": 30 }
+        })))
+        .build();
+
+    let req = api.get("/users/2").await?;
+    let res: Raw<User> = send!(req, CodeDataMessage).await?;
+    let (user, raw) = res.into_parts();
+    assert_eq!(2, user.id);
+    assert_eq!("bob", user.name);
+    assert_eq!(json!({ "id": 2, "name": "bob", "age": 30 }), raw);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_extract_raw_mismatch() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(reply(json!({ "id": "1" })))
+        .build();
+
+    let req = api.get("/users/1").await?;
+    let res: ApiResult<Raw<User>> = send!(req).await;
+    assert!(res.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use std::time::{Duration, Instant};
+
+use apisdk::{
+    send, send_json, ApiError, ApiResult, Fault, FaultInjectionMiddleware, FaultRule, Matcher,
+    Method, TransportError,
+};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn touch(&self) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?;
+        send!(req).await
+    }
+
+    async fn echo(&self) -> ApiResult<Value> {
+        let req = self.post("/path/echo").await?;
+        send_json!(req, json!({"name": "apisdk"})).await
+    }
+}
+
+#[tokio::test]
+async fn test_fault_error_on_path() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let faults = FaultInjectionMiddleware::new(0)
+        .with_rule(FaultRule::new(Fault::Status(503)).with_path("/path/json"));
+    let api = TheApi::builder().with_middleware(faults).build();
+
+    let res = api.touch().await;
+    match res {
+        Err(ApiError::HttpServerStatus(503, _)) => {}
+        _ => panic!("unexpected result: {:?}", res),
+    }
+
+    // Other paths are not affected
+    let res = api.echo().await?;
+    assert_eq!(json!({"name": "apisdk"}), res["data"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fault_transport_error() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let faults = FaultInjectionMiddleware::new(0).with_rule(
+        FaultRule::new(Fault::Transport(TransportError::ConnectionReset))
+            .with_matcher(Matcher::new().method(Method::POST)),
+    );
+    let api = TheApi::builder().with_middleware(faults).build();
+
+    let res = api.echo().await;
+    assert!(matches!(
+        res,
+        Err(ApiError::Connection(TransportError::ConnectionReset))
+    ));
+    api.touch().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fault_delay() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let faults = FaultInjectionMiddleware::new(0)
+        .with_rule(FaultRule::new(Fault::Delay(Duration::from_millis(200))));
+    let api = TheApi::builder().with_middleware(faults).build();
+
+    let start = Instant::now();
+    api.touch().await?;
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fault_deterministic() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    async fn failures(seed: u64) -> ApiResult<Vec<bool>> {
+        let faults = FaultInjectionMiddleware::new(seed)
+            .with_rule(FaultRule::new(Fault::Status(500)).with_probability(0.5));
+        let api = TheApi::builder().with_middleware(faults).build();
+        let mut failures = vec![];
+        for _ in 0..16 {
+            failures.push(api.touch().await.is_err());
+        }
+        Ok(failures)
+    }
+
+    let first = failures(42).await?;
+    assert_eq!(first, failures(42).await?);
+    assert!(first.iter().any(|f| *f));
+    assert!(first.iter().any(|f| !*f));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fault_nan_probability() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let faults = FaultInjectionMiddleware::new(42)
+        .with_rule(FaultRule::new(Fault::Status(500)).with_probability(f64::NAN));
+    let api = TheApi::builder().with_middleware(faults).build();
+    api.touch().await?;
+
+    Ok(())
+}
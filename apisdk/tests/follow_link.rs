@@ -0,0 +1,81 @@
+use apisdk::{send, ApiError, ApiResult, MockServer, Request, ResponseBody};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// Reply the url of request
+fn echo_url() -> MockServer {
+    MockServer::new(|req: Request| Ok(ResponseBody::Json(json!({ "url": req.url().as_str() }))))
+}
+
+impl TheApi {
+    async fn follow(&self, body: &Value, pointer: &str) -> ApiResult<String> {
+        let req = self.follow_link(body, pointer).await?;
+        let res: Value = send!(req).await?;
+        Ok(res["url"].as_str().unwrap_or_default().to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_follow_link() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().with_initialiser(echo_url()).build();
+
+    let body = json!({
+        "items": [],
+        "_links": {
+            "next": { "href": "users?page=2" },
+            "first": { "href": "/v1/users?page=1" },
+            "docs": { "href": "https://docs.example.com/users#list" },
+        }
+    });
+    assert_eq!(
+        "http://localhost:3030/v1/users?page=2",
+        api.follow(&body, "/_links/next/href").await?
+    );
+    assert_eq!(
+        "http://localhost:3030/v1/users?page=1",
+        api.follow(&body, "/_links/first/href").await?
+    );
+    assert_eq!(
+        "https://docs.example.com/users",
+        api.follow(&body, "/_links/docs/href").await?
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_follow_link_with_content_location() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().with_initialiser(echo_url()).build();
+
+    let body = json!({
+        "_links": { "owner": { "href": "../accounts/7" } },
+        "__headers__": { "content-location": "/v1/orders/42" },
+    });
+    assert_eq!(
+        "http://localhost:3030/v1/accounts/7",
+        api.follow(&body, "/_links/owner/href").await?
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_follow_link_missing() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().build();
+
+    let body = json!({ "_links": {} });
+    let res = api.follow_link(&body, "/_links/next/href").await;
+    assert!(matches!(res, Err(ApiError::InvalidLink(..))));
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use apisdk::{send, send_form, ApiError, ApiResult, MockServer, Request, ResponseBody};
+use serde::Deserialize;
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct Token {
+    access_token: String,
+    token_type: String,
+    expires_in: u64,
+    scope: Option<String>,
+}
+
+/// Reply the token as a legacy OAuth token endpoint
+fn token_endpoint() -> MockServer {
+    MockServer::new(|_: Request| {
+        Ok(ResponseBody::Bytes {
+            content_type: "application/x-www-form-urlencoded; charset=utf-8".to_string(),
+            data: b"access_token=abc%2F123&token_type=bearer&expires_in=3600".to_vec(),
+            content_encoding: None,
+        })
+    })
+}
+
+#[tokio::test]
+async fn test_form_response() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().with_initialiser(token_endpoint()).build();
+
+    let req = api.post("/oauth/token").await?;
+    let form = vec![("grant_type", "client_credentials")];
+    let token: Token = send_form!(req, form, Form).await?;
+    assert_eq!("abc/123", token.access_token);
+    assert_eq!("bearer", token.token_type);
+    assert_eq!(3600, token.expires_in);
+    assert!(token.scope.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_form_response_mismatch() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().with_initialiser(token_endpoint()).build();
+
+    let req = api.get("/oauth/token").await?;
+    let res: ApiResult<Vec<u64>> = send!(req, Form).await;
+    assert!(matches!(res, Err(ApiError::DecodeForm(..))));
+
+    Ok(())
+}
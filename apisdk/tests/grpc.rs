@@ -0,0 +1,59 @@
+use apisdk::{send, ApiError, ApiResult, GrpcCode, GrpcWebJson};
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn grpc_error(&self, grpc: bool) -> ApiResult<Value> {
+        let req = self.get("/path/grpc-error").await?;
+        let req = if grpc {
+            req.with_extension(GrpcWebJson)
+        } else {
+            req
+        };
+        send!(req).await
+    }
+}
+
+#[tokio::test]
+async fn test_grpc_status() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let e = api.grpc_error(true).await.unwrap_err();
+    log::debug!("e = {:?}", e);
+    match e {
+        ApiError::Grpc {
+            code,
+            ref message,
+            ref details,
+        } => {
+            assert_eq!(GrpcCode::NotFound, code);
+            assert_eq!("NOT_FOUND", code.as_str());
+            assert_eq!("user not found", message);
+            assert_eq!(1, details.len());
+        }
+        _ => panic!("unexpected error: {:?}", e),
+    }
+    assert_eq!(404, e.as_error_code());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_grpc_status_disabled() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let e = api.grpc_error(false).await.unwrap_err();
+    assert!(matches!(e, ApiError::HttpClientStatus(404, _)));
+
+    Ok(())
+}
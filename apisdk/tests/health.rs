@@ -0,0 +1,51 @@
+use apisdk::{http_api, ApiError, ApiResult};
+
+use crate::common::{init_logger, start_server};
+
+#[allow(unused)]
+mod common;
+
+#[http_api("http://localhost:3030/v1", health)]
+#[derive(Debug, Clone)]
+pub struct HealthApi;
+
+#[tokio::test]
+async fn test_health_ok() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = HealthApi::default();
+
+    assert!(api.health("/health").await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_health_unavailable() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = HealthApi::default();
+
+    assert!(!api.health("/unavailable").await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_health_unreachable() -> ApiResult<()> {
+    init_logger();
+
+    let api = HealthApi::builder()
+        .with_rewriter(|mut url: apisdk::Url| {
+            url.set_port(Some(1)).ok();
+            Ok(url)
+        })
+        .build();
+
+    let e = api.health("/health").await.unwrap_err();
+    assert!(matches!(e, ApiError::Reqwest(_)));
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use apisdk::{
+    async_trait, send, send_json, ApiResult, HashedTokenAuth, HedgingMiddleware, ManualClock,
+    MockServer, ParsedHashedToken, Request, Responder, ResponseBody,
+};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// The port of the second endpoint
+const FAST_PORT: u16 = 3031;
+
+/// Set the flag when the slow request is dropped before completion
+struct CancelGuard {
+    cancelled: Arc<AtomicBool>,
+    completed: bool,
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.cancelled.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+/// The primary endpoint is slow, and the second endpoint is fast
+#[derive(Default)]
+struct Endpoints {
+    hits: Arc<AtomicUsize>,
+    cancelled: Arc<AtomicBool>,
+    tokens: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl Responder for Endpoints {
+    async fn handle(&self, req: Request) -> anyhow::Result<ResponseBody> {
+        self.hits.fetch_add(1, Ordering::SeqCst);
+        if let Some(auth) = req.headers().get("authorization") {
+            let token = auth.to_str()?.trim_start_matches("Bearer ").to_string();
+            self.tokens.lock().unwrap().push(token);
+        }
+        if req.url().port() == Some(FAST_PORT) {
+            return Ok(ResponseBody::Json(json!({ "endpoint": "fast" })));
+        }
+        let mut guard = CancelGuard {
+            cancelled: self.cancelled.clone(),
+            completed: false,
+        };
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        guard.completed = true;
+        Ok(ResponseBody::Json(json!({ "endpoint": "slow" })))
+    }
+}
+
+fn build_api(endpoints: Endpoints) -> TheApi {
+    let fast = SocketAddr::from((Ipv4Addr::LOCALHOST, FAST_PORT));
+    TheApi::builder()
+        .with_middleware(HedgingMiddleware::new(Duration::from_millis(50), fast))
+        .with_initialiser(MockServer::new(endpoints))
+        .build()
+}
+
+#[tokio::test]
+async fn test_hedge_fast_wins() -> ApiResult<()> {
+    init_logger();
+
+    let endpoints = Endpoints::default();
+    let hits = endpoints.hits.clone();
+    let cancelled = endpoints.cancelled.clone();
+    let api = build_api(endpoints);
+
+    let start = Instant::now();
+    let req = api.get("/path/json").await?;
+    let res: Value = send!(req).await?;
+    assert_eq!("fast", res["endpoint"]);
+    assert!(start.elapsed() < Duration::from_millis(400));
+    assert_eq!(2, hits.load(Ordering::SeqCst));
+    assert!(cancelled.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_hedge_skip_non_idempotent() -> ApiResult<()> {
+    init_logger();
+
+    let endpoints = Endpoints::default();
+    let hits = endpoints.hits.clone();
+    let cancelled = endpoints.cancelled.clone();
+    let api = build_api(endpoints);
+
+    let req = api.post("/path/json").await?;
+    let res: Value = send_json!(req, json!({})).await?;
+    assert_eq!("slow", res["endpoint"]);
+    assert_eq!(1, hits.load(Ordering::SeqCst));
+    assert!(!cancelled.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_hedge_keep_clock() -> ApiResult<()> {
+    init_logger();
+
+    let endpoints = Endpoints::default();
+    let tokens = endpoints.tokens.clone();
+    let fast = SocketAddr::from((Ipv4Addr::LOCALHOST, FAST_PORT));
+    let api = TheApi::builder()
+        .with_clock(ManualClock::new(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000),
+        ))
+        .with_authenticator(HashedTokenAuth::new("app_id", "app_secret"))
+        .with_middleware(HedgingMiddleware::new(Duration::from_millis(50), fast))
+        .with_initialiser(MockServer::new(endpoints))
+        .build();
+
+    let req = api.get("/path/json").await?;
+    let res: Value = send!(req).await?;
+    assert_eq!("fast", res["endpoint"]);
+
+    // Both the primary and the hedged request are signed by the manual clock
+    let tokens = tokens.lock().unwrap().clone();
+    assert_eq!(2, tokens.len());
+    for token in tokens {
+        let parsed = ParsedHashedToken::parse(&token).unwrap();
+        assert_eq!(1700000000, parsed.timestamp);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::{
+    net::Ipv4Addr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use apisdk::{http_api, send, ApiResult, CodeDataMessage, SocketAddrs};
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server};
+
+#[allow(unused)]
+mod common;
+
+#[http_api("http://api.example.com:3030/v1")]
+#[derive(Debug, Clone)]
+pub struct ExampleApi;
+
+impl ExampleApi {
+    async fn host(&self) -> ApiResult<String> {
+        let req = self.get("/path/json").await?;
+        let data: Value = send!(req, CodeDataMessage).await?;
+        Ok(data["headers"]["host"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_host_override() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = ExampleApi::builder()
+        .with_host_override("api.example.com", (Ipv4Addr::LOCALHOST, 0))
+        .build();
+    assert_eq!("api.example.com:3030", api.host().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_host_override_with_resolver() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    // The override takes precedence over DnsResolver
+    let resolved = Arc::new(AtomicUsize::new(0));
+    let counter = resolved.clone();
+    let api = ExampleApi::builder()
+        .with_resolver(move |_: &str| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            None::<SocketAddrs>
+        })
+        .with_host_override("api.example.com", (Ipv4Addr::LOCALHOST, 0))
+        .build();
+    assert_eq!("api.example.com:3030", api.host().await?);
+    assert_eq!(0, resolved.load(Ordering::SeqCst));
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::sync::{Arc, Mutex};
+
+use apisdk::{ApiResult, Initialiser, RequestBuilder};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// This initialiser records its name when applied
+struct Record {
+    name: &'static str,
+    records: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Record {
+    fn new(name: &'static str, records: &Arc<Mutex<Vec<&'static str>>>) -> Self {
+        Self {
+            name,
+            records: records.clone(),
+        }
+    }
+}
+
+impl Initialiser for Record {
+    fn init(&self, req: RequestBuilder) -> RequestBuilder {
+        self.records.lock().unwrap().push(self.name);
+        req
+    }
+}
+
+#[tokio::test]
+async fn test_initialiser_order() -> ApiResult<()> {
+    init_logger();
+
+    let records = Arc::new(Mutex::new(vec![]));
+    let api = TheApi::builder()
+        .with_initialiser(Record::new("first", &records))
+        .with_initialisers(vec![
+            Arc::new(Record::new("second", &records)) as Arc<dyn Initialiser>,
+            Arc::new(Record::new("third", &records)),
+        ])
+        .build();
+
+    let _ = api.get("/path/json").await?;
+    assert_eq!(vec!["first", "second", "third"], *records.lock().unwrap());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_initialiser_priority() -> ApiResult<()> {
+    init_logger();
+
+    let records = Arc::new(Mutex::new(vec![]));
+    let api = TheApi::builder()
+        .with_initialiser(Record::new("default", &records))
+        .with_initialiser_priority(Record::new("late", &records), 10)
+        .with_initialiser_priority(Record::new("early", &records), -10)
+        .build();
+
+    let _ = api.get("/path/json").await?;
+    assert_eq!(vec!["early", "default", "late"], *records.lock().unwrap());
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use apisdk::{http_api, send, ApiError, ApiResult, IpVersion, SocketAddrs};
+use serde_json::{json, Value};
+use warp::Filter;
+
+use crate::common::init_logger;
+
+#[allow(unused)]
+mod common;
+
+#[http_api("http://dualhost/v1")]
+#[derive(Debug, Clone)]
+pub struct DualApi;
+
+impl DualApi {
+    async fn remote(&self) -> ApiResult<IpAddr> {
+        let req = self.get("/remote").await?;
+        let res: Value = send!(req).await?;
+        res["remote"]
+            .as_str()
+            .and_then(|s| s.parse().ok())
+            .ok_or(ApiError::IllegalJson(res))
+    }
+}
+
+/// Start a dual-stack server, which replies the remote address
+fn start_dual_stack_server() -> SocketAddr {
+    let routes =
+        warp::path!("v1" / "remote")
+            .and(warp::addr::remote())
+            .map(|remote: Option<SocketAddr>| {
+                let remote = remote.map(|r| r.ip().to_canonical().to_string());
+                warp::reply::json(&json!({ "remote": remote }))
+            });
+    let (addr, server) = warp::serve(routes).bind_ephemeral((Ipv6Addr::UNSPECIFIED, 0));
+    tokio::spawn(server);
+    addr
+}
+
+/// Build api, whose host is resolved to both `127.0.0.1` and `::1`
+fn build_api(port: u16, ip_version: IpVersion) -> DualApi {
+    DualApi::builder()
+        .with_resolver(move |_: &str| {
+            Some(SocketAddrs::new_multi(vec![
+                SocketAddr::from((Ipv6Addr::LOCALHOST, port)),
+                SocketAddr::from((Ipv4Addr::LOCALHOST, port)),
+            ]))
+        })
+        .with_rewriter(move |mut url: apisdk::Url| {
+            let _ = url.set_port(Some(port));
+            Ok(url)
+        })
+        .with_ip_version_preference(ip_version)
+        .build()
+}
+
+#[tokio::test]
+async fn test_ip_version_v4() -> ApiResult<()> {
+    init_logger();
+    let addr = start_dual_stack_server();
+
+    let api = build_api(addr.port(), IpVersion::V4);
+    assert!(api.remote().await?.is_ipv4());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ip_version_v6() -> ApiResult<()> {
+    init_logger();
+    let addr = start_dual_stack_server();
+
+    let api = build_api(addr.port(), IpVersion::V6);
+    assert!(api.remote().await?.is_ipv6());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ip_version_dual_stack() -> ApiResult<()> {
+    init_logger();
+    let addr = start_dual_stack_server();
+
+    // The first resolved address (IPv6) is preferred
+    let api = build_api(addr.port(), IpVersion::DualStack);
+    assert!(api.remote().await?.is_ipv6());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ip_version_not_resolved() -> ApiResult<()> {
+    init_logger();
+
+    let api = DualApi::builder()
+        .with_resolver(|_: &str| Some(SocketAddrs::from((Ipv4Addr::LOCALHOST, 80))))
+        .with_ip_version_preference(IpVersion::V6)
+        .build();
+    let res = api.remote().await;
+    assert!(matches!(res, Err(ApiError::Reqwest(ref e)) if e.is_connect()));
+
+    Ok(())
+}
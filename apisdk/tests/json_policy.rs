@@ -0,0 +1,110 @@
+use apisdk::{send_json, ApiResult, CodeDataMessage, JsonPolicy, RenameRule};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Serialize)]
+pub struct UserInfo {
+    pub user_name: String,
+    pub nick_name: Option<String>,
+    pub home_address: Address,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Address {
+    pub street_name: String,
+}
+
+impl TheApi {
+    async fn echo(&self, user: &UserInfo) -> ApiResult<Value> {
+        let req = self.post("/path/echo").await?;
+        send_json!(req, user, CodeDataMessage).await
+    }
+
+    async fn echo_skip_nulls(&self, user: &UserInfo) -> ApiResult<Value> {
+        let req = self.post("/path/echo").await?;
+        let req = req.with_extension(JsonPolicy::new().skip_nulls());
+        send_json!(req, user, CodeDataMessage).await
+    }
+}
+
+fn user() -> UserInfo {
+    UserInfo {
+        user_name: "name".to_string(),
+        nick_name: None,
+        home_address: Address {
+            street_name: "street".to_string(),
+        },
+    }
+}
+
+#[tokio::test]
+async fn test_json_policy_default() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.echo(&user()).await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!(
+        json!({
+            "user_name": "name",
+            "nick_name": null,
+            "home_address": {"street_name": "street"}
+        }),
+        res
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_json_policy_camel_case() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder()
+        .with_json_policy(
+            JsonPolicy::new()
+                .rename_all(RenameRule::CamelCase)
+                .skip_nulls(),
+        )
+        .build();
+
+    let res = api.echo(&user()).await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!(
+        json!({
+            "userName": "name",
+            "homeAddress": {"streetName": "street"}
+        }),
+        res
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_json_policy_per_request() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.echo_skip_nulls(&user()).await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!(
+        json!({
+            "user_name": "name",
+            "home_address": {"street_name": "street"}
+        }),
+        res
+    );
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+use apisdk::{http_api, send, ApiError, ApiResult};
+use serde_json::Value;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::common::init_logger;
+
+#[allow(unused)]
+mod common;
+
+/// The port of the server, which replies the address of peer
+const PEER_PORT: u16 = 3032;
+
+#[http_api("http://127.0.0.1:3032/v1")]
+#[derive(Debug, Clone)]
+pub struct PeerApi;
+
+impl PeerApi {
+    async fn peer(&self) -> ApiResult<String> {
+        let req = self.get("/peer").await?;
+        let data: Value = send!(req).await?;
+        Ok(data["peer"].as_str().unwrap_or_default().to_string())
+    }
+}
+
+/// Start the server, which replies the IP address of each connection
+async fn start_peer_server() {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, PEER_PORT))
+        .await
+        .unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut stream, peer)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                let body = format!(r#"{{"peer":"{}"}}"#, peer.ip());
+                let res = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(res.as_bytes()).await;
+            });
+        }
+    });
+}
+
+#[tokio::test]
+async fn test_local_address() -> ApiResult<()> {
+    init_logger();
+    start_peer_server().await;
+
+    // The whole 127.0.0.0/8 is bound to loopback interface
+    let addr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2));
+    let api = PeerApi::builder().with_local_address(addr).build();
+    assert_eq!("127.0.0.2", api.peer().await?);
+
+    // The address which doesn't belong to this host
+    let addr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    let api = PeerApi::builder().with_local_address(addr).build();
+    let res = api.peer().await;
+    assert!(matches!(res, Err(ApiError::Reqwest(e)) if e.is_connect()));
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+#![cfg(not(feature = "tracing"))]
+
+use std::{sync::Mutex, time::Duration};
+
+use apisdk::{
+    send, ApiResult, ChallengeAuthMiddleware, CodeDataMessage, DigestAuth, LogConfig, MockServer,
+    RequestId, RespondAsyncOps, ResponseBody, RetryMiddleware, RetryPolicy, StatusCode,
+};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::{json, Value};
+
+use crate::common::{start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// This logger is used to capture all messages
+struct CaptureLogger {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.lines.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger {
+    lines: Mutex::new(vec![]),
+};
+
+/// Count the responses logged for the request
+/// - request_id: the id of request
+/// - kind: `Response (MOCK)` for mocked responses, or `Response {` for real ones
+fn count_responses(request_id: &str, kind: &str) -> usize {
+    let prefix = format!("#[{}] {}", request_id, kind);
+    let lines = LOGGER.lines.lock().unwrap();
+    lines
+        .iter()
+        .filter(|line| line.starts_with(&prefix))
+        .count()
+}
+
+impl TheApi {
+    async fn touch_unavailable(&self, request_id: &str) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?;
+        let req = req
+            .with_extension(LogConfig::new("info"))
+            .with_extension(RequestId::new(request_id))
+            .with_extension(
+                MockServer::new(|_| Ok(ResponseBody::Json(json!({}))))
+                    .with_status(StatusCode::SERVICE_UNAVAILABLE),
+            );
+        send!(req).await
+    }
+
+    async fn touch_accepted(&self, request_id: &str) -> ApiResult<Payload> {
+        let req = self.get("/path/accepted?location=/v1/path/json").await?;
+        let req = req
+            .with_extension(LogConfig::new("info"))
+            .with_extension(RequestId::new(request_id))
+            .with_respond_async(Duration::from_millis(10), Duration::from_secs(5));
+        send!(req, CodeDataMessage).await
+    }
+
+    async fn touch_digest(&self, request_id: &str) -> ApiResult<Value> {
+        let req = self.get("/path/digest").await?;
+        let req = req
+            .with_extension(LogConfig::new("info"))
+            .with_extension(RequestId::new(request_id));
+        send!(req, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_log_each_retry() -> ApiResult<()> {
+    let _ = log::set_logger(&LOGGER).map(|_| log::set_max_level(LevelFilter::Trace));
+
+    let policy = RetryPolicy::new(2, Duration::from_millis(10));
+    let api = TheApi::builder()
+        .with_middleware(RetryMiddleware::new(policy))
+        .build();
+
+    let res = api.touch_unavailable("log-each-retry").await;
+    assert!(res.is_err());
+    assert_eq!(3, count_responses("log-each-retry", "Response (MOCK)"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_log_each_poll() -> ApiResult<()> {
+    let _ = log::set_logger(&LOGGER).map(|_| log::set_max_level(LevelFilter::Trace));
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.touch_accepted("log-each-poll").await?;
+    assert_eq!("/v1/path/json", res.path);
+    assert_eq!(2, count_responses("log-each-poll", "Response {"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_log_each_challenge() -> ApiResult<()> {
+    let _ = log::set_logger(&LOGGER).map(|_| log::set_max_level(LevelFilter::Trace));
+    start_server().await;
+
+    let api = TheApi::builder()
+        .with_middleware(ChallengeAuthMiddleware::new(DigestAuth::new(
+            "Mufasa",
+            "Circle of Life",
+        )))
+        .build();
+
+    let res = api.touch_digest("log-each-challenge").await?;
+    assert_eq!("Mufasa", res["username"]);
+    assert_eq!(2, count_responses("log-each-challenge", "Response {"));
+
+    Ok(())
+}
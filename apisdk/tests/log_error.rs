@@ -0,0 +1,68 @@
+#![cfg(not(feature = "tracing"))]
+
+use std::{sync::Mutex, time::Duration};
+
+use apisdk::{send, ApiResult, CodeDataMessage, LogConfig, MockServer};
+use log::{LevelFilter, Log, Metadata, Record};
+use regex::Regex;
+
+use crate::common::{start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// This logger is used to capture all messages
+struct CaptureLogger {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.lines.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger {
+    lines: Mutex::new(vec![]),
+};
+
+impl TheApi {
+    async fn touch_slow_error(&self) -> ApiResult<Payload> {
+        let req = self.get("/path/json").await?;
+        let req = req
+            .with_extension(LogConfig::new("info"))
+            .with_extension(MockServer::new(|_| {
+                std::thread::sleep(Duration::from_millis(20));
+                Err(anyhow::format_err!("slow error"))
+            }));
+        send!(req, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_log_error_with_elapsed() -> ApiResult<()> {
+    let _ = log::set_logger(&LOGGER).map(|_| log::set_max_level(LevelFilter::Trace));
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.touch_slow_error().await;
+    assert!(res.is_err());
+
+    let regex = Regex::new(r"Error @(\d+)ms: .*slow error").unwrap();
+    let lines = LOGGER.lines.lock().unwrap();
+    let elapsed: u64 = lines
+        .iter()
+        .find_map(|line| regex.captures(line))
+        .and_then(|c| c[1].parse().ok())
+        .expect("No error log with elapsed time");
+    assert!(elapsed > 0);
+
+    Ok(())
+}
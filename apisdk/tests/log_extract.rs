@@ -0,0 +1,68 @@
+#![cfg(not(feature = "tracing"))]
+
+use std::sync::Mutex;
+
+use apisdk::{send, ApiResult, CodeDataMessage, LogConfig, MockServer, ResponseBody};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::json;
+
+use crate::common::{start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// This logger is used to capture all messages
+struct CaptureLogger {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.lines.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger {
+    lines: Mutex::new(vec![]),
+};
+
+impl TheApi {
+    async fn touch_malformed(&self) -> ApiResult<Payload> {
+        let req = self.get("/path/json").await?;
+        let req = req
+            .with_extension(LogConfig::new("info"))
+            .with_extension(MockServer::new(|_| {
+                Ok(ResponseBody::Json(json!({
+                    "code": 0,
+                    "data": "malformed-data"
+                })))
+            }));
+        send!(req, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_log_body_when_extract_failed() -> ApiResult<()> {
+    let _ = log::set_logger(&LOGGER).map(|_| log::set_max_level(LevelFilter::Trace));
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.touch_malformed().await;
+    assert!(res.is_err());
+
+    let lines = LOGGER.lines.lock().unwrap();
+    let line = lines
+        .iter()
+        .find(|line| line.contains("Extract Error"))
+        .expect("No extract error log");
+    assert!(line.contains("malformed-data"));
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+#![cfg(not(feature = "tracing"))]
+
+use std::{net::SocketAddr, sync::Mutex};
+
+use apisdk::{send, ApiResult, LogConfig};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::{json, Value};
+use warp::Filter;
+
+use crate::common::TheApi;
+
+#[allow(unused)]
+mod common;
+
+/// This logger is used to capture all messages
+struct CaptureLogger {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.lines.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger {
+    lines: Mutex::new(vec![]),
+};
+
+/// Start a server, which replies with CDN headers
+fn start_cdn_server() -> SocketAddr {
+    let routes = warp::path!("cdn").map(|| {
+        let reply = warp::reply::json(&json!({ "cached": true }));
+        let reply = warp::reply::with_header(reply, "X-Cache", "HIT");
+        let reply = warp::reply::with_header(reply, "CF-Ray", "8a1b2c3d4e5f-SIN");
+        warp::reply::with_header(reply, "X-Served-By", "cache-sin-1")
+    });
+    let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    addr
+}
+
+impl TheApi {
+    async fn touch_cdn(&self, addr: SocketAddr) -> ApiResult<Value> {
+        let req = self.get(format!("http://{}/cdn", addr)).await?;
+        let req = req.with_extension(
+            LogConfig::new("info")
+                .with_response_header("X-Cache")
+                .with_response_header("cf-ray"),
+        );
+        send!(req).await
+    }
+}
+
+#[tokio::test]
+async fn test_log_selected_response_headers() -> ApiResult<()> {
+    let _ = log::set_logger(&LOGGER).map(|_| log::set_max_level(LevelFilter::Trace));
+    let addr = start_cdn_server();
+
+    let api = TheApi::default();
+
+    let res = api.touch_cdn(addr).await?;
+    assert_eq!(json!({ "cached": true }), res);
+
+    let lines = LOGGER.lines.lock().unwrap();
+    let line = lines
+        .iter()
+        .find(|line| line.contains("Response Headers"))
+        .expect("No response headers log");
+    assert!(line.contains("x-cache: HIT"));
+    assert!(line.contains("cf-ray: 8a1b2c3d4e5f-SIN"));
+    assert!(!line.contains("x-served-by"));
+    assert!(!line.contains("content-type"));
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "tracing")]
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use apisdk::{send, ApiResult, CodeDataMessage, LogConfig};
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, Layer, Registry};
+
+use crate::common::{start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+type Fields = HashMap<String, String>;
+
+/// This layer is used to capture fields of all events
+#[derive(Clone, Default)]
+struct CaptureLayer {
+    events: Arc<Mutex<Vec<Fields>>>,
+}
+
+struct FieldVisitor<'a>(&'a mut Fields);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+}
+
+impl<S: Subscriber> Layer<S> for CaptureLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = Fields::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        self.events.lock().unwrap().push(fields);
+    }
+}
+
+impl TheApi {
+    async fn touch(&self) -> ApiResult<Payload> {
+        let req = self.get("/path/json").await?;
+        let req = req.with_extension(LogConfig::new("info"));
+        send!(req, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_tracing_fields() -> ApiResult<()> {
+    let layer = CaptureLayer::default();
+    let _guard = tracing::subscriber::set_default(Registry::default().with(layer.clone()));
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.touch().await?;
+    log::debug!("res = {:?}", res);
+
+    let events = layer.events.lock().unwrap();
+    let request = events
+        .iter()
+        .find(|e| e.get("message").map(|m| m.as_str()) == Some("Request"))
+        .expect("No request event");
+    assert!(!request.get("request_id").unwrap().is_empty());
+    assert_eq!("GET", request.get("method").unwrap());
+
+    let response = events
+        .iter()
+        .find(|e| e.get("message").map(|m| m.as_str()) == Some("Response"))
+        .expect("No response event");
+    assert_eq!(request.get("request_id"), response.get("request_id"));
+    assert_eq!("200", response.get("status").unwrap());
+
+    Ok(())
+}
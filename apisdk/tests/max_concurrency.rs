@@ -0,0 +1,96 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use apisdk::{send, ApiError, ApiResult, CancellationToken, MockServer, Responder, ResponseBody};
+use async_trait::async_trait;
+use reqwest::Request;
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// This responder records the max count of in-flight requests
+#[derive(Clone, Default)]
+struct Backend {
+    in_flight: Arc<AtomicUsize>,
+    max_in_flight: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Responder for Backend {
+    async fn handle(&self, req: Request) -> anyhow::Result<ResponseBody> {
+        let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(current, Ordering::SeqCst);
+        let delay = match req.url().path() {
+            "/v1/slow" => Duration::from_secs(5),
+            _ => Duration::from_millis(50),
+        };
+        tokio::time::sleep(delay).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        Ok(ResponseBody::Json(json!({})))
+    }
+}
+
+#[tokio::test]
+async fn test_max_concurrency() -> ApiResult<()> {
+    init_logger();
+
+    let backend = Backend::default();
+    let api = Arc::new(
+        TheApi::builder()
+            .with_max_concurrency(3)
+            .with_initialiser(MockServer::new(backend.clone()))
+            .build(),
+    );
+
+    let calls = (0..10).map(|_| {
+        let api = api.clone();
+        tokio::spawn(async move {
+            let req = api.get("/path").await?;
+            let _: Value = send!(req).await?;
+            ApiResult::Ok(())
+        })
+    });
+    for call in futures::future::join_all(calls).await {
+        call.unwrap()?;
+    }
+    assert_eq!(3, backend.max_in_flight.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_concurrency_release_on_cancel() -> ApiResult<()> {
+    init_logger();
+
+    let backend = Backend::default();
+    let api = TheApi::builder()
+        .with_max_concurrency(1)
+        .with_initialiser(MockServer::new(backend.clone()))
+        .build();
+
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        canceller.cancel();
+    });
+    let req = api.get("/slow").await?.with_extension(token);
+    let res: ApiResult<Value> = send!(req).await;
+    assert!(matches!(res, Err(ApiError::Cancelled)));
+
+    // The permit has been released, so the next request doesn't wait for the slow one
+    let start = Instant::now();
+    let req = api.get("/path").await?;
+    let _: Value = send!(req).await?;
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    Ok(())
+}
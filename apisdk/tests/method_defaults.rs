@@ -0,0 +1,75 @@
+use apisdk::{
+    header::{HeaderMap, HeaderValue, CACHE_CONTROL},
+    send, send_json, ApiResult, CodeDataMessage, Method,
+};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn get_headers(&self) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?;
+        let data: Value = send!(req, CodeDataMessage).await?;
+        Ok(data["headers"].clone())
+    }
+
+    async fn post_headers(&self) -> ApiResult<Value> {
+        let req = self.post("/path/json").await?;
+        let data: Value = send_json!(req, json!({}), CodeDataMessage).await?;
+        Ok(data["headers"].clone())
+    }
+
+    async fn post_headers_with(&self, headers: HeaderMap) -> ApiResult<Value> {
+        let req = self.post("/path/json").await?.headers(headers);
+        let data: Value = send_json!(req, json!({}), CodeDataMessage).await?;
+        Ok(data["headers"].clone())
+    }
+}
+
+fn build_api() -> TheApi {
+    let mut get = HeaderMap::new();
+    get.insert(CACHE_CONTROL, HeaderValue::from_static("max-age=60"));
+    let mut post = HeaderMap::new();
+    post.insert("idempotency-key", HeaderValue::from_static("key-1"));
+
+    TheApi::builder()
+        .with_method_defaults(Method::GET, get)
+        .with_method_defaults(Method::POST, post)
+        .build()
+}
+
+#[tokio::test]
+async fn test_method_defaults() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = build_api();
+
+    let headers = api.post_headers().await?;
+    assert_eq!("key-1", headers["idempotency-key"]);
+    assert!(headers.get("cache-control").is_none());
+
+    let headers = api.get_headers().await?;
+    assert_eq!("max-age=60", headers["cache-control"]);
+    assert!(headers.get("idempotency-key").is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_method_defaults_replaced() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = build_api();
+
+    let mut headers = HeaderMap::new();
+    headers.insert("idempotency-key", HeaderValue::from_static("key-2"));
+    let headers = api.post_headers_with(headers).await?;
+    assert_eq!("key-2", headers["idempotency-key"]);
+
+    Ok(())
+}
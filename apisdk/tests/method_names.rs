@@ -0,0 +1,46 @@
+use apisdk::{http_api, send, ApiResult, CodeDataMessage, Method};
+
+use crate::common::{init_logger, start_server, Payload};
+
+#[allow(unused)]
+mod common;
+
+/// The api defines the methods with the same names as the opt-in helpers
+#[http_api("http://localhost:3030/v1")]
+#[derive(Debug, Clone)]
+pub struct AccountApi;
+
+impl AccountApi {
+    async fn reset(&self, account: &str) -> ApiResult<Payload> {
+        let req = self.post(format!("/path/json?reset={}", account)).await?;
+        send!(req, CodeDataMessage).await
+    }
+
+    async fn sign_url(&self, file: &str) -> ApiResult<String> {
+        let url = self.core.sign_url(Method::GET, file).await?;
+        Ok(url.to_string())
+    }
+
+    fn pool_stats(&self) -> u64 {
+        self.core.pool_stats().requests
+    }
+}
+
+#[tokio::test]
+async fn test_method_names() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = AccountApi::default();
+
+    let res = api.reset("alice").await?;
+    assert_eq!("/v1/path/json", res.path);
+    assert_eq!(Some("alice"), res.query.get("reset").map(|v| v.as_str()));
+
+    let url = api.sign_url("/files/1").await?;
+    assert_eq!("http://localhost:3030/v1/files/1", url);
+
+    assert_eq!(1, api.pool_stats());
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use apisdk::{
+    send, ApiError, ApiResult, Extensions, Middleware, MockServer, ResponseBody, StatusCode,
+};
+use async_trait::async_trait;
+use reqwest::{Request, Response};
+use reqwest_middleware::Next;
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn touch_mock(&self, status: StatusCode) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?;
+        let req = req.with_extension(
+            MockServer::new(|_| Ok(ResponseBody::Json(json!({})))).with_status(status),
+        );
+        send!(req).await
+    }
+}
+
+/// This middleware is used to count 5xx responses
+#[derive(Clone, Default)]
+struct ServerErrorCounter {
+    count: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Middleware for ServerErrorCounter {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        let res = next.run(req, extensions).await?;
+        if res.status().is_server_error() {
+            self.count.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(res)
+    }
+}
+
+#[tokio::test]
+async fn test_middleware_observes_response() -> ApiResult<()> {
+    init_logger();
+
+    let counter = ServerErrorCounter::default();
+    let api = TheApi::builder().with_middleware(counter.clone()).build();
+
+    api.touch_mock(StatusCode::OK).await?;
+    for status in [StatusCode::SERVICE_UNAVAILABLE, StatusCode::BAD_GATEWAY] {
+        let res = api.touch_mock(status).await;
+        assert!(matches!(res, Err(ApiError::HttpServerStatus(..))));
+    }
+    let res = api.touch_mock(StatusCode::NOT_FOUND).await;
+    assert!(matches!(res, Err(ApiError::HttpClientStatus(404, _))));
+
+    assert_eq!(2, counter.count.load(Ordering::SeqCst));
+
+    Ok(())
+}
@@ -1,4 +1,7 @@
-use apisdk::{send, ApiError, ApiResult, CodeDataMessage, MockServer, ResponseBody};
+use apisdk::{
+    async_trait, send, send_form, send_json, ApiError, ApiResult, CodeDataMessage, Matcher,
+    MockOps, MockRouter, MockServer, Request, RequestBody, Responder, ResponseBody, TransportError,
+};
 use serde::Deserialize;
 use serde_json::json;
 
@@ -28,6 +31,11 @@ impl TheApi {
         send!(req, CodeDataMessage).await
     }
 
+    async fn touch_no_mock(&self) -> ApiResult<MockPayload> {
+        let req = self.get("/path/json").await?.no_mock();
+        send!(req, CodeDataMessage).await
+    }
+
     async fn touch_mock(&self) -> ApiResult<MockPayload> {
         let req = self.get("/path/json").await?;
         let req = req.with_extension(MockServer::new(|_| {
@@ -40,6 +48,49 @@ impl TheApi {
         }));
         send!(req, CodeDataMessage).await
     }
+
+    async fn echo_mock(&self, message: &str) -> ApiResult<MockPayload> {
+        let req = self.post("/path/json").await?;
+        let req = req.with_extension(MockServer::with_body(|_, body| {
+            let message = match body {
+                RequestBody::Json(json) => json.get("message").cloned(),
+                _ => None,
+            };
+            Ok(ResponseBody::Json(json!({
+                "code": 0,
+                "data": {
+                    "mock": true,
+                    "message": message,
+                }
+            })))
+        }));
+        let payload = json!({
+            "message": message,
+        });
+        send_json!(req, payload, CodeDataMessage).await
+    }
+
+    async fn echo_form_mock(&self, form: Vec<(&str, &str)>) -> ApiResult<MockPayload> {
+        let req = self.post("/path/form").await?;
+        let req = req.with_extension(MockServer::with_body(|_, body| {
+            let message = match body {
+                RequestBody::Form(fields) => fields
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .collect::<Vec<_>>()
+                    .join("&"),
+                _ => String::new(),
+            };
+            Ok(ResponseBody::Json(json!({
+                "code": 0,
+                "data": {
+                    "mock": true,
+                    "message": message,
+                }
+            })))
+        }));
+        send_form!(req, form, CodeDataMessage).await
+    }
 }
 
 #[tokio::test]
@@ -56,6 +107,37 @@ async fn test_mock_single() -> ApiResult<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_mock_echo_body() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let res = api.echo_mock("hello").await?;
+    log::debug!("res = {:?}", res);
+    assert!(res.mock);
+    assert_eq!(Some("hello".to_string()), res.message);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_echo_form() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let res = api
+        .echo_form_mock(vec![("tag", "a"), ("id", "1"), ("tag", "b")])
+        .await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!(Some("tag=a&id=1&tag=b".to_string()), res.message);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_mock_all() -> ApiResult<()> {
     init_logger();
@@ -79,6 +161,33 @@ async fn test_mock_all() -> ApiResult<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_mock_all_but_bypassed() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::new(|_| {
+            Ok(ResponseBody::Json(json!({
+                "code": 0,
+                "data": {
+                    "mock": true
+                }
+            })))
+        }))
+        .build();
+
+    let res = api.touch().await?;
+    log::debug!("res = {:?}", res);
+    assert!(res.mock);
+
+    let res = api.touch_no_mock().await?;
+    log::debug!("res = {:?}", res);
+    assert!(!res.mock);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_mock_error() -> ApiResult<()> {
     init_logger();
@@ -94,3 +203,166 @@ async fn test_mock_error() -> ApiResult<()> {
 
     Ok(())
 }
+
+#[derive(Debug, Deserialize)]
+pub struct PagePayload {
+    pub page: u32,
+}
+
+impl TheApi {
+    async fn list_page(&self, query: &[(&str, &str)]) -> ApiResult<PagePayload> {
+        let req = self.get("/path/json").await?.query(query);
+        send!(req, CodeDataMessage).await
+    }
+}
+
+fn page_router() -> MockRouter {
+    MockRouter::new()
+        .route(
+            Matcher::new()
+                .path("/path/json")
+                .query("page", "1")
+                .exact_query(),
+            |_| Ok(ResponseBody::Json(json!({"code": 0, "data": {"page": 1}}))),
+        )
+        .route(
+            Matcher::new()
+                .path("/path/json")
+                .query("page", "2")
+                .exact_query(),
+            |_| Ok(ResponseBody::Json(json!({"code": 0, "data": {"page": 2}}))),
+        )
+}
+
+#[tokio::test]
+async fn test_mock_match_query() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::new(page_router()))
+        .build();
+
+    let res = api.list_page(&[("page", "1")]).await?;
+    assert_eq!(1, res.page);
+    let res = api.list_page(&[("page", "2")]).await?;
+    assert_eq!(2, res.page);
+
+    // Unexpected extra param
+    let res = api.list_page(&[("page", "2"), ("size", "10")]).await;
+    log::debug!("res = {:?}", res);
+    assert!(res.is_err());
+
+    // Unmatched param
+    let res = api.list_page(&[("page", "3")]).await;
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_with_query() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::with_query(|_, query| {
+            let page: u32 = query.get("page").and_then(|p| p.parse().ok()).unwrap_or(1);
+            Ok(ResponseBody::Json(
+                json!({"code": 0, "data": {"page": page}}),
+            ))
+        }))
+        .build();
+
+    let res = api.list_page(&[("page", "5")]).await?;
+    assert_eq!(5, res.page);
+    let res = api.list_page(&[]).await?;
+    assert_eq!(1, res.page);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_connection_reset() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::fail_with(TransportError::ConnectionReset))
+        .build();
+
+    let res = api.touch().await;
+    log::debug!("res = {:?}", res);
+    match res {
+        Err(e @ ApiError::Connection(TransportError::ConnectionReset)) => {
+            assert_eq!(None, e.status());
+            assert!(e.is_retryable());
+        }
+        _ => panic!("unexpected result: {:?}", res),
+    }
+
+    // The real server is still reachable
+    let res = api.touch_no_mock().await?;
+    assert!(!res.mock);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_connection_refused_by_router() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let router = MockRouter::new()
+        .route(Matcher::new().query("page", "1"), |_| {
+            Ok(ResponseBody::Json(json!({"code": 0, "data": {"page": 1}})))
+        })
+        .route(Matcher::new(), |_| {
+            Err(TransportError::ConnectionRefused.into())
+        });
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::new(router))
+        .build();
+
+    let res = api.list_page(&[("page", "1")]).await?;
+    assert_eq!(1, res.page);
+
+    let res = api.list_page(&[("page", "2")]).await;
+    assert!(matches!(
+        res,
+        Err(ApiError::Connection(TransportError::ConnectionRefused))
+    ));
+
+    Ok(())
+}
+
+/// This responder always fails
+struct BrokenResponder;
+
+#[async_trait]
+impl Responder for BrokenResponder {
+    async fn handle(&self, _req: Request) -> anyhow::Result<ResponseBody> {
+        Err(anyhow::format_err!("fixture not found"))
+    }
+}
+
+#[tokio::test]
+async fn test_mock_error_with_type_name() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::new(BrokenResponder))
+        .build();
+
+    let res = api.touch().await;
+    match res {
+        Err(ApiError::Middleware(e)) => {
+            let message = e.to_string();
+            assert!(message.contains("fixture not found"));
+            assert!(message.contains("BrokenResponder"));
+        }
+        _ => panic!("unexpected result: {:?}", res),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use apisdk::{send, ApiError, ApiResult, MockServer};
+use serde_json::Value;
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+}
+
+#[tokio::test]
+async fn test_mock_fixtures() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::from_fixtures(fixtures())?)
+        .build();
+
+    // The fixture with query is more specific, though it sorts after
+    let req = api.get("/users").await?.query(&[("page", "2")]);
+    let res: Value = send!(req).await?;
+    assert_eq!(2, res["page"]);
+    assert_eq!("carol", res["users"][0]);
+
+    let req = api.get("/users").await?;
+    let res: Value = send!(req).await?;
+    assert_eq!(1, res["page"]);
+
+    let req = api.get("/users").await?.query(&[("page", "3")]);
+    let res: Value = send!(req).await?;
+    assert_eq!(1, res["page"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_fixtures_unmatched() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::from_fixtures(fixtures())?)
+        .build();
+
+    let req = api.post("/users").await?;
+    let res: ApiResult<Value> = send!(req).await;
+    assert!(matches!(res, Err(ApiError::Middleware(..))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_fixtures_missing_dir() {
+    let res = MockServer::from_fixtures(fixtures().join("missing"));
+    assert!(matches!(res, Err(ApiError::Other(..))));
+}
@@ -0,0 +1,133 @@
+use apisdk::{send, ApiError, ApiResult, MockServer};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct User {
+    id: i64,
+    name: String,
+    email: String,
+    roles: Vec<String>,
+}
+
+fn spec() -> Value {
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": "Users", "version": "1.0.0" },
+        "paths": {
+            "/users/{id}": {
+                "get": {
+                    "responses": {
+                        "200": {
+                            "description": "OK",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/User" }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "/users/me": {
+                "get": {
+                    "responses": {
+                        "200": {
+                            "description": "OK",
+                            "content": {
+                                "application/json": {
+                                    "example": { "id": 1, "name": "me", "email": "me@example.com", "roles": [] }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        "components": {
+            "schemas": {
+                "User": {
+                    "type": "object",
+                    "required": ["id", "name", "email", "roles"],
+                    "properties": {
+                        "id": { "type": "integer", "format": "int64" },
+                        "name": { "type": "string" },
+                        "email": { "type": "string", "format": "email" },
+                        "roles": {
+                            "type": "array",
+                            "items": { "type": "string", "enum": ["admin", "guest"] }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+impl TheApi {
+    async fn get_user(&self, id: &str) -> ApiResult<User> {
+        let req = self.get(format!("/users/{}", id)).await?;
+        let req = req.with_extension(MockServer::from_openapi(&spec())?);
+        send!(req).await
+    }
+
+    async fn delete_user(&self, id: &str) -> ApiResult<Value> {
+        let req = self.delete(format!("/users/{}", id)).await?;
+        let req = req.with_extension(MockServer::from_openapi(&spec())?);
+        send!(req).await
+    }
+}
+
+#[tokio::test]
+async fn test_mock_openapi_schema() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::default();
+
+    let user = api.get_user("42").await?;
+    log::debug!("user = {:?}", user);
+    assert_eq!(
+        User {
+            id: 0,
+            name: "string".to_string(),
+            email: "user@example.com".to_string(),
+            roles: vec!["admin".to_string()],
+        },
+        user
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_openapi_example() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::default();
+
+    // The literal path is preferred to the templated one
+    let user = api.get_user("me").await?;
+    assert_eq!(1, user.id);
+    assert_eq!("me", user.name);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_openapi_not_matched() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::default();
+
+    let res = api.delete_user("42").await;
+    assert!(matches!(res, Err(ApiError::Middleware(_))));
+
+    assert!(MockServer::from_openapi(&json!({})).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use apisdk::{
+    send, ApiError, ApiResult, CodeDataMessage, Matcher, MockRouter, MockServer, ResponseBody, Url,
+};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// The api points to an unreachable port, so only the mocked and passed-through requests succeed
+fn build_api(mock: MockServer) -> TheApi {
+    TheApi::builder()
+        .with_rewriter(|mut url: Url| {
+            url.set_port(Some(1)).ok();
+            Ok(url)
+        })
+        .with_initialiser(mock)
+        .build()
+}
+
+fn router() -> MockRouter {
+    MockRouter::new().route(Matcher::new().path("/path/a"), |_| {
+        Ok(ResponseBody::Json(json!({ "mocked": true })))
+    })
+}
+
+#[tokio::test]
+async fn test_mock_passthrough() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let mock = MockServer::new(router()).with_passthrough("http://127.0.0.1:3030")?;
+    let api = build_api(mock);
+
+    // The mocked path
+    let req = api.get("/path/a").await?;
+    let res: Value = send!(req).await?;
+    assert_eq!(true, res["mocked"]);
+
+    // Other paths pass through to the local test server
+    let req = api.get("/path/json").await?;
+    let res: Payload = send!(req, CodeDataMessage).await?;
+    assert_eq!("/v1/path/json", res.path);
+
+    // The status of upstream is kept
+    let req = api.get("/unavailable").await?;
+    let res: ApiResult<Value> = send!(req).await;
+    assert!(matches!(res, Err(ApiError::HttpServerStatus(503, _))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_without_passthrough() -> ApiResult<()> {
+    init_logger();
+
+    let api = build_api(MockServer::new(router()));
+
+    let req = api.get("/path/json").await?;
+    let res: ApiResult<Value> = send!(req).await;
+    match res {
+        Err(ApiError::Middleware(e)) => assert!(e.to_string().contains("No responder matches")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    Ok(())
+}
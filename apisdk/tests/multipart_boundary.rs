@@ -0,0 +1,109 @@
+use apisdk::{
+    header::CONTENT_TYPE, multipart::Part, send_multipart, ApiError, ApiResult, CodeDataMessage,
+    MockServer, MultipartForm, MultipartFormOps, MultipartPart, RequestBody, ResponseBody,
+};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+fn build_form() -> MultipartForm {
+    MultipartForm::new().text("name", "data.json").with_part(
+        "file",
+        MultipartPart::bytes(r#"{"a":1}"#)
+            .with_content_type("application/json")
+            .with_file_name("data.json"),
+    )
+}
+
+#[tokio::test]
+async fn test_multipart_boundary() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::with_body(|req, body| {
+            let content_type = req.headers()[CONTENT_TYPE].to_str()?.to_string();
+            let body = match body {
+                RequestBody::Bytes(bytes) => String::from_utf8(bytes)?,
+                other => anyhow::bail!("unexpected body: {:?}", other),
+            };
+            Ok(ResponseBody::Json(
+                json!({ "content_type": content_type, "body": body }),
+            ))
+        }))
+        .build();
+
+    let req = api.post("/path/multipart").await?;
+    let form = build_form().with_boundary("fixed-boundary-1234");
+    let res: Value = send_multipart!(req, form).await?;
+    assert_eq!(
+        "multipart/form-data; boundary=fixed-boundary-1234",
+        res["content_type"]
+    );
+    assert_eq!(
+        concat!(
+            "--fixed-boundary-1234\r\n",
+            "Content-Disposition: form-data; name=\"name\"\r\n",
+            "\r\n",
+            "data.json\r\n",
+            "--fixed-boundary-1234\r\n",
+            "Content-Disposition: form-data; name=\"file\"; filename=\"data.json\"\r\n",
+            "Content-Type: application/json\r\n",
+            "\r\n",
+            "{\"a\":1}\r\n",
+            "--fixed-boundary-1234--\r\n",
+        ),
+        res["body"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_multipart_boundary_server() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    // The form encoded with the custom boundary could be parsed by server
+    let req = api.post("/path/multipart").await?;
+    let form = build_form().with_boundary("fixed-boundary-1234");
+    let res: Value = send_multipart!(req, form, CodeDataMessage).await?;
+    assert_eq!(
+        "multipart/form-data; boundary=fixed-boundary-1234",
+        res["headers"]["content-type"]
+    );
+    assert_eq!("application/json", res["multipart"]["file"]);
+
+    // The content type of part is kept with the random boundary
+    let req = api.post("/path/multipart").await?;
+    let res: Value = send_multipart!(req, build_form(), CodeDataMessage).await?;
+    assert_ne!(
+        "multipart/form-data; boundary=fixed-boundary-1234",
+        res["headers"]["content-type"]
+    );
+    assert_eq!("application/json", res["multipart"]["file"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_multipart_boundary_with_reqwest_part() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::new(|_| Ok(ResponseBody::Json(json!({})))))
+        .build();
+
+    let req = api.post("/path/multipart").await?;
+    let form = MultipartForm::new()
+        .part("file", Part::bytes(vec![1, 2, 3]))
+        .with_boundary("fixed-boundary-1234");
+    let res: ApiResult<Value> = send_multipart!(req, form).await;
+    assert!(matches!(res, Err(ApiError::MultipartForm)));
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "otel")]
+
+use std::sync::{Arc, Weak};
+
+use apisdk::{send, ApiResult, CodeDataMessage, OtelMetricsMiddleware};
+use opentelemetry::{
+    metrics::{MeterProvider as _, Result as MetricsResult},
+    Key, Value as OtelValue,
+};
+use opentelemetry_sdk::{
+    metrics::{
+        data::{Histogram, ResourceMetrics, Temporality},
+        reader::{AggregationSelector, MetricReader, TemporalitySelector},
+        Aggregation, InstrumentKind, ManualReader, MeterProvider, Pipeline,
+    },
+    Resource,
+};
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// This reader keeps the metrics in memory, and could be collected after registered to MeterProvider
+#[derive(Debug, Clone, Default)]
+struct InMemoryReader(Arc<ManualReader>);
+
+impl InMemoryReader {
+    fn collect(&self) -> ResourceMetrics {
+        let mut metrics = ResourceMetrics {
+            resource: Resource::empty(),
+            scope_metrics: vec![],
+        };
+        self.0.collect(&mut metrics).unwrap();
+        metrics
+    }
+}
+
+impl TemporalitySelector for InMemoryReader {
+    fn temporality(&self, kind: InstrumentKind) -> Temporality {
+        self.0.temporality(kind)
+    }
+}
+
+impl AggregationSelector for InMemoryReader {
+    fn aggregation(&self, kind: InstrumentKind) -> Aggregation {
+        self.0.aggregation(kind)
+    }
+}
+
+impl MetricReader for InMemoryReader {
+    fn register_pipeline(&self, pipeline: Weak<Pipeline>) {
+        self.0.register_pipeline(pipeline)
+    }
+
+    fn collect(&self, rm: &mut ResourceMetrics) -> MetricsResult<()> {
+        self.0.collect(rm)
+    }
+
+    fn force_flush(&self) -> MetricsResult<()> {
+        self.0.force_flush()
+    }
+
+    fn shutdown(&self) -> MetricsResult<()> {
+        self.0.shutdown()
+    }
+}
+
+#[tokio::test]
+async fn test_otel_metrics() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let reader = InMemoryReader::default();
+    let provider = MeterProvider::builder().with_reader(reader.clone()).build();
+
+    let api = TheApi::builder()
+        .with_middleware(OtelMetricsMiddleware::new(&provider.meter("apisdk")))
+        .build();
+
+    let req = api.get("/path/json").await?;
+    let _: Value = send!(req, CodeDataMessage).await?;
+
+    let metrics = reader.collect();
+    let metric = metrics
+        .scope_metrics
+        .iter()
+        .flat_map(|sm| sm.metrics.iter())
+        .find(|m| m.name == "http.client.request.duration")
+        .expect("duration should be recorded");
+    let histogram = metric
+        .data
+        .as_any()
+        .downcast_ref::<Histogram<f64>>()
+        .unwrap();
+    assert_eq!(1, histogram.data_points.len());
+
+    let point = &histogram.data_points[0];
+    assert_eq!(1, point.count);
+    let attribute = |key: &'static str| {
+        point
+            .attributes
+            .iter()
+            .find(|(k, _)| **k == Key::from_static_str(key))
+            .map(|(_, v)| v.clone())
+    };
+    assert_eq!(Some(OtelValue::from("GET")), attribute("http.method"));
+    assert_eq!(Some(OtelValue::I64(200)), attribute("http.status_code"));
+    assert_eq!(
+        Some(OtelValue::from("localhost")),
+        attribute("server.address")
+    );
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use apisdk::{http_api, send, ApiResult, CodeDataMessage};
+
+use crate::common::{init_logger, start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[http_api("http://localhost:3030")]
+#[derive(Debug, Clone)]
+pub struct RootApi;
+
+impl TheApi {
+    async fn touch(&self, path: &str) -> ApiResult<Payload> {
+        let req = self.get(path).await?;
+        send!(req, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_path_prefix() -> ApiResult<()> {
+    init_logger();
+
+    let api = RootApi::builder().with_path_prefix("/v2").build();
+
+    let req = api.get("/users").await?.build()?;
+    assert_eq!("http://localhost:3030/v2/users", req.url().as_str());
+
+    // The duplicated `/` is avoided
+    let api = RootApi::builder().with_path_prefix("v2/").build();
+    let url = api.build_url("users?page=2").await?;
+    assert_eq!("http://localhost:3030/v2/users?page=2", url.as_str());
+
+    // The absolute URL is not affected
+    let url = api.build_url("https://example.com/users").await?;
+    assert_eq!("https://example.com/users", url.as_str());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_path_prefix_after_base_path() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    // The base url is `http://localhost:3030/v1`
+    let api = TheApi::builder().with_path_prefix("/path").build();
+
+    let res = api.touch("/json").await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!("/v1/path/json", res.path);
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use apisdk::{ApiResult, Extensions, Middleware, MockServer, ResponseBody};
+use async_trait::async_trait;
+use futures::StreamExt;
+use reqwest::{Request, Response};
+use reqwest_middleware::Next;
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// Collect the items emitted within the duration
+async fn collect_within<S>(stream: &mut S, duration: Duration) -> Vec<S::Item>
+where
+    S: futures::Stream + Unpin,
+{
+    let mut items = vec![];
+    let _ = tokio::time::timeout(duration, async {
+        while let Some(item) = stream.next().await {
+            items.push(item);
+        }
+    })
+    .await;
+    items
+}
+
+#[tokio::test]
+async fn test_poll_changes() -> ApiResult<()> {
+    init_logger();
+
+    let polls = Arc::new(AtomicUsize::new(0));
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::new({
+            let polls = polls.clone();
+            move |_| {
+                let n = polls.fetch_add(1, Ordering::SeqCst) + 1;
+                // The keys are reordered, but the payload is the same until the third poll
+                let body = match n {
+                    1 => json!({ "version": 1, "name": "config" }),
+                    2 => json!({ "name": "config", "version": 1 }),
+                    _ => json!({ "name": "config", "version": 2 }),
+                };
+                Ok(ResponseBody::Json(body))
+            }
+        }))
+        .build();
+
+    let mut changes = api.poll_changes("/config", Duration::from_millis(20));
+    let initial = changes.next().await.unwrap()?;
+    assert_eq!(1, initial["version"]);
+
+    let items = collect_within(&mut changes, Duration::from_millis(300)).await;
+    assert_eq!(1, items.len());
+    assert_eq!(2, items[0].as_ref().unwrap()["version"]);
+    assert!(polls.load(Ordering::SeqCst) > 3);
+
+    Ok(())
+}
+
+/// This middleware acts as a server which supports `ETag`
+#[derive(Clone, Default)]
+struct ConditionalServer {
+    full: Arc<AtomicUsize>,
+    not_modified: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Middleware for ConditionalServer {
+    async fn handle(
+        &self,
+        req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        let res = match req.headers().get("if-none-match") {
+            Some(etag) if etag == "\"v1\"" => {
+                self.not_modified.fetch_add(1, Ordering::SeqCst);
+                hyper::Response::builder().status(304).body(String::new())
+            }
+            _ => {
+                self.full.fetch_add(1, Ordering::SeqCst);
+                hyper::Response::builder()
+                    .header("content-type", "application/json")
+                    .header("etag", "\"v1\"")
+                    .body(json!({ "version": 1 }).to_string())
+            }
+        };
+        Ok(Response::from(res.unwrap()))
+    }
+}
+
+#[tokio::test]
+async fn test_poll_changes_etag() -> ApiResult<()> {
+    init_logger();
+
+    let server = ConditionalServer::default();
+    let api = TheApi::builder().with_middleware(server.clone()).build();
+
+    let mut changes = api.poll_changes("/config", Duration::from_millis(20));
+    let initial: Value = changes.next().await.unwrap()?;
+    assert_eq!(1, initial["version"]);
+
+    let items = collect_within(&mut changes, Duration::from_millis(200)).await;
+    assert!(items.is_empty());
+    assert_eq!(1, server.full.load(Ordering::SeqCst));
+    assert!(server.not_modified.load(Ordering::SeqCst) >= 3);
+
+    Ok(())
+}
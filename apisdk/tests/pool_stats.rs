@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use apisdk::{send, ApiResult, MockServer, PoolStats, ResponseBody};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn touch(&self) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?;
+        send!(req).await
+    }
+
+    async fn touch_mock(&self) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?;
+        let req = req.with_extension(MockServer::new(|_| Ok(ResponseBody::Json(json!({})))));
+        send!(req).await
+    }
+}
+
+#[tokio::test]
+async fn test_pool_stats() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+    assert_eq!(PoolStats::default(), api.pool_stats());
+
+    api.touch().await?;
+    let stats = api.pool_stats();
+    log::debug!("stats = {:?}", stats);
+    assert_eq!(1, stats.requests);
+    assert_eq!(1, stats.new_connections);
+    assert_eq!(0, stats.reused_connections);
+
+    // The idle connection is reused by the subsequent requests
+    for _ in 0..3 {
+        api.touch().await?;
+    }
+    let stats = api.pool_stats();
+    log::debug!("stats = {:?}", stats);
+    assert_eq!(4, stats.requests);
+    assert_eq!(1, stats.new_connections);
+    assert_eq!(3, stats.reused_connections);
+
+    // The mocked requests are not sent to network
+    api.touch_mock().await?;
+    assert_eq!(4, api.pool_stats().requests);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pool_idle_timeout() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder()
+        .with_tcp_keepalive(Duration::from_secs(30))
+        .with_pool_idle_timeout(Duration::from_millis(200))
+        .build();
+
+    api.touch().await?;
+    api.touch().await?;
+    assert_eq!(1, api.pool_stats().new_connections);
+    assert_eq!(1, api.pool_stats().reused_connections);
+
+    // The idle connection is evicted, so a new connection is established
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    api.touch().await?;
+    let stats = api.pool_stats();
+    log::debug!("stats = {:?}", stats);
+    assert_eq!(2, stats.new_connections);
+    assert_eq!(1, stats.reused_connections);
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use apisdk::{send, ApiError, ApiResult, PreconditionOps};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+async fn update(api: &TheApi, etag: &str) -> ApiResult<Value> {
+    let req = api
+        .put("/path/versioned")
+        .await?
+        .with_if_match(etag)
+        .json(&json!({ "name": "apisdk" }));
+    send!(req).await
+}
+
+async fn patch(api: &TheApi, etag: &str) -> ApiResult<Value> {
+    let req = api.patch("/path/versioned").await?.with_if_match(etag);
+    send!(req).await
+}
+
+#[tokio::test]
+async fn test_if_match_satisfied() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let res = update(&api, "v2").await?;
+    assert_eq!("v2", res["etag"]);
+
+    let res = patch(&api, "*").await?;
+    assert_eq!("v2", res["etag"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_if_match_precondition_failed() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let e = update(&api, "v1").await.unwrap_err();
+    assert_eq!(Some(412), e.status());
+    assert!(matches!(&e, ApiError::PreconditionFailed(Some(etag)) if etag == "\"v2\""));
+
+    let e = patch(&api, "W/\"v1\"").await.unwrap_err();
+    assert!(matches!(e, ApiError::PreconditionFailed(..)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_if_match_refetch_and_retry() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let req = api.put("/path/versioned").await?.with_if_match("v1");
+    let etag = match send!(req).await {
+        Err(ApiError::PreconditionFailed(Some(etag))) => etag,
+        other => panic!("unexpected result: {:?}", other.map(|_: Value| ())),
+    };
+
+    let req = api.put("/path/versioned").await?.with_if_match(etag);
+    let res: Value = send!(req).await?;
+    assert_eq!("v2", res["etag"]);
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use apisdk::{ApiResult, Method, PresignedUrlAuth};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+fn auth() -> PresignedUrlAuth {
+    PresignedUrlAuth::new("app-key", "app-secret", Duration::from_secs(300))
+}
+
+#[tokio::test]
+async fn test_presigned_url() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().with_authenticator(auth()).build();
+
+    let url = api.sign_url(Method::GET, "/files/1?version=2").await?;
+    let query: Vec<_> = url.query_pairs().map(|(n, _)| n.to_string()).collect();
+    assert!(query.contains(&"version".to_string()));
+    assert!(query.contains(&"X-Access-Key".to_string()));
+    assert!(query.contains(&"X-Expires".to_string()));
+    assert!(query.contains(&"X-Signature".to_string()));
+
+    assert!(auth().verify(&Method::GET, &url));
+    assert!(!auth().verify(&Method::DELETE, &url));
+
+    let mut tampered = url.clone();
+    tampered.set_path("/v1/files/2");
+    assert!(!auth().verify(&Method::GET, &tampered));
+
+    let other = PresignedUrlAuth::new("app-key", "other-secret", Duration::from_secs(300));
+    assert!(!other.verify(&Method::GET, &url));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_presigned_url_param_names() -> ApiResult<()> {
+    init_logger();
+
+    let auth = || auth().with_param_names("AccessKeyId", "Expires", "Signature");
+    let api = TheApi::builder().with_authenticator(auth()).build();
+
+    let url = api.sign_url(Method::GET, "/files/1").await?;
+    assert!(url.query_pairs().any(|(n, _)| n == "Signature"));
+    assert!(!url.query_pairs().any(|(n, _)| n == "X-Signature"));
+    assert!(auth().verify(&Method::GET, &url));
+
+    Ok(())
+}
@@ -0,0 +1,234 @@
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use apisdk::{
+    http_api, send, ApiResult, ManualClock, Priority, RateLimitHeaders, RateLimitMiddleware,
+    RateLimitReset, Url,
+};
+use serde_json::{json, Value};
+use warp::Filter;
+
+use crate::common::init_logger;
+
+#[allow(unused)]
+mod common;
+
+#[http_api("http://localhost/v1")]
+#[derive(Debug, Clone)]
+pub struct LimitedApi;
+
+impl LimitedApi {
+    async fn touch(&self) -> ApiResult<Value> {
+        let req = self.get("/limited").await?;
+        send!(req).await
+    }
+
+    async fn touch_tagged(&self, tag: &str, priority: Priority) -> ApiResult<Value> {
+        let req = self.get(format!("/limited/{}", tag)).await?;
+        let req = req.with_extension(priority);
+        send!(req).await
+    }
+}
+
+/// Start a server, whose quota (2 requests) will be reset in 1 second
+fn start_limited_server() -> SocketAddr {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let routes = warp::path!("v1" / "limited").map(move || {
+        let count = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        let remaining = 2usize.saturating_sub(count);
+        let reply = warp::reply::json(&json!({ "count": count }));
+        let reply = warp::reply::with_header(reply, "Quota-Limit", "2");
+        let reply = warp::reply::with_header(reply, "Quota-Remaining", remaining.to_string());
+        warp::reply::with_header(reply, "Quota-Reset", "1")
+    });
+    let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    addr
+}
+
+#[tokio::test]
+async fn test_rate_limit_throttle() -> ApiResult<()> {
+    init_logger();
+    let addr = start_limited_server();
+    let base_url = format!("http://127.0.0.1:{}/v1", addr.port());
+
+    let names = RateLimitHeaders::new(
+        "Quota-Limit",
+        "Quota-Remaining",
+        "Quota-Reset",
+        RateLimitReset::DeltaSeconds,
+    );
+    let limiter = RateLimitMiddleware::new(names);
+    let api = LimitedApiBuilder::new(&base_url)
+        .with_middleware(limiter.clone())
+        .build();
+
+    let url = Url::parse(&base_url).unwrap();
+    assert_eq!(None, limiter.get_info(&url));
+
+    // The quota is not exhausted yet
+    let start = Instant::now();
+    api.touch().await?;
+    let info = limiter.get_info(&url).unwrap();
+    assert_eq!(Some(2), info.limit);
+    assert_eq!(Some(1), info.remaining);
+    api.touch().await?;
+    assert!(start.elapsed() < Duration::from_millis(500));
+
+    // The quota is exhausted, so the next request waits until it's reset
+    let info = limiter.get_info(&url).unwrap();
+    assert!(info.is_exhausted());
+    let start = Instant::now();
+    let res = api.touch().await?;
+    assert_eq!(3, res["count"]);
+    assert!(start.elapsed() >= Duration::from_millis(500));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rate_limit_max_wait() -> ApiResult<()> {
+    init_logger();
+    let addr = start_limited_server();
+    let base_url = format!("http://127.0.0.1:{}/v1", addr.port());
+
+    let names = RateLimitHeaders {
+        limit: "quota-limit".to_string(),
+        remaining: "quota-remaining".to_string(),
+        reset: "quota-reset".to_string(),
+        reset_format: RateLimitReset::DeltaSeconds,
+    };
+    let limiter = RateLimitMiddleware::new(names).with_max_wait(Duration::from_millis(10));
+    let api = LimitedApiBuilder::new(&base_url)
+        .with_middleware(limiter)
+        .build();
+
+    let start = Instant::now();
+    for _ in 0..4 {
+        api.touch().await?;
+    }
+    assert!(start.elapsed() < Duration::from_millis(500));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rate_limit_priority() -> ApiResult<()> {
+    init_logger();
+
+    // The quota (1 request) is always exhausted, and will be reset in 1 second
+    let served = Arc::new(Mutex::new(vec![]));
+    let routes = warp::path!("v1" / "limited" / String).map({
+        let served = served.clone();
+        move |tag: String| {
+            served.lock().unwrap().push(tag);
+            let reply = warp::reply::json(&json!({}));
+            let reply = warp::reply::with_header(reply, "ratelimit-remaining", "0");
+            warp::reply::with_header(reply, "ratelimit-reset", "1")
+        }
+    });
+    let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    let base_url = format!("http://127.0.0.1:{}/v1", addr.port());
+
+    let limiter = RateLimitMiddleware::new(RateLimitHeaders::ietf())
+        .with_max_wait(Duration::from_millis(200));
+    let api = LimitedApiBuilder::new(&base_url)
+        .with_middleware(limiter)
+        .build();
+
+    api.touch_tagged("first", Priority::Normal).await?;
+
+    // The background requests are queued, and then a user-facing request arrives
+    let mut handles = vec![];
+    for (tag, priority) in [
+        ("low-1", Priority::Low),
+        ("low-2", Priority::Low),
+        ("high", Priority::High),
+    ] {
+        let api = api.clone();
+        handles.push(tokio::spawn(async move {
+            api.touch_tagged(tag, priority).await
+        }));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    for handle in handles {
+        handle.await.unwrap()?;
+    }
+
+    assert_eq!(
+        vec!["first", "high", "low-1", "low-2"],
+        *served.lock().unwrap()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rate_limit_reset_concurrency() -> ApiResult<()> {
+    init_logger();
+
+    // The quota (3 requests) will be reset in 1 hour, and each request takes 100ms
+    let counter = Arc::new(AtomicUsize::new(0));
+    let active = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let routes = warp::path!("v1" / "limited").and_then({
+        let active = active.clone();
+        let peak = peak.clone();
+        move || {
+            let counter = counter.clone();
+            let active = active.clone();
+            let peak = peak.clone();
+            async move {
+                let count = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                let current = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                active.fetch_sub(1, Ordering::SeqCst);
+                let remaining = 3usize.saturating_sub(count);
+                let reply = warp::reply::json(&json!({ "count": count }));
+                let reply = warp::reply::with_header(reply, "ratelimit-limit", "3");
+                let reply =
+                    warp::reply::with_header(reply, "ratelimit-remaining", remaining.to_string());
+                let reply = warp::reply::with_header(reply, "ratelimit-reset", "3600");
+                Ok::<_, warp::Rejection>(reply)
+            }
+        }
+    });
+    let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    let base_url = format!("http://127.0.0.1:{}/v1", addr.port());
+
+    let limiter =
+        RateLimitMiddleware::new(RateLimitHeaders::ietf()).with_max_wait(Duration::from_secs(7200));
+    let api = LimitedApiBuilder::new(&base_url)
+        .with_middleware(limiter)
+        .with_clock(ManualClock::default())
+        .build();
+
+    for _ in 0..3 {
+        api.touch().await?;
+    }
+    assert_eq!(1, peak.load(Ordering::SeqCst));
+
+    // The waiting is driven by the clock, and the requests are sent together after the reset
+    let start = Instant::now();
+    let mut handles = vec![];
+    for _ in 0..3 {
+        let api = api.clone();
+        handles.push(tokio::spawn(async move { api.touch().await }));
+    }
+    for handle in handles {
+        handle.await.unwrap()?;
+    }
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert!(peak.load(Ordering::SeqCst) > 1);
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use apisdk::{send, send_json, ApiResult, RecordingMiddleware, RedactPolicy};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn touch(&self) -> ApiResult<Value> {
+        let req = self.get("/path/json").await?.query(&[("page", "1")]);
+        let req = req
+            .bearer_auth("secret-token")
+            .header("X-Api-Key", "secret-key");
+        send!(req).await
+    }
+
+    async fn echo(&self) -> ApiResult<Value> {
+        let req = self.post("/path/echo").await?;
+        send_json!(req, json!({"name": "apisdk"})).await
+    }
+}
+
+#[tokio::test]
+async fn test_record_har() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let recorder =
+        RecordingMiddleware::new(10).with_redact(RedactPolicy::default().with_header("x-api-key"));
+    let api = TheApi::builder().with_middleware(recorder.clone()).build();
+
+    // The response is still readable after being recorded
+    let res = api.touch().await?;
+    assert_eq!(Some("1"), res["data"]["query"]["page"].as_str());
+    api.echo().await?;
+    assert_eq!(2, recorder.len());
+
+    let har = recorder.export_har();
+    log::debug!("har = {}", har);
+    assert_eq!("1.2", har["log"]["version"]);
+    let entries = har["log"]["entries"].as_array().unwrap();
+    assert_eq!(2, entries.len());
+
+    let get = &entries[0];
+    assert_eq!("GET", get["request"]["method"]);
+    assert_eq!(
+        "http://localhost:3030/v1/path/json?page=1",
+        get["request"]["url"]
+    );
+    assert_eq!(
+        json!([{"name": "page", "value": "1"}]),
+        get["request"]["queryString"]
+    );
+    assert_eq!(200, get["response"]["status"]);
+    assert!(get["startedDateTime"].as_str().unwrap().ends_with('Z'));
+
+    // Sensitive headers are redacted
+    let headers = get["request"]["headers"].to_string();
+    assert!(headers.contains("authorization"));
+    assert!(headers.contains("[REDACTED]"));
+    assert!(!headers.contains("secret-token"));
+    assert!(!headers.contains("secret-key"));
+
+    let post = &entries[1];
+    assert_eq!("POST", post["request"]["method"]);
+    assert_eq!(200, post["response"]["status"]);
+    let body: Value =
+        serde_json::from_str(post["request"]["postData"]["text"].as_str().unwrap()).unwrap();
+    assert_eq!(json!({"name": "apisdk"}), body);
+    let content: Value =
+        serde_json::from_str(post["response"]["content"]["text"].as_str().unwrap()).unwrap();
+    assert_eq!(json!({"name": "apisdk"}), content["data"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_record_ring_buffer() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let recorder = RecordingMiddleware::new(1);
+    let api = TheApi::builder().with_middleware(recorder.clone()).build();
+
+    api.touch().await?;
+    api.echo().await?;
+
+    // Only the last one is kept
+    let har = recorder.export_har();
+    let entries = har["log"]["entries"].as_array().unwrap();
+    assert_eq!(1, entries.len());
+    assert_eq!("POST", entries[0]["request"]["method"]);
+
+    recorder.clear();
+    assert!(recorder.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "stats")]
+
+use apisdk::{send, send_json, ApiResult, RequestStats};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[tokio::test]
+async fn test_request_stats() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+    assert_eq!(RequestStats::default(), api.core.request_stats());
+
+    const N: u64 = 5;
+    for _ in 0..N {
+        let req = api.get("/path/json").await?;
+        let _: Value = send!(req).await?;
+    }
+    let payload = json!({ "key": "value" });
+    let req = api.post("/path/json").await?;
+    let _: Value = send_json!(req, payload).await?;
+
+    let stats = api.core.request_stats();
+    log::debug!("stats = {:?}", stats);
+    assert_eq!(N + 1, stats.requests);
+    assert_eq!(0, stats.failures);
+    assert_eq!(payload.to_string().len() as u64, stats.request_bytes);
+    assert!(stats.response_bytes > 0);
+    assert!(stats.latency.min > std::time::Duration::ZERO);
+    assert!(stats.latency.min <= stats.latency.p50);
+    assert!(stats.latency.p50 <= stats.latency.p99);
+    assert!(stats.latency.p99 <= stats.latency.max);
+    assert!(stats.latency.mean <= stats.latency.max);
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::net::{Ipv4Addr, SocketAddr};
+
+use apisdk::{send, ApiResult, MockServer, Request, ResponseBody};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi, TheApiBuilder};
+
+#[allow(unused)]
+mod common;
+
+/// Reply the url of request
+fn echo_url() -> MockServer {
+    MockServer::new(|req: Request| Ok(ResponseBody::Json(json!({ "url": req.url().to_string() }))))
+}
+
+fn builder() -> TheApiBuilder {
+    TheApi::builder().with_initialiser(echo_url())
+}
+
+impl TheApi {
+    async fn touch(&self) -> ApiResult<String> {
+        let req = self.get("/path/json").await?;
+        let res: Value = send!(req).await?;
+        Ok(res["url"].as_str().unwrap_or_default().to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_reset_router() -> ApiResult<()> {
+    init_logger();
+
+    let api = builder().build();
+    let cloned = api.clone();
+    assert_eq!("http://localhost:3030/v1/path/json", api.touch().await?);
+
+    // The request built before reset keeps the old config
+    let in_flight = api.get("/path/json").await?;
+
+    let endpoint = SocketAddr::from((Ipv4Addr::LOCALHOST, 3031));
+    api.reset(builder().with_rewriter(endpoint));
+    assert_eq!("http://127.0.0.1:3031/v1/path/json", api.touch().await?);
+    assert_eq!("http://127.0.0.1:3031/v1/path/json", cloned.touch().await?);
+
+    // The core of api is replaced as well
+    let url = api.core.build_url("/path/json").await?;
+    assert_eq!("http://127.0.0.1:3031/v1/path/json", url.as_str());
+
+    let res: Value = send!(in_flight).await?;
+    assert_eq!("http://localhost:3030/v1/path/json", res["url"]);
+
+    Ok(())
+}
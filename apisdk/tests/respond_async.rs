@@ -0,0 +1,252 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use apisdk::{
+    send, AccessTokenAuth, ApiError, ApiResult, CodeDataMessage, Extensions, ManualClock,
+    Middleware, RespondAsyncOps,
+};
+use async_trait::async_trait;
+use reqwest::{Request, Response};
+use reqwest_middleware::Next;
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn poll_with_credentials(&self, location: &str) -> ApiResult<Payload> {
+        let req = self
+            .get(format!("/path/accepted?location={}", location))
+            .await?
+            .header("Authorization", "Bearer secret")
+            .header("Cookie", "session=secret")
+            .header("X-Custom", "kept")
+            .with_respond_async(Duration::from_millis(10), Duration::from_secs(5));
+        send!(req, CodeDataMessage).await
+    }
+
+    async fn poll(&self, location: &str) -> ApiResult<Payload> {
+        let req = self
+            .get(format!("/path/accepted?location={}", location))
+            .await?
+            .with_respond_async(Duration::from_millis(10), Duration::from_secs(5));
+        send!(req, CodeDataMessage).await
+    }
+}
+
+/// This middleware acts as a server of long-running operations
+///
+/// The initial request is accepted with `Location`, and the operation completes after `pending` polls
+#[derive(Clone, Default)]
+struct Operations {
+    pending: usize,
+    retry_after: Option<u64>,
+    polls: Arc<AtomicUsize>,
+}
+
+impl Operations {
+    fn reply(&self, status: u16, body: &str) -> Response {
+        let mut res = hyper::Response::builder()
+            .status(status)
+            .header("content-type", "application/json");
+        if status == 202 {
+            res = res.header("location", "/v1/operations/42");
+            if let Some(seconds) = self.retry_after {
+                res = res.header("retry-after", seconds.to_string());
+            }
+        }
+        Response::from(res.body(body.to_string()).unwrap())
+    }
+}
+
+#[async_trait]
+impl Middleware for Operations {
+    async fn handle(
+        &self,
+        req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        if req.url().path() != "/v1/operations/42" {
+            assert_eq!("respond-async", req.headers()["prefer"]);
+            return Ok(self.reply(202, "{}"));
+        }
+        assert_eq!("GET", req.method().as_str());
+        let polls = self.polls.fetch_add(1, Ordering::SeqCst) + 1;
+        if polls < self.pending {
+            Ok(self.reply(202, r#"{"status": "running"}"#))
+        } else {
+            Ok(self.reply(200, r#"{"status": "done", "id": 42}"#))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_respond_async() -> ApiResult<()> {
+    init_logger();
+
+    let operations = Operations {
+        pending: 2,
+        ..Default::default()
+    };
+    let api = TheApi::builder()
+        .with_middleware(operations.clone())
+        .build();
+
+    let req = api
+        .post("/reports")
+        .await?
+        .with_respond_async(Duration::from_millis(10), Duration::from_secs(5));
+    let res: Value = send!(req).await?;
+    assert_eq!("done", res["status"]);
+    assert_eq!(42, res["id"]);
+    assert_eq!(2, operations.polls.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_respond_async_retry_after() -> ApiResult<()> {
+    init_logger();
+
+    let operations = Operations {
+        pending: 1,
+        retry_after: Some(1),
+        ..Default::default()
+    };
+    let api = TheApi::builder()
+        .with_middleware(operations.clone())
+        .build();
+
+    let start = Instant::now();
+    let req = api
+        .post("/reports")
+        .await?
+        .with_respond_async(Duration::from_millis(10), Duration::from_secs(5));
+    let res: Value = send!(req).await?;
+    assert_eq!("done", res["status"]);
+    assert!(start.elapsed() >= Duration::from_secs(1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_respond_async_timeout() -> ApiResult<()> {
+    init_logger();
+
+    let operations = Operations {
+        pending: usize::MAX,
+        ..Default::default()
+    };
+    let api = TheApi::builder()
+        .with_middleware(operations.clone())
+        .build();
+
+    let req = api
+        .post("/reports")
+        .await?
+        .with_respond_async(Duration::from_millis(20), Duration::from_millis(100));
+    let res: ApiResult<Value> = send!(req).await;
+    assert!(matches!(res, Err(ApiError::Timeout)));
+    assert!(operations.polls.load(Ordering::SeqCst) >= 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_respond_async_clock() -> ApiResult<()> {
+    init_logger();
+
+    let operations = Operations {
+        pending: usize::MAX,
+        retry_after: Some(10),
+        ..Default::default()
+    };
+    let api = TheApi::builder()
+        .with_clock(ManualClock::new(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000),
+        ))
+        .with_middleware(operations.clone())
+        .build();
+
+    // The delays are waited by the manual clock, rather than the real time
+    let start = Instant::now();
+    let req = api
+        .post("/reports")
+        .await?
+        .with_respond_async(Duration::from_millis(10), Duration::from_secs(60));
+    let res: ApiResult<Value> = send!(req).await;
+    assert!(matches!(res, Err(ApiError::Timeout)));
+    assert_eq!(6, operations.polls.load(Ordering::SeqCst));
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_respond_async_credentials() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    // The base url is `http://localhost:3030/v1`, so the credentials are kept in the same origin
+    let res = api.poll_with_credentials("/v1/path/json").await?;
+    assert_eq!(
+        Some("Bearer secret"),
+        res.headers.get("authorization").map(|v| v.as_str())
+    );
+    assert_eq!(
+        Some("session=secret"),
+        res.headers.get("cookie").map(|v| v.as_str())
+    );
+    assert_eq!(
+        Some("kept"),
+        res.headers.get("x-custom").map(|v| v.as_str())
+    );
+
+    // But removed in another origin
+    let res = api
+        .poll_with_credentials("http://127.0.0.1:3030/v1/path/json")
+        .await?;
+    assert_eq!("/v1/path/json", res.path);
+    assert!(!res.headers.contains_key("authorization"));
+    assert!(!res.headers.contains_key("cookie"));
+    assert_eq!(
+        Some("kept"),
+        res.headers.get("x-custom").map(|v| v.as_str())
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_respond_async_authenticator() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder()
+        .with_authenticator(AccessTokenAuth::new("secret"))
+        .build();
+
+    // The poll is signed in the same origin
+    let res = api.poll("/v1/path/json").await?;
+    assert_eq!(
+        Some("Bearer secret"),
+        res.headers.get("authorization").map(|v| v.as_str())
+    );
+
+    // But not in another origin
+    let res = api.poll("http://127.0.0.1:3030/v1/path/json").await?;
+    assert_eq!("/v1/path/json", res.path);
+    assert!(!res.headers.contains_key("authorization"));
+
+    Ok(())
+}
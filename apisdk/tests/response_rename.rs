@@ -0,0 +1,77 @@
+use apisdk::{
+    send, ApiResult, CodeDataMessage, MockServer, RenameRule, ResponseBody, ResponseRenameAll,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct User {
+    user_id: i64,
+    display_name: String,
+    tag_list: Vec<Tag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tag {
+    tag_name: String,
+}
+
+fn camel_case_user() -> MockServer {
+    MockServer::new(|_| {
+        Ok(ResponseBody::Json(json!({
+            "userId": 1,
+            "displayName": "Alice",
+            "tagList": [{ "tagName": "admin" }]
+        })))
+    })
+}
+
+#[tokio::test]
+async fn test_response_rename_all() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(camel_case_user())
+        .with_initialiser(ResponseRenameAll(RenameRule::SnakeCase))
+        .build();
+
+    let req = api.get("/users/1").await?;
+    let user: User = send!(req).await?;
+    assert_eq!(1, user.user_id);
+    assert_eq!("Alice", user.display_name);
+    assert_eq!("admin", user.tag_list[0].tag_name);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_response_rename_all_per_request() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::new(|_| {
+            Ok(ResponseBody::Json(json!({
+                "code": 0,
+                "data": { "userId": 1, "displayName": "Alice", "tagList": [] }
+            })))
+        }))
+        .build();
+
+    let req = api.get("/users/1").await?;
+    let req = req.with_extension(ResponseRenameAll(RenameRule::SnakeCase));
+    let user: User = send!(req, CodeDataMessage).await?;
+    assert_eq!(1, user.user_id);
+    assert!(user.tag_list.is_empty());
+
+    // Without the extension, the fields are missing
+    let req = api.get("/users/1").await?;
+    let res: ApiResult<User> = send!(req, CodeDataMessage).await;
+    assert!(res.is_err());
+
+    Ok(())
+}
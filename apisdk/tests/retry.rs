@@ -0,0 +1,147 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use apisdk::{
+    send, ApiError, ApiResult, Method, MockServer, ResponseBody, RetryMiddleware, RetryPolicy,
+    StatusCode,
+};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn touch_status(&self, status: StatusCode, hits: Arc<AtomicUsize>) -> ApiResult<Value> {
+        self.touch_method(Method::GET, status, hits).await
+    }
+
+    async fn touch_method(
+        &self,
+        method: Method,
+        status: StatusCode,
+        hits: Arc<AtomicUsize>,
+    ) -> ApiResult<Value> {
+        let req = self.request(method, "/path/json").await?;
+        let req = req.with_extension(
+            MockServer::new(move |_| {
+                hits.fetch_add(1, Ordering::SeqCst);
+                Ok(ResponseBody::Json(json!({})))
+            })
+            .with_status(status),
+        );
+        send!(req).await
+    }
+}
+
+#[tokio::test]
+async fn test_retry_default() -> ApiResult<()> {
+    init_logger();
+
+    let policy = RetryPolicy::new(2, Duration::from_millis(10));
+    let api = TheApi::builder()
+        .with_middleware(RetryMiddleware::new(policy))
+        .build();
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let res = api
+        .touch_status(StatusCode::SERVICE_UNAVAILABLE, hits.clone())
+        .await;
+    assert!(matches!(res, Err(ApiError::HttpServerStatus(503, _))));
+    assert_eq!(3, hits.load(Ordering::SeqCst));
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let res = api.touch_status(StatusCode::CONFLICT, hits.clone()).await;
+    assert!(matches!(res, Err(ApiError::HttpClientStatus(409, _))));
+    assert_eq!(1, hits.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retry_if() -> ApiResult<()> {
+    init_logger();
+
+    let attempts = Arc::new(std::sync::Mutex::new(vec![]));
+    let policy = RetryPolicy::retry_if({
+        let attempts = attempts.clone();
+        move |e: &ApiError, attempt| {
+            attempts.lock().unwrap().push(attempt);
+            match e.status() {
+                Some(409) if attempt <= 2 => Some(Duration::from_millis(10 * attempt as u64)),
+                _ => None,
+            }
+        }
+    });
+    let api = TheApi::builder()
+        .with_middleware(RetryMiddleware::new(policy))
+        .build();
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let res = api.touch_status(StatusCode::CONFLICT, hits.clone()).await;
+    assert!(matches!(res, Err(ApiError::HttpClientStatus(409, _))));
+    assert_eq!(3, hits.load(Ordering::SeqCst));
+    assert_eq!(vec![1, 2, 3], *attempts.lock().unwrap());
+
+    // Not retried, since the predicate doesn't cover it
+    let hits = Arc::new(AtomicUsize::new(0));
+    let res = api
+        .touch_status(StatusCode::SERVICE_UNAVAILABLE, hits.clone())
+        .await;
+    assert!(res.is_err());
+    assert_eq!(1, hits.load(Ordering::SeqCst));
+
+    // Succeeded at first
+    let hits = Arc::new(AtomicUsize::new(0));
+    api.touch_status(StatusCode::OK, hits.clone()).await?;
+    assert_eq!(1, hits.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retry_non_idempotent() -> ApiResult<()> {
+    init_logger();
+
+    let policy = RetryPolicy::new(2, Duration::from_millis(10));
+    let api = TheApi::builder()
+        .with_middleware(RetryMiddleware::new(policy.clone()))
+        .build();
+
+    // Not retried by default
+    for method in [Method::POST, Method::PATCH] {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let res = api
+            .touch_method(method, StatusCode::SERVICE_UNAVAILABLE, hits.clone())
+            .await;
+        assert!(matches!(res, Err(ApiError::HttpServerStatus(503, _))));
+        assert_eq!(1, hits.load(Ordering::SeqCst));
+    }
+
+    // Idempotent methods are retried
+    let hits = Arc::new(AtomicUsize::new(0));
+    let res = api
+        .touch_method(Method::PUT, StatusCode::SERVICE_UNAVAILABLE, hits.clone())
+        .await;
+    assert!(res.is_err());
+    assert_eq!(3, hits.load(Ordering::SeqCst));
+
+    // Retried when opted in
+    let api = TheApi::builder()
+        .with_middleware(RetryMiddleware::new(policy.with_non_idempotent(true)))
+        .build();
+    let hits = Arc::new(AtomicUsize::new(0));
+    let res = api
+        .touch_method(Method::POST, StatusCode::SERVICE_UNAVAILABLE, hits.clone())
+        .await;
+    assert!(res.is_err());
+    assert_eq!(3, hits.load(Ordering::SeqCst));
+
+    Ok(())
+}
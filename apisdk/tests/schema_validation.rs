@@ -0,0 +1,111 @@
+#![cfg(feature = "schema")]
+
+use apisdk::{
+    jsonschema::JSONSchema, send_with_schema_validation, ApiError, ApiResult, CodeDataMessage,
+    MockServer, ResponseBody,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct User {
+    id: i64,
+    name: String,
+}
+
+fn user_schema() -> JSONSchema {
+    JSONSchema::compile(&json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "integer" },
+            "name": { "type": "string" }
+        },
+        "required": ["id", "name"]
+    }))
+    .unwrap()
+}
+
+fn reply(payload: Value) -> MockServer {
+    MockServer::new(move |_| Ok(ResponseBody::Json(payload.clone())))
+}
+
+#[tokio::test]
+async fn test_schema_validation() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(reply(json!({ "id": 1, "name": "alice" })))
+        .build();
+
+    let schema = user_schema();
+    let req = api.get("/users/1").await?;
+    let user: User = send_with_schema_validation!(req, schema).await?;
+    assert_eq!(1, user.id);
+    assert_eq!("alice", user.name);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_schema_validation_missing_field() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(reply(json!({ "id": 1 })))
+        .build();
+
+    let schema = user_schema();
+    let req = api.get("/users/1").await?;
+    let res: ApiResult<User> = send_with_schema_validation!(req, schema).await;
+    match res {
+        Err(ApiError::SchemaValidation(violations)) => {
+            assert_eq!(1, violations.len());
+            assert_eq!("", violations[0].pointer);
+            assert!(violations[0].message.contains("\"name\""));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_schema_validation_with_extractor() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_initialiser(reply(json!({
+            "code": 0,
+            "data": { "id": "1", "name": "alice" }
+        })))
+        .build();
+
+    let schema = JSONSchema::compile(&json!({
+        "type": "object",
+        "properties": {
+            "data": { "$ref": "#/definitions/user" }
+        },
+        "definitions": {
+            "user": {
+                "type": "object",
+                "properties": { "id": { "type": "integer" } }
+            }
+        }
+    }))
+    .unwrap();
+    let req = api.get("/users/1").await?;
+    let res: ApiResult<User> = send_with_schema_validation!(req, schema, CodeDataMessage).await;
+    match res {
+        Err(ApiError::SchemaValidation(violations)) => {
+            assert_eq!("/data/id", violations[0].pointer);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    Ok(())
+}
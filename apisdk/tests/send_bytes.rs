@@ -0,0 +1,68 @@
+#![cfg(not(feature = "tracing"))]
+
+use std::sync::Mutex;
+
+use apisdk::{send_bytes, ApiResult};
+use log::{LevelFilter, Log, Metadata, Record};
+use serde::Deserialize;
+
+use crate::common::{start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// This logger is used to capture all messages
+struct CaptureLogger {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.lines.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+/// The request received by server
+#[derive(Debug, Deserialize)]
+struct Received {
+    content_type: String,
+    body: Vec<u8>,
+}
+
+static LOGGER: CaptureLogger = CaptureLogger {
+    lines: Mutex::new(vec![]),
+};
+
+/// `{"id": 1, "tags": ["a"]}` encoded as CBOR
+const CBOR: &[u8] = &[
+    0xa2, 0x62, 0x69, 0x64, 0x01, 0x64, 0x74, 0x61, 0x67, 0x73, 0x81, 0x61, 0x61,
+];
+
+#[tokio::test]
+async fn test_send_bytes() -> ApiResult<()> {
+    let _ = log::set_logger(&LOGGER).map(|_| log::set_max_level(LevelFilter::Trace));
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    // The server replies the received body and content type
+    let req = api.post("/path/bytes").await?;
+    let received: Received = send_bytes!(req, CBOR.to_vec(), "application/cbor").await?;
+    assert_eq!("application/cbor", received.content_type);
+    assert_eq!(CBOR, received.body.as_slice());
+
+    // The content type and length are logged, instead of the bytes
+    let lines = LOGGER.lines.lock().unwrap();
+    let expected = format!("application/cbor, {} bytes", CBOR.len());
+    assert!(lines
+        .iter()
+        .any(|line| line.contains("Request Bytes") && line.ends_with(&expected)));
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::{net::SocketAddr, time::Duration};
+
+use apisdk::{send_channel_body, ApiResult};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use warp::{hyper::body::Bytes, Filter};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// Start a server, which replies the received body and transfer-encoding
+fn start_upload_server() -> SocketAddr {
+    let routes = warp::post()
+        .and(warp::path!("v1" / "upload"))
+        .and(warp::header::optional::<String>("transfer-encoding"))
+        .and(warp::body::bytes())
+        .map(|encoding: Option<String>, body: Bytes| {
+            warp::reply::json(&json!({
+                "encoding": encoding,
+                "body": String::from_utf8_lossy(&body),
+            }))
+        });
+    let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    addr
+}
+
+#[tokio::test]
+async fn test_send_channel_body() -> ApiResult<()> {
+    init_logger();
+    let addr = start_upload_server();
+
+    let (tx, rx) = mpsc::channel::<String>(1);
+    tokio::spawn(async move {
+        for i in 0..5 {
+            tx.send(format!("line {}\n", i)).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // Dropping the sender ends the request body
+    });
+
+    let api = TheApi::default();
+    let req = api.post(format!("http://{}/v1/upload", addr)).await?;
+    let res: Value = send_channel_body!(req, rx, Json).await?;
+    log::debug!("res = {:?}", res);
+
+    assert_eq!("chunked", res["encoding"]);
+    assert_eq!("line 0\nline 1\nline 2\nline 3\nline 4\n", res["body"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_channel_body_empty() -> ApiResult<()> {
+    init_logger();
+    let addr = start_upload_server();
+
+    let (tx, rx) = mpsc::channel::<Vec<u8>>(1);
+    drop(tx);
+
+    let api = TheApi::default();
+    let req = api.post(format!("http://{}/v1/upload", addr)).await?;
+    let res: Value = send_channel_body!(req, rx, Json).await?;
+
+    assert_eq!("", res["body"]);
+
+    Ok(())
+}
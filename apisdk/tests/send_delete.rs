@@ -0,0 +1,51 @@
+use std::sync::{Arc, Mutex};
+
+use apisdk::{send_json, ApiResult, CodeDataMessage, MockServer, RequestBody, ResponseBody};
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[tokio::test]
+async fn test_send_delete_with_body() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let payload = json!({ "query": { "match": { "user": "alice" } } });
+    let req = api.delete("/path/echo").await?;
+    let res: Value = send_json!(req, &payload, CodeDataMessage).await?;
+    assert_eq!(payload, res);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_delete_with_body_mock() -> ApiResult<()> {
+    init_logger();
+
+    let received = Arc::new(Mutex::new(None));
+    let api = TheApi::builder()
+        .with_initialiser(MockServer::with_body({
+            let received = received.clone();
+            move |req, body| {
+                assert_eq!("DELETE", req.method().as_str());
+                if let RequestBody::Json(json) = body {
+                    *received.lock().unwrap() = Some(json);
+                }
+                Ok(ResponseBody::Json(json!({ "acknowledged": true })))
+            }
+        }))
+        .build();
+
+    let payload = json!({ "ids": [1, 2, 3] });
+    let req = api.delete("/index/_doc").await?;
+    let res: Value = send_json!(req, &payload).await?;
+    assert_eq!(true, res["acknowledged"]);
+    assert_eq!(Some(payload), received.lock().unwrap().take());
+
+    Ok(())
+}
@@ -1,6 +1,13 @@
-use std::collections::HashMap;
-
-use apisdk::{send_form, ApiResult, CodeDataMessage, DynamicForm, MultipartForm, MultipartFormOps};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use apisdk::{
+    send_form, ApiResult, CodeDataMessage, DynamicForm, MockServer, MultipartForm,
+    MultipartFormOps, ResponseBody,
+};
 use serde_json::{json, Value};
 
 use crate::common::{init_logger, start_server, TheApi};
@@ -115,3 +122,82 @@ async fn test_send_form_via_multipart_form() {
     let res = api.form_via_multipart_form().await.unwrap();
     log::debug!("res = {:?}", res);
 }
+
+#[tokio::test]
+async fn test_send_form_with_repeated_keys() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().build();
+
+    let body = Arc::new(Mutex::new(vec![]));
+    let captured = body.clone();
+    let mock = MockServer::new(move |req: apisdk::Request| {
+        let body = req.body().and_then(|b| b.as_bytes()).unwrap_or_default();
+        *captured.lock().unwrap() = body.to_vec();
+        Ok(ResponseBody::Json(json!({"ok": true})))
+    });
+
+    let req = api.post("/path/form").await?.with_extension(mock);
+    let form = vec![("sig", "a"), ("b", "1"), ("sig", "b")];
+    let res: Value = send_form!(req, form).await?;
+    assert_eq!(json!({"ok": true}), res);
+
+    let body = body.lock().unwrap().clone();
+    assert_eq!("sig=a&b=1&sig=b", String::from_utf8(body).unwrap());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_form_with_lazy_value() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().build();
+
+    let bodies = Arc::new(Mutex::new(vec![]));
+    let captured = bodies.clone();
+    let mock = MockServer::new(move |req: apisdk::Request| {
+        let body = req.body().and_then(|b| b.as_bytes()).unwrap_or_default();
+        captured
+            .lock()
+            .unwrap()
+            .push(String::from_utf8_lossy(body).to_string());
+        Ok(ResponseBody::Json(json!({"ok": true})))
+    });
+
+    let now = || {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos()
+            .to_string()
+    };
+    let built = now();
+    let forms = [
+        DynamicForm::new().text("key", "value").text_with("ts", now),
+        DynamicForm::new().text("key", "value").text_with("ts", now),
+    ];
+    for form in forms {
+        let req = api.post("/path/form").await?.with_extension(mock.clone());
+        let _: Value = send_form!(req, form).await?;
+    }
+
+    let timestamps: Vec<String> = bodies
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|body| {
+            let (_, ts) = body.split_once("ts=").unwrap();
+            ts.split('&').next().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(2, timestamps.len());
+    assert_ne!(timestamps[0], timestamps[1]);
+    // The values are produced when sending, rather than building
+    for ts in timestamps {
+        assert!(ts.parse::<u128>().unwrap() > built.parse::<u128>().unwrap());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use apisdk::{send_json_array, ApiResult, JsonArrayStream};
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Deserialize)]
+pub struct Item {
+    pub id: u32,
+    pub name: String,
+}
+
+impl TheApi {
+    async fn stream_values(&self, count: usize) -> ApiResult<JsonArrayStream<Value>> {
+        let req = self.get(format!("/path/json-array/{}", count)).await?;
+        send_json_array!(req).await
+    }
+
+    async fn stream_items(&self, count: usize) -> ApiResult<JsonArrayStream<Item>> {
+        let req = self.get(format!("/path/json-array/{}", count)).await?;
+        send_json_array!(req, Item).await
+    }
+}
+
+#[tokio::test]
+async fn test_send_json_array() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let mut stream = api.stream_values(3).await?;
+    let mut items = vec![];
+    while let Some(item) = stream.next().await {
+        items.push(item?);
+    }
+    log::debug!("items = {:?}", items);
+    assert_eq!(3, items.len());
+    assert_eq!(Some(3), items[2]["id"].as_u64());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_json_array_large() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    // Count items one by one, without collecting them
+    let mut stream = api.stream_items(100_000).await?;
+    let mut count = 0;
+    let mut last_id = 0;
+    while let Some(item) = stream.next().await {
+        let item = item?;
+        assert_eq!(last_id + 1, item.id);
+        last_id = item.id;
+        count += 1;
+    }
+    assert_eq!(100_000, count);
+
+    Ok(())
+}
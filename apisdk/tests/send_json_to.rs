@@ -0,0 +1,93 @@
+use apisdk::{send_json_to, ApiError, ApiResult};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::json;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Payload {
+    id: u32,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Echo {
+    code: i64,
+    data: Payload,
+}
+
+/// A payload, which could not be serialized
+struct Unserializable;
+
+impl Serialize for Unserializable {
+    fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Err(serde::ser::Error::custom("unserializable"))
+    }
+}
+
+impl TheApi {
+    async fn echo_payload(&self, payload: &Payload) -> ApiResult<Echo> {
+        let req = self.post("/path/echo").await?;
+        send_json_to!(req, payload).await
+    }
+
+    async fn echo_unserializable(&self) -> ApiResult<Echo> {
+        let req = self.post("/path/echo").await?;
+        send_json_to!(req, Unserializable, Echo).await
+    }
+
+    async fn echo_mismatched(&self) -> ApiResult<Echo> {
+        let req = self.post("/path/echo").await?;
+        send_json_to!(req, json!({"id": "not-a-number"})).await
+    }
+}
+
+#[tokio::test]
+async fn test_send_json_to() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let payload = Payload {
+        id: 1,
+        name: "apisdk".to_string(),
+    };
+    let res = api.echo_payload(&payload).await?;
+    assert_eq!(0, res.code);
+    assert_eq!(payload, res.data);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_json_to_serialize_error() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.echo_unserializable().await;
+    assert!(matches!(res, Err(ApiError::EncodeJson(_))), "{:?}", res);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_json_to_deserialize_error() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.echo_mismatched().await;
+    assert!(matches!(res, Err(ApiError::DecodeJson(_))), "{:?}", res);
+
+    Ok(())
+}
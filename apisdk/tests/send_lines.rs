@@ -0,0 +1,52 @@
+use apisdk::{send_lines, ApiError, ApiResult, LineStream};
+use futures::StreamExt;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn stream_lines(&self) -> ApiResult<LineStream> {
+        let req = self.get("/path/lines").await?;
+        send_lines!(req).await
+    }
+
+    async fn stream_not_found(&self) -> ApiResult<LineStream> {
+        let req = self.get("/not-found").await?;
+        send_lines!(req).await
+    }
+}
+
+#[tokio::test]
+async fn test_send_lines() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let mut stream = api.stream_lines().await?;
+    assert!(stream.is_chunked());
+
+    let mut lines = vec![];
+    while let Some(line) = stream.next().await {
+        lines.push(line?);
+    }
+    log::debug!("lines = {:?}", lines);
+    assert_eq!(vec!["first line", "", "second line", "third line"], lines);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_lines_status() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.stream_not_found().await;
+    assert!(matches!(res, Err(ApiError::HttpClientStatus(..))));
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use std::net::SocketAddr;
+
+use apisdk::{http_api, send_ndjson, ApiError, ApiResult, MockServer, NdjsonStream, ResponseBody};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{json, Value};
+use warp::Filter;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Item {
+    pub id: u32,
+    pub name: String,
+}
+
+impl TheApi {
+    async fn stream_items(&self) -> ApiResult<NdjsonStream<Item>> {
+        let req = self.get("/path/ndjson").await?;
+        send_ndjson!(req).await
+    }
+
+    async fn stream_malformed(&self) -> ApiResult<NdjsonStream<Item>> {
+        let req = self.get("/path/ndjson").await?;
+        let req = req.with_extension(MockServer::new(|_| {
+            Ok(ResponseBody::Text(
+                "{\"id\":1,\"name\":\"a\"}\nmalformed\n{\"id\":3,\"name\":\"c\"}\n".to_string(),
+            ))
+        }));
+        send_ndjson!(req).await
+    }
+}
+
+#[tokio::test]
+async fn test_send_ndjson() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let mut stream = api.stream_items().await?;
+    let mut items = vec![];
+    while let Some(item) = stream.next().await {
+        items.push(item?);
+    }
+    log::debug!("items = {:?}", items);
+    assert_eq!(3, items.len());
+    assert_eq!(
+        vec![1, 2, 3],
+        items.iter().map(|i| i.id).collect::<Vec<_>>()
+    );
+    assert_eq!("c", items[2].name);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_ndjson_malformed() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let items: Vec<_> = api.stream_malformed().await?.collect().await;
+    log::debug!("items = {:?}", items);
+    assert_eq!(3, items.len());
+    assert!(items[1].is_err());
+    assert_eq!(3, items[2].as_ref().unwrap().id);
+
+    let items: Vec<_> = api.stream_malformed().await?.fail_fast().collect().await;
+    assert_eq!(2, items.len());
+    assert!(items[1].is_err());
+
+    Ok(())
+}
+
+#[http_api("http://127.0.0.1/v1")]
+#[derive(Debug, Clone)]
+pub struct IngestApi;
+
+impl IngestApi {
+    async fn ingest(&self, items: Vec<Item>) -> ApiResult<Value> {
+        let req = self.post("/ingest").await?;
+        send_ndjson!(req, items).await
+    }
+
+    async fn ingest_unit<T: Serialize>(&self, items: &[T]) -> ApiResult<()> {
+        let req = self.post("/ingest").await?;
+        send_ndjson!(req, items, ()).await
+    }
+}
+
+/// Start a server, which replies the raw body and content-type
+fn start_ingest_server() -> SocketAddr {
+    let routes = warp::path!("v1" / "ingest")
+        .and(warp::header::<String>("content-type"))
+        .and(warp::body::bytes())
+        .map(|content_type: String, body: warp::hyper::body::Bytes| {
+            warp::reply::json(&json!({
+                "content_type": content_type,
+                "body": String::from_utf8_lossy(&body),
+            }))
+        });
+    let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    addr
+}
+
+#[tokio::test]
+async fn test_send_ndjson_body() -> ApiResult<()> {
+    init_logger();
+    let addr = start_ingest_server();
+
+    let api = IngestApiBuilder::new(format!("http://127.0.0.1:{}/v1", addr.port())).build();
+
+    let items = vec![
+        Item {
+            id: 1,
+            name: "a".to_string(),
+        },
+        Item {
+            id: 2,
+            name: "b".to_string(),
+        },
+        Item {
+            id: 3,
+            name: "c".to_string(),
+        },
+    ];
+    let res = api.ingest(items).await?;
+    assert_eq!("application/x-ndjson", res["content_type"]);
+    assert_eq!(
+        "{\"id\":1,\"name\":\"a\"}\n{\"id\":2,\"name\":\"b\"}\n{\"id\":3,\"name\":\"c\"}\n",
+        res["body"]
+    );
+
+    api.ingest_unit(&[json!({"id": 1})]).await?;
+
+    Ok(())
+}
+
+struct Unserializable;
+
+impl Serialize for Unserializable {
+    fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("unserializable"))
+    }
+}
+
+#[tokio::test]
+async fn test_send_ndjson_body_unserializable() -> ApiResult<()> {
+    init_logger();
+
+    let api = IngestApi::default();
+
+    let res = api.ingest_unit(&[Unserializable]).await;
+    assert!(matches!(res, Err(ApiError::EncodeJson(_))));
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use apisdk::{http_api, send, ApiResult, CodeDataMessage, ReqwestClient};
+
+use crate::common::{init_logger, start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[http_api("http://127.0.0.1:3030/v1/path")]
+#[derive(Debug, Clone)]
+pub struct PathApi;
+
+impl TheApi {
+    async fn touch(&self) -> ApiResult<Payload> {
+        let req = self.get("/path/json").await?;
+        send!(req, CodeDataMessage).await
+    }
+}
+
+impl PathApi {
+    async fn touch(&self) -> ApiResult<Payload> {
+        let req = self.get("/json").await?;
+        send!(req, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_shared_client() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let client = Arc::new(ReqwestClient::new());
+    let api1 = TheApi::builder().with_shared_client(client.clone()).build();
+    let api2 = PathApi::builder()
+        .with_shared_client(client.clone())
+        .with_initialiser(|req: apisdk::RequestBuilder| req.header("X-Api", "path"))
+        .build();
+
+    // Both apis function, with their own initialisers
+    let res = api1.touch().await?;
+    assert_eq!("/v1/path/json", res.path);
+    assert_eq!(None, res.headers.get("x-api"));
+    let res = api2.touch().await?;
+    assert_eq!("/v1/path/json", res.path);
+    assert_eq!(Some("path"), res.headers.get("x-api").map(|v| v.as_str()));
+
+    // The client is literally shared
+    let shared1 = api1.core.shared_client().unwrap();
+    let shared2 = api2.core.shared_client().unwrap();
+    assert!(Arc::ptr_eq(&shared1, &shared2));
+    assert!(Arc::ptr_eq(&client, &shared1));
+
+    // The rebased api keeps sharing the client
+    let rebased = api1.core.rebase("http://127.0.0.1:3030/v1")?;
+    assert!(Arc::ptr_eq(&client, &rebased.shared_client().unwrap()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shared_client_not_set() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().build();
+    assert!(api.core.shared_client().is_none());
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use apisdk::{
+    send, ApiError, ApiResult, CodeDataMessage, Middleware, MiddlewareError, Request, Response,
+    ResponseBody, StatusCode,
+};
+use async_trait::async_trait;
+use reqwest_middleware::Next;
+use serde_json::json;
+use task_local_extensions::Extensions;
+
+use crate::common::{init_logger, start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+impl TheApi {
+    async fn touch(&self) -> ApiResult<Payload> {
+        let req = self.get("/path/json").await?;
+        send!(req, CodeDataMessage).await
+    }
+
+    async fn touch_maintenance(&self) -> ApiResult<Payload> {
+        let req = self.get("/path/maintenance").await?;
+        send!(req, CodeDataMessage).await
+    }
+}
+
+/// This middleware replies 503 for maintenance path, without hitting the network
+pub struct Maintenance;
+
+#[async_trait]
+impl Middleware for Maintenance {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response, MiddlewareError> {
+        if req.url().path().ends_with("/maintenance") {
+            let body = ResponseBody::Json(json!({
+                "code": 503,
+                "message": "Under maintenance"
+            }));
+            return Ok(body.into_response(StatusCode::SERVICE_UNAVAILABLE, req.url().clone())?);
+        }
+        next.run(req, extensions).await
+    }
+}
+
+#[tokio::test]
+async fn test_short_circuit() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_middleware(Maintenance).build();
+
+    let res = api.touch_maintenance().await;
+    log::debug!("res = {:?}", res);
+    assert!(matches!(res, Err(ApiError::HttpServerStatus(503, _))));
+
+    let res = api.touch().await?;
+    log::debug!("res = {:?}", res);
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use apisdk::{
+    digest, send, send_json, ApiAuthenticator, ApiResult, Carrier, MockServer, ResponseBody,
+    SignableRequest, TokenGenerator,
+};
+use async_trait::async_trait;
+use reqwest::Request;
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+const SECRET: &str = "secret";
+
+/// This authenticator signs the method, path and the hash of body
+struct BodySigner {
+    carrier: Carrier,
+}
+
+impl BodySigner {
+    fn new() -> Self {
+        Self {
+            carrier: Carrier::Header("X-Signature".to_string()),
+        }
+    }
+}
+
+fn string_to_sign(req: &Request) -> String {
+    format!(
+        "{}\n{}\n{}",
+        req.method(),
+        req.url().path(),
+        req.body_sha256().unwrap_or_default()
+    )
+}
+
+#[async_trait]
+impl TokenGenerator for BodySigner {
+    async fn generate_token(&self, req: &Request) -> Result<String, reqwest_middleware::Error> {
+        Ok(digest::hmac_sha256(SECRET, string_to_sign(req)))
+    }
+}
+
+impl ApiAuthenticator for BodySigner {
+    fn get_carrier(&self) -> &Carrier {
+        &self.carrier
+    }
+}
+
+/// Verify the signature as the server, and reply the hash of body
+fn verifier() -> MockServer {
+    MockServer::new(|req: Request| {
+        let signature = req.headers()["x-signature"].to_str()?;
+        let expected = digest::hmac_sha256(SECRET, string_to_sign(&req));
+        Ok(ResponseBody::Json(json!({
+            "verified": signature == expected,
+            "body_sha256": req.body_sha256(),
+        })))
+    })
+}
+
+#[tokio::test]
+async fn test_sign_body() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_authenticator(BodySigner::new())
+        .with_initialiser(verifier())
+        .build();
+
+    let req = api.post("/orders").await?;
+    let payload = json!({ "sku": "apple", "quantity": 3 });
+    let res: Value = send_json!(req, payload.clone()).await?;
+    assert_eq!(true, res["verified"]);
+    assert_eq!(digest::sha256(payload.to_string()), res["body_sha256"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sign_empty_body() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder()
+        .with_authenticator(BodySigner::new())
+        .with_initialiser(verifier())
+        .build();
+
+    let req = api.get("/orders").await?;
+    let res: Value = send!(req).await?;
+    assert_eq!(true, res["verified"]);
+    assert_eq!(digest::sha256(""), res["body_sha256"]);
+
+    Ok(())
+}
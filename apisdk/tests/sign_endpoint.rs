@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use apisdk::{
+    digest, send, ApiAuthenticator, ApiError, ApiResult, CodeDataMessage, Extensions, Method,
+    TokenGenerator, Url, UrlRewriter,
+};
+use async_trait::async_trait;
+use reqwest::Request;
+use serde_json::Value;
+
+use crate::common::{init_logger, start_server, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// Dispatch requests to the endpoints in turn
+struct RoundRobin {
+    endpoints: Vec<&'static str>,
+    next: AtomicUsize,
+}
+
+#[async_trait]
+impl UrlRewriter for RoundRobin {
+    async fn rewrite(&self, url: Url) -> Result<Url, ApiError> {
+        let index = self.next.fetch_add(1, Ordering::SeqCst) % self.endpoints.len();
+        let mut url = url;
+        let _ = url.set_host(Some(self.endpoints[index]));
+        Ok(url)
+    }
+}
+
+/// Sign `{host}:{port}{path}?{query}`, and append `signature` to query
+struct QuerySigner;
+
+fn sign(authority: &str, path: &str, query: &str) -> String {
+    digest::sha256(format!("{}{}?{}", authority, path, query))
+}
+
+#[async_trait]
+impl TokenGenerator for QuerySigner {
+    async fn generate_token(&self, req: &Request) -> Result<String, reqwest_middleware::Error> {
+        let url = req.url();
+        let authority = format!(
+            "{}:{}",
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        );
+        Ok(sign(
+            &authority,
+            url.path(),
+            url.query().unwrap_or_default(),
+        ))
+    }
+}
+
+#[async_trait]
+impl ApiAuthenticator for QuerySigner {
+    async fn authenticate(
+        &self,
+        req: Request,
+        _extensions: &Extensions,
+    ) -> Result<Request, reqwest_middleware::Error> {
+        let signature = self.generate_token(&req).await?;
+        let mut req = req;
+        req.url_mut()
+            .query_pairs_mut()
+            .append_pair("signature", &signature);
+        Ok(req)
+    }
+}
+
+fn build_api() -> TheApi {
+    TheApi::builder()
+        .with_rewriter(RoundRobin {
+            endpoints: vec!["localhost", "127.0.0.1"],
+            next: AtomicUsize::new(0),
+        })
+        .with_authenticator(QuerySigner)
+        .build()
+}
+
+#[tokio::test]
+async fn test_sign_resolved_endpoint() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = build_api();
+
+    let mut hosts = vec![];
+    for page in 1..=4 {
+        let req = api.get("/path/json").await?.query(&[("page", page)]);
+        let data: Value = send!(req, CodeDataMessage).await?;
+
+        // The server sees the resolved endpoint, which must be signed
+        let host = data["headers"]["host"].as_str().unwrap_or_default();
+        let path = data["path"].as_str().unwrap_or_default();
+        let expected = sign(host, path, &format!("page={}", page));
+        assert_eq!(Some(expected.as_str()), data["query"]["signature"].as_str());
+        hosts.push(host.to_string());
+    }
+    assert_eq!(
+        vec![
+            "localhost:3030",
+            "127.0.0.1:3030",
+            "localhost:3030",
+            "127.0.0.1:3030"
+        ],
+        hosts
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sign_url_resolved_endpoint() -> ApiResult<()> {
+    init_logger();
+
+    let api = build_api();
+
+    for authority in ["localhost:3030", "127.0.0.1:3030"] {
+        let url = api.sign_url(Method::GET, "/object?page=1").await?;
+        assert_eq!(
+            format!(
+                "http://{}/v1/object?page=1&signature={}",
+                authority,
+                sign(authority, "/v1/object", "page=1")
+            ),
+            url.as_str()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use apisdk::{digest, ApiAuthenticator, ApiResult, Extensions, Method, TokenGenerator};
+use async_trait::async_trait;
+use reqwest::Request;
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// Sign `{method} {path}?{query}&expires={expires}`, and append `expires` and `signature` to query
+struct QuerySigner {
+    secret: String,
+    expires: u64,
+}
+
+impl QuerySigner {
+    fn signature(&self, method: &str, path: &str, query: &str) -> String {
+        digest::sha256(format!(
+            "{} {}?{}&expires={}&{}",
+            method, path, query, self.expires, self.secret
+        ))
+    }
+}
+
+#[async_trait]
+impl TokenGenerator for QuerySigner {
+    async fn generate_token(&self, req: &Request) -> Result<String, reqwest_middleware::Error> {
+        let url = req.url();
+        Ok(self.signature(
+            req.method().as_str(),
+            url.path(),
+            url.query().unwrap_or_default(),
+        ))
+    }
+}
+
+#[async_trait]
+impl ApiAuthenticator for QuerySigner {
+    async fn authenticate(
+        &self,
+        req: Request,
+        _extensions: &Extensions,
+    ) -> Result<Request, reqwest_middleware::Error> {
+        let signature = self.generate_token(&req).await?;
+        let mut req = req;
+        req.url_mut()
+            .query_pairs_mut()
+            .append_pair("expires", &self.expires.to_string())
+            .append_pair("signature", &signature);
+        Ok(req)
+    }
+}
+
+#[tokio::test]
+async fn test_sign_url() -> ApiResult<()> {
+    init_logger();
+
+    let signer = QuerySigner {
+        secret: "secret".to_string(),
+        expires: 1700000000,
+    };
+    let signature = signer.signature("GET", "/v1/bucket/object.txt", "version=2");
+
+    let api = TheApi::builder().with_authenticator(signer).build();
+
+    let url = api
+        .sign_url(Method::GET, "/bucket/object.txt?version=2")
+        .await?;
+    assert_eq!(
+        format!(
+            "http://localhost:3030/v1/bucket/object.txt?version=2&expires=1700000000&signature={}",
+            signature
+        ),
+        url.as_str()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sign_url_without_authenticator() -> ApiResult<()> {
+    init_logger();
+
+    let api = TheApi::builder().build();
+
+    let url = api.sign_url(Method::GET, "/bucket/object.txt").await?;
+    assert_eq!("http://localhost:3030/v1/bucket/object.txt", url.as_str());
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use apisdk::{async_trait, send, ApiResult, MockServer, Request, Responder, ResponseBody};
+use futures::future::join_all;
+use serde_json::{json, Value};
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// Reply slowly, and count the hits
+#[derive(Default)]
+struct SlowCounter {
+    hits: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Responder for SlowCounter {
+    async fn handle(&self, req: Request) -> anyhow::Result<ResponseBody> {
+        let hit = self.hits.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok(ResponseBody::Json(json!({
+            "path": req.url().path(),
+            "hit": hit,
+        })))
+    }
+}
+
+impl TheApi {
+    async fn touch(&self, path: &str) -> ApiResult<Value> {
+        let req = self.get(path).await?;
+        send!(req).await
+    }
+
+    async fn touch_as(&self, path: &str, token: &str) -> ApiResult<Value> {
+        let req = self.get(path).await?.bearer_auth(token);
+        send!(req).await
+    }
+}
+
+fn build_api(single_flight: bool) -> (TheApi, Arc<AtomicUsize>) {
+    let counter = SlowCounter::default();
+    let hits = counter.hits.clone();
+    let api = TheApi::builder()
+        .with_single_flight(single_flight)
+        .with_initialiser(MockServer::new(counter))
+        .build();
+    (api, hits)
+}
+
+#[tokio::test]
+async fn test_single_flight() -> ApiResult<()> {
+    init_logger();
+
+    let (api, hits) = build_api(true);
+
+    let results = join_all((0..10).map(|_| api.touch("/path/json"))).await;
+    assert_eq!(1, hits.load(Ordering::SeqCst));
+    for res in results {
+        let res = res?;
+        assert_eq!("/v1/path/json", res["path"]);
+        assert_eq!(1, res["hit"]);
+    }
+
+    // The finished call is not reused
+    let res = api.touch("/path/json").await?;
+    assert_eq!(2, res["hit"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_flight_different_urls() -> ApiResult<()> {
+    init_logger();
+
+    let (api, hits) = build_api(true);
+
+    let results = join_all([
+        api.touch("/path/json?page=1"),
+        api.touch("/path/json?page=2"),
+        api.touch("/path/json?page=1"),
+    ])
+    .await;
+    assert_eq!(2, hits.load(Ordering::SeqCst));
+    assert_eq!(results[0].as_ref().unwrap(), results[2].as_ref().unwrap());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_flight_different_headers() -> ApiResult<()> {
+    init_logger();
+
+    let (api, hits) = build_api(true);
+
+    let results = join_all([
+        api.touch_as("/path/json", "alice"),
+        api.touch_as("/path/json", "bob"),
+        api.touch_as("/path/json", "alice"),
+    ])
+    .await;
+    assert_eq!(2, hits.load(Ordering::SeqCst));
+    assert_eq!(results[0].as_ref().unwrap(), results[2].as_ref().unwrap());
+    assert_ne!(results[0].as_ref().unwrap(), results[1].as_ref().unwrap());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_flight_leader_dropped() -> ApiResult<()> {
+    init_logger();
+
+    let (api, hits) = build_api(true);
+
+    // The leader is dropped before completion, and nobody joins it
+    let res = tokio::time::timeout(Duration::from_millis(10), api.touch("/path/json")).await;
+    assert!(res.is_err());
+
+    // The later request starts a new call
+    let res = api.touch("/path/json").await?;
+    assert_eq!(2, res["hit"]);
+    assert_eq!(2, hits.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_flight_disabled() -> ApiResult<()> {
+    init_logger();
+
+    let (api, hits) = build_api(false);
+
+    let results = join_all((0..10).map(|_| api.touch("/path/json"))).await;
+    assert_eq!(10, hits.load(Ordering::SeqCst));
+    assert!(results.iter().all(|res| res.is_ok()));
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use apisdk::{http_api, ApiResult};
+
+use crate::common::init_logger;
+
+#[allow(unused)]
+mod common;
+
+#[http_api("http://localhost:3030/api/")]
+#[derive(Debug, Clone)]
+pub struct SlashApi;
+
+#[http_api("http://localhost:3030/api")]
+#[derive(Debug, Clone)]
+pub struct NoSlashApi;
+
+#[tokio::test]
+async fn test_strict_path_join() -> ApiResult<()> {
+    init_logger();
+
+    let api = SlashApi::builder().with_strict_path_join(true).build();
+    let url = api.build_url("users").await?;
+    assert_eq!("http://localhost:3030/api/users", url.as_str());
+
+    let api = NoSlashApi::builder().with_strict_path_join(true).build();
+    let url = api.build_url("/users").await?;
+    assert_eq!("http://localhost:3030/api/users", url.as_str());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_strict_path_join_trailing_slash() -> ApiResult<()> {
+    init_logger();
+
+    let api = NoSlashApi::builder().with_strict_path_join(true).build();
+
+    // The trailing `/` is preserved
+    let url = api.build_url("/users/").await?;
+    assert_eq!("http://localhost:3030/api/users/", url.as_str());
+    let url = api.build_url("/users").await?;
+    assert_eq!("http://localhost:3030/api/users", url.as_str());
+
+    // The duplicated `/` is collapsed, and the empty path adds nothing
+    let url = api.build_url("//users?page=2").await?;
+    assert_eq!("http://localhost:3030/api/users?page=2", url.as_str());
+    let url = api.build_url("").await?;
+    assert_eq!("http://localhost:3030/api", url.as_str());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_strict_path_join_with_path_prefix() -> ApiResult<()> {
+    init_logger();
+
+    let api = SlashApi::builder()
+        .with_path_prefix("v2")
+        .with_strict_path_join(true)
+        .build();
+    let req = api.get("users/").await?.build()?;
+    assert_eq!("http://localhost:3030/api/v2/users/", req.url().as_str());
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use std::{
+    io::Cursor,
+    sync::{Arc, Mutex},
+};
+
+use apisdk::{ApiError, ApiResult, Extensions, Middleware, TransportError};
+use async_trait::async_trait;
+use reqwest::{Request, Response};
+use reqwest_middleware::Next;
+
+use crate::common::{init_logger, TheApi};
+
+#[allow(unused)]
+mod common;
+
+/// The state of the upload
+#[derive(Default)]
+struct Upload {
+    /// The received bytes
+    data: Vec<u8>,
+    /// The methods and offsets of received requests
+    requests: Vec<(String, Option<u64>)>,
+}
+
+/// This middleware acts as a minimal Tus server
+///
+/// The PATCH request at `interrupt_at` stores half of the chunk, and then the connection is reset
+#[derive(Clone, Default)]
+struct TusServer {
+    interrupt_at: Option<u64>,
+    status: Option<u16>,
+    upload: Arc<Mutex<Upload>>,
+}
+
+impl TusServer {
+    fn reply(&self, status: u16, headers: &[(&str, String)]) -> Response {
+        let mut res = hyper::Response::builder()
+            .status(status)
+            .header("tus-resumable", "1.0.0");
+        for (name, value) in headers {
+            res = res.header(*name, value);
+        }
+        Response::from(res.body("").unwrap())
+    }
+}
+
+#[async_trait]
+impl Middleware for TusServer {
+    async fn handle(
+        &self,
+        req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> Result<Response, reqwest_middleware::Error> {
+        assert_eq!("1.0.0", req.headers()["tus-resumable"]);
+        let offset = req
+            .headers()
+            .get("upload-offset")
+            .map(|v| v.to_str().unwrap().parse::<u64>().unwrap());
+        let mut upload = self.upload.lock().unwrap();
+        upload.requests.push((req.method().to_string(), offset));
+        let current = upload.data.len() as u64;
+
+        match req.method().as_str() {
+            "POST" => {
+                assert_eq!("/v1/uploads", req.url().path());
+                assert!(req.headers().contains_key("upload-length"));
+                Ok(self.reply(201, &[("location", "/v1/uploads/1".to_string())]))
+            }
+            "HEAD" => Ok(self.reply(200, &[("upload-offset", current.to_string())])),
+            "PATCH" => {
+                assert_eq!("/v1/uploads/1", req.url().path());
+                assert_eq!(
+                    "application/offset+octet-stream",
+                    req.headers()["content-type"]
+                );
+                if let Some(status) = self.status {
+                    return Ok(self.reply(status, &[]));
+                }
+                if offset != Some(current) {
+                    return Ok(self.reply(409, &[]));
+                }
+                let chunk = req.body().and_then(|b| b.as_bytes()).unwrap();
+                if self.interrupt_at == offset && upload.requests.len() < 4 {
+                    upload.data.extend_from_slice(&chunk[..chunk.len() / 2]);
+                    let e = ApiError::Connection(TransportError::ConnectionReset);
+                    return Err(reqwest_middleware::Error::Middleware(e.into()));
+                }
+                upload.data.extend_from_slice(chunk);
+                let current = upload.data.len().to_string();
+                Ok(self.reply(204, &[("upload-offset", current)]))
+            }
+            method => panic!("unexpected method: {}", method),
+        }
+    }
+}
+
+fn source() -> Vec<u8> {
+    (0..35u8).collect()
+}
+
+#[tokio::test]
+async fn test_tus_upload() -> ApiResult<()> {
+    init_logger();
+
+    let server = TusServer::default();
+    let api = TheApi::builder().with_middleware(server.clone()).build();
+
+    let location = api
+        .tus_upload("/uploads", Cursor::new(source()), 10)
+        .await?;
+    assert_eq!("http://localhost:3030/v1/uploads/1", location.as_str());
+
+    let upload = server.upload.lock().unwrap();
+    assert_eq!(source(), upload.data);
+    let patches: Vec<_> = upload.requests.iter().filter_map(|(_, o)| *o).collect();
+    assert_eq!(vec![0, 10, 20, 30], patches);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tus_upload_resume() -> ApiResult<()> {
+    init_logger();
+
+    let server = TusServer {
+        interrupt_at: Some(10),
+        ..Default::default()
+    };
+    let api = TheApi::builder().with_middleware(server.clone()).build();
+
+    api.tus_upload("/uploads", Cursor::new(source()), 10)
+        .await?;
+
+    let upload = server.upload.lock().unwrap();
+    assert_eq!(source(), upload.data);
+    let requests: Vec<_> = upload
+        .requests
+        .iter()
+        .map(|(method, offset)| (method.as_str(), *offset))
+        .collect();
+    assert_eq!(
+        vec![
+            ("POST", None),
+            ("PATCH", Some(0)),
+            ("PATCH", Some(10)),
+            ("HEAD", None),
+            ("PATCH", Some(15)),
+            ("PATCH", Some(25)),
+        ],
+        requests
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tus_upload_fatal() -> ApiResult<()> {
+    init_logger();
+
+    let server = TusServer {
+        status: Some(404),
+        ..Default::default()
+    };
+    let api = TheApi::builder().with_middleware(server.clone()).build();
+
+    let res = api.tus_upload("/uploads", Cursor::new(source()), 10).await;
+    assert!(matches!(res, Err(ApiError::HttpClientStatus(404, _))));
+
+    let upload = server.upload.lock().unwrap();
+    assert_eq!(2, upload.requests.len());
+
+    Ok(())
+}
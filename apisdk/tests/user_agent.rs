@@ -0,0 +1,72 @@
+use apisdk::{header::USER_AGENT, send, ApiResult, CodeDataMessage, DEFAULT_USER_AGENT};
+use serde::Deserialize;
+
+use crate::common::{init_logger, start_server, Payload, TheApi};
+
+#[allow(unused)]
+mod common;
+
+#[derive(Debug, Deserialize)]
+pub struct Headers {
+    #[serde(default, rename = "user-agent")]
+    pub user_agent: String,
+}
+
+impl TheApi {
+    async fn touch(&self) -> ApiResult<Payload<Headers>> {
+        let req = self.get("/path/json").await?;
+        send!(req, CodeDataMessage).await
+    }
+
+    async fn touch_with_user_agent(&self, user_agent: &str) -> ApiResult<Payload<Headers>> {
+        let req = self.get("/path/json").await?;
+        let req = req.header(USER_AGENT, user_agent);
+        send!(req, CodeDataMessage).await
+    }
+}
+
+#[tokio::test]
+async fn test_user_agent_default() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::default();
+
+    let res = api.touch().await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!(DEFAULT_USER_AGENT, res.headers.user_agent);
+    assert_eq!(
+        format!("apisdk-rs/{}", env!("CARGO_PKG_VERSION")),
+        res.headers.user_agent
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_user_agent_custom() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_user_agent("my-app/1.2.3").build();
+
+    let res = api.touch().await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!("my-app/1.2.3", res.headers.user_agent);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_user_agent_per_request() -> ApiResult<()> {
+    init_logger();
+    start_server().await;
+
+    let api = TheApi::builder().with_user_agent("my-app/1.2.3").build();
+
+    let res = api.touch_with_user_agent("my-job/0.1").await?;
+    log::debug!("res = {:?}", res);
+    assert_eq!("my-job/0.1", res.headers.user_agent);
+
+    Ok(())
+}